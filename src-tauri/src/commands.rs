@@ -1,23 +1,52 @@
-use crate::network::client::SlaveClient;
-use crate::network::server::{ClientInfo, MasterServer, SlaveStatus};
-use crate::obs::client::{OBSClient, OBSConnectionConfig, OBSConnectionStatus};
-use crate::obs::events::OBSEventHandler;
-use crate::sync::master::MasterSync;
-use crate::sync::protocol::{SyncMessage, SyncTargetType};
-use crate::sync::slave::SlaveSync;
+use crate::network::client::{MasterEndpoint, SlaveClient, DEFAULT_MAX_RECONNECT_ATTEMPTS};
+use crate::network::keepalive::KeepaliveConfig;
+use crate::network::probe::{self, MasterProbeResult};
+use crate::network::reconnect::ReconnectStrategy;
+use crate::network::server::{
+    ClientInfo, FleetReport, HandshakeGuard, HeartbeatConfig, MasterServer, SlaveDesyncSummary,
+    SlaveDetail, SlaveLatency, SlaveStatus, DEFAULT_HEARTBEAT_INTERVAL_SECS,
+    DEFAULT_HEARTBEAT_TIMEOUT_SECS,
+};
+use crate::network::status::{StatusEvent, StatusLevel};
+use crate::network::tls::{self, MasterTls};
+use crate::obs::client::{OBSClient, OBSConnectionConfig, OBSConnectionEvent, OBSConnectionStatus};
+use crate::obs::commands::OBSCommands;
+use crate::obs::events::{OBSEventHandler, RawEventPassthrough};
+use crate::sync::anchor::{TransformAnchor, TransformAnchors};
+use crate::sync::command::{HotkeyCommands, SemanticCommand};
+use crate::sync::diff::{DesyncReport, DiffTolerance};
+use crate::sync::filters::{SourceSyncRules, SyncFilters};
+use crate::sync::image_cache::ImageCacheLimits;
+use crate::sync::master::{AmbiguousSource, MasterSync, StateDumpSummary};
+use crate::sync::profile::{SyncProfile, SyncTuning};
+use crate::sync::protocol::{
+    Capabilities, SceneTargetOverrides, SyncMessage, SyncMessageType, SyncTargetType,
+    TransformFieldMask,
+};
+use crate::sync::reencode::ImageReencode;
+use crate::sync::slave::{
+    AlertSeverity, DesyncAlert, MediaAvailability, NormalizeChange, PlannedAction, SlaveSync,
+    StateSyncReport, TransformInterpolation, DEFAULT_APPLY_CONCURRENCY, DEFAULT_SYNC_HYSTERESIS,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{Emitter, Manager, State};
 use tokio::fs;
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio_rustls::rustls::ClientConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum AppMode {
     Master,
     Slave,
+    /// Slave that also sends its own scene switches and transforms to the master
+    Bidirectional,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +54,23 @@ pub enum AppMode {
 pub struct NetworkConfig {
     pub host: String,
     pub port: u16,
+    #[serde(default)]
+    pub path_token: Option<String>,
+    /// Connect with wss://
+    #[serde(default)]
+    pub use_tls: bool,
+    /// PEM certificate the master's certificate must chain to (e.g. its self-signed cert)
+    #[serde(default)]
+    pub tls_ca_cert_path: Option<String>,
+    /// SHA-256 fingerprint of the master certificate; when set, only that certificate is accepted
+    #[serde(default)]
+    pub tls_fingerprint: Option<String>,
+    /// Shared token the master requires (MasterSettings.auth_token)
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Masters tried in order when the one at host:port can't be reached
+    #[serde(default)]
+    pub fallback_masters: Vec<MasterEndpoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +81,39 @@ pub struct AppSettings {
     pub slave: SlaveSettings,
     #[serde(default)]
     pub donation_dialog_shown: bool,
+    #[serde(default)]
+    pub sync_profile: SyncProfile,
+    /// Replaces the sync profile's desync tolerances when set
+    #[serde(default)]
+    pub diff_tolerance: Option<DiffTolerance>,
+    #[serde(default)]
+    pub scene_target_overrides: SceneTargetOverrides,
+    #[serde(default)]
+    pub sync_filters: SyncFilters,
+    #[serde(default)]
+    pub keepalive: KeepaliveConfig,
+    #[serde(default)]
+    pub auto_start: AutoStartSettings,
+}
+
+/// What to bring up automatically on launch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStartSettings {
+    pub connect_obs: bool,
+    pub role: Option<AppMode>,
+    /// Port to serve on (master) or connect to (slave); defaults to the role's default port
+    pub port: Option<u16>,
+    /// Master to connect to in slave role; defaults to `slave.default_host`
+    pub master_host: Option<String>,
+}
+
+/// Payload of the `auto-start-failed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStartFailure {
+    pub step: String,
+    pub error: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +128,50 @@ pub struct OBSSettings {
 #[serde(rename_all = "camelCase")]
 pub struct MasterSettings {
     pub default_port: u16,
+    #[serde(default)]
+    pub handshake_guard: HandshakeGuard,
+    #[serde(default)]
+    pub raw_event_passthrough: RawEventPassthrough,
+    #[serde(default)]
+    pub image_reencode: ImageReencode,
+    /// Sources positioned relative to a canvas edge on slaves, by source name
+    #[serde(default)]
+    pub transform_anchors: TransformAnchors,
+    /// Commands sent to slaves when a hotkey is triggered through `trigger_hotkey_command`
+    #[serde(default)]
+    pub hotkey_commands: HotkeyCommands,
+    /// Serve wss:// instead of ws://
+    #[serde(default)]
+    pub use_tls: bool,
+    /// PEM certificate and key; a self-signed pair in the app data directory is used when unset
+    #[serde(default)]
+    pub tls_cert_path: Option<String>,
+    #[serde(default)]
+    pub tls_key_path: Option<String>,
+    /// Slaves must authenticate with this token before receiving any state
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Apply and re-broadcast scene switches and transforms sent by bidirectional slaves
+    #[serde(default)]
+    pub accept_slave_updates: bool,
+    /// How often heartbeats are sent to slaves
+    #[serde(default = "default_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+    /// Slaves silent for this long are dropped as disconnected
+    #[serde(default = "default_heartbeat_timeout_secs")]
+    pub heartbeat_timeout_secs: u64,
+    /// Cap on state and image traffic to all slaves together, in kilobits per second;
+    /// 0 for unlimited. Scene changes and other small messages are not held back by it.
+    #[serde(default)]
+    pub bandwidth_limit_kbps: u32,
+}
+
+fn default_heartbeat_interval_secs() -> u64 {
+    DEFAULT_HEARTBEAT_INTERVAL_SECS
+}
+
+fn default_heartbeat_timeout_secs() -> u64 {
+    DEFAULT_HEARTBEAT_TIMEOUT_SECS
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +179,64 @@ pub struct MasterSettings {
 pub struct SlaveSettings {
     pub default_host: String,
     pub default_port: u16,
+    /// Masters to fail over to when the default one can't be reached
+    #[serde(default)]
+    pub fallback_masters: Vec<MasterEndpoint>,
+    #[serde(default)]
+    pub transform_mask: TransformFieldMask,
+    #[serde(default)]
+    pub interpolation: TransformInterpolation,
+    #[serde(default = "default_apply_concurrency")]
+    pub apply_concurrency: usize,
+    /// Delay between reconnect attempts; its `cap` is the backoff ceiling
+    #[serde(default)]
+    pub reconnect_strategy: ReconnectStrategy,
+    /// Reconnect attempts before giving up; `null` retries forever
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: Option<u32>,
+    /// Consecutive desync checks that must agree before the synced state flips
+    #[serde(default = "default_sync_hysteresis")]
+    pub sync_hysteresis: u32,
+    /// Find scene items by (scene, source name) instead of the master's item ids
+    #[serde(default)]
+    pub prefer_name_matching: bool,
+    /// Observe only: report what would change in OBS (`planned-action` events) without applying it
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Age and size limits for the temp files received images are written to
+    #[serde(default)]
+    pub image_cache: ImageCacheLimits,
+    /// Name the master's dashboard shows for this machine, e.g. "Stage-Left-PC"
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Stop applying a StateSync at the first failed item and request a fresh one
+    #[serde(default)]
+    pub strict_state_sync: bool,
+    /// Sources customized on this machine, by name, that sync leaves alone
+    #[serde(default)]
+    pub ignored_sources: Vec<String>,
+    /// Connect with wss://; the TLS fields mean the same as in `NetworkConfig`
+    #[serde(default)]
+    pub use_tls: bool,
+    #[serde(default)]
+    pub tls_ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub tls_fingerprint: Option<String>,
+    /// Token for a master that requires authentication
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+fn default_apply_concurrency() -> usize {
+    DEFAULT_APPLY_CONCURRENCY
+}
+
+fn default_sync_hysteresis() -> u32 {
+    DEFAULT_SYNC_HYSTERESIS
+}
+
+fn default_max_reconnect_attempts() -> Option<u32> {
+    Some(DEFAULT_MAX_RECONNECT_ATTEMPTS)
 }
 
 impl Default for AppSettings {
@@ -66,17 +247,101 @@ impl Default for AppSettings {
                 port: 4455,
                 password: String::new(),
             },
-            master: MasterSettings { default_port: 8080 },
+            master: MasterSettings {
+                default_port: 8080,
+                handshake_guard: HandshakeGuard::default(),
+                raw_event_passthrough: RawEventPassthrough::default(),
+                image_reencode: ImageReencode::default(),
+                transform_anchors: TransformAnchors::new(),
+                hotkey_commands: HotkeyCommands::new(),
+                use_tls: false,
+                tls_cert_path: None,
+                tls_key_path: None,
+                auth_token: None,
+                accept_slave_updates: false,
+                heartbeat_interval_secs: DEFAULT_HEARTBEAT_INTERVAL_SECS,
+                heartbeat_timeout_secs: DEFAULT_HEARTBEAT_TIMEOUT_SECS,
+                bandwidth_limit_kbps: 0,
+            },
             slave: SlaveSettings {
                 default_host: "192.168.1.100".to_string(),
                 default_port: 8080,
+                fallback_masters: Vec::new(),
+                transform_mask: TransformFieldMask::default(),
+                interpolation: TransformInterpolation::default(),
+                apply_concurrency: DEFAULT_APPLY_CONCURRENCY,
+                reconnect_strategy: ReconnectStrategy::default(),
+                max_reconnect_attempts: default_max_reconnect_attempts(),
+                sync_hysteresis: DEFAULT_SYNC_HYSTERESIS,
+                prefer_name_matching: false,
+                dry_run: false,
+                image_cache: ImageCacheLimits::default(),
+                display_name: None,
+                strict_state_sync: false,
+                ignored_sources: Vec::new(),
+                use_tls: false,
+                tls_ca_cert_path: None,
+                tls_fingerprint: None,
+                auth_token: None,
             },
             donation_dialog_shown: false,
+            sync_profile: SyncProfile::default(),
+            diff_tolerance: None,
+            scene_target_overrides: SceneTargetOverrides::new(),
+            sync_filters: SyncFilters::default(),
+            keepalive: KeepaliveConfig::default(),
+            auto_start: AutoStartSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// The sync profile's tuning with the tolerance override applied
+    pub fn tuning(&self) -> SyncTuning {
+        let mut tuning = self.sync_profile.tuning();
+        if let Some(tolerance) = &self.diff_tolerance {
+            tuning.diff_tolerance = tolerance.clone();
+        }
+        tuning
+    }
+
+    /// Reject values that would leave the app unable to connect, naming the offending setting
+    pub fn validate(&self) -> Result<(), String> {
+        fn check_endpoint(name: &str, host: &str, port: u16) -> Result<(), String> {
+            if host.trim().is_empty() {
+                return Err(format!("{}: host must not be empty", name));
+            }
+            check_port(name, port)
+        }
+        fn check_port(name: &str, port: u16) -> Result<(), String> {
+            if port == 0 {
+                return Err(format!("{}: port must be between 1 and 65535", name));
+            }
+            Ok(())
+        }
+
+        check_endpoint("obs", &self.obs.host, self.obs.port)?;
+        check_port("master.defaultPort", self.master.default_port)?;
+        check_endpoint("slave", &self.slave.default_host, self.slave.default_port)?;
+        for (i, master) in self.slave.fallback_masters.iter().enumerate() {
+            check_endpoint(
+                &format!("slave.fallbackMasters[{}]", i),
+                &master.host,
+                master.port,
+            )?;
+        }
+        if self.slave.apply_concurrency == 0 {
+            return Err("slave.applyConcurrency must be at least 1".to_string());
         }
+        Ok(())
     }
 }
 
 async fn get_config_path(state: &AppState) -> Result<PathBuf, String> {
+    get_app_data_file(state, "config.json").await
+}
+
+async fn get_app_data_file(state: &AppState, file_name: &str) -> Result<PathBuf, String> {
     let app_handle = state.app_handle.read().await;
     if let Some(handle) = app_handle.as_ref() {
         let app_data_dir = handle
@@ -86,12 +351,35 @@ async fn get_config_path(state: &AppState) -> Result<PathBuf, String> {
         fs::create_dir_all(&app_data_dir)
             .await
             .map_err(|e| format!("Failed to create app data directory: {}", e))?;
-        Ok(app_data_dir.join("config.json"))
+        Ok(app_data_dir.join(file_name))
     } else {
         Err("App handle not available".to_string())
     }
 }
 
+/// Random id generated on first use and kept in the app data directory, so the
+/// master recognises this machine across reconnects and restarts
+async fn load_machine_id(state: &AppState) -> String {
+    let path = match get_app_data_file(state, "machine_id").await {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Machine id not persisted: {}", e);
+            return uuid::Uuid::new_v4().to_string();
+        }
+    };
+    if let Ok(id) = fs::read_to_string(&path).await {
+        let id = id.trim();
+        if !id.is_empty() {
+            return id.to_string();
+        }
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = fs::write(&path, &id).await {
+        eprintln!("Failed to save machine id to {:?}: {}", path, e);
+    }
+    id
+}
+
 async fn get_log_dir(state: &AppState) -> Result<PathBuf, String> {
     let app_handle = state.app_handle.read().await;
     if let Some(handle) = app_handle.as_ref() {
@@ -120,33 +408,361 @@ pub async fn save_settings(
     state: State<'_, AppState>,
     settings: AppSettings,
 ) -> Result<(), String> {
-    let config_path = get_config_path(&state).await?;
-    let json = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&config_path, json)
+    write_settings(&state, &settings).await?;
+    state.save_metrics().await;
+    Ok(())
+}
+
+/// Copy of the last config.json that parsed, used when the primary is corrupt
+fn settings_backup_path(config_path: &std::path::Path) -> PathBuf {
+    config_path.with_extension("json.bak")
+}
+
+async fn parse_settings_file(path: &std::path::Path) -> Result<AppSettings, String> {
+    let content = fs::read_to_string(path)
         .await
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings file: {}", e))
+}
+
+/// Write config.json through a synced temp file and a rename, so a crash mid-write
+/// leaves either the old or the new file. The previous file is kept as the backup
+/// if it was valid.
+async fn write_settings(state: &AppState, settings: &AppSettings) -> Result<(), String> {
+    let config_path = get_config_path(state).await?;
+    write_settings_at(state, &config_path, settings).await?;
     println!("Settings saved to: {:?}", config_path);
     Ok(())
 }
 
+/// Save `settings` and record the values the app changed as in effect. Values that were
+/// already different on disk, such as hand edits, stay pending until they are reloaded.
+async fn write_settings_at(
+    state: &AppState,
+    config_path: &std::path::Path,
+    settings: &AppSettings,
+) -> Result<(), String> {
+    let on_disk = read_settings_file(config_path).await.ok();
+    write_settings_file(config_path, settings).await?;
+
+    let mut known = state.known_settings.write().await;
+    *known = Some(match (known.take(), on_disk) {
+        (Some(previous), Some(on_disk)) => {
+            let to_value = |settings: &AppSettings| {
+                serde_json::to_value(settings)
+                    .map_err(|e| format!("Failed to serialize settings: {}", e))
+            };
+            let mut merged = to_value(&previous)?;
+            merge_setting_changes(&mut merged, &to_value(&on_disk)?, &to_value(settings)?);
+            serde_json::from_value(merged)
+                .map_err(|e| format!("Failed to merge settings: {}", e))?
+        }
+        _ => settings.clone(),
+    });
+    Ok(())
+}
+
+/// Apply to `target` the values that differ between `before` and `after`
+fn merge_setting_changes(
+    target: &mut serde_json::Value,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+) {
+    match (before, after, target.as_object_mut()) {
+        (
+            serde_json::Value::Object(before_map),
+            serde_json::Value::Object(after_map),
+            Some(target_map),
+        ) => {
+            let keys: std::collections::BTreeSet<&String> =
+                before_map.keys().chain(after_map.keys()).collect();
+            for key in keys {
+                match after_map.get(key) {
+                    Some(after_value) => merge_setting_changes(
+                        target_map
+                            .entry(key.clone())
+                            .or_insert(serde_json::Value::Null),
+                        before_map.get(key).unwrap_or(&serde_json::Value::Null),
+                        after_value,
+                    ),
+                    None => {
+                        target_map.remove(key);
+                    }
+                }
+            }
+        }
+        _ if before != after => *target = after.clone(),
+        _ => {}
+    }
+}
+
+async fn write_settings_file(
+    config_path: &std::path::Path,
+    settings: &AppSettings,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let temp_path = config_path.with_extension("json.tmp");
+    {
+        let mut file = fs::File::create(&temp_path)
+            .await
+            .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        file.sync_all()
+            .await
+            .map_err(|e| format!("Failed to write settings file: {}", e))?;
+    }
+
+    if parse_settings_file(config_path).await.is_ok() {
+        if let Err(e) = fs::copy(config_path, settings_backup_path(config_path)).await {
+            eprintln!("Failed to back up settings file: {}", e);
+        }
+    }
+    fs::rename(&temp_path, config_path)
+        .await
+        .map_err(|e| format!("Failed to replace settings file: {}", e))
+}
+
 #[tauri::command]
 pub async fn load_settings(state: State<'_, AppState>) -> Result<AppSettings, String> {
-    let config_path = get_config_path(&state).await?;
+    read_settings(&state).await
+}
+
+async fn read_settings(state: &AppState) -> Result<AppSettings, String> {
+    let config_path = get_config_path(state).await?;
+    read_settings_file(&config_path).await
+}
 
+/// Load config.json, falling back to its backup if it can't be parsed
+async fn read_settings_file(config_path: &std::path::Path) -> Result<AppSettings, String> {
     if !config_path.exists() {
         // Return default settings if file doesn't exist
         return Ok(AppSettings::default());
     }
 
-    let content = fs::read_to_string(&config_path)
+    match parse_settings_file(config_path).await {
+        Ok(settings) => Ok(settings),
+        Err(primary_error) => {
+            let backup_path = settings_backup_path(config_path);
+            let settings = parse_settings_file(&backup_path)
+                .await
+                .map_err(|_| primary_error.clone())?;
+            eprintln!(
+                "{}; loaded previous settings from {:?}",
+                primary_error, backup_path
+            );
+            Ok(settings)
+        }
+    }
+}
+
+/// Settings (camelCase paths) that only take effect on the next launch, OBS connection
+/// or master connection
+const RESTART_REQUIRED_SETTINGS: &[&str] = &[
+    "obs",
+    "master.defaultPort",
+    "master.useTls",
+    "master.tlsCertPath",
+    "master.tlsKeyPath",
+    "master.authToken",
+    "master.acceptSlaveUpdates",
+    "master.heartbeatIntervalSecs",
+    "master.heartbeatTimeoutSecs",
+    "slave.defaultHost",
+    "slave.defaultPort",
+    "slave.fallbackMasters",
+    "slave.useTls",
+    "slave.tlsCaCertPath",
+    "slave.tlsFingerprint",
+    "slave.authToken",
+    "slave.reconnectStrategy",
+    "slave.displayName",
+    "keepalive",
+    "autoStart",
+];
+
+/// Outcome of `reload_settings`, as camelCase setting paths such as "slave.transformMask"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsReload {
+    /// Changed values now in effect
+    pub applied: Vec<String>,
+    /// Changed values that need a restart or reconnect to take effect
+    pub restart_required: Vec<String>,
+}
+
+/// Collect the paths of leaf values that differ between two serialized settings
+fn changed_setting_paths(
+    prefix: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    changed: &mut Vec<String>,
+) {
+    match (old, new) {
+        (serde_json::Value::Object(old_map), serde_json::Value::Object(new_map)) => {
+            let keys: std::collections::BTreeSet<&String> =
+                old_map.keys().chain(new_map.keys()).collect();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                let null = serde_json::Value::Null;
+                changed_setting_paths(
+                    &path,
+                    old_map.get(key).unwrap_or(&null),
+                    new_map.get(key).unwrap_or(&null),
+                    changed,
+                );
+            }
+        }
+        _ if old != new => changed.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+/// Push every runtime-adjustable setting into the subsystems that are currently running
+async fn apply_runtime_settings(state: &AppState, settings: &AppSettings) {
+    let tuning = settings.tuning();
+    *state.sync_profile.write().await = settings.sync_profile;
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync.set_tuning(tuning.clone()).await;
+        master_sync
+            .set_scene_target_overrides(settings.scene_target_overrides.clone())
+            .await;
+        master_sync.set_filters(settings.sync_filters.clone()).await;
+        master_sync
+            .set_image_reencode(settings.master.image_reencode.clone())
+            .await;
+        master_sync
+            .set_transform_anchors(settings.master.transform_anchors.clone())
+            .await;
+    }
+    if let Some(server) = state.master_server.read().await.as_ref() {
+        server
+            .set_handshake_guard(settings.master.handshake_guard.clone())
+            .await;
+        server.set_bandwidth_limit_kbps(settings.master.bandwidth_limit_kbps);
+    }
+    if let Some(event_handler) = state.obs_event_handler.read().await.as_ref() {
+        event_handler
+            .set_raw_passthrough(settings.master.raw_event_passthrough.clone())
+            .await;
+    }
+
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_tuning(tuning).await;
+        slave_sync
+            .set_scene_target_overrides(settings.scene_target_overrides.clone())
+            .await;
+        slave_sync
+            .set_transform_mask(settings.slave.transform_mask)
+            .await;
+        slave_sync
+            .set_interpolation(settings.slave.interpolation)
+            .await;
+        slave_sync.set_apply_concurrency(settings.slave.apply_concurrency);
+        slave_sync.set_sync_hysteresis(settings.slave.sync_hysteresis);
+        slave_sync.set_prefer_name_matching(settings.slave.prefer_name_matching);
+        slave_sync.set_dry_run(settings.slave.dry_run);
+        slave_sync.set_strict_state_sync(settings.slave.strict_state_sync);
+        slave_sync
+            .set_image_cache_limits(settings.slave.image_cache)
+            .await;
+        slave_sync
+            .set_ignored_sources(settings.slave.ignored_sources.clone())
+            .await;
+    }
+
+    if let Some(slave_client) = state.slave_client.read().await.as_ref() {
+        slave_client
+            .set_max_reconnect_attempts(settings.slave.max_reconnect_attempts.unwrap_or(0))
+            .await;
+    }
+}
+
+/// Re-read config.json (e.g. after it was edited by hand) and apply it to the running app
+#[tauri::command]
+pub async fn reload_settings(state: State<'_, AppState>) -> Result<SettingsReload, String> {
+    let config_path = get_config_path(&state).await?;
+    let reload = reload_settings_at(&state, &config_path).await?;
+    println!("Settings reloaded from disk: {:?}", reload);
+    Ok(reload)
+}
+
+/// Apply the settings in `config_path` and report how they differ from those in effect
+async fn reload_settings_at(
+    state: &AppState,
+    config_path: &std::path::Path,
+) -> Result<SettingsReload, String> {
+    let settings = read_settings_file(config_path).await?;
+    apply_runtime_settings(state, &settings).await;
+    let previous = state.known_settings.write().await.replace(settings.clone());
+    settings_reload(previous.as_ref(), &settings)
+}
+
+/// Write the saved settings to `path`, e.g. to set up another machine the same way
+#[tauri::command]
+pub async fn export_settings(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let settings = read_settings(&state).await?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, json)
         .await
-        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    println!("Settings exported to: {}", path);
+    Ok(())
+}
+
+/// Replace the saved settings with those in `path` and apply them. Fields this version
+/// doesn't know, e.g. from a file exported by a newer one, are ignored.
+#[tauri::command]
+pub async fn import_settings(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<SettingsReload, String> {
+    let settings = parse_settings_file(std::path::Path::new(&path)).await?;
+    settings
+        .validate()
+        .map_err(|e| format!("Invalid settings in {}: {}", path, e))?;
+
+    let config_path = get_config_path(&state).await?;
+    write_settings_file(&config_path, &settings).await?;
+    apply_runtime_settings(&state, &settings).await;
+    let previous = state.known_settings.write().await.replace(settings.clone());
+
+    let reload = settings_reload(previous.as_ref(), &settings)?;
+    println!("Settings imported from {}: {:?}", path, reload);
+    Ok(reload)
+}
 
-    let settings: AppSettings = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings file: {}", e))?;
+/// Split the settings that changed from `previous` by whether they are already in effect
+fn settings_reload(
+    previous: Option<&AppSettings>,
+    settings: &AppSettings,
+) -> Result<SettingsReload, String> {
+    let to_value = |settings: &AppSettings| {
+        serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+    };
+    let mut changed = Vec::new();
+    if let Some(previous) = previous {
+        changed_setting_paths("", &to_value(previous)?, &to_value(settings)?, &mut changed);
+    }
 
-    Ok(settings)
+    let (restart_required, applied) = changed.into_iter().partition(|path: &String| {
+        RESTART_REQUIRED_SETTINGS
+            .iter()
+            .any(|prefix| path == prefix || path.starts_with(&format!("{}.", prefix)))
+    });
+    Ok(SettingsReload {
+        applied,
+        restart_required,
+    })
 }
 
 #[tauri::command]
@@ -184,6 +800,21 @@ pub struct SyncMetric {
     pub message_type: String,
     pub latency_ms: f64,
     pub message_size_bytes: usize,
+    #[serde(default)]
+    pub kind: MetricKind,
+}
+
+/// What a metric measures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MetricKind {
+    /// Message received from the master, with its one-way latency
+    #[default]
+    Received,
+    /// Message broadcast by the master; latency is unknown until it is acknowledged
+    Sent,
+    /// Ack of a sent message; `latency_ms` is the round trip and no new message is counted
+    Acked,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -194,17 +825,142 @@ pub struct PerformanceMetrics {
     pub messages_per_second: f64,
     pub total_bytes: usize,
     pub recent_metrics: Vec<SyncMetric>,
+    /// Estimated master clock minus local clock, from heartbeat round trips
+    pub clock_offset_ms: Option<f64>,
+    pub heartbeat_rtt_ms: Option<f64>,
+}
+
+/// Latency and volume of one message type among the retained metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageTypeMetrics {
+    pub message_type: String,
+    /// Messages of this type; acks are not counted
+    pub count: usize,
+    /// Latency samples behind the average; sent messages only yield one once acked
+    pub latency_samples: usize,
+    /// 0 without samples
+    pub average_latency_ms: f64,
+    pub total_bytes: usize,
+}
+
+/// Clock offset estimate smoothed over heartbeat samples
+#[derive(Debug, Clone, Copy)]
+struct ClockEstimate {
+    offset_ms: f64,
+    rtt_ms: f64,
 }
 
+/// How long a sent message waits for acks before it no longer yields latency samples
+const IN_FLIGHT_MAX_AGE_MS: i64 = 10_000;
+
+/// Performance metrics kept across restarts, in the app data directory
+const METRICS_FILE: &str = "metrics.json";
+
+/// Weight of a new heartbeat sample in the smoothed estimate
+const CLOCK_SMOOTHING: f64 = 0.2;
+
 pub struct PerformanceMonitor {
     metrics: Arc<RwLock<VecDeque<SyncMetric>>>,
+    clock: Arc<RwLock<Option<ClockEstimate>>>,
+    /// Send time and type of broadcast messages awaiting acks, by message id
+    in_flight: Arc<RwLock<HashMap<String, (i64, String)>>>,
 }
 
 impl PerformanceMonitor {
     pub fn new(max_metrics: usize) -> Self {
         Self {
             metrics: Arc::new(RwLock::new(VecDeque::with_capacity(max_metrics))),
+            clock: Arc::new(RwLock::new(None)),
+            in_flight: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Update the clock offset from a heartbeat reply received at `receive_time`.
+    /// Assumes the master stamped its reply halfway through the round trip.
+    pub async fn record_heartbeat(&self, message: &SyncMessage, receive_time: i64) {
+        let (Some(client_time), Some(server_time)) = (
+            message.payload.get("client_time").and_then(|v| v.as_i64()),
+            message.payload.get("server_time").and_then(|v| v.as_i64()),
+        ) else {
+            return;
+        };
+        let rtt_ms = (receive_time - client_time).max(0) as f64;
+        let offset_ms = server_time as f64 - (client_time as f64 + rtt_ms / 2.0);
+
+        let mut clock = self.clock.write().await;
+        *clock = Some(match *clock {
+            Some(prev) => ClockEstimate {
+                offset_ms: prev.offset_ms + CLOCK_SMOOTHING * (offset_ms - prev.offset_ms),
+                rtt_ms: prev.rtt_ms + CLOCK_SMOOTHING * (rtt_ms - prev.rtt_ms),
+            },
+            None => ClockEstimate { offset_ms, rtt_ms },
+        });
+    }
+
+    /// Record a received sync message, correcting its latency for the estimated clock offset
+    pub async fn record_receive(&self, message: &SyncMessage, receive_time: i64) {
+        let offset_ms = self.clock.read().await.map(|c| c.offset_ms).unwrap_or(0.0);
+        let latency_ms = if message.timestamp > 0 {
+            // Convert the master's send time to our clock before subtracting
+            (receive_time as f64 - (message.timestamp as f64 - offset_ms)).max(0.0)
+        } else {
+            0.0
+        };
+
+        let message_size_bytes = match serde_json::to_string(message) {
+            Ok(json) => json.len(),
+            Err(_) => 0,
+        };
+
+        self.record_metric(SyncMetric {
+            timestamp: receive_time,
+            message_type: format!("{:?}", message.message_type),
+            latency_ms,
+            message_size_bytes,
+            kind: MetricKind::Received,
+        })
+        .await;
+    }
+
+    /// Record a broadcast message of `size_bytes` serialized JSON; messages that expect
+    /// an ack are remembered so the ack yields a round-trip latency
+    pub async fn record_send(&self, message: &SyncMessage, size_bytes: usize) {
+        let now = chrono::Utc::now().timestamp_millis();
+        let message_type = format!("{:?}", message.message_type);
+        if message.requires_ack {
+            let mut in_flight = self.in_flight.write().await;
+            in_flight.retain(|_, (sent_at, _)| now - *sent_at < IN_FLIGHT_MAX_AGE_MS);
+            in_flight.insert(message.message_id.clone(), (now, message_type.clone()));
         }
+        self.record_metric(SyncMetric {
+            timestamp: now,
+            message_type,
+            latency_ms: 0.0,
+            message_size_bytes: size_bytes,
+            kind: MetricKind::Sent,
+        })
+        .await;
+    }
+
+    /// Record the round trip of a sent message acknowledged at `receive_time`.
+    /// Each slave's ack is a separate sample. `last_sent` is when the copy that slave
+    /// acknowledged went out, so resends don't inflate the figure; the broadcast time
+    /// is used when it's unknown.
+    pub async fn record_ack(&self, message_id: &str, receive_time: i64, last_sent: Option<i64>) {
+        let Some((sent_at, message_type)) = self.in_flight.read().await.get(message_id).cloned()
+        else {
+            return;
+        };
+        let sent_at = last_sent.map_or(sent_at, |last_sent| last_sent.max(sent_at));
+        self.record_metric(SyncMetric {
+            timestamp: receive_time,
+            message_type,
+            latency_ms: (receive_time - sent_at).max(0) as f64,
+            message_size_bytes: 0,
+            kind: MetricKind::Acked,
+        })
+        .await;
     }
 
     pub async fn record_metric(&self, metric: SyncMetric) {
@@ -220,9 +976,68 @@ impl PerformanceMonitor {
         }
     }
 
+    /// Write the retained metrics to `path` as JSON
+    pub async fn save_to_disk(&self, path: &std::path::Path) -> Result<(), String> {
+        let json = {
+            let metrics = self.metrics.read().await;
+            serde_json::to_string(&*metrics)
+                .map_err(|e| format!("Failed to serialize metrics: {}", e))?
+        };
+        fs::write(path, json)
+            .await
+            .map_err(|e| format!("Failed to write metrics to {:?}: {}", path, e))
+    }
+
+    /// Replace the metrics with those saved at `path`, keeping the newest that fit.
+    /// A missing or unreadable file leaves the metrics empty.
+    pub async fn load_from_disk(&self, path: &std::path::Path) {
+        let saved: VecDeque<SyncMetric> = match fs::read_to_string(path).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Ignoring corrupt metrics file {:?}: {}", path, e);
+                VecDeque::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(e) => {
+                eprintln!("Failed to read metrics file {:?}: {}", path, e);
+                VecDeque::new()
+            }
+        };
+
+        let mut metrics = self.metrics.write().await;
+        let max_capacity = metrics.capacity();
+        let skip = saved.len().saturating_sub(max_capacity);
+        metrics.clear();
+        metrics.extend(saved.into_iter().skip(skip));
+        println!("Restored {} performance metrics", metrics.len());
+    }
+
+    /// Retained metrics as CSV, oldest first
+    pub async fn to_csv(&self) -> String {
+        let mut csv = String::from("timestamp,kind,message_type,latency_ms,message_size_bytes\n");
+        for metric in self.metrics.read().await.iter() {
+            let kind = match metric.kind {
+                MetricKind::Received => "received",
+                MetricKind::Sent => "sent",
+                MetricKind::Acked => "acked",
+            };
+            csv.push_str(&format!(
+                "{},{},{},{:.3},{}\n",
+                metric.timestamp,
+                kind,
+                metric.message_type,
+                metric.latency_ms,
+                metric.message_size_bytes
+            ));
+        }
+        csv
+    }
+
     pub async fn get_metrics(&self) -> PerformanceMetrics {
         let metrics = self.metrics.read().await;
         let recent_metrics: Vec<SyncMetric> = metrics.iter().cloned().collect();
+        let clock = *self.clock.read().await;
+        let clock_offset_ms = clock.map(|c| c.offset_ms);
+        let heartbeat_rtt_ms = clock.map(|c| c.rtt_ms);
 
         if recent_metrics.is_empty() {
             return PerformanceMetrics {
@@ -231,28 +1046,42 @@ impl PerformanceMonitor {
                 messages_per_second: 0.0,
                 total_bytes: 0,
                 recent_metrics: vec![],
+                clock_offset_ms,
+                heartbeat_rtt_ms,
             };
         }
 
-        let total_messages = recent_metrics.len();
-        let average_latency =
-            recent_metrics.iter().map(|m| m.latency_ms).sum::<f64>() / total_messages as f64;
+        // Acks only add latency samples; sends have no latency of their own
+        let messages: Vec<&SyncMetric> = recent_metrics
+            .iter()
+            .filter(|m| m.kind != MetricKind::Acked)
+            .collect();
+        let latencies: Vec<f64> = recent_metrics
+            .iter()
+            .filter(|m| m.kind != MetricKind::Sent)
+            .map(|m| m.latency_ms)
+            .collect();
+
+        let total_messages = messages.len();
+        let average_latency = if latencies.is_empty() {
+            0.0
+        } else {
+            latencies.iter().sum::<f64>() / latencies.len() as f64
+        };
 
-        let total_bytes: usize = recent_metrics.iter().map(|m| m.message_size_bytes).sum();
+        let total_bytes: usize = messages.iter().map(|m| m.message_size_bytes).sum();
 
-        // Calculate messages per second (based on time span of recent metrics)
-        let messages_per_second = if recent_metrics.len() > 1 {
-            let time_span_secs = (recent_metrics.last().unwrap().timestamp
-                - recent_metrics.first().unwrap().timestamp)
-                as f64
-                / 1000.0;
-            if time_span_secs > 0.0 {
-                total_messages as f64 / time_span_secs
-            } else {
-                0.0
+        // Calculate messages per second (based on time span of recent messages)
+        let messages_per_second = match (messages.first(), messages.last()) {
+            (Some(first), Some(last)) if messages.len() > 1 => {
+                let time_span_secs = (last.timestamp - first.timestamp) as f64 / 1000.0;
+                if time_span_secs > 0.0 {
+                    total_messages as f64 / time_span_secs
+                } else {
+                    0.0
+                }
             }
-        } else {
-            0.0
+            _ => 0.0,
         };
 
         PerformanceMetrics {
@@ -261,8 +1090,58 @@ impl PerformanceMonitor {
             messages_per_second,
             total_bytes,
             recent_metrics: recent_metrics.into_iter().rev().take(100).collect(), // Last 100 metrics
+            clock_offset_ms,
+            heartbeat_rtt_ms,
         }
     }
+
+    /// The retained metrics grouped by message type, slowest average latency first.
+    /// Counted like `get_metrics`; empty when nothing has been recorded.
+    pub async fn get_metrics_by_type(&self) -> Vec<MessageTypeMetrics> {
+        let metrics = self.metrics.read().await;
+        let mut by_type: HashMap<&str, (MessageTypeMetrics, f64)> = HashMap::new();
+        for metric in metrics.iter() {
+            let (entry, latency_sum) =
+                by_type
+                    .entry(metric.message_type.as_str())
+                    .or_insert_with(|| {
+                        (
+                            MessageTypeMetrics {
+                                message_type: metric.message_type.clone(),
+                                count: 0,
+                                latency_samples: 0,
+                                average_latency_ms: 0.0,
+                                total_bytes: 0,
+                            },
+                            0.0,
+                        )
+                    });
+            if metric.kind != MetricKind::Acked {
+                entry.count += 1;
+                entry.total_bytes += metric.message_size_bytes;
+            }
+            if metric.kind != MetricKind::Sent {
+                entry.latency_samples += 1;
+                *latency_sum += metric.latency_ms;
+            }
+        }
+
+        let mut grouped: Vec<MessageTypeMetrics> = by_type
+            .into_values()
+            .map(|(mut entry, latency_sum)| {
+                if entry.latency_samples > 0 {
+                    entry.average_latency_ms = latency_sum / entry.latency_samples as f64;
+                }
+                entry
+            })
+            .collect();
+        grouped.sort_by(|a, b| {
+            b.average_latency_ms
+                .total_cmp(&a.average_latency_ms)
+                .then_with(|| a.message_type.cmp(&b.message_type))
+        });
+        grouped
+    }
 }
 
 #[derive(Clone)]
@@ -277,12 +1156,21 @@ pub struct AppState {
     // Slave mode components
     pub slave_client: Arc<RwLock<Option<Arc<SlaveClient>>>>,
     pub slave_sync: Arc<RwLock<Option<Arc<SlaveSync>>>>,
+    // Bidirectional mode: watches the slave's own OBS for changes to send to the master
+    pub local_change_sync: Arc<RwLock<Option<Arc<MasterSync>>>>,
+    pub local_event_handler: Arc<RwLock<Option<Arc<OBSEventHandler>>>>,
     // Message channels
     pub sync_message_tx: Arc<Mutex<Option<mpsc::UnboundedSender<SyncMessage>>>>,
     // Tauri app handle
     pub app_handle: Arc<RwLock<Option<tauri::AppHandle>>>,
     // Performance monitoring
     pub performance_monitor: Arc<PerformanceMonitor>,
+    // Active sync profile
+    pub sync_profile: Arc<RwLock<SyncProfile>>,
+    // Settings as last read or written by this process; baseline for reload_settings
+    known_settings: Arc<RwLock<Option<AppSettings>>>,
+    // Set once shutdown() has run
+    shut_down: Arc<AtomicBool>,
 }
 
 impl AppState {
@@ -296,31 +1184,105 @@ impl AppState {
             obs_event_handler: Arc::new(RwLock::new(None)),
             slave_client: Arc::new(RwLock::new(None)),
             slave_sync: Arc::new(RwLock::new(None)),
+            local_change_sync: Arc::new(RwLock::new(None)),
+            local_event_handler: Arc::new(RwLock::new(None)),
             sync_message_tx: Arc::new(Mutex::new(None)),
             app_handle: Arc::new(RwLock::new(None)),
             performance_monitor: Arc::new(PerformanceMonitor::new(1000)), // Keep last 1000 metrics
+            sync_profile: Arc::new(RwLock::new(SyncProfile::default())),
+            known_settings: Arc::new(RwLock::new(None)),
+            shut_down: Arc::new(AtomicBool::new(false)),
         }
     }
 
     pub async fn set_app_handle(&self, handle: tauri::AppHandle) {
         *self.app_handle.write().await = Some(handle);
     }
-}
 
-#[tauri::command]
-pub async fn connect_obs(
-    state: State<'_, AppState>,
-    config: OBSConnectionConfig,
-) -> Result<(), String> {
-    state
-        .obs_client
-        .connect(config)
-        .await
-        .map_err(|e| e.to_string())
-}
+    /// Record the saved settings as those in effect, so `reload_settings` can tell what
+    /// was edited on disk since launch (needs the app handle)
+    pub async fn load_known_settings(&self) {
+        match read_settings(self).await {
+            Ok(settings) => *self.known_settings.write().await = Some(settings),
+            Err(e) => eprintln!("Failed to load settings: {}", e),
+        }
+    }
 
-#[tauri::command]
-pub async fn disconnect_obs(state: State<'_, AppState>) -> Result<(), String> {
+    /// Restore the metrics saved by the previous run (needs the app handle)
+    pub async fn load_metrics(&self) {
+        match get_app_data_file(self, METRICS_FILE).await {
+            Ok(path) => self.performance_monitor.load_from_disk(&path).await,
+            Err(e) => eprintln!("Failed to locate metrics file: {}", e),
+        }
+    }
+
+    async fn save_metrics(&self) {
+        let result = match get_app_data_file(self, METRICS_FILE).await {
+            Ok(path) => self.performance_monitor.save_to_disk(&path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to save performance metrics: {}", e);
+        }
+    }
+
+    /// Stop every sync subsystem in order. Called on app exit; safe to call more than once.
+    pub async fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        println!("Shutting down sync subsystems...");
+
+        // Master: stop producing messages first, then close slave connections
+        if let Some(master_sync) = self.master_sync.write().await.take() {
+            master_sync.stop_monitoring().await;
+        }
+        *self.obs_event_handler.write().await = None;
+        if let Some(server) = self.master_server.write().await.take() {
+            server.stop().await;
+        }
+        *self.sync_message_tx.lock().await = None;
+
+        // Slave: stop checking, then disconnect from the master
+        if let Some(local_change_sync) = self.local_change_sync.write().await.take() {
+            local_change_sync.stop_monitoring().await;
+        }
+        *self.local_event_handler.write().await = None;
+        if let Some(slave_sync) = self.slave_sync.write().await.take() {
+            slave_sync.stop_periodic_check().await;
+            slave_sync.stop_image_cleanup().await;
+        }
+        if let Some(client) = self.slave_client.write().await.take() {
+            client.disconnect().await;
+        }
+
+        if let Err(e) = self.obs_client.disconnect().await {
+            eprintln!("Failed to disconnect from OBS: {}", e);
+        }
+        self.save_metrics().await;
+
+        // Make sure nothing is left in the output buffers when the process exits
+        println!("Shutdown complete");
+        tracing::info!("Shutdown complete");
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+    }
+}
+
+#[tauri::command]
+pub async fn connect_obs(
+    state: State<'_, AppState>,
+    config: OBSConnectionConfig,
+) -> Result<(), String> {
+    state
+        .obs_client
+        .connect(config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn disconnect_obs(state: State<'_, AppState>) -> Result<(), String> {
     state
         .obs_client
         .disconnect()
@@ -344,8 +1306,55 @@ pub async fn get_app_mode(state: State<'_, AppState>) -> Result<Option<AppMode>,
     Ok(state.mode.read().await.clone())
 }
 
+/// "Take": transition preview to program on this machine's OBS. On a master the
+/// resulting scene change is synced to slaves like any other.
+#[tauri::command]
+pub async fn trigger_studio_transition(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        if slave_sync.is_dry_run() {
+            return Err("Observe-only mode is on; OBS is not changed".to_string());
+        }
+    }
+    let client_arc = state.obs_client.get_client_arc();
+    let client_lock = client_arc.read().await;
+    let client = client_lock.as_ref().ok_or("OBS is not connected")?;
+    OBSCommands::trigger_studio_transition(client)
+        .await
+        .map_err(|e| format!("Failed to trigger transition: {:#}", e))
+}
+
+/// Certificate and key from the master settings, or the generated self-signed pair
+async fn load_master_tls(state: &AppState, master: &MasterSettings) -> Result<MasterTls, String> {
+    let (cert_path, key_path) = match (&master.tls_cert_path, &master.tls_key_path) {
+        (Some(cert), Some(key)) => (PathBuf::from(cert), PathBuf::from(key)),
+        _ => {
+            let cert_path = get_app_data_file(state, "master_cert.pem").await?;
+            let key_path = get_app_data_file(state, "master_key.pem").await?;
+            tls::ensure_self_signed(&cert_path, &key_path, tls::local_names())
+                .map_err(|e| e.to_string())?;
+            (cert_path, key_path)
+        }
+    };
+    MasterTls::load(&cert_path, &key_path).map_err(|e| format!("Failed to load TLS: {}", e))
+}
+
+/// Fingerprint of the certificate the running master serves, for pinning on slaves
+#[tauri::command]
+pub async fn get_master_tls_fingerprint(
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    match state.master_server.read().await.as_ref() {
+        Some(server) => Ok(server.tls_fingerprint().await),
+        None => Err("Master server is not running".to_string()),
+    }
+}
+
 #[tauri::command]
 pub async fn start_master_server(state: State<'_, AppState>, port: u16) -> Result<(), String> {
+    start_master(&state, port).await
+}
+
+async fn start_master(state: &AppState, port: u16) -> Result<(), String> {
     // Check if OBS is connected
     if !state.obs_client.is_connected().await {
         return Err("OBS is not connected".to_string());
@@ -354,25 +1363,157 @@ pub async fn start_master_server(state: State<'_, AppState>, port: u16) -> Resul
     // Update port
     *state.network_port.write().await = port;
 
+    let settings = read_settings(state).await.unwrap_or_default();
+    *state.sync_profile.write().await = settings.sync_profile;
+    let master_tls = if settings.master.use_tls {
+        let master_tls = load_master_tls(state, &settings.master).await?;
+        println!(
+            "Master TLS enabled, certificate fingerprint: {}",
+            master_tls.fingerprint
+        );
+        Some(master_tls)
+    } else {
+        None
+    };
+
     // Create MasterSync
     let (master_sync, sync_rx) = MasterSync::new(state.obs_client.clone());
+    master_sync.set_tuning(settings.tuning()).await;
+    master_sync
+        .set_scene_target_overrides(settings.scene_target_overrides.clone())
+        .await;
+    master_sync.set_filters(settings.sync_filters.clone()).await;
+    master_sync
+        .set_image_reencode(settings.master.image_reencode.clone())
+        .await;
+    master_sync
+        .set_transform_anchors(settings.master.transform_anchors.clone())
+        .await;
     let master_sync = Arc::new(master_sync);
     *state.master_sync.write().await = Some(master_sync.clone());
 
     // Create and start MasterServer
-    let master_server = Arc::new(MasterServer::new(port));
+    let master_server = Arc::new(MasterServer::new(port, settings.master.auth_token.clone()));
+    master_server
+        .set_handshake_guard(settings.master.handshake_guard)
+        .await;
+    master_server
+        .set_keepalive(settings.keepalive.clone())
+        .await;
+    master_server
+        .set_heartbeat(HeartbeatConfig {
+            interval_secs: settings.master.heartbeat_interval_secs,
+            timeout_secs: settings.master.heartbeat_timeout_secs,
+        })
+        .await;
+    master_server.set_tls(master_tls).await;
+    master_server.set_bandwidth_limit_kbps(settings.master.bandwidth_limit_kbps);
+
+    // Warn the UI about scenes where name-based matching is ambiguous
+    let app_handle_for_ambiguous = state.app_handle.clone();
+    master_sync
+        .set_ambiguous_sources_callback(move |sources| {
+            let app_handle = app_handle_for_ambiguous.clone();
+            tokio::spawn(async move {
+                if let Some(handle) = app_handle.read().await.as_ref() {
+                    if let Err(e) = handle.emit("ambiguous-sources", sources) {
+                        eprintln!("Failed to emit ambiguous sources event: {}", e);
+                    }
+                }
+            });
+        })
+        .await;
+
+    // Emitted inline rather than from a spawned task, so progress events keep their order
+    if let Some(handle) = state.app_handle.read().await.clone() {
+        master_sync
+            .set_resync_progress_callback(move |progress| {
+                if let Err(e) = handle.emit("resync-progress", progress) {
+                    eprintln!("Failed to emit resync progress event: {}", e);
+                }
+            })
+            .await;
+    }
+
+    // Bidirectional slaves' own changes are applied here, then broadcast as usual
+    if settings.master.accept_slave_updates {
+        let master_sync_for_remote = master_sync.clone();
+        master_server
+            .set_remote_update_callback(move |client_id, message| {
+                let master_sync = master_sync_for_remote.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = master_sync.apply_remote_update(&message).await {
+                        eprintln!(
+                            "Failed to apply {:?} from {}: {}",
+                            message.message_type, client_id, e
+                        );
+                    }
+                });
+            })
+            .await;
+    }
+
+    // Emitted inline rather than from a spawned task, so the events keep their order
+    if let Some(handle) = state.app_handle.read().await.clone() {
+        master_server
+            .set_clients_changed_callback(move |clients| {
+                if let Err(e) = handle.emit("clients-changed", clients) {
+                    eprintln!("Failed to emit clients-changed event: {}", e);
+                }
+            })
+            .await;
+    }
+
+    // Resyncs of a single slave go straight to its connection. Weak, since the
+    // server's callbacks already keep the master sync alive.
+    let master_server_for_sender = Arc::downgrade(&master_server);
+    master_sync
+        .set_client_sender(move |client_id, message| {
+            let master_server = master_server_for_sender.upgrade();
+            async move {
+                master_server
+                    .ok_or_else(|| anyhow::anyhow!("Master server is not running"))?
+                    .send_to_client(&client_id, message)
+                    .await
+            }
+        })
+        .await;
 
     // Set up callback to send initial state when new slave connects
     let master_sync_for_callback = master_sync.clone();
+    let master_server_for_callback = Arc::downgrade(&master_server);
+    let app_handle_for_callback = state.app_handle.clone();
     master_server
         .set_initial_state_callback(move |client_id: String| {
             let master_sync_clone = master_sync_for_callback.clone();
+            let master_server = master_server_for_callback.clone();
+            let app_handle = app_handle_for_callback.clone();
             async move {
                 println!("Sending initial state to new slave: {}", client_id);
                 // Small delay to ensure connection is fully established
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                if let Err(e) = master_sync_clone.send_initial_state().await {
+                // Probes hang up right after the hello
+                let Some(master_server) = master_server.upgrade() else {
+                    return;
+                };
+                if !master_server.is_client_connected(&client_id).await {
+                    println!("{} disconnected before initial state", client_id);
+                    return;
+                }
+                let held_images = master_server.held_images(&client_id).await;
+                if let Err(e) = master_sync_clone
+                    .send_initial_state_to(&client_id, &held_images)
+                    .await
+                {
                     eprintln!("Failed to send initial state to {}: {}", client_id, e);
+                    emit_status_event(
+                        &app_handle,
+                        StatusEvent::new(
+                            StatusLevel::Error,
+                            format!("Failed to send initial state to {}: {}", client_id, e),
+                        ),
+                    )
+                    .await;
                 }
             }
         })
@@ -387,6 +1528,9 @@ pub async fn start_master_server(state: State<'_, AppState>, port: u16) -> Resul
 
     // Create OBS event handler
     let (event_handler, event_rx) = OBSEventHandler::new();
+    event_handler
+        .set_raw_passthrough(settings.master.raw_event_passthrough.clone())
+        .await;
     let event_handler = Arc::new(event_handler);
 
     // Start listening to OBS events
@@ -426,20 +1570,141 @@ pub async fn stop_master_server(state: State<'_, AppState>) -> Result<(), String
     Ok(())
 }
 
+/// Sources added more than once to the same scene on the master, where sync by
+/// source name can't tell the items apart
+#[tauri::command]
+pub async fn get_ambiguous_sources(
+    state: State<'_, AppState>,
+) -> Result<Vec<AmbiguousSource>, String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    Ok(master_sync.ambiguous_sources().await)
+}
+
+/// How long the outgoing master keeps running after broadcasting a handoff
+const HANDOFF_FLUSH_MS: u64 = 1000;
+
+/// Move the master role to another machine: slaves are told to reconnect to
+/// `new_host:new_port` (retrying until it is reachable), then this server stops.
+#[tauri::command]
+pub async fn handoff_master(
+    state: State<'_, AppState>,
+    new_host: String,
+    new_port: u16,
+) -> Result<(), String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    master_sync
+        .send_handoff(&new_host, new_port)
+        .map_err(|e| e.to_string())?;
+    println!("Handing off master role to {}:{}", new_host, new_port);
+
+    // Give the broadcast time to reach slaves before the connections close
+    tokio::time::sleep(tokio::time::Duration::from_millis(HANDOFF_FLUSH_MS)).await;
+    stop_master_server(state).await
+}
+
+/// Client TLS config for connecting to a master, or None for plain ws://
+fn slave_tls_config(
+    use_tls: bool,
+    ca_cert_path: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Result<Option<Arc<ClientConfig>>, String> {
+    if !use_tls {
+        return Ok(None);
+    }
+    tls::client_config(ca_cert_path.map(std::path::Path::new), fingerprint)
+        .map(Some)
+        .map_err(|e| format!("Invalid TLS configuration: {}", e))
+}
+
+/// Check that an obs-sync master answers at `host:port` without entering slave mode.
+/// The TLS fields mean the same as in `NetworkConfig`; when `use_tls` is left out the
+/// saved slave settings are used.
+#[tauri::command]
+pub async fn probe_master(
+    state: State<'_, AppState>,
+    host: String,
+    port: u16,
+    path_token: Option<String>,
+    use_tls: Option<bool>,
+    tls_ca_cert_path: Option<String>,
+    tls_fingerprint: Option<String>,
+) -> Result<MasterProbeResult, String> {
+    let tls = match use_tls {
+        Some(use_tls) => slave_tls_config(
+            use_tls,
+            tls_ca_cert_path.as_deref(),
+            tls_fingerprint.as_deref(),
+        )?,
+        None => {
+            let slave = read_settings(&state).await.unwrap_or_default().slave;
+            slave_tls_config(
+                slave.use_tls,
+                slave.tls_ca_cert_path.as_deref(),
+                slave.tls_fingerprint.as_deref(),
+            )?
+        }
+    };
+    probe::probe_master(&host, port, path_token.as_deref(), tls)
+        .await
+        .map_err(|e| format!("{:#}", e))
+}
+
 #[tauri::command]
 pub async fn connect_to_master(
     state: State<'_, AppState>,
     config: NetworkConfig,
 ) -> Result<(), String> {
+    connect_slave(&state, config).await
+}
+
+async fn connect_slave(state: &AppState, config: NetworkConfig) -> Result<(), String> {
     // Check if OBS is connected
     if !state.obs_client.is_connected().await {
         return Err("OBS is not connected".to_string());
     }
 
     println!("Connecting to master at {}:{}", config.host, config.port);
+    if !config.fallback_masters.is_empty() {
+        let fallbacks: Vec<String> = config
+            .fallback_masters
+            .iter()
+            .map(MasterEndpoint::to_string)
+            .collect();
+        println!("Fallback masters: {}", fallbacks.join(", "));
+    }
+
+    let settings = read_settings(state).await.unwrap_or_default();
 
     // Create SlaveClient
-    let slave_client = Arc::new(SlaveClient::new(config.host.clone(), config.port));
+    let tls = slave_tls_config(
+        config.use_tls,
+        config.tls_ca_cert_path.as_deref(),
+        config.tls_fingerprint.as_deref(),
+    )?;
+    let slave_client = Arc::new(
+        SlaveClient::new(config.host.clone(), config.port)
+            .with_fallback_masters(config.fallback_masters.clone())
+            .with_path_token(config.path_token.clone())
+            .with_keepalive(settings.keepalive.clone())
+            .with_reconnect_strategy(settings.slave.reconnect_strategy)
+            .with_max_reconnect_attempts(settings.slave.max_reconnect_attempts)
+            .with_tls(tls)
+            .with_auth_token(config.auth_token.clone())
+            .with_identity(
+                load_machine_id(state).await,
+                settings.slave.display_name.clone(),
+            ),
+    );
 
     // Set up connection status callback to emit Tauri events
     let app_handle_for_callback = state.app_handle.clone();
@@ -455,6 +1720,13 @@ pub async fn connect_to_master(
             });
         })
         .await;
+    let app_handle_for_status = state.app_handle.clone();
+    slave_client
+        .set_status_callback(move |event| {
+            let app_handle = app_handle_for_status.clone();
+            tokio::spawn(async move { emit_status_event(&app_handle, event).await });
+        })
+        .await;
 
     // Connect to master and get sync message receiver and sender
     let (sync_rx, send_tx) = slave_client
@@ -462,21 +1734,84 @@ pub async fn connect_to_master(
         .await
         .map_err(|e| format!("Failed to connect to master: {}", e))?;
 
-    *state.slave_client.write().await = Some(slave_client);
+    *state.slave_client.write().await = Some(slave_client.clone());
 
     // Create SlaveSync
+    *state.sync_profile.write().await = settings.sync_profile;
+    let tuning = settings.tuning();
     let (slave_sync, alert_rx) = SlaveSync::new(state.obs_client.clone());
     slave_sync.set_state_report_sender(send_tx).await;
+    slave_sync.set_tuning(tuning.clone()).await;
+    slave_sync
+        .set_scene_target_overrides(settings.scene_target_overrides.clone())
+        .await;
+    slave_sync
+        .set_transform_mask(settings.slave.transform_mask)
+        .await;
+    slave_sync
+        .set_interpolation(settings.slave.interpolation)
+        .await;
+    slave_sync.set_apply_concurrency(settings.slave.apply_concurrency);
+    slave_sync.set_sync_hysteresis(settings.slave.sync_hysteresis);
+    slave_sync.set_prefer_name_matching(settings.slave.prefer_name_matching);
+    slave_sync.set_dry_run(settings.slave.dry_run);
+    slave_sync.set_strict_state_sync(settings.slave.strict_state_sync);
+    slave_sync
+        .set_image_cache_limits(settings.slave.image_cache)
+        .await;
+    slave_sync
+        .set_ignored_sources(settings.slave.ignored_sources.clone())
+        .await;
+    let (planned_action_tx, mut planned_action_rx) = mpsc::unbounded_channel::<PlannedAction>();
+    slave_sync
+        .set_planned_action_sender(planned_action_tx)
+        .await;
+    let (state_sync_report_tx, mut state_sync_report_rx) =
+        mpsc::unbounded_channel::<StateSyncReport>();
+    slave_sync
+        .set_state_sync_report_sender(state_sync_report_tx)
+        .await;
+    match get_app_data_file(state, "slave_cache.json").await {
+        Ok(path) => slave_sync.set_cache_path(path).await,
+        Err(e) => eprintln!("Slave cache disabled: {}", e),
+    }
     let slave_sync = Arc::new(slave_sync);
     *state.slave_sync.write().await = Some(slave_sync.clone());
 
-    // Start periodic state checking (every 5 seconds)
-    slave_sync.start_periodic_check(5);
-    println!("Started periodic desync detection (interval: 5s)");
+    // Images still on disk don't need their bytes in the next StateSync
+    let slave_sync_for_images = Arc::downgrade(&slave_sync);
+    slave_client
+        .set_held_images_callback(move || {
+            let slave_sync = slave_sync_for_images.upgrade();
+            async move {
+                match slave_sync {
+                    Some(slave_sync) => slave_sync.held_image_hashes().await,
+                    None => Vec::new(),
+                }
+            }
+        })
+        .await;
+
+    if let Some(local_change_sync) = state.local_change_sync.write().await.take() {
+        local_change_sync.stop_monitoring().await;
+    }
+    *state.local_event_handler.write().await = None;
+    if matches!(*state.mode.read().await, Some(AppMode::Bidirectional)) {
+        start_local_change_forwarding(state, &settings, slave_sync.clone()).await?;
+    }
+
+    // Start periodic state checking
+    slave_sync.start_periodic_check().await;
+    slave_sync.start_image_cleanup().await;
+    println!(
+        "Started periodic desync detection (interval: {}s)",
+        tuning.desync_check_interval_secs
+    );
 
     // Start processing sync messages
     let slave_sync_for_processing = slave_sync.clone();
     let performance_monitor_for_processing = state.performance_monitor.clone();
+    let app_handle_for_processing = state.app_handle.clone();
     tokio::spawn(async move {
         let mut rx = sync_rx;
         let mut first_message = true;
@@ -487,34 +1822,65 @@ pub async fn connect_to_master(
                 first_message = false;
             }
 
-            // Calculate latency and record metric
+            // Record metric (heartbeat replies also refresh the clock offset)
             let receive_time = chrono::Utc::now().timestamp_millis();
-            let latency_ms = if message.timestamp > 0 {
-                (receive_time - message.timestamp) as f64
-            } else {
-                0.0
-            };
-
-            // Calculate message size
-            let message_size_bytes = match serde_json::to_string(&message) {
-                Ok(json) => json.len(),
-                Err(_) => 0,
-            };
-
-            // Record metric
-            let message_type_str = format!("{:?}", message.message_type);
-            let metric = SyncMetric {
-                timestamp: receive_time,
-                message_type: message_type_str,
-                latency_ms,
-                message_size_bytes,
-            };
+            if message.message_type == SyncMessageType::Heartbeat {
+                performance_monitor_for_processing
+                    .record_heartbeat(&message, receive_time)
+                    .await;
+            }
             performance_monitor_for_processing
-                .record_metric(metric)
+                .record_receive(&message, receive_time)
                 .await;
 
-            if let Err(e) = slave_sync_for_processing.apply_sync_message(message).await {
-                eprintln!("Failed to apply sync message: {}", e);
+            let message_type = message.message_type.clone();
+            let ack_id = message.requires_ack.then(|| message.message_id.clone());
+            match slave_sync_for_processing.apply_sync_message(message).await {
+                Ok(()) => {
+                    // Unacknowledged messages are resent by the master
+                    if let Some(message_id) = ack_id {
+                        if let Err(e) = slave_sync_for_processing.send_ack(&message_id).await {
+                            eprintln!("Failed to acknowledge {:?}: {}", message_type, e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to apply sync message: {}", e);
+                    emit_status_event(
+                        &app_handle_for_processing,
+                        StatusEvent::new(
+                            StatusLevel::Error,
+                            format!("Failed to apply {:?}: {}", message_type, e),
+                        ),
+                    )
+                    .await;
+                    slave_sync_for_processing
+                        .record_apply_error(format!("{:?}: {}", message_type, e))
+                        .await;
+                }
+            }
+        }
+    });
+
+    // Forward dry-run planned actions to the frontend
+    let app_handle_for_plans = state.app_handle.clone();
+    tokio::spawn(async move {
+        while let Some(action) = planned_action_rx.recv().await {
+            if let Some(handle) = app_handle_for_plans.read().await.as_ref() {
+                if let Err(e) = handle.emit("planned-action", action) {
+                    eprintln!("Failed to emit planned action event: {}", e);
+                }
+            }
+        }
+    });
+
+    let app_handle_for_reports = state.app_handle.clone();
+    tokio::spawn(async move {
+        while let Some(report) = state_sync_report_rx.recv().await {
+            if let Some(handle) = app_handle_for_reports.read().await.as_ref() {
+                if let Err(e) = handle.emit("state-sync-report", report) {
+                    eprintln!("Failed to emit state sync report event: {}", e);
+                }
             }
         }
     });
@@ -540,6 +1906,243 @@ pub async fn connect_to_master(
     Ok(())
 }
 
+/// Bidirectional mode: watch this slave's OBS the way a master does and send scene
+/// switches and transforms to the master, which applies and re-broadcasts them
+async fn start_local_change_forwarding(
+    state: &AppState,
+    settings: &AppSettings,
+    slave_sync: Arc<SlaveSync>,
+) -> Result<(), String> {
+    let (local_change_sync, mut local_rx) = MasterSync::new(state.obs_client.clone());
+    local_change_sync.set_tuning(settings.tuning()).await;
+    local_change_sync
+        .set_filters(settings.sync_filters.clone())
+        .await;
+
+    let (event_handler, event_rx) = OBSEventHandler::new();
+    {
+        let client_arc = state.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let obs_client = client_lock
+            .as_ref()
+            .ok_or_else(|| "OBS is not connected".to_string())?;
+        event_handler
+            .start_listening(obs_client)
+            .await
+            .map_err(|e| format!("Failed to start OBS event listener: {}", e))?;
+    }
+    local_change_sync.start_monitoring(event_rx).await;
+    *state.local_event_handler.write().await = Some(Arc::new(event_handler));
+
+    tokio::spawn(async move {
+        while let Some(message) = local_rx.recv().await {
+            if !matches!(
+                message.message_type,
+                SyncMessageType::SceneChange
+                    | SyncMessageType::TransformUpdate
+                    | SyncMessageType::TransformBatch
+            ) {
+                continue;
+            }
+            if let Err(e) = slave_sync.send_local_update(message).await {
+                eprintln!("Failed to send local change to master: {}", e);
+            }
+        }
+    });
+
+    *state.local_change_sync.write().await = Some(Arc::new(local_change_sync));
+    println!("Bidirectional sync: sending local scene and transform changes to the master");
+    Ok(())
+}
+
+/// Connect to OBS and start the configured role from saved settings. A failed step is
+/// reported through an `auto-start-failed` event.
+pub async fn run_auto_start(state: &AppState) {
+    let result = match read_settings(state).await {
+        Ok(settings) => auto_start(state, &settings).await,
+        Err(e) => Err(AutoStartFailure {
+            step: "settings".to_string(),
+            error: e,
+        }),
+    };
+    if let Err(failure) = result {
+        emit_auto_start_failure(state, failure).await;
+    }
+}
+
+/// Run the `auto_start` section of `settings`; later steps are skipped once one fails
+async fn auto_start(state: &AppState, settings: &AppSettings) -> Result<(), AutoStartFailure> {
+    let auto_start = settings.auto_start.clone();
+    if !auto_start.connect_obs && auto_start.role.is_none() {
+        return Ok(());
+    }
+    println!("Auto-start: {:?}", auto_start);
+
+    if auto_start.connect_obs {
+        let config = OBSConnectionConfig {
+            host: settings.obs.host.clone(),
+            port: settings.obs.port,
+            password: Some(settings.obs.password.clone()).filter(|p| !p.is_empty()),
+        };
+        state
+            .obs_client
+            .connect(config)
+            .await
+            .map_err(|e| AutoStartFailure {
+                step: "obs".to_string(),
+                error: e.to_string(),
+            })?;
+    }
+
+    let Some(role) = auto_start.role else {
+        return Ok(());
+    };
+    *state.mode.write().await = Some(role.clone());
+    let result = match role {
+        AppMode::Master => {
+            let port = auto_start.port.unwrap_or(settings.master.default_port);
+            start_master(state, port).await
+        }
+        AppMode::Slave | AppMode::Bidirectional => {
+            let config = NetworkConfig {
+                host: auto_start
+                    .master_host
+                    .unwrap_or_else(|| settings.slave.default_host.clone()),
+                port: auto_start.port.unwrap_or(settings.slave.default_port),
+                path_token: None,
+                use_tls: settings.slave.use_tls,
+                tls_ca_cert_path: settings.slave.tls_ca_cert_path.clone(),
+                tls_fingerprint: settings.slave.tls_fingerprint.clone(),
+                auth_token: settings.slave.auth_token.clone(),
+                fallback_masters: settings.slave.fallback_masters.clone(),
+            };
+            connect_slave(state, config).await
+        }
+    };
+    result.map_err(|error| AutoStartFailure {
+        step: match role {
+            AppMode::Master => "master",
+            AppMode::Slave | AppMode::Bidirectional => "slave",
+        }
+        .to_string(),
+        error,
+    })
+}
+
+/// Follow the OBS connection for the life of the app. Drops and reconnects are shown
+/// on screen; after a reconnect the event handlers listen on the new connection and
+/// the sync state is rebuilt, since a restarted OBS may not match what was synced.
+pub async fn watch_obs_connection(state: AppState) {
+    let mut events = state.obs_client.subscribe();
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if let Some(handle) = state.app_handle.read().await.as_ref() {
+            if let Err(e) = handle.emit("obs-connection", &event) {
+                eprintln!("Failed to emit OBS connection event: {}", e);
+            }
+        }
+        match event {
+            OBSConnectionEvent::Lost => {
+                emit_status_event(
+                    &state.app_handle,
+                    StatusEvent::new(StatusLevel::Warning, "Lost connection to OBS, reconnecting"),
+                )
+                .await;
+            }
+            OBSConnectionEvent::Reconnected { attempts } => {
+                emit_status_event(
+                    &state.app_handle,
+                    StatusEvent::new(
+                        StatusLevel::Info,
+                        format!("Reconnected to OBS after {} attempt(s)", attempts),
+                    ),
+                )
+                .await;
+                if let Err(e) = resume_after_obs_reconnect(&state).await {
+                    eprintln!("Failed to resume sync after OBS reconnect: {}", e);
+                    emit_status_event(
+                        &state.app_handle,
+                        StatusEvent::new(
+                            StatusLevel::Error,
+                            format!("Failed to resume sync after OBS reconnect: {}", e),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+}
+
+/// Re-subscribe the running event handlers to the new OBS connection and resync
+async fn resume_after_obs_reconnect(state: &AppState) -> Result<(), String> {
+    let handlers = [
+        state.obs_event_handler.read().await.clone(),
+        state.local_event_handler.read().await.clone(),
+    ];
+    {
+        let client_arc = state.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let obs_client = client_lock
+            .as_ref()
+            .ok_or_else(|| "OBS is not connected".to_string())?;
+        for event_handler in handlers.into_iter().flatten() {
+            event_handler
+                .start_listening(obs_client)
+                .await
+                .map_err(|e| format!("Failed to start OBS event listener: {}", e))?;
+        }
+    }
+
+    // The master pushes its fresh state to every slave; a slave asks for the master's
+    // A paused master leaves that to the resync when it resumes
+    if let Some(master_sync) = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .filter(|master_sync| !master_sync.is_paused())
+    {
+        master_sync
+            .send_initial_state(None)
+            .await
+            .map_err(|e| format!("Failed to resync slaves: {}", e))?;
+    }
+    if let Some(slave_client) = state.slave_client.read().await.clone() {
+        // What OBS shows now may not be what was applied before it went away
+        if let Some(slave_sync) = state.slave_sync.read().await.clone() {
+            slave_sync.clear_applied_cache().await;
+        }
+        slave_client
+            .request_resync()
+            .await
+            .map_err(|e| format!("Failed to request resync: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Put a connection or sync problem on screen as a `connection-status` event
+async fn emit_status_event(app_handle: &RwLock<Option<tauri::AppHandle>>, event: StatusEvent) {
+    if let Some(handle) = app_handle.read().await.as_ref() {
+        if let Err(e) = handle.emit("connection-status", event) {
+            eprintln!("Failed to emit connection status event: {}", e);
+        }
+    }
+}
+
+async fn emit_auto_start_failure(state: &AppState, failure: AutoStartFailure) {
+    eprintln!("Auto-start failed at {}: {}", failure.step, failure.error);
+    if let Some(handle) = state.app_handle.read().await.as_ref() {
+        if let Err(e) = handle.emit("auto-start-failed", failure) {
+            eprintln!("Failed to emit auto-start failure event: {}", e);
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn disconnect_from_master(state: State<'_, AppState>) -> Result<(), String> {
     // Disconnect slave client
@@ -548,7 +2151,14 @@ pub async fn disconnect_from_master(state: State<'_, AppState>) -> Result<(), St
     }
 
     // Clear slave components
-    *state.slave_sync.write().await = None;
+    if let Some(local_change_sync) = state.local_change_sync.write().await.take() {
+        local_change_sync.stop_monitoring().await;
+    }
+    *state.local_event_handler.write().await = None;
+    // The periodic check holds the state report sender, which keeps the client's send task alive
+    if let Some(slave_sync) = state.slave_sync.write().await.take() {
+        slave_sync.stop_periodic_check().await;
+    }
 
     println!("Disconnected from master");
     Ok(())
@@ -564,40 +2174,228 @@ pub async fn is_slave_connected(state: State<'_, AppState>) -> Result<bool, Stri
 }
 
 #[tauri::command]
-pub async fn get_slave_reconnection_status(
-    state: State<'_, AppState>,
-) -> Result<Option<crate::network::client::ReconnectionStatus>, String> {
+pub async fn retry_connection(state: State<'_, AppState>) -> Result<(), String> {
     if let Some(client) = state.slave_client.read().await.as_ref() {
-        Ok(Some(client.get_reconnection_status().await))
+        client
+            .retry_connection()
+            .await
+            .map_err(|e| format!("Failed to retry connection: {}", e))
     } else {
-        Ok(None)
+        Err("Not in slave mode".to_string())
     }
 }
 
+/// Change the slave's reconnect attempt limit without reconnecting (0 = retry forever).
+/// The limit is saved and used for future connections as well.
 #[tauri::command]
-pub async fn resync_all_slaves(state: State<'_, AppState>) -> Result<(), String> {
-    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
-        master_sync
-            .send_initial_state()
-            .await
-            .map_err(|e| format!("Failed to resync all slaves: {}", e))?;
-        println!("Resync triggered for all slaves");
-        Ok(())
-    } else {
-        Err("Master server is not running".to_string())
+pub async fn set_max_reconnect_attempts(
+    state: State<'_, AppState>,
+    attempts: u32,
+) -> Result<(), String> {
+    if let Some(client) = state.slave_client.read().await.as_ref() {
+        client.set_max_reconnect_attempts(attempts).await;
     }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.max_reconnect_attempts = (attempts != 0).then_some(attempts);
+    write_settings(&state, &settings).await?;
+
+    println!("Max reconnect attempts set to {}", attempts);
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn resync_specific_slave(
-    state: State<'_, AppState>,
-    client_id: String,
+pub async fn get_desync_report(state: State<'_, AppState>) -> Result<DesyncReport, String> {
+    let slave_sync = state
+        .slave_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Not in slave mode".to_string())?;
+    let diffs = slave_sync
+        .compare_now()
+        .await
+        .map_err(|e| format!("Failed to compare state: {}", e))?
+        .ok_or_else(|| "No state received from master yet".to_string())?;
+    Ok(DesyncReport::from_differences(&diffs))
+}
+
+#[tauri::command]
+pub async fn validate_media_availability(
+    state: State<'_, AppState>,
+) -> Result<Vec<MediaAvailability>, String> {
+    SlaveSync::validate_media_availability(&state.obs_client)
+        .await
+        .map_err(|e| format!("Failed to validate media availability: {}", e))
+}
+
+/// Make the local scene collection match the master's layout. Removes scenes and items,
+/// so it only runs with `confirm` set.
+#[tauri::command]
+pub async fn normalize_to_master(
+    state: State<'_, AppState>,
+    confirm: bool,
+) -> Result<Vec<NormalizeChange>, String> {
+    if !confirm {
+        return Err(
+            "normalize_to_master removes and reorders local scenes and items; pass confirm to proceed"
+                .to_string(),
+        );
+    }
+    let slave_sync = state
+        .slave_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Not in slave mode".to_string())?;
+    slave_sync
+        .normalize_to_master(tokio::time::Duration::from_secs(30))
+        .await
+        .map_err(|e| format!("Failed to normalize to master: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_slave_reconnection_status(
+    state: State<'_, AppState>,
+) -> Result<Option<crate::network::client::ReconnectionStatus>, String> {
+    if let Some(client) = state.slave_client.read().await.as_ref() {
+        Ok(Some(client.get_reconnection_status().await))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+pub async fn resync_all_slaves(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync
+            .send_initial_state(None)
+            .await
+            .map_err(|e| format!("Failed to resync all slaves: {}", e))?;
+        println!("Resync triggered for all slaves");
+        Ok(())
+    } else {
+        Err("Master server is not running".to_string())
+    }
+}
+
+/// Stop resyncs still collecting state; slaves keep what they have
+#[tauri::command]
+pub async fn cancel_resync(state: State<'_, AppState>) -> Result<(), String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    master_sync.cancel_resync();
+    println!("Cancelled state syncs in progress");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pause_master_sync(state: State<'_, AppState>) -> Result<(), String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    master_sync.pause();
+    println!("Master sync paused; OBS changes are not sent to slaves");
+    Ok(())
+}
+
+/// Resume sending OBS changes; with `resync`, slaves also get the full current state
+#[tauri::command]
+pub async fn resume_master_sync(state: State<'_, AppState>, resync: bool) -> Result<(), String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    master_sync.resume();
+    println!("Master sync resumed");
+    if resync {
+        master_sync
+            .send_initial_state(None)
+            .await
+            .map_err(|e| format!("Failed to resync all slaves: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Map an OBS hotkey name to the command slaves run when it is triggered (`None` removes it)
+#[tauri::command]
+pub async fn set_hotkey_command(
+    state: State<'_, AppState>,
+    hotkey_name: String,
+    command: Option<SemanticCommand>,
+) -> Result<(), String> {
+    let mut settings = read_settings(&state).await?;
+    match command.clone() {
+        Some(command) => settings
+            .master
+            .hotkey_commands
+            .insert(hotkey_name.clone(), command),
+        None => settings.master.hotkey_commands.remove(&hotkey_name),
+    };
+    write_settings(&state, &settings).await?;
+
+    println!("Hotkey {} mapped to {:?}", hotkey_name, command);
+    Ok(())
+}
+
+/// Trigger an OBS hotkey on the master and send its mapped command to slaves.
+/// obs-websocket doesn't report hotkey presses, so mapped hotkeys must be fired
+/// through this command (e.g. from the UI or a control surface) to propagate.
+#[tauri::command]
+pub async fn trigger_hotkey_command(
+    state: State<'_, AppState>,
+    hotkey_name: String,
+) -> Result<Option<SemanticCommand>, String> {
+    let master_sync = state
+        .master_sync
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+
+    {
+        let client_arc = state.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().ok_or("OBS is not connected")?;
+        client
+            .hotkeys()
+            .trigger_by_name(&hotkey_name, None)
+            .await
+            .map_err(|e| format!("Failed to trigger hotkey {}: {}", hotkey_name, e))?;
+    }
+
+    let command = read_settings(&state)
+        .await?
+        .master
+        .hotkey_commands
+        .get(&hotkey_name)
+        .cloned();
+    if let Some(command) = &command {
+        master_sync
+            .send_command(command)
+            .map_err(|e| e.to_string())?;
+        println!("Hotkey {} sent {:?} to slaves", hotkey_name, command);
+    }
+    Ok(command)
+}
+
+#[tauri::command]
+pub async fn resync_specific_slave(
+    state: State<'_, AppState>,
+    client_id: String,
 ) -> Result<(), String> {
     if let Some(master_sync) = state.master_sync.read().await.as_ref() {
-        // For now, resync all slaves (we can enhance this later to target specific client)
-        // The master server already handles sending to specific clients via the callback
         master_sync
-            .send_initial_state()
+            .send_initial_state(Some(&client_id))
             .await
             .map_err(|e| format!("Failed to resync slave {}: {}", client_id, e))?;
         println!("Resync triggered for slave: {}", client_id);
@@ -607,9 +2405,25 @@ pub async fn resync_specific_slave(
     }
 }
 
+/// Re-send one source's transforms, settings, image and filters to all slaves
+#[tauri::command]
+pub async fn push_source(state: State<'_, AppState>, source_name: String) -> Result<usize, String> {
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync
+            .push_source(&source_name)
+            .await
+            .map_err(|e| format!("Failed to push {}: {}", source_name, e))
+    } else {
+        Err("Master server is not running".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn request_resync_from_master(state: State<'_, AppState>) -> Result<(), String> {
     if let Some(slave_client) = state.slave_client.read().await.as_ref() {
+        if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+            slave_sync.expect_full_resync();
+        }
         slave_client
             .request_resync()
             .await
@@ -628,17 +2442,376 @@ pub async fn set_sync_targets(
 ) -> Result<(), String> {
     println!("Setting sync targets: {:?}", targets);
 
-    // Update targets for master mode
     if let Some(master_sync) = state.master_sync.read().await.as_ref() {
-        master_sync.set_active_targets(targets).await;
-    } else {
-        // Just log the targets if not in master mode (slave mode doesn't need to set targets)
-        println!("Sync targets set (not in master mode)");
+        master_sync.set_active_targets(targets.clone()).await;
+    }
+    // Slaves resolve scene overrides against the same global targets as the master
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_sync_targets(targets).await;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_scene_target_overrides(
+    state: State<'_, AppState>,
+    overrides: SceneTargetOverrides,
+) -> Result<(), String> {
+    println!("Setting scene target overrides: {:?}", overrides);
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync
+            .set_scene_target_overrides(overrides.clone())
+            .await;
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync
+            .set_scene_target_overrides(overrides.clone())
+            .await;
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.scene_target_overrides = overrides;
+    write_settings(&state, &settings).await
+}
+
+#[tauri::command]
+pub async fn get_sync_filters(state: State<'_, AppState>) -> Result<SyncFilters, String> {
+    Ok(read_settings(&state).await?.sync_filters)
+}
+
+/// Replace one filter list ("scenes", "sources", "includeSources", "inputKinds" or
+/// "messageTypes")
+#[tauri::command]
+pub async fn set_sync_filter(
+    state: State<'_, AppState>,
+    kind: String,
+    list: Vec<String>,
+) -> Result<SyncFilters, String> {
+    let mut settings = read_settings(&state).await?;
+    settings
+        .sync_filters
+        .set(&kind, list)
+        .map_err(|e| e.to_string())?;
+    write_settings(&state, &settings).await?;
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync.set_filters(settings.sync_filters.clone()).await;
+    }
+
+    println!(
+        "Sync filter '{}' updated: {:?}",
+        kind, settings.sync_filters
+    );
+    Ok(settings.sync_filters)
+}
+
+/// Replace both source filter lists, the included and the excluded names or glob
+/// patterns. Sources the new rules exclude are removed from connected slaves.
+#[tauri::command]
+pub async fn set_source_sync_rules(
+    state: State<'_, AppState>,
+    rules: SourceSyncRules,
+) -> Result<SyncFilters, String> {
+    let mut settings = read_settings(&state).await?;
+    settings.sync_filters.set_source_rules(rules.clone());
+    write_settings(&state, &settings).await?;
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        let removed = master_sync
+            .set_source_rules(rules)
+            .await
+            .map_err(|e| e.to_string())?;
+        if removed > 0 {
+            println!("Removed {} newly excluded scene items from slaves", removed);
+        }
+    }
+
+    println!(
+        "Source sync rules updated: include {:?}, exclude {:?}",
+        settings.sync_filters.include_sources, settings.sync_filters.sources
+    );
+    Ok(settings.sync_filters)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    pub sync_profile: SyncProfile,
+    pub tuning: SyncTuning,
+}
+
+#[tauri::command]
+pub async fn set_sync_profile(
+    state: State<'_, AppState>,
+    profile: SyncProfile,
+) -> Result<(), String> {
+    let mut settings = read_settings(&state).await?;
+    settings.sync_profile = profile;
+    let tuning = settings.tuning();
+    *state.sync_profile.write().await = profile;
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync.set_tuning(tuning.clone()).await;
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_tuning(tuning).await;
+    }
+
+    write_settings(&state, &settings).await?;
+
+    println!("Sync profile set to {:?}", profile);
+    Ok(())
+}
+
+/// Override the sync profile's desync tolerances; None goes back to the profile's own
+#[tauri::command]
+pub async fn set_diff_tolerance(
+    state: State<'_, AppState>,
+    tolerance: Option<DiffTolerance>,
+) -> Result<(), String> {
+    let mut settings = read_settings(&state).await?;
+    settings.diff_tolerance = tolerance;
+    let tuning = settings.tuning();
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync.set_tuning(tuning.clone()).await;
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_tuning(tuning.clone()).await;
+    }
+
+    write_settings(&state, &settings).await?;
+
+    println!("Diff tolerance set to {:?}", tuning.diff_tolerance);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_transform_mask(
+    state: State<'_, AppState>,
+    mask: TransformFieldMask,
+) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_transform_mask(mask).await;
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.transform_mask = mask;
+    write_settings(&state, &settings).await?;
+
+    println!("Transform field mask set to {:?}", mask);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_transform_interpolation(
+    state: State<'_, AppState>,
+    interpolation: TransformInterpolation,
+) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_interpolation(interpolation).await;
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.interpolation = interpolation;
+    write_settings(&state, &settings).await?;
+
+    println!("Transform interpolation set to {:?}", interpolation);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_apply_concurrency(state: State<'_, AppState>) -> Result<usize, String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        return Ok(slave_sync.apply_concurrency());
+    }
+    Ok(read_settings(&state)
+        .await
+        .map(|settings| settings.slave.apply_concurrency)
+        .unwrap_or(DEFAULT_APPLY_CONCURRENCY))
+}
+
+#[tauri::command]
+pub async fn set_apply_concurrency(state: State<'_, AppState>, limit: usize) -> Result<(), String> {
+    if limit == 0 {
+        return Err("Concurrency limit must be at least 1".to_string());
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_apply_concurrency(limit);
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.apply_concurrency = limit;
+    write_settings(&state, &settings).await?;
+
+    println!("StateSync apply concurrency set to {}", limit);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_sync_hysteresis(state: State<'_, AppState>, checks: u32) -> Result<(), String> {
+    if checks == 0 {
+        return Err("Hysteresis must be at least 1 check".to_string());
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_sync_hysteresis(checks);
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.sync_hysteresis = checks;
+    write_settings(&state, &settings).await?;
+
+    println!("Sync state hysteresis set to {} checks", checks);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_prefer_name_matching(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_prefer_name_matching(enabled);
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.prefer_name_matching = enabled;
+    write_settings(&state, &settings).await?;
+
+    println!("Prefer name matching set to {}", enabled);
+    Ok(())
+}
+
+/// Sources the slave leaves alone: updates from the master for them are skipped and
+/// their differences don't count as a desync
+#[tauri::command]
+pub async fn set_slave_source_overrides(
+    state: State<'_, AppState>,
+    names: Vec<String>,
+) -> Result<(), String> {
+    for name in &names {
+        crate::sync::sanitize::check_name("source name", name).map_err(|e| e.to_string())?;
+    }
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_ignored_sources(names.clone()).await;
+    }
+
+    println!("Ignored sources set to {:?}", names);
+    let mut settings = read_settings(&state).await?;
+    settings.slave.ignored_sources = names;
+    write_settings(&state, &settings).await
+}
+
+/// Observe-only slave: changes from the master are emitted as `planned-action`
+/// events instead of being applied to OBS
+#[tauri::command]
+pub async fn set_slave_dry_run(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_dry_run(enabled);
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.dry_run = enabled;
+    write_settings(&state, &settings).await?;
+
+    println!("Slave dry run set to {}", enabled);
+    Ok(())
+}
+
+/// Strict StateSync: abort at the first failed item and request a fresh StateSync once
+#[tauri::command]
+pub async fn set_strict_state_sync(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync.set_strict_state_sync(enabled);
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.slave.strict_state_sync = enabled;
+    write_settings(&state, &settings).await?;
+
+    println!("Strict state sync set to {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_raw_event_passthrough(
+    state: State<'_, AppState>,
+    config: RawEventPassthrough,
+) -> Result<(), String> {
+    if let Some(event_handler) = state.obs_event_handler.read().await.as_ref() {
+        event_handler.set_raw_passthrough(config.clone()).await;
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.master.raw_event_passthrough = config.clone();
+    write_settings(&state, &settings).await?;
+
+    println!("Raw event passthrough set to {:?}", config);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_image_reencode(
+    state: State<'_, AppState>,
+    reencode: ImageReencode,
+) -> Result<(), String> {
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync.set_image_reencode(reencode.clone()).await;
+    }
+
+    let mut settings = read_settings(&state).await?;
+    settings.master.image_reencode = reencode.clone();
+    write_settings(&state, &settings).await?;
+
+    println!("Image re-encode set to {:?}", reencode);
+    Ok(())
+}
+
+/// Pin a source to a canvas edge on slaves, or clear its anchor with `None`
+#[tauri::command]
+pub async fn set_transform_anchor(
+    state: State<'_, AppState>,
+    source_name: String,
+    anchor: Option<TransformAnchor>,
+) -> Result<(), String> {
+    let mut settings = read_settings(&state).await?;
+    match anchor {
+        Some(anchor) => settings
+            .master
+            .transform_anchors
+            .insert(source_name.clone(), anchor),
+        None => settings.master.transform_anchors.remove(&source_name),
+    };
+    write_settings(&state, &settings).await?;
+
+    if let Some(master_sync) = state.master_sync.read().await.as_ref() {
+        master_sync
+            .set_transform_anchors(settings.master.transform_anchors.clone())
+            .await;
     }
 
+    println!("Transform anchor for {} set to {:?}", source_name, anchor);
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_effective_config(state: State<'_, AppState>) -> Result<EffectiveConfig, String> {
+    let sync_profile = *state.sync_profile.read().await;
+    let mut tuning = sync_profile.tuning();
+    if let Some(tolerance) = read_settings(&state).await?.diff_tolerance {
+        tuning.diff_tolerance = tolerance;
+    }
+    Ok(EffectiveConfig {
+        sync_profile,
+        tuning,
+    })
+}
+
 #[tauri::command]
 pub async fn get_connected_clients_count(state: State<'_, AppState>) -> Result<usize, String> {
     if let Some(server) = state.master_server.read().await.as_ref() {
@@ -668,6 +2841,81 @@ pub async fn get_slave_statuses(state: State<'_, AppState>) -> Result<Vec<SlaveS
     }
 }
 
+/// Per-slave sync health for the dashboard, including slaves that haven't reported yet
+#[tauri::command]
+pub async fn get_desync_summary(
+    state: State<'_, AppState>,
+) -> Result<Vec<SlaveDesyncSummary>, String> {
+    if let Some(server) = state.master_server.read().await.as_ref() {
+        Ok(server.desync_summary().await)
+    } else {
+        Ok(vec![])
+    }
+}
+
+#[tauri::command]
+pub async fn get_slave_detail(
+    state: State<'_, AppState>,
+    client_id: String,
+) -> Result<SlaveDetail, String> {
+    let server_lock = state.master_server.read().await;
+    let server = server_lock
+        .as_ref()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    server
+        .get_slave_detail(&client_id)
+        .await
+        .ok_or_else(|| format!("Slave {} not found", client_id))
+}
+
+/// Write every connected slave's status to `path`, as CSV if it ends in `.csv`, else JSON
+#[tauri::command]
+pub async fn export_fleet_report(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<FleetReport, String> {
+    let server_lock = state.master_server.read().await;
+    let server = server_lock
+        .as_ref()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    let obs_version = state.obs_client.get_status().await.obs_version;
+    let report = server.fleet_report(obs_version).await;
+
+    let path = PathBuf::from(path);
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let contents = if is_csv {
+        report.to_csv()
+    } else {
+        serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize fleet report: {}", e))?
+    };
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| format!("Failed to write fleet report to {:?}: {}", path, e))?;
+
+    println!(
+        "Exported fleet report with {} client(s) to {:?}",
+        report.clients.len(),
+        path
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn measure_sync_latency(
+    state: State<'_, AppState>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<SlaveLatency>, String> {
+    let server_lock = state.master_server.read().await;
+    let server = server_lock
+        .as_ref()
+        .ok_or_else(|| "Master server is not running".to_string())?;
+    let timeout = tokio::time::Duration::from_millis(timeout_ms.unwrap_or(2000));
+    Ok(server.measure_latency(timeout).await)
+}
+
 #[tauri::command]
 pub async fn get_obs_sources(state: State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
     let client_arc = state.obs_client.get_client_arc();
@@ -686,13 +2934,18 @@ pub async fn get_obs_sources(state: State<'_, AppState>) -> Result<Vec<serde_jso
                         Ok(items) => {
                             for item in items {
                                 // Store source info (avoid duplicates)
-                                sources_map.entry(item.source_name.clone()).or_insert_with(|| {
+                                if sources_map.contains_key(&item.source_name) {
+                                    continue;
+                                }
+                                let kind = OBSCommands::resolve_source_kind(client, &item).await;
+                                sources_map.insert(
+                                    item.source_name.clone(),
                                     serde_json::json!({
                                         "sourceName": item.source_name,
-                                        "sourceType": item.input_kind.clone().unwrap_or_else(|| "unknown".to_string()),
-                                        "sourceKind": item.input_kind.clone().unwrap_or_else(|| "unknown".to_string()),
-                                    })
-                                });
+                                        "sourceType": kind,
+                                        "sourceKind": kind,
+                                    }),
+                                );
                             }
                         }
                         Err(e) => {
@@ -712,6 +2965,208 @@ pub async fn get_obs_sources(state: State<'_, AppState>) -> Result<Vec<serde_jso
     }
 }
 
+/// A scene item exactly as obws reports it, for comparing item ids between machines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawSceneItem {
+    pub id: i64,
+    pub source_name: String,
+    pub input_kind: Option<String>,
+    pub enabled: bool,
+}
+
+impl RawSceneItem {
+    fn new(item: obws::responses::scene_items::SceneItem, enabled: bool) -> Self {
+        Self {
+            id: item.id,
+            source_name: item.source_name,
+            input_kind: item.input_kind,
+            enabled,
+        }
+    }
+}
+
+/// Fails with "Scene not found" unless `scene_name` is one of `scenes`
+fn require_scene<'a>(
+    mut scenes: impl Iterator<Item = &'a str>,
+    scene_name: &str,
+) -> Result<(), String> {
+    if scenes.any(|name| name == scene_name) {
+        Ok(())
+    } else {
+        Err(format!("Scene not found: {}", scene_name))
+    }
+}
+
+#[tauri::command]
+pub async fn get_scene_items(
+    state: State<'_, AppState>,
+    scene_name: String,
+) -> Result<Vec<RawSceneItem>, String> {
+    let client_arc = state.obs_client.get_client_arc();
+    let client_lock = client_arc.read().await;
+    let client = client_lock.as_ref().ok_or("OBS is not connected")?;
+
+    let scenes = client
+        .scenes()
+        .list()
+        .await
+        .map_err(|e| format!("Failed to get scenes: {}", e))?;
+    require_scene(
+        scenes.scenes.iter().map(|scene| scene.id.name.as_str()),
+        &scene_name,
+    )?;
+
+    let scene_id = obws::requests::scenes::SceneId::Name(&scene_name);
+    let items = client
+        .scene_items()
+        .list(scene_id)
+        .await
+        .map_err(|e| format!("Failed to get scene items for {}: {}", scene_name, e))?;
+
+    let mut raw_items = Vec::with_capacity(items.len());
+    for item in items {
+        let enabled = client
+            .scene_items()
+            .enabled(scene_id, item.id)
+            .await
+            .map_err(|e| format!("Failed to get enabled state of item {}: {}", item.id, e))?;
+        raw_items.push(RawSceneItem::new(item, enabled));
+    }
+    Ok(raw_items)
+}
+
+#[tauri::command]
+pub async fn export_scene_graph(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let filters = read_settings(&state).await?.sync_filters;
+    MasterSync::export_scene_graph(&state.obs_client, &filters)
+        .await
+        .map_err(|e| format!("Failed to export scene graph: {}", e))
+}
+
+/// Current OBS state of this machine as JSON, for diffing a master against a slave
+#[tauri::command]
+pub async fn dump_obs_state(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    MasterSync::dump_obs_state(&state.obs_client)
+        .await
+        .map_err(|e| format!("Failed to dump OBS state: {}", e))
+}
+
+#[tauri::command]
+pub async fn dump_master_state(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<StateDumpSummary, String> {
+    let settings = read_settings(&state).await?;
+    MasterSync::dump_state(
+        &state.obs_client,
+        &settings.sync_filters,
+        &settings.master.image_reencode,
+        &settings.master.transform_anchors,
+        &PathBuf::from(path),
+    )
+    .await
+    .map_err(|e| format!("Failed to dump master state: {}", e))
+}
+
+/// Developer-only commands are available in debug builds or with OBS_SYNC_DEV_TOOLS set
+fn dev_tools_enabled() -> bool {
+    cfg!(debug_assertions) || std::env::var_os("OBS_SYNC_DEV_TOOLS").is_some()
+}
+
+/// Make the desync UI light up without touching OBS: injects a synthetic
+/// DesyncAlert and, in master mode, a fake unsynced slave status.
+#[tauri::command]
+pub async fn simulate_desync(
+    state: State<'_, AppState>,
+    scene: String,
+    source: String,
+    severity: AlertSeverity,
+) -> Result<(), String> {
+    if !dev_tools_enabled() {
+        return Err("simulate_desync is only available with developer tools enabled".to_string());
+    }
+    let message = "Simulated desync (no OBS changes were made)".to_string();
+
+    // Slave side: go through the normal alert channel so the emit path is exercised
+    if let Some(slave_sync) = state.slave_sync.read().await.as_ref() {
+        slave_sync
+            .send_alert(
+                scene.clone(),
+                source.clone(),
+                message.clone(),
+                severity.clone(),
+            )
+            .map_err(|e| format!("Failed to inject alert: {}", e))?;
+    } else if let Some(handle) = state.app_handle.read().await.as_ref() {
+        let alert = DesyncAlert::new(
+            scene.clone(),
+            source.clone(),
+            message.clone(),
+            severity.clone(),
+        );
+        handle
+            .emit("desync-alert", alert)
+            .map_err(|e| format!("Failed to emit desync alert: {}", e))?;
+    }
+
+    // Master side: attach the fake status to a connected slave so it shows up in the list
+    if let Some(server) = state.master_server.read().await.as_ref() {
+        let client_id = server
+            .get_connected_clients_info()
+            .await
+            .first()
+            .map(|info| info.id.clone())
+            .unwrap_or_else(|| "simulated-slave".to_string());
+        server
+            .set_slave_status(SlaveStatus {
+                client_id,
+                is_synced: false,
+                desync_details: vec![serde_json::json!({
+                    "category": "TransformMismatch",
+                    "scene_name": scene,
+                    "source_name": source,
+                    "description": message,
+                    "severity": match severity {
+                        AlertSeverity::Error => "Critical",
+                        AlertSeverity::Warning => "Warning",
+                    },
+                })],
+                last_report_time: chrono::Utc::now().timestamp_millis(),
+                report_latency_ms: None,
+                apply_errors: Vec::new(),
+                rtt_ms: None,
+            })
+            .await;
+    }
+
+    println!("Simulated desync injected for {}/{}", scene, source);
+    Ok(())
+}
+
+/// What this instance advertises in its current role, TLS and auth included
+#[tauri::command]
+pub async fn get_capabilities(state: State<'_, AppState>) -> Result<Capabilities, String> {
+    if let Some(server) = state.master_server.read().await.as_ref() {
+        return Ok(server.capabilities().await);
+    }
+    if let Some(client) = state.slave_client.read().await.as_ref() {
+        return Ok(client.capabilities());
+    }
+    Ok(Capabilities::current())
+}
+
+#[tauri::command]
+pub async fn get_master_capabilities(
+    state: State<'_, AppState>,
+) -> Result<Option<Capabilities>, String> {
+    if let Some(client) = state.slave_client.read().await.as_ref() {
+        Ok(client.get_master_capabilities().await)
+    } else {
+        Ok(None)
+    }
+}
+
 #[tauri::command]
 pub async fn get_performance_metrics(
     state: State<'_, AppState>,
@@ -719,6 +3174,25 @@ pub async fn get_performance_metrics(
     Ok(state.performance_monitor.get_metrics().await)
 }
 
+/// Latency, count and bytes of the retained metrics per message type
+#[tauri::command]
+pub async fn get_metrics_by_type(
+    state: State<'_, AppState>,
+) -> Result<Vec<MessageTypeMetrics>, String> {
+    Ok(state.performance_monitor.get_metrics_by_type().await)
+}
+
+/// Write the retained performance metrics to `path` as CSV
+#[tauri::command]
+pub async fn export_metrics_csv(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let csv = state.performance_monitor.to_csv().await;
+    fs::write(&path, csv)
+        .await
+        .map_err(|e| format!("Failed to write metrics to {}: {}", path, e))?;
+    println!("Exported performance metrics to {}", path);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_local_ip_address() -> Result<String, String> {
     use network_interface::{NetworkInterface, NetworkInterfaceConfig};
@@ -770,3 +3244,355 @@ pub fn get_local_ip_address() -> Result<String, String> {
 pub fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::filters::FILTER_KINDS;
+
+    /// A config.json path in a fresh directory under the system temp dir
+    fn temp_config_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("obs-sync-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("config.json")
+    }
+
+    #[tokio::test]
+    async fn each_filter_list_round_trips_through_save_and_load() {
+        let config_path = temp_config_path();
+        let message_type = serde_json::to_value(SyncMessageType::TransformUpdate).unwrap();
+
+        for kind in FILTER_KINDS {
+            let list = match kind {
+                "messageTypes" => vec![message_type.as_str().unwrap().to_string()],
+                _ => vec![format!("{} one", kind), format!("{} two", kind)],
+            };
+            let mut settings = AppSettings::default();
+            settings.sync_filters.set(kind, list).unwrap();
+            assert_ne!(settings.sync_filters, SyncFilters::default(), "{}", kind);
+
+            write_settings_file(&config_path, &settings).await.unwrap();
+            let loaded = read_settings_file(&config_path).await.unwrap();
+            assert_eq!(loaded.sync_filters, settings.sync_filters, "{}", kind);
+        }
+
+        let _ = std::fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn corrupt_primary_config_loads_from_the_backup() {
+        let config_path = temp_config_path();
+        let mut first = AppSettings::default();
+        first.sync_filters.scenes = vec!["Backstage".to_string()];
+        let mut second = first.clone();
+        second.sync_filters.scenes = vec!["Green room".to_string()];
+
+        // The second write keeps the first file as the backup
+        write_settings_file(&config_path, &first).await.unwrap();
+        write_settings_file(&config_path, &second).await.unwrap();
+        std::fs::write(&config_path, "{ \"syncFilters\": ").unwrap();
+
+        let loaded = read_settings_file(&config_path).await.unwrap();
+        assert_eq!(loaded.sync_filters, first.sync_filters);
+
+        // Without a usable backup the primary's parse error is reported
+        std::fs::remove_file(settings_backup_path(&config_path)).unwrap();
+        let error = read_settings_file(&config_path).await.unwrap_err();
+        assert!(
+            error.starts_with("Failed to parse settings file"),
+            "{}",
+            error
+        );
+
+        let _ = std::fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn reload_applies_hand_edits_to_the_live_check() {
+        let config_path = temp_config_path();
+        let state = AppState::new();
+        let (slave_sync, _alerts) = SlaveSync::new(Arc::new(OBSClient::new()));
+        let slave_sync = Arc::new(slave_sync);
+        *state.slave_sync.write().await = Some(slave_sync.clone());
+
+        // Baseline at launch, then an in-app change
+        let mut settings = AppSettings::default();
+        write_settings_file(&config_path, &settings).await.unwrap();
+        *state.known_settings.write().await = Some(settings.clone());
+        settings.slave.ignored_sources = vec!["Clock".to_string()];
+        write_settings_at(&state, &config_path, &settings)
+            .await
+            .unwrap();
+
+        // Edited by hand: a slower check, and a fallback master that needs a reconnect
+        let mut edited = read_settings_file(&config_path).await.unwrap();
+        edited.sync_profile = SyncProfile::Relaxed;
+        edited.slave.fallback_masters = vec![MasterEndpoint {
+            host: "10.0.0.2".to_string(),
+            port: 8080,
+        }];
+        write_settings_file(&config_path, &edited).await.unwrap();
+
+        // Another in-app write carries the hand edits along but doesn't apply them
+        edited.slave.ignored_sources.push("Weather".to_string());
+        write_settings_at(&state, &config_path, &edited)
+            .await
+            .unwrap();
+
+        let reload = reload_settings_at(&state, &config_path).await.unwrap();
+        assert_eq!(reload.applied, vec!["syncProfile".to_string()]);
+        assert_eq!(
+            reload.restart_required,
+            vec!["slave.fallbackMasters".to_string()]
+        );
+        assert_eq!(
+            slave_sync.tuning().await.desync_check_interval_secs,
+            SyncProfile::Relaxed.tuning().desync_check_interval_secs
+        );
+
+        // Nothing left to report once reloaded
+        let again = reload_settings_at(&state, &config_path).await.unwrap();
+        assert!(again.applied.is_empty() && again.restart_required.is_empty());
+
+        let _ = std::fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn metrics_csv_tells_sent_acked_and_received_apart() {
+        let monitor = PerformanceMonitor::new(10);
+        let mut scene = SyncMessage::new(
+            SyncMessageType::SceneChange,
+            SyncTargetType::Program,
+            serde_json::json!({ "scene_name": "Main" }),
+        );
+        scene.requires_ack = true;
+        monitor.record_send(&scene, 40).await;
+        monitor
+            .record_ack(
+                &scene.message_id,
+                chrono::Utc::now().timestamp_millis(),
+                None,
+            )
+            .await;
+        monitor
+            .record_receive(&scene, chrono::Utc::now().timestamp_millis())
+            .await;
+
+        let csv = monitor.to_csv().await;
+        let kinds: Vec<&str> = csv
+            .lines()
+            .map(|line| line.split(',').nth(1).unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["kind", "sent", "acked", "received"]);
+    }
+
+    #[tokio::test]
+    async fn ack_round_trip_is_timed_from_the_last_resend() {
+        let monitor = PerformanceMonitor::new(10);
+        let mut scene = SyncMessage::new(
+            SyncMessageType::SceneChange,
+            SyncTargetType::Program,
+            serde_json::json!({ "scene_name": "Main" }),
+        );
+        scene.requires_ack = true;
+        monitor.record_send(&scene, 40).await;
+
+        // Resent 2s after the broadcast and acked 30ms after that
+        let now = chrono::Utc::now().timestamp_millis() + 2_030;
+        monitor
+            .record_ack(&scene.message_id, now, Some(now - 30))
+            .await;
+        let metrics = monitor.get_metrics().await;
+        assert_eq!(metrics.average_latency_ms, 30.0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_a_running_master_once() {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let state = AppState::new();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = Arc::new(MasterServer::new(port, None));
+        let (_tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+        *state.master_server.write().await = Some(server.clone());
+
+        let (mut slave, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let hello = SyncMessage::client_hello(Some("slave-a".to_string()), None, None, Vec::new());
+        slave
+            .send(Message::Text(serde_json::to_string(&hello).unwrap()))
+            .await
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while server.get_connected_clients_count().await < 1 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("slave did not connect");
+
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), state.shutdown())
+            .await
+            .expect("shutdown hung");
+        assert!(state.master_server.read().await.is_none());
+
+        // The slave sees its connection end rather than hanging on a dead master
+        let closed = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while let Some(Ok(frame)) = slave.next().await {
+                if frame.is_close() {
+                    break;
+                }
+            }
+        })
+        .await;
+        assert!(closed.is_ok());
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err());
+
+        // A second call, as from a second exit hook, returns straight away
+        tokio::time::timeout(tokio::time::Duration::from_millis(100), state.shutdown())
+            .await
+            .expect("second shutdown did work again");
+    }
+
+    #[tokio::test]
+    async fn received_latency_is_corrected_for_the_master_clock() {
+        let monitor = PerformanceMonitor::new(10);
+        // The master's clock runs 5s ahead; heartbeats take 40ms there and back
+        let skew = 5_000;
+        let sent = 1_000_000;
+        let reply = SyncMessage {
+            payload: serde_json::json!({ "client_time": sent, "server_time": sent + 20 + skew }),
+            ..SyncMessage::heartbeat()
+        };
+        monitor.record_heartbeat(&reply, sent + 40).await;
+
+        // Stamped by the master 30ms before it arrived
+        let received = 2_000_000;
+        let scene = SyncMessage {
+            timestamp: received - 30 + skew,
+            ..SyncMessage::new(
+                SyncMessageType::SceneChange,
+                SyncTargetType::Program,
+                serde_json::json!({ "scene_name": "Main" }),
+            )
+        };
+        monitor.record_receive(&scene, received).await;
+
+        let metrics = monitor.get_metrics().await;
+        assert_eq!(metrics.clock_offset_ms, Some(skew as f64));
+        assert_eq!(metrics.heartbeat_rtt_ms, Some(40.0));
+        assert_eq!(metrics.recent_metrics[0].latency_ms, 30.0);
+    }
+
+    #[tokio::test]
+    async fn auto_start_stops_at_the_first_failed_step() {
+        let unused_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let settings = |auto_start: AutoStartSettings| {
+            let mut settings = AppSettings::default();
+            settings.obs.host = "127.0.0.1".to_string();
+            settings.obs.port = unused_port;
+            settings.auto_start = auto_start;
+            settings
+        };
+
+        // Nothing configured, nothing done
+        let state = AppState::new();
+        assert!(auto_start(&state, &settings(AutoStartSettings::default()))
+            .await
+            .is_ok());
+        assert!(state.mode.read().await.is_none());
+
+        // OBS can't be reached, so the master is never started
+        let state = AppState::new();
+        let failure = auto_start(
+            &state,
+            &settings(AutoStartSettings {
+                connect_obs: true,
+                role: Some(AppMode::Master),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(failure.step, "obs");
+        assert!(state.mode.read().await.is_none());
+        assert!(state.master_server.read().await.is_none());
+
+        // Each role is attempted, and reported under its own step
+        for (role, step) in [(AppMode::Master, "master"), (AppMode::Slave, "slave")] {
+            let state = AppState::new();
+            let failure = auto_start(
+                &state,
+                &settings(AutoStartSettings {
+                    role: Some(role),
+                    master_host: Some("127.0.0.1".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(failure.step, step);
+            assert_eq!(failure.error, "OBS is not connected");
+            assert!(state.mode.read().await.is_some());
+        }
+    }
+
+    #[test]
+    fn raw_scene_items_keep_what_obws_reports() {
+        // As returned by GetSceneItemList
+        let items: Vec<obws::responses::scene_items::SceneItem> =
+            serde_json::from_value(serde_json::json!([
+                {
+                    "sceneItemId": 7,
+                    "sceneItemIndex": 0,
+                    "sourceName": "Camera",
+                    "sourceType": "OBS_SOURCE_TYPE_INPUT",
+                    "inputKind": "dshow_input",
+                    "isGroup": null,
+                },
+                {
+                    "sceneItemId": 12,
+                    "sceneItemIndex": 1,
+                    "sourceName": "Intermission",
+                    "sourceType": "OBS_SOURCE_TYPE_SCENE",
+                    "inputKind": null,
+                    "isGroup": false,
+                },
+            ]))
+            .unwrap();
+
+        let raw: Vec<RawSceneItem> = items
+            .into_iter()
+            .zip([true, false])
+            .map(|(item, enabled)| RawSceneItem::new(item, enabled))
+            .collect();
+
+        let json = serde_json::to_value(&raw).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "id": 7, "sourceName": "Camera", "inputKind": "dshow_input", "enabled": true },
+                { "id": 12, "sourceName": "Intermission", "inputKind": null, "enabled": false },
+            ])
+        );
+        let scenes = ["Main", "Break"];
+        assert!(require_scene(scenes.into_iter(), "Break").is_ok());
+        assert_eq!(
+            require_scene(scenes.into_iter(), "Missing"),
+            Err("Scene not found: Missing".to_string())
+        );
+    }
+}
@@ -1,3 +1,6 @@
+#[macro_use]
+mod logging;
+
 mod commands;
 mod network;
 mod obs;
@@ -119,8 +122,12 @@ pub fn run() {
             let handle = app.handle().clone();
             let state: tauri::State<AppState> = app.state();
             let state_inner = state.inner().clone();
+            tauri::async_runtime::spawn(commands::watch_obs_connection(state_inner.clone()));
             tauri::async_runtime::spawn(async move {
                 state_inner.set_app_handle(handle).await;
+                state_inner.load_known_settings().await;
+                state_inner.load_metrics().await;
+                commands::run_auto_start(&state_inner).await;
             });
             Ok(())
         })
@@ -129,31 +136,88 @@ pub fn run() {
             commands::connect_obs,
             commands::disconnect_obs,
             commands::get_obs_status,
+            commands::trigger_studio_transition,
             commands::set_app_mode,
             commands::get_app_mode,
             commands::start_master_server,
             commands::stop_master_server,
+            commands::get_master_tls_fingerprint,
+            commands::handoff_master,
+            commands::get_ambiguous_sources,
+            commands::probe_master,
             commands::connect_to_master,
             commands::disconnect_from_master,
             commands::is_slave_connected,
             commands::set_sync_targets,
+            commands::set_sync_profile,
+            commands::set_diff_tolerance,
+            commands::set_scene_target_overrides,
+            commands::get_sync_filters,
+            commands::set_sync_filter,
+            commands::set_source_sync_rules,
+            commands::set_transform_mask,
+            commands::set_transform_interpolation,
+            commands::get_apply_concurrency,
+            commands::set_apply_concurrency,
+            commands::set_sync_hysteresis,
+            commands::set_prefer_name_matching,
+            commands::set_slave_dry_run,
+            commands::set_slave_source_overrides,
+            commands::set_strict_state_sync,
+            commands::set_raw_event_passthrough,
+            commands::set_image_reencode,
+            commands::set_transform_anchor,
+            commands::get_effective_config,
             commands::get_connected_clients_count,
             commands::get_connected_clients_info,
             commands::get_slave_statuses,
+            commands::get_slave_detail,
+            commands::get_desync_summary,
+            commands::measure_sync_latency,
+            commands::export_fleet_report,
             commands::get_obs_sources,
+            commands::get_scene_items,
+            commands::export_scene_graph,
+            commands::dump_master_state,
+            commands::dump_obs_state,
             commands::get_slave_reconnection_status,
+            commands::validate_media_availability,
+            commands::normalize_to_master,
+            commands::retry_connection,
+            commands::set_max_reconnect_attempts,
+            commands::get_desync_report,
             commands::resync_all_slaves,
+            commands::cancel_resync,
+            commands::pause_master_sync,
+            commands::resume_master_sync,
+            commands::set_hotkey_command,
+            commands::trigger_hotkey_command,
             commands::resync_specific_slave,
+            commands::push_source,
             commands::request_resync_from_master,
+            commands::simulate_desync,
             commands::save_settings,
             commands::load_settings,
+            commands::reload_settings,
+            commands::export_settings,
+            commands::import_settings,
             commands::get_log_file_path,
             commands::open_log_file,
             commands::get_performance_metrics,
+            commands::get_metrics_by_type,
+            commands::export_metrics_csv,
+            commands::get_capabilities,
+            commands::get_master_capabilities,
             commands::get_local_ip_address,
             get_app_version,
             get_git_commit,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let state: tauri::State<AppState> = app.state();
+                tauri::async_runtime::block_on(state.inner().shutdown());
+            }
+        });
 }
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long an identical error is suppressed after it was last printed
+const REPEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Forget messages that haven't been seen for this long once the map grows
+const MAX_TRACKED_MESSAGES: usize = 256;
+
+struct SeenMessage {
+    last_logged: Instant,
+    suppressed: u32,
+}
+
+/// Collapses identical log lines so a persistent failure logs at most once per interval
+pub struct LogThrottle {
+    interval: Duration,
+    seen: Mutex<HashMap<String, SeenMessage>>,
+}
+
+impl LogThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The line to print for `message`, or `None` if it was printed within the interval.
+    /// A line printed after suppressed repeats carries a "(repeated N times)" suffix.
+    pub fn check(&self, message: &str) -> Option<String> {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        if let Some(entry) = seen.get_mut(message) {
+            if now.duration_since(entry.last_logged) < self.interval {
+                entry.suppressed += 1;
+                return None;
+            }
+            let suppressed = std::mem::take(&mut entry.suppressed);
+            entry.last_logged = now;
+            return Some(if suppressed > 0 {
+                format!("{} (repeated {} times)", message, suppressed)
+            } else {
+                message.to_string()
+            });
+        }
+
+        if seen.len() >= MAX_TRACKED_MESSAGES {
+            let interval = self.interval;
+            seen.retain(|_, entry| now.duration_since(entry.last_logged) < interval);
+        }
+        seen.insert(
+            message.to_string(),
+            SeenMessage {
+                last_logged: now,
+                suppressed: 0,
+            },
+        );
+        Some(message.to_string())
+    }
+}
+
+/// Print an error to stderr unless the identical message was printed recently
+pub fn throttled_error(message: String) {
+    static THROTTLE: OnceLock<LogThrottle> = OnceLock::new();
+    if let Some(line) = THROTTLE
+        .get_or_init(|| LogThrottle::new(REPEAT_INTERVAL))
+        .check(&message)
+    {
+        eprintln!("{}", line);
+    }
+}
+
+/// `eprintln!` for hot error paths; identical messages are collapsed by `throttled_error`
+macro_rules! throttled_eprintln {
+    ($($arg:tt)*) => {
+        $crate::logging::throttled_error(format!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_errors_are_collapsed_within_the_interval() {
+        let throttle = LogThrottle::new(Duration::from_secs(60));
+
+        assert_eq!(
+            throttle.check("OBS unreachable").as_deref(),
+            Some("OBS unreachable")
+        );
+        for _ in 0..5 {
+            assert_eq!(throttle.check("OBS unreachable"), None);
+        }
+        // A different message isn't held back by the first one
+        assert_eq!(throttle.check("Disk full").as_deref(), Some("Disk full"));
+    }
+
+    #[test]
+    fn next_line_after_the_interval_counts_suppressed_repeats() {
+        let throttle = LogThrottle::new(Duration::from_millis(50));
+
+        assert!(throttle.check("OBS unreachable").is_some());
+        assert_eq!(throttle.check("OBS unreachable"), None);
+        assert_eq!(throttle.check("OBS unreachable"), None);
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(
+            throttle.check("OBS unreachable").as_deref(),
+            Some("OBS unreachable (repeated 2 times)")
+        );
+
+        // Nothing was suppressed since, so the count isn't repeated
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(
+            throttle.check("OBS unreachable").as_deref(),
+            Some("OBS unreachable")
+        );
+    }
+}
@@ -1,14 +1,71 @@
-use crate::sync::protocol::SyncMessage;
+use super::history::{ReceivedSeqs, SeenMessageIds};
+use super::keepalive::{apply_keepalive, KeepaliveConfig};
+use super::reconnect::ReconnectStrategy;
+use super::status::{StatusCallback, StatusEvent, StatusLevel};
+use crate::sync::protocol::{
+    is_protocol_compatible, protocol_mismatch_reason, Capabilities, MasterHandoffPayload,
+    ResumeFromPayload, ServerHelloPayload, SyncMessage, SyncMessageType, PROTOCOL_VERSION,
+};
 use anyhow::Result;
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_rustls::rustls::ClientConfig;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::{
+    client_async_tls_with_config, tungstenite::Message, Connector, MaybeTlsStream, WebSocketStream,
+};
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsSink = futures_util::stream::SplitSink<WsStream, Message>;
+
+/// Channel ends the connection loop needs; kept so the loop can be restarted
+#[derive(Clone)]
+struct ConnectionLink {
+    /// Messages received from the master, consumed by SlaveSync. Bounded: when SlaveSync
+    /// falls behind, the socket stops being read and the master's queue for this slave
+    /// takes the backlog, where it is capped.
+    incoming_tx: mpsc::Sender<SyncMessage>,
+    /// Messages queued for the master. Lives as long as the client, not one socket: SlaveSync
+    /// keeps a clone for StateReports, and the send task moves over to each new socket.
+    outgoing_tx: mpsc::UnboundedSender<SyncMessage>,
+    /// Hands the sink of each new WebSocket to the send task
+    send_ready_tx: mpsc::UnboundedSender<WsSink>,
+}
+
+/// Messages from the master buffered for SlaveSync before the socket stops being read
+const INCOMING_CAPACITY: usize = 256;
+
+/// How often the slave pings the master to measure RTT and clock offset
+const HEARTBEAT_INTERVAL_SECS: u64 = 5;
+
+/// The master answers every heartbeat, so this long without any frame means the link is dead
+const HEARTBEAT_TIMEOUT_SECS: u64 = 3 * HEARTBEAT_INTERVAL_SECS;
+
+/// Reconnect attempts before the slave gives up, unless configured otherwise
+pub const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// Minimum gap between resyncs requested because of unparseable frames
+const PARSE_ERROR_RESYNC_INTERVAL_SECS: u64 = 5;
+
+/// Failed attempts before moving on to the next master when reconnecting is unlimited
+const FAILOVER_AFTER_ATTEMPTS: u32 = 3;
+
+/// Address of a master a slave can connect to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MasterEndpoint {
+    pub host: String,
+    pub port: u16,
+}
+
+impl std::fmt::Display for MasterEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,45 +74,181 @@ pub struct ReconnectionStatus {
     pub attempt_count: u32,
     pub max_attempts: u32,
     pub last_error: Option<String>,
+    /// Frames from the master that could not be parsed since this client was created
+    pub parse_error_count: u32,
+    /// The master announced a deliberate shutdown, so reconnecting was stopped
+    pub master_stopped: bool,
+    /// Master connected to or being tried, as host:port
+    pub active_master: String,
 }
 
 type ConnectionStatusCallback = Arc<dyn Fn(bool) + Send + Sync>;
+type HeldImagesCallback = Arc<
+    dyn Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Vec<String>> + Send>>
+        + Send
+        + Sync,
+>;
 
 #[derive(Clone)]
 pub struct SlaveClient {
-    host: String,
-    port: u16,
-    ws_stream: Arc<RwLock<Option<WsStream>>>,
+    /// Masters in order of preference, the primary first. A handoff replaces the active one.
+    masters: Arc<RwLock<Vec<MasterEndpoint>>>,
+    /// Index into `masters`; moves on when a master can't be reached
+    active_master: Arc<AtomicUsize>,
+    /// Set by a handoff so the next connection requests full state from the new master
+    handoff_pending: Arc<AtomicBool>,
+    path_token: Option<String>,
+    keepalive: KeepaliveConfig,
+    reconnect_strategy: ReconnectStrategy,
+    /// Connect with wss:// using this config instead of plain ws://
+    tls: Option<Arc<ClientConfig>>,
+    /// Token sent as the first frame of every connection when the master requires one
+    auth_token: Option<String>,
+    /// Sent in ClientHello so the master can tell slaves apart across reconnects
+    machine_id: Option<String>,
+    display_name: Option<String>,
     should_reconnect: Arc<AtomicBool>,
-    max_reconnect_attempts: u32,
+    /// Read at every reconnect attempt so changes apply to a running loop; 0 retries forever
+    max_reconnect_attempts: Arc<AtomicU32>,
     message_tx: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
     sync_message_tx: Arc<RwLock<Option<mpsc::UnboundedSender<SyncMessage>>>>,
     reconnection_status: Arc<RwLock<ReconnectionStatus>>,
     current_attempt: Arc<AtomicU32>,
     is_connected: Arc<AtomicBool>,
     connection_status_callback: Arc<RwLock<Option<ConnectionStatusCallback>>>,
+    /// Notified of connection failures, losses and recoveries
+    status_callback: Arc<RwLock<Option<StatusCallback>>>,
+    /// Lists the images already on disk, so the master can leave them out of a StateSync
+    held_images_callback: Arc<RwLock<Option<HeldImagesCallback>>>,
+    /// Capabilities negotiated with the master (None until the master replies)
+    master_capabilities: Arc<RwLock<Option<Capabilities>>>,
+    connection_link: Arc<RwLock<Option<ConnectionLink>>>,
+    /// Whether the connect/reconnect loop is currently running
+    connection_loop_active: Arc<AtomicBool>,
+    /// Master broadcasts received so far; kept across reconnects so a short drop is
+    /// caught up by replaying what was missed
+    received_seqs: Arc<RwLock<Option<ReceivedSeqs>>>,
+    /// Ids of acknowledged messages received so far, also kept across reconnects
+    seen_ack_ids: Arc<RwLock<SeenMessageIds>>,
 }
 
 impl SlaveClient {
     pub fn new(host: String, port: u16) -> Self {
+        let primary = MasterEndpoint { host, port };
         Self {
-            host,
-            port,
-            ws_stream: Arc::new(RwLock::new(None)),
+            active_master: Arc::new(AtomicUsize::new(0)),
+            handoff_pending: Arc::new(AtomicBool::new(false)),
+            path_token: None,
+            keepalive: KeepaliveConfig::default(),
+            reconnect_strategy: ReconnectStrategy::default(),
+            tls: None,
+            auth_token: None,
+            machine_id: None,
+            display_name: None,
             should_reconnect: Arc::new(AtomicBool::new(true)),
-            max_reconnect_attempts: 10,
+            max_reconnect_attempts: Arc::new(AtomicU32::new(DEFAULT_MAX_RECONNECT_ATTEMPTS)),
             message_tx: Arc::new(RwLock::new(None)),
             sync_message_tx: Arc::new(RwLock::new(None)),
             reconnection_status: Arc::new(RwLock::new(ReconnectionStatus {
                 is_reconnecting: false,
                 attempt_count: 0,
-                max_attempts: 10,
+                max_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
                 last_error: None,
+                parse_error_count: 0,
+                master_stopped: false,
+                active_master: primary.to_string(),
             })),
             current_attempt: Arc::new(AtomicU32::new(0)),
             is_connected: Arc::new(AtomicBool::new(false)),
             connection_status_callback: Arc::new(RwLock::new(None)),
+            status_callback: Arc::new(RwLock::new(None)),
+            held_images_callback: Arc::new(RwLock::new(None)),
+            master_capabilities: Arc::new(RwLock::new(None)),
+            connection_link: Arc::new(RwLock::new(None)),
+            connection_loop_active: Arc::new(AtomicBool::new(false)),
+            received_seqs: Arc::new(RwLock::new(None)),
+            seen_ack_ids: Arc::new(RwLock::new(SeenMessageIds::default())),
+            masters: Arc::new(RwLock::new(vec![primary])),
+        }
+    }
+
+    /// Masters to fail over to, in order, when the primary can't be reached
+    pub fn with_fallback_masters(self, fallbacks: Vec<MasterEndpoint>) -> Self {
+        if let Ok(mut masters) = self.masters.try_write() {
+            masters.extend(fallbacks);
         }
+        self
+    }
+
+    async fn current_master(&self) -> MasterEndpoint {
+        let masters = self.masters.read().await;
+        masters[self.active_master.load(Ordering::SeqCst) % masters.len()].clone()
+    }
+
+    async fn replace_current_master(&self, endpoint: MasterEndpoint) {
+        let mut masters = self.masters.write().await;
+        let index = self.active_master.load(Ordering::SeqCst) % masters.len();
+        masters[index] = endpoint;
+    }
+
+    /// Path token appended to the master URL when the master's handshake guard requires one
+    pub fn with_path_token(mut self, token: Option<String>) -> Self {
+        self.path_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    pub fn with_keepalive(mut self, keepalive: KeepaliveConfig) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    pub fn with_reconnect_strategy(mut self, strategy: ReconnectStrategy) -> Self {
+        self.reconnect_strategy = strategy;
+        self
+    }
+
+    /// Reconnect attempts before giving up; `None` retries forever
+    pub fn with_max_reconnect_attempts(self, attempts: Option<u32>) -> Self {
+        self.max_reconnect_attempts
+            .store(attempts.unwrap_or(0), Ordering::SeqCst);
+        self
+    }
+
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Identify this slave to the master by a stable machine id and an optional friendly name
+    pub fn with_identity(mut self, machine_id: String, display_name: Option<String>) -> Self {
+        self.machine_id = Some(machine_id);
+        self.display_name = display_name.filter(|name| !name.trim().is_empty());
+        self
+    }
+
+    pub fn with_tls(mut self, tls: Option<Arc<ClientConfig>>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Open the TCP connection ourselves so keepalive is set before the WebSocket upgrade
+    async fn open_websocket(
+        host: &str,
+        port: u16,
+        url: &str,
+        keepalive: &KeepaliveConfig,
+        tls: Option<Arc<ClientConfig>>,
+    ) -> Result<WsStream> {
+        let tcp = TcpStream::connect((host, port)).await?;
+        if let Err(e) = apply_keepalive(&tcp, keepalive) {
+            eprintln!("Failed to enable TCP keepalive: {}", e);
+        }
+        let connector = match tls {
+            Some(config) => Connector::Rustls(config),
+            None => Connector::Plain,
+        };
+        let (ws_stream, _) = client_async_tls_with_config(url, tcp, None, Some(connector)).await?;
+        Ok(ws_stream)
     }
 
     pub async fn set_connection_status_callback<F>(&self, callback: F)
@@ -65,6 +258,43 @@ impl SlaveClient {
         *self.connection_status_callback.write().await = Some(Arc::new(callback));
     }
 
+    /// Set where ClientHello and StateSyncRequest get the master hashes of the images
+    /// this slave still has
+    pub async fn set_held_images_callback<F, Fut>(&self, callback: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Vec<String>> + Send + 'static,
+    {
+        let wrapped: HeldImagesCallback = Arc::new(move || Box::pin(callback()));
+        *self.held_images_callback.write().await = Some(wrapped);
+    }
+
+    async fn held_images(&self) -> Vec<String> {
+        let callback = self.held_images_callback.read().await.clone();
+        match callback {
+            Some(callback) => callback().await,
+            None => Vec::new(),
+        }
+    }
+
+    pub async fn set_status_callback<F>(&self, callback: F)
+    where
+        F: Fn(StatusEvent) + Send + Sync + 'static,
+    {
+        *self.status_callback.write().await = Some(Arc::new(callback));
+    }
+
+    /// Send a status event carrying the current reconnection status
+    async fn report_status(&self, level: StatusLevel, message: String) {
+        let callback = self.status_callback.read().await.clone();
+        if let Some(callback) = callback {
+            let reconnection = self.reconnection_status.read().await.clone();
+            callback(StatusEvent::new(level, message).with_reconnection(reconnection));
+        }
+    }
+
+    /// Whether a WebSocket to the master is open; set by the connection loop when
+    /// `connect_async` succeeds and cleared when the connection ends
     pub async fn is_connected(&self) -> bool {
         self.is_connected.load(Ordering::SeqCst)
     }
@@ -83,6 +313,34 @@ impl SlaveClient {
         }
     }
 
+    /// What this slave advertises: TLS and auth as it connects to the master
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::for_connection(self.tls.is_some(), self.auth_token.is_some())
+    }
+
+    pub async fn get_master_capabilities(&self) -> Option<Capabilities> {
+        self.master_capabilities.read().await.clone()
+    }
+
+    /// Whether an unparseable frame still looks like a sync message of a known type,
+    /// i.e. a real update was probably lost (truncated or corrupted in transit)
+    fn looks_like_sync_message(text: &str) -> bool {
+        text.trim_start().starts_with('{')
+            && Capabilities::current()
+                .message_types
+                .iter()
+                .filter_map(|t| serde_json::to_string(t).ok())
+                .any(|name| text.contains(&name))
+    }
+
+    /// Change how many reconnect attempts are made before giving up (0 = unlimited).
+    /// Takes effect from the next attempt, including on a loop that is already running.
+    pub async fn set_max_reconnect_attempts(&self, attempts: u32) {
+        self.max_reconnect_attempts
+            .store(attempts, Ordering::SeqCst);
+        self.reconnection_status.write().await.max_attempts = attempts;
+    }
+
     pub async fn get_reconnection_status(&self) -> ReconnectionStatus {
         self.reconnection_status.read().await.clone()
     }
@@ -90,7 +348,7 @@ impl SlaveClient {
     pub async fn request_resync(&self) -> Result<()> {
         let tx = self.sync_message_tx.read().await;
         if let Some(sender) = tx.as_ref() {
-            let request = SyncMessage::state_sync_request();
+            let request = SyncMessage::state_sync_request(self.held_images().await);
             sender
                 .send(request)
                 .map_err(|_| anyhow::anyhow!("Failed to send resync request"))?;
@@ -104,47 +362,49 @@ impl SlaveClient {
     pub async fn connect(
         &self,
     ) -> Result<(
-        mpsc::UnboundedReceiver<SyncMessage>,
+        mpsc::Receiver<SyncMessage>,
         mpsc::UnboundedSender<SyncMessage>,
     )> {
-        let (tx, rx) = mpsc::unbounded_channel::<SyncMessage>();
+        let (tx, rx) = mpsc::channel::<SyncMessage>(INCOMING_CAPACITY);
         let (send_tx, mut send_rx) = mpsc::unbounded_channel::<SyncMessage>();
 
-        let host = self.host.clone();
-        let port = self.port;
-        let should_reconnect = self.should_reconnect.clone();
-        let max_attempts = self.max_reconnect_attempts;
-        let message_tx_for_send = self.message_tx.clone();
-        let sync_message_tx_for_store = self.sync_message_tx.clone();
-
         // Channel to notify when first connection is established
         let (first_connection_tx, mut first_connection_rx) =
             mpsc::unbounded_channel::<Result<(), String>>();
 
         // Spawn task to handle sending messages (will be connected when WebSocket is ready)
-        let send_tx_for_sending = send_tx.clone();
-        let (send_ready_tx, mut send_ready_rx) =
-            mpsc::unbounded_channel::<futures_util::stream::SplitSink<_, _>>();
+        let (send_ready_tx, mut send_ready_rx) = mpsc::unbounded_channel::<WsSink>();
+        let master_capabilities_for_send = self.master_capabilities.clone();
 
         tokio::spawn(async move {
-            let mut current_sender: Option<futures_util::stream::SplitSink<_, _>> = None;
+            let mut current_sender: Option<WsSink> = None;
+            // Set once the connection loop is gone for good; no new socket will come
+            let mut sockets_closed = false;
 
             loop {
                 tokio::select! {
-                    // Receive new WebSocket sender
-                    sender = send_ready_rx.recv() => {
-                        if let Some(s) = sender {
-                            current_sender = Some(s);
-                        }
-                    }
+                    // A new socket is taken before queued messages, so what is queued
+                    // right after a reconnect (the ClientHello first) goes out on it
+                    // rather than on the dead one
+                    biased;
+                    sender = send_ready_rx.recv(), if !sockets_closed => match sender {
+                        Some(s) => current_sender = Some(s),
+                        None => sockets_closed = true,
+                    },
                     // Receive message to send
                     msg = send_rx.recv() => {
                         if let Some(msg) = msg {
+                            // Don't send message types the master did not advertise
+                            if let Some(caps) = master_capabilities_for_send.read().await.as_ref() {
+                                if !caps.supports(&msg.message_type) {
+                                    continue;
+                                }
+                            }
                             if let Some(ref mut sender) = current_sender {
                                 let json = match serde_json::to_string(&msg) {
                                     Ok(j) => j,
                                     Err(e) => {
-                                        eprintln!("Failed to serialize sync message: {}", e);
+                                        throttled_eprintln!("Failed to serialize sync message: {}", e);
                                         continue;
                                     }
                                 };
@@ -160,16 +420,103 @@ impl SlaveClient {
             }
         });
 
+        // Periodic heartbeat; the master's reply feeds clock-offset estimation. It keeps
+        // running while reconnection is off, so a loop restarted by `retry_connection`
+        // still has heartbeats, and ends once the outgoing channel is dropped.
+        let heartbeat_tx = send_tx.downgrade();
+        let is_connected_for_heartbeat = self.is_connected.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS)).await;
+                let Some(heartbeat_tx) = heartbeat_tx.upgrade() else {
+                    break;
+                };
+                if is_connected_for_heartbeat.load(Ordering::SeqCst)
+                    && heartbeat_tx.send(SyncMessage::heartbeat()).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
         // Spawn connection task with auto-reconnect
+        let link = ConnectionLink {
+            incoming_tx: tx,
+            outgoing_tx: send_tx.clone(),
+            send_ready_tx,
+        };
+        *self.connection_link.write().await = Some(link.clone());
+        self.spawn_connection_loop(link, first_connection_tx);
+
+        // Wait for first connection to be established (with timeout)
+        let timeout = tokio::time::Duration::from_secs(30);
+        match tokio::time::timeout(timeout, first_connection_rx.recv()).await {
+            Ok(Some(Ok(()))) => {
+                // Connection established successfully
+                Ok((rx, send_tx))
+            }
+            Ok(Some(Err(e))) => {
+                // Connection failed
+                self.set_connected(false).await;
+                Err(anyhow::anyhow!("Failed to connect to master: {}", e))
+            }
+            Ok(None) => {
+                // Channel closed unexpectedly
+                self.set_connected(false).await;
+                Err(anyhow::anyhow!("Connection attempt was cancelled"))
+            }
+            Err(_) => {
+                // Timeout
+                self.set_connected(false).await;
+                Err(anyhow::anyhow!(
+                    "Connection timeout after {} seconds",
+                    timeout.as_secs()
+                ))
+            }
+        }
+    }
+
+    /// Run the connect/reconnect loop in the background, forwarding traffic through `link`.
+    /// The loop ends when reconnection is disabled or max attempts are reached.
+    fn spawn_connection_loop(
+        &self,
+        link: ConnectionLink,
+        first_connection_tx: mpsc::UnboundedSender<Result<(), String>>,
+    ) {
+        let masters = self.masters.clone();
+        let active_master = self.active_master.clone();
+        let handoff_pending = self.handoff_pending.clone();
+        let path = self.path_token.clone().unwrap_or_default();
+        let keepalive = self.keepalive.clone();
+        let tls = self.tls.clone();
+        let auth_token = self.auth_token.clone();
+        let reconnect_strategy = self.reconnect_strategy;
+        let should_reconnect = self.should_reconnect.clone();
+        let max_reconnect_attempts = self.max_reconnect_attempts.clone();
+        let message_tx_for_send = self.message_tx.clone();
+        let sync_message_tx_for_store = self.sync_message_tx.clone();
+        let ConnectionLink {
+            incoming_tx: tx,
+            outgoing_tx: send_tx_for_sending,
+            send_ready_tx,
+        } = link;
+        let loop_active = self.connection_loop_active.clone();
+        loop_active.store(true, Ordering::SeqCst);
+
         let reconnection_status_for_task = self.reconnection_status.clone();
         let current_attempt_for_task = self.current_attempt.clone();
-        let first_connection_tx_for_task = first_connection_tx.clone();
+        let first_connection_tx_for_task = first_connection_tx;
         let client_for_status = Arc::new(self.clone());
         tokio::spawn(async move {
             let mut attempt = 0;
             let mut is_first_connection = true;
+            // Masters that used up their attempts since the last successful connection
+            let mut failed_masters = 0;
 
             loop {
+                let max_attempts = max_reconnect_attempts.load(Ordering::SeqCst);
+                let master_count = masters.read().await.len();
+                let attempts_exhausted = max_attempts != 0 && attempt >= max_attempts;
                 if !should_reconnect.load(Ordering::SeqCst) {
                     // Update status: not reconnecting
                     {
@@ -183,6 +530,33 @@ impl SlaveClient {
                     break;
                 }
 
+                // Move on to the next master once this one has used up its attempts. With
+                // unlimited attempts the masters are cycled through forever.
+                let attempts_per_master = if max_attempts == 0 {
+                    FAILOVER_AFTER_ATTEMPTS
+                } else {
+                    max_attempts
+                };
+                if master_count > 1 && attempt >= attempts_per_master {
+                    failed_masters += 1;
+                    if max_attempts == 0 || failed_masters < master_count {
+                        active_master.store(
+                            (active_master.load(Ordering::SeqCst) + 1) % master_count,
+                            Ordering::SeqCst,
+                        );
+                        attempt = 0;
+                        let endpoint = client_for_status.current_master().await;
+                        println!("Failing over to master at {}", endpoint);
+                        client_for_status
+                            .report_status(
+                                StatusLevel::Warning,
+                                format!("Failing over to master at {}", endpoint),
+                            )
+                            .await;
+                        continue;
+                    }
+                }
+
                 if attempt > 0 {
                     // Update status: reconnecting
                     {
@@ -193,8 +567,8 @@ impl SlaveClient {
                     }
                     current_attempt_for_task.store(attempt, Ordering::SeqCst);
 
-                    // Exponential backoff: 1s, 2s, 4s, 8s, 16s, max 30s
-                    let delay = std::cmp::min(2_u64.pow(attempt - 1), 30);
+                    // Backoff per the configured strategy (default: 1s, 2s, 4s, 8s, 16s, max 30s)
+                    let delay = reconnect_strategy.delay(attempt).as_secs();
                     println!(
                         "Reconnecting to master in {} seconds... (attempt {}/{})",
                         delay, attempt, max_attempts
@@ -202,7 +576,7 @@ impl SlaveClient {
                     tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
                 }
 
-                if attempt >= max_attempts {
+                if attempts_exhausted {
                     eprintln!(
                         "Max reconnection attempts ({}) reached. Stopping reconnection.",
                         max_attempts
@@ -218,6 +592,15 @@ impl SlaveClient {
                     }
                     current_attempt_for_task.store(0, Ordering::SeqCst);
                     client_for_status.clone().set_connected(false).await;
+                    client_for_status
+                        .report_status(
+                            StatusLevel::Error,
+                            format!(
+                                "Gave up reconnecting to master after {} attempts",
+                                max_attempts
+                            ),
+                        )
+                        .await;
                     // Notify first connection failure
                     if is_first_connection {
                         let _ = first_connection_tx_for_task.send(Err(format!(
@@ -228,11 +611,16 @@ impl SlaveClient {
                     break;
                 }
 
-                let url = format!("ws://{}:{}", host, port);
-                match connect_async(&url).await {
-                    Ok((ws_stream, _)) => {
+                let MasterEndpoint { host, port } = client_for_status.current_master().await;
+                reconnection_status_for_task.write().await.active_master =
+                    format!("{}:{}", host, port);
+                let scheme = if tls.is_some() { "wss" } else { "ws" };
+                let url = format!("{}://{}:{}/{}", scheme, host, port, path);
+                match Self::open_websocket(&host, port, &url, &keepalive, tls.clone()).await {
+                    Ok(ws_stream) => {
                         println!("Connected to master: {}", url);
                         attempt = 0; // Reset attempt counter on successful connection
+                        failed_masters = 0;
                         client_for_status.clone().set_connected(true).await;
                         // Update status: connected successfully
                         {
@@ -240,6 +628,7 @@ impl SlaveClient {
                             status.is_reconnecting = false;
                             status.attempt_count = 0;
                             status.last_error = None;
+                            status.master_stopped = false;
                         }
                         current_attempt_for_task.store(0, Ordering::SeqCst);
 
@@ -247,9 +636,25 @@ impl SlaveClient {
                         if is_first_connection {
                             is_first_connection = false;
                             let _ = first_connection_tx_for_task.send(Ok(()));
+                        } else {
+                            client_for_status
+                                .report_status(
+                                    StatusLevel::Info,
+                                    format!("Reconnected to master at {}:{}", host, port),
+                                )
+                                .await;
                         }
 
-                        let (ws_sender, mut ws_receiver) = ws_stream.split();
+                        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+                        // Authenticate before anything else can be queued on the socket
+                        if let Some(token) = auth_token.as_deref() {
+                            if let Ok(json) = serde_json::to_string(&SyncMessage::auth(token)) {
+                                if let Err(e) = ws_sender.send(Message::Text(json)).await {
+                                    eprintln!("Failed to send auth to master: {}", e);
+                                }
+                            }
+                        }
                         let tx_clone = tx.clone();
 
                         // Store sync message sender for resync requests
@@ -261,24 +666,269 @@ impl SlaveClient {
                         // Send ws_sender to sending task
                         let _ = send_ready_tx.send(ws_sender);
 
+                        // Tell the master our protocol version, then advertise our
+                        // capabilities; the master replies with its own
+                        *client_for_status.master_capabilities.write().await = None;
+                        let resume = client_for_status
+                            .received_seqs
+                            .read()
+                            .await
+                            .as_ref()
+                            .map(ReceivedSeqs::resume_point);
+                        let held_images = client_for_status.held_images().await;
+                        let _ = send_tx_for_sending.send(SyncMessage::client_hello(
+                            client_for_status.machine_id.clone(),
+                            client_for_status.display_name.clone(),
+                            resume,
+                            held_images.clone(),
+                        ));
+                        let _ = send_tx_for_sending
+                            .send(SyncMessage::capabilities(&client_for_status.capabilities()));
+
+                        // A new master after a handoff doesn't know our state yet
+                        if handoff_pending.swap(false, Ordering::SeqCst) {
+                            println!("Requesting state from new master after handoff");
+                            let _ = send_tx_for_sending
+                                .send(SyncMessage::state_sync_request(held_images));
+                        }
+
                         // Handle incoming messages
                         let should_reconnect_clone = should_reconnect.clone();
                         let message_tx_for_cleanup = message_tx_for_send.clone();
                         let sync_message_tx_for_cleanup = sync_message_tx_for_store.clone();
                         let reconnection_status_for_incoming = reconnection_status_for_task.clone();
                         let client_for_disconnect = client_for_status.clone();
-                        tokio::spawn(async move {
-                            while let Some(msg) = ws_receiver.next().await {
+                        let send_tx_for_recovery = send_tx_for_sending.clone();
+                        let handoff_pending_for_incoming = handoff_pending.clone();
+                        let incoming_task = tokio::spawn(async move {
+                            let mut last_recovery: Option<tokio::time::Instant> = None;
+                            let mut master_stopped = false;
+                            // Why the master and this slave can't talk, if they can't
+                            let mut incompatible: Option<String> = None;
+                            let heartbeat_timeout =
+                                tokio::time::Duration::from_secs(HEARTBEAT_TIMEOUT_SECS);
+                            loop {
+                                // A dead link behind a NAT may never produce a read error
+                                let msg = match tokio::time::timeout(
+                                    heartbeat_timeout,
+                                    ws_receiver.next(),
+                                )
+                                .await
+                                {
+                                    Ok(Some(msg)) => msg,
+                                    Ok(None) => break,
+                                    Err(_) => {
+                                        eprintln!(
+                                            "No heartbeat from master for {}s, reconnecting",
+                                            HEARTBEAT_TIMEOUT_SECS
+                                        );
+                                        break;
+                                    }
+                                };
                                 match msg {
                                     Ok(Message::Text(text)) => {
                                         match serde_json::from_str::<SyncMessage>(&text) {
+                                            Ok(sync_msg)
+                                                if sync_msg.message_type
+                                                    == SyncMessageType::Capabilities =>
+                                            {
+                                                match serde_json::from_value::<Capabilities>(
+                                                    sync_msg.payload,
+                                                ) {
+                                                    Ok(remote) => {
+                                                        let negotiated = client_for_disconnect
+                                                            .capabilities()
+                                                            .intersect(&remote);
+                                                        println!(
+                                                            "Negotiated capabilities with master: {:?}",
+                                                            negotiated
+                                                        );
+                                                        *client_for_disconnect
+                                                            .master_capabilities
+                                                            .write()
+                                                            .await = Some(negotiated);
+                                                    }
+                                                    Err(e) => {
+                                                        eprintln!(
+                                                            "Invalid capabilities from master: {}",
+                                                            e
+                                                        );
+                                                    }
+                                                }
+                                            }
+                                            Ok(sync_msg)
+                                                if sync_msg.message_type
+                                                    == SyncMessageType::MasterHandoff =>
+                                            {
+                                                match serde_json::from_value::<MasterHandoffPayload>(
+                                                    sync_msg.payload,
+                                                ) {
+                                                    Ok(handoff) => {
+                                                        // Drop this connection; the reconnect loop
+                                                        // retries the new address until it is up
+                                                        println!(
+                                                            "Master handed off to {}:{}",
+                                                            handoff.new_host, handoff.new_port
+                                                        );
+                                                        client_for_disconnect
+                                                            .replace_current_master(
+                                                                MasterEndpoint {
+                                                                    host: handoff.new_host,
+                                                                    port: handoff.new_port,
+                                                                },
+                                                            )
+                                                            .await;
+                                                        handoff_pending_for_incoming
+                                                            .store(true, Ordering::SeqCst);
+                                                        break;
+                                                    }
+                                                    Err(e) => {
+                                                        eprintln!("Invalid master handoff: {}", e);
+                                                    }
+                                                }
+                                            }
+                                            Ok(sync_msg)
+                                                if sync_msg.message_type
+                                                    == SyncMessageType::ServerHello =>
+                                            {
+                                                if let Ok(hello) =
+                                                    serde_json::from_value::<ServerHelloPayload>(
+                                                        sync_msg.payload,
+                                                    )
+                                                {
+                                                    println!(
+                                                        "Master is running obs-sync {} (protocol v{})",
+                                                        hello.app_version, hello.protocol_version
+                                                    );
+                                                    if !is_protocol_compatible(
+                                                        hello.protocol_version,
+                                                    ) {
+                                                        incompatible =
+                                                            Some(protocol_mismatch_reason(
+                                                                hello.protocol_version,
+                                                                PROTOCOL_VERSION,
+                                                            ));
+                                                        break;
+                                                    }
+                                                }
+                                            }
+                                            Ok(sync_msg)
+                                                if sync_msg.message_type
+                                                    == SyncMessageType::ResumeFrom =>
+                                            {
+                                                match serde_json::from_value::<ResumeFromPayload>(
+                                                    sync_msg.payload,
+                                                ) {
+                                                    Ok(resume) => {
+                                                        let mut received = client_for_disconnect
+                                                            .received_seqs
+                                                            .write()
+                                                            .await;
+                                                        let same_history =
+                                                            received.as_ref().is_some_and(|r| {
+                                                                r.history_id() == resume.history_id
+                                                            });
+                                                        if resume.replayed && same_history {
+                                                            println!(
+                                                                "Master is replaying changes after #{}",
+                                                                resume.last_seq
+                                                            );
+                                                        } else if same_history {
+                                                            // Full state follows for what was missed
+                                                            if let Some(received) =
+                                                                received.as_mut()
+                                                            {
+                                                                received.skip_to(resume.last_seq);
+                                                            }
+                                                        } else {
+                                                            *received = Some(ReceivedSeqs::new(
+                                                                resume.history_id,
+                                                                resume.last_seq,
+                                                            ));
+                                                        }
+                                                    }
+                                                    Err(e) => {
+                                                        eprintln!("Invalid resume position: {}", e);
+                                                    }
+                                                }
+                                            }
+                                            Ok(sync_msg)
+                                                if sync_msg.message_type
+                                                    == SyncMessageType::ServerShutdown =>
+                                            {
+                                                println!("Master stopped, not reconnecting");
+                                                master_stopped = true;
+                                                break;
+                                            }
                                             Ok(sync_msg) => {
-                                                if tx_clone.send(sync_msg).is_err() {
+                                                // Broadcasts both replayed and sent live arrive twice,
+                                                // and the master resends what wasn't acked in time
+                                                let resent = sync_msg.requires_ack
+                                                    && !client_for_disconnect
+                                                        .seen_ack_ids
+                                                        .write()
+                                                        .await
+                                                        .insert(&sync_msg.message_id);
+                                                let duplicate = resent
+                                                    || match (
+                                                        sync_msg.seq,
+                                                        client_for_disconnect
+                                                            .received_seqs
+                                                            .write()
+                                                            .await
+                                                            .as_mut(),
+                                                    ) {
+                                                        (Some(seq), Some(received)) => {
+                                                            !received.receive(seq)
+                                                        }
+                                                        _ => false,
+                                                    };
+                                                if duplicate {
+                                                    // Stop the master resending it; the copy that was
+                                                    // kept, or the full state that follows, covers it
+                                                    if sync_msg.requires_ack {
+                                                        let _ = send_tx_for_recovery.send(
+                                                            SyncMessage::ack(&sync_msg.message_id),
+                                                        );
+                                                    }
+                                                    continue;
+                                                }
+                                                if tx_clone.send(sync_msg).await.is_err() {
                                                     break;
                                                 }
                                             }
                                             Err(e) => {
-                                                eprintln!("Failed to parse sync message: {}", e);
+                                                throttled_eprintln!(
+                                                    "Failed to parse sync message: {}",
+                                                    e
+                                                );
+                                                reconnection_status_for_incoming
+                                                    .write()
+                                                    .await
+                                                    .parse_error_count += 1;
+
+                                                // Recover the lost update with a full resync
+                                                let recently_recovered =
+                                                    last_recovery.is_some_and(|at| {
+                                                        at.elapsed().as_secs()
+                                                            < PARSE_ERROR_RESYNC_INTERVAL_SECS
+                                                    });
+                                                if Self::looks_like_sync_message(&text)
+                                                    && !recently_recovered
+                                                {
+                                                    println!(
+                                                        "Corrupt sync frame received, requesting resync"
+                                                    );
+                                                    last_recovery =
+                                                        Some(tokio::time::Instant::now());
+                                                    let held_images =
+                                                        client_for_disconnect.held_images().await;
+                                                    let _ = send_tx_for_recovery.send(
+                                                        SyncMessage::state_sync_request(
+                                                            held_images,
+                                                        ),
+                                                    );
+                                                }
                                             }
                                         }
                                     }
@@ -286,8 +936,13 @@ impl SlaveClient {
                                         // Pong will be handled by the sending task via ws_sender
                                         // This is handled automatically by tokio-tungstenite
                                     }
-                                    Ok(Message::Close(_)) => {
+                                    Ok(Message::Close(frame)) => {
                                         println!("Connection closed by master");
+                                        if let Some(frame) =
+                                            frame.filter(|f| f.code == CloseCode::Protocol)
+                                        {
+                                            incompatible = Some(frame.reason.to_string());
+                                        }
                                         break;
                                     }
                                     Err(e) => {
@@ -297,8 +952,13 @@ impl SlaveClient {
                                     _ => {}
                                 }
                             }
-                            // Connection lost, signal for reconnection
-                            should_reconnect_clone.store(true, Ordering::SeqCst);
+                            // Connection lost, signal for reconnection unless the master
+                            // went away on purpose or reconnecting can't help
+                            if let Some(reason) = &incompatible {
+                                eprintln!("{}", reason);
+                            }
+                            let retry = !master_stopped && incompatible.is_none();
+                            should_reconnect_clone.store(retry, Ordering::SeqCst);
                             // Clear message sender
                             {
                                 let mut tx = message_tx_for_cleanup.write().await;
@@ -312,16 +972,31 @@ impl SlaveClient {
                             // Update status: connection lost, will reconnect
                             {
                                 let mut status = reconnection_status_for_incoming.write().await;
-                                status.is_reconnecting = true;
+                                status.is_reconnecting = retry;
                                 status.attempt_count = 0;
-                                status.last_error = Some("Connection lost".to_string());
+                                status.master_stopped = master_stopped;
+                                status.last_error = Some(match &incompatible {
+                                    Some(reason) => reason.clone(),
+                                    None if master_stopped => "Master stopped".to_string(),
+                                    None => "Connection lost".to_string(),
+                                });
                             }
                             client_for_disconnect.set_connected(false).await;
+                            let (level, message) = match incompatible {
+                                Some(reason) => (StatusLevel::Error, reason),
+                                None if master_stopped => {
+                                    (StatusLevel::Info, "Master stopped".to_string())
+                                }
+                                None => (
+                                    StatusLevel::Warning,
+                                    "Connection to master lost".to_string(),
+                                ),
+                            };
+                            client_for_disconnect.report_status(level, message).await;
                         });
 
-                        // Wait for connection to break
-                        // The spawned task above will handle reconnection
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        // Reconnect (or stop) once the connection breaks
+                        let _ = incoming_task.await;
                     }
                     Err(e) => {
                         attempt += 1;
@@ -338,41 +1013,56 @@ impl SlaveClient {
                         }
                         current_attempt_for_task.store(attempt, Ordering::SeqCst);
                         client_for_status.clone().set_connected(false).await;
+                        client_for_status
+                            .report_status(
+                                StatusLevel::Warning,
+                                format!("Failed to connect to master: {}", e),
+                            )
+                            .await;
                         // Notify first connection failure
-                        if is_first_connection && attempt >= max_attempts {
+                        if is_first_connection
+                            && max_attempts != 0
+                            && attempt >= max_attempts
+                            && failed_masters + 1 >= master_count
+                        {
                             let _ = first_connection_tx_for_task.send(Err(format!("{}", e)));
                         }
                     }
                 }
             }
+            loop_active.store(false, Ordering::SeqCst);
         });
+    }
 
-        // Wait for first connection to be established (with timeout)
-        let timeout = tokio::time::Duration::from_secs(30);
-        match tokio::time::timeout(timeout, first_connection_rx.recv()).await {
-            Ok(Some(Ok(()))) => {
-                // Connection established successfully
-                Ok((rx, send_tx))
-            }
-            Ok(Some(Err(e))) => {
-                // Connection failed
-                self.set_connected(false).await;
-                Err(anyhow::anyhow!("Failed to connect to master: {}", e))
-            }
-            Ok(None) => {
-                // Channel closed unexpectedly
-                self.set_connected(false).await;
-                Err(anyhow::anyhow!("Connection attempt was cancelled"))
-            }
-            Err(_) => {
-                // Timeout
-                self.set_connected(false).await;
-                Err(anyhow::anyhow!(
-                    "Connection timeout after {} seconds",
-                    timeout.as_secs()
-                ))
-            }
+    /// Restart the reconnection loop after it gave up, keeping the existing channels
+    /// (and therefore the SlaveSync consuming them) intact
+    pub async fn retry_connection(&self) -> Result<()> {
+        if self.connection_loop_active.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("Already connected or reconnecting"));
         }
+        let link = self
+            .connection_link
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Not connected to master"))?;
+
+        self.should_reconnect.store(true, Ordering::SeqCst);
+        self.current_attempt.store(0, Ordering::SeqCst);
+        {
+            let mut status = self.reconnection_status.write().await;
+            status.is_reconnecting = true;
+            status.attempt_count = 0;
+            status.max_attempts = self.max_reconnect_attempts.load(Ordering::SeqCst);
+            status.last_error = None;
+            status.master_stopped = false;
+        }
+
+        // Nobody waits for the first connection here; the status carries the outcome
+        let (first_connection_tx, _) = mpsc::unbounded_channel();
+        println!("Retrying connection to master");
+        self.spawn_connection_loop(link, first_connection_tx);
+        Ok(())
     }
 
     pub async fn disconnect(&self) {
@@ -400,10 +1090,194 @@ impl SlaveClient {
             let mut sync_tx = self.sync_message_tx.write().await;
             *sync_tx = None;
         }
+        *self.connection_link.write().await = None;
+    }
+}
 
-        let mut stream_lock = self.ws_stream.write().await;
-        if let Some(mut stream) = stream_lock.take() {
-            let _ = stream.close(None).await;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Accept one WebSocket connection on `listener` and hand back the open socket
+    fn accept_one(
+        listener: TcpListener,
+    ) -> tokio::task::JoinHandle<tokio_tungstenite::WebSocketStream<TcpStream>> {
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio_tungstenite::accept_async(stream).await.unwrap()
+        })
+    }
+
+    #[tokio::test]
+    async fn retry_connection_resumes_after_giving_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = SlaveClient::new("127.0.0.1".to_string(), port)
+            .with_max_reconnect_attempts(Some(1))
+            .with_reconnect_strategy(ReconnectStrategy::Fixed { secs: 0 });
+
+        let master = accept_one(listener);
+        let (_incoming, _outgoing) = client.connect().await.unwrap();
+        // The master drops the connection and is gone when the slave tries again
+        drop(master.await.unwrap());
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while client.connection_loop_active.load(Ordering::SeqCst) {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("slave never gave up");
+        let status = client.get_reconnection_status().await;
+        assert!(!status.is_reconnecting);
+        assert!(status
+            .last_error
+            .unwrap()
+            .contains("Max reconnection attempts"));
+
+        let master = accept_one(TcpListener::bind(("127.0.0.1", port)).await.unwrap());
+        client.retry_connection().await.unwrap();
+        assert_eq!(client.get_reconnection_status().await.attempt_count, 0);
+        let _socket = tokio::time::timeout(tokio::time::Duration::from_secs(5), master)
+            .await
+            .expect("slave did not reconnect")
+            .unwrap();
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while !client.is_connected().await {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("slave did not report the connection");
+        assert!(client.get_reconnection_status().await.last_error.is_none());
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn corrupt_sync_frame_requests_a_resync() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = SlaveClient::new("127.0.0.1".to_string(), port);
+        let master = accept_one(listener);
+        let (_incoming, _outgoing) = client.connect().await.unwrap();
+        let mut master = master.await.unwrap();
+
+        // Noise is counted but not recovered; a truncated SceneChange is
+        master
+            .send(Message::Text("not json".to_string()))
+            .await
+            .unwrap();
+        master
+            .send(Message::Text(
+                r#"{"type":"scene_change","payload":{"scene_na"#.to_string(),
+            ))
+            .await
+            .unwrap();
+
+        let request = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while let Some(Ok(frame)) = master.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let message: SyncMessage = serde_json::from_str(&text).unwrap();
+                if message.message_type == SyncMessageType::StateSyncRequest {
+                    return Some(message);
+                }
+            }
+            None
+        })
+        .await
+        .expect("slave did not ask for a resync");
+        assert!(request.is_some());
+        assert_eq!(client.get_reconnection_status().await.parse_error_count, 2);
+        assert!(!SlaveClient::looks_like_sync_message("not json"));
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn handoff_redirects_the_slave_to_the_new_master() {
+        let old_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let old_port = old_listener.local_addr().unwrap().port();
+        // Free port for the new master, which isn't listening yet when the handoff arrives
+        let new_port = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        // Unlimited attempts: without a delay the default limit runs out before the new master is up
+        let client = SlaveClient::new("127.0.0.1".to_string(), old_port)
+            .with_max_reconnect_attempts(None)
+            .with_reconnect_strategy(ReconnectStrategy::Fixed { secs: 0 });
+        let old_master = accept_one(old_listener);
+        let (_incoming, _outgoing) = client.connect().await.unwrap();
+        let mut old_master = old_master.await.unwrap();
+
+        let handoff = SyncMessage::master_handoff("127.0.0.1", new_port);
+        old_master
+            .send(Message::Text(serde_json::to_string(&handoff).unwrap()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        assert_eq!(client.current_master().await.port, new_port);
+
+        // The slave keeps retrying until the new master comes up, then asks it for state
+        let new_master = accept_one(TcpListener::bind(("127.0.0.1", new_port)).await.unwrap());
+        let mut new_master = tokio::time::timeout(tokio::time::Duration::from_secs(5), new_master)
+            .await
+            .expect("slave did not reconnect to the new master")
+            .unwrap();
+        let requested = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while let Some(Ok(frame)) = new_master.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let message: SyncMessage = serde_json::from_str(&text).unwrap();
+                if message.message_type == SyncMessageType::StateSyncRequest {
+                    return true;
+                }
+            }
+            false
+        })
+        .await
+        .expect("slave did not request state from the new master");
+        assert!(requested);
+        assert!(!client.handoff_pending.load(Ordering::SeqCst));
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn changed_attempt_limit_governs_the_next_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = SlaveClient::new("127.0.0.1".to_string(), port)
+            .with_max_reconnect_attempts(Some(1))
+            .with_reconnect_strategy(ReconnectStrategy::Fixed { secs: 0 });
+        let master = accept_one(listener);
+        let (_incoming, _outgoing) = client.connect().await.unwrap();
+
+        // Switched to unlimited while connected, the slave outlasts its original single attempt
+        client.set_max_reconnect_attempts(0).await;
+        assert_eq!(client.get_reconnection_status().await.max_attempts, 0);
+        drop(master.await.unwrap());
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        assert!(client.connection_loop_active.load(Ordering::SeqCst));
+        assert!(client.get_reconnection_status().await.attempt_count > 1);
+
+        // Bounded again, it gives up
+        client.set_max_reconnect_attempts(2).await;
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while client.connection_loop_active.load(Ordering::SeqCst) {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("slave kept retrying past the new limit");
+        assert!(client
+            .get_reconnection_status()
+            .await
+            .last_error
+            .unwrap()
+            .contains("Max reconnection attempts (2)"));
     }
 }
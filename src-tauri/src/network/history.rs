@@ -0,0 +1,237 @@
+use crate::sync::protocol::{ResumePoint, SyncMessage, SyncMessageType};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+/// Broadcasts kept for replay to slaves that reconnect after a short drop
+const MAX_HISTORY_MESSAGES: usize = 1024;
+/// Images and full states make messages large, so the history is capped by size too
+const MAX_HISTORY_BYTES: usize = 32 * 1024 * 1024;
+
+/// Received sequence numbers kept above the contiguous run before a gap is given up on.
+/// A gap that never fills means the master never sent that message to this slave.
+const MAX_AHEAD: usize = 4096;
+
+/// Acknowledged message ids a slave remembers to recognise resends
+const MAX_SEEN_ACK_IDS: usize = 512;
+
+struct HistoryEntry {
+    seq: u64,
+    message_type: SyncMessageType,
+    json: String,
+}
+
+/// The master's recent broadcasts, numbered in the order they were made
+pub struct MessageHistory {
+    /// New for every master run
+    id: String,
+    next_seq: u64,
+    entries: VecDeque<HistoryEntry>,
+    bytes: usize,
+}
+
+impl Default for MessageHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageHistory {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            next_seq: 1,
+            entries: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Sequence number of the newest broadcast, 0 before the first
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Number `message`, serialize it and keep it for replay. Returns the JSON to send.
+    pub fn record(&mut self, message: &mut SyncMessage) -> serde_json::Result<String> {
+        message.seq = Some(self.next_seq);
+        let json = serde_json::to_string(message)?;
+        self.bytes += json.len();
+        self.entries.push_back(HistoryEntry {
+            seq: self.next_seq,
+            message_type: message.message_type.clone(),
+            json: json.clone(),
+        });
+        self.next_seq += 1;
+        while self.entries.len() > MAX_HISTORY_MESSAGES || self.bytes > MAX_HISTORY_BYTES {
+            match self.entries.pop_front() {
+                Some(entry) => self.bytes -= entry.json.len(),
+                None => break,
+            }
+        }
+        Ok(json)
+    }
+
+    /// The broadcasts made after `point` with their numbers, or None when they can't
+    /// all be replayed because the master restarted since or the oldest have been dropped
+    pub fn since(&self, point: &ResumePoint) -> Option<Vec<(u64, SyncMessageType, String)>> {
+        if point.history_id != self.id || point.last_seq > self.last_seq() {
+            return None;
+        }
+        let oldest = self
+            .entries
+            .front()
+            .map_or(self.next_seq, |entry| entry.seq);
+        if point.last_seq + 1 < oldest {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .filter(|entry| entry.seq > point.last_seq)
+                .map(|entry| (entry.seq, entry.message_type.clone(), entry.json.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Ids of the messages the master asked a slave to acknowledge. Targeted messages
+/// carry no sequence number, so a resend after a slow apply or a reconnect is
+/// recognised by its id instead.
+#[derive(Default)]
+pub struct SeenMessageIds {
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl SeenMessageIds {
+    /// Record `id`; false if it was seen before and should be dropped
+    pub fn insert(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > MAX_SEEN_ACK_IDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Which of the master's broadcasts a slave has received. Bulk messages can be
+/// overtaken by later control messages, so the resume point is the end of the
+/// contiguous run rather than the highest number seen.
+pub struct ReceivedSeqs {
+    history_id: String,
+    contiguous: u64,
+    /// Received numbers past a gap in the contiguous run
+    ahead: BTreeSet<u64>,
+}
+
+impl ReceivedSeqs {
+    /// Start tracking from `last_seq` of the master history `history_id`
+    pub fn new(history_id: String, last_seq: u64) -> Self {
+        Self {
+            history_id,
+            contiguous: last_seq,
+            ahead: BTreeSet::new(),
+        }
+    }
+
+    pub fn history_id(&self) -> &str {
+        &self.history_id
+    }
+
+    pub fn resume_point(&self) -> ResumePoint {
+        ResumePoint {
+            history_id: self.history_id.clone(),
+            last_seq: self.contiguous,
+        }
+    }
+
+    /// Treat everything up to `seq` as received: the master replaced what it dropped
+    /// on the way to this slave with a full state
+    pub fn skip_to(&mut self, seq: u64) {
+        if seq <= self.contiguous {
+            return;
+        }
+        self.contiguous = seq;
+        self.ahead = self.ahead.split_off(&(seq + 1));
+        while self.ahead.remove(&(self.contiguous + 1)) {
+            self.contiguous += 1;
+        }
+    }
+
+    /// Record `seq`; false if it was received before and should be dropped
+    pub fn receive(&mut self, seq: u64) -> bool {
+        if seq <= self.contiguous || !self.ahead.insert(seq) {
+            return false;
+        }
+        if self.ahead.len() > MAX_AHEAD {
+            if let Some(&first) = self.ahead.first() {
+                self.contiguous = first - 1;
+            }
+        }
+        while self.ahead.remove(&(self.contiguous + 1)) {
+            self.contiguous += 1;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::protocol::SyncTargetType;
+    use serde_json::Value;
+
+    #[test]
+    fn resume_point_moves_past_dropped_broadcasts_after_a_full_state() {
+        let mut received = ReceivedSeqs::new("run".to_string(), 0);
+        for seq in [1, 2, 5, 6] {
+            assert!(received.receive(seq));
+        }
+        // 3 and 4 were dropped on the master, so the run stops before them
+        assert_eq!(received.resume_point().last_seq, 2);
+
+        received.skip_to(6);
+        assert_eq!(received.resume_point().last_seq, 6);
+        assert!(!received.receive(4));
+        assert!(received.receive(7));
+        assert_eq!(received.resume_point().last_seq, 7);
+    }
+
+    #[test]
+    fn history_replays_only_what_follows_the_resume_point() {
+        let mut history = MessageHistory::new();
+        for _ in 0..5 {
+            let mut message = SyncMessage::new(
+                SyncMessageType::MuteUpdate,
+                SyncTargetType::Source,
+                Value::Null,
+            );
+            history.record(&mut message).unwrap();
+        }
+        let point = ResumePoint {
+            history_id: history.id().to_string(),
+            last_seq: 3,
+        };
+        let seqs: Vec<u64> = history
+            .since(&point)
+            .unwrap()
+            .into_iter()
+            .map(|(seq, _, _)| seq)
+            .collect();
+        assert_eq!(seqs, vec![4, 5]);
+
+        // Another run's numbers mean nothing here
+        let other = ResumePoint {
+            history_id: "other".to_string(),
+            last_seq: 3,
+        };
+        assert!(history.since(&other).is_none());
+    }
+}
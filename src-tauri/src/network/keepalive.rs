@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// TCP keepalive applied to master and slave sockets before the WebSocket upgrade,
+/// so half-open connections behind NATs/firewalls are noticed by the OS.
+///
+/// What can be tuned depends on the platform: `idle_secs` is honoured everywhere
+/// (TCP_KEEPIDLE on Linux, TCP_KEEPALIVE on macOS, keepalivetime on Windows), while
+/// `interval_secs` is only applied on Linux, macOS, Windows and the BSDs. The number
+/// of probes before the connection is dropped is always left at the OS default.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeepaliveConfig {
+    pub enabled: bool,
+    /// Idle time before the first probe
+    pub idle_secs: u64,
+    /// Time between unanswered probes
+    pub interval_secs: u64,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_secs: 30,
+            interval_secs: 10,
+        }
+    }
+}
+
+/// Enable SO_KEEPALIVE on `stream` according to `config` (no-op when disabled)
+pub fn apply_keepalive(stream: &TcpStream, config: &KeepaliveConfig) -> std::io::Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let keepalive =
+        socket2::TcpKeepalive::new().with_time(Duration::from_secs(config.idle_secs.max(1)));
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "windows",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "dragonfly",
+    ))]
+    let keepalive = keepalive.with_interval(Duration::from_secs(config.interval_secs.max(1)));
+    socket2::SockRef::from(stream).set_tcp_keepalive(&keepalive)
+}
@@ -1,2 +1,9 @@
 pub mod client;
+pub mod history;
+pub mod keepalive;
+pub mod probe;
+pub mod queue;
+pub mod reconnect;
 pub mod server;
+pub mod status;
+pub mod tls;
@@ -0,0 +1,129 @@
+use crate::sync::protocol::{
+    is_protocol_compatible, ServerHelloPayload, SyncMessage, SyncMessageType,
+};
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::time::{timeout, Duration, Instant};
+use tokio_rustls::rustls::ClientConfig;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+
+/// How long a probe waits for the TCP/WebSocket handshake and then for the hello
+const PROBE_TIMEOUT_SECS: u64 = 2;
+
+/// What a successful `probe_master` found at the address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MasterProbeResult {
+    pub app_version: String,
+    pub protocol_version: u16,
+    /// Whether this build can sync with the master
+    pub compatible: bool,
+    pub auth_required: bool,
+    /// Time from opening the connection to receiving the hello
+    pub latency_ms: u64,
+}
+
+/// Connect to `host:port`, wait for the master's ServerHello and hang up again.
+/// Nothing is sent besides the close frame, so the master never treats the probe as a slave.
+/// With `tls` the probe connects over wss://, verified the same way a slave's connection is.
+pub async fn probe_master(
+    host: &str,
+    port: u16,
+    path_token: Option<&str>,
+    tls: Option<Arc<ClientConfig>>,
+) -> Result<MasterProbeResult> {
+    let scheme = if tls.is_some() { "wss" } else { "ws" };
+    let url = format!(
+        "{}://{}:{}/{}",
+        scheme,
+        host,
+        port,
+        path_token.unwrap_or_default()
+    );
+    let connector = match tls {
+        Some(config) => Connector::Rustls(config),
+        None => Connector::Plain,
+    };
+    let limit = Duration::from_secs(PROBE_TIMEOUT_SECS);
+    let started = Instant::now();
+
+    let connect =
+        tokio_tungstenite::connect_async_tls_with_config(&url, None, false, Some(connector));
+    let (mut ws_stream, _) = timeout(limit, connect)
+        .await
+        .with_context(|| format!("No response from {}:{}", host, port))?
+        .with_context(|| format!("No WebSocket server at {}:{}", host, port))?;
+
+    let first_frame = timeout(limit, ws_stream.next()).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let _ = ws_stream.close(None).await;
+    let text = match first_frame {
+        Ok(Some(Ok(Message::Text(text)))) => text,
+        Ok(Some(Ok(Message::Close(frame)))) => bail!(
+            "Connection closed by {}:{}{}",
+            host,
+            port,
+            frame.map(|f| format!(": {}", f.reason)).unwrap_or_default()
+        ),
+        Ok(Some(Err(e))) => bail!("Connection to {}:{} failed: {}", host, port, e),
+        // Masters from before ServerHello existed also end up here
+        Ok(None) | Err(_) | Ok(Some(Ok(_))) => {
+            bail!("{}:{} is not an obs-sync master", host, port)
+        }
+    };
+
+    let hello = serde_json::from_str::<SyncMessage>(&text)
+        .ok()
+        .filter(|msg| msg.message_type == SyncMessageType::ServerHello)
+        .and_then(|msg| serde_json::from_value::<ServerHelloPayload>(msg.payload).ok())
+        .with_context(|| format!("{}:{} is not an obs-sync master", host, port))?;
+
+    Ok(MasterProbeResult {
+        app_version: hello.app_version,
+        protocol_version: hello.protocol_version,
+        compatible: is_protocol_compatible(hello.protocol_version),
+        auth_required: hello.auth_required,
+        latency_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::server::MasterServer;
+    use crate::network::tls::{self, MasterTls};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn probe_reaches_a_pinned_tls_master() {
+        let dir = std::env::temp_dir().join(format!("obs-sync-probe-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (cert_path, key_path) = (dir.join("cert.pem"), dir.join("key.pem"));
+        tls::ensure_self_signed(&cert_path, &key_path, tls::local_names()).unwrap();
+        let master_tls = MasterTls::load(&cert_path, &key_path).unwrap();
+        let fingerprint = master_tls.fingerprint.clone();
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = MasterServer::new(port, None);
+        server.set_tls(Some(master_tls)).await;
+        let (_tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+
+        let pinned = tls::client_config(None, Some(&fingerprint)).unwrap();
+        let result = probe_master("127.0.0.1", port, None, Some(pinned))
+            .await
+            .unwrap();
+        assert!(result.compatible);
+        assert!(!result.auth_required);
+
+        // A plain ws:// probe doesn't get through the TLS listener
+        assert!(probe_master("127.0.0.1", port, None, None).await.is_err());
+    }
+}
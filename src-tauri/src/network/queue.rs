@@ -0,0 +1,313 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Bytes one connection may have queued before its oldest droppable messages go
+const MAX_QUEUED_BYTES: usize = 64 * 1024 * 1024;
+/// Messages one connection may have queued before its oldest droppable messages go
+const MAX_QUEUED_MESSAGES: usize = 4096;
+
+/// Why a message could not be queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    /// The connection is being torn down
+    Closed,
+    /// The queue filled up with messages that can't be dropped, so the connection is given up
+    Stalled,
+}
+
+impl std::fmt::Display for QueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueueError::Closed => write!(f, "Connection is closed"),
+            QueueError::Stalled => write!(f, "Connection is not keeping up"),
+        }
+    }
+}
+
+impl std::error::Error for QueueError {}
+
+struct QueuedFrame {
+    /// Order of queueing across both lanes, so the oldest droppable frame can be found
+    order: u64,
+    message: Message,
+    /// Master broadcast number the frame carries, if any
+    seq: Option<u64>,
+    droppable: bool,
+}
+
+#[derive(Default)]
+struct QueueState {
+    control: VecDeque<QueuedFrame>,
+    bulk: VecDeque<QueuedFrame>,
+    next_order: u64,
+    bytes: usize,
+    /// Droppable frames discarded since `take_dropped` was last called
+    dropped: u64,
+    /// Highest broadcast number among the frames handed out or discarded
+    handled_seq: u64,
+    closed: bool,
+}
+
+impl QueueState {
+    fn len(&self) -> usize {
+        self.control.len() + self.bulk.len()
+    }
+
+    /// A single frame is always accepted, however large
+    fn is_full(&self) -> bool {
+        self.len() > 1 && (self.bytes > MAX_QUEUED_BYTES || self.len() > MAX_QUEUED_MESSAGES)
+    }
+
+    /// Discard the oldest droppable frame of either lane; false if there is none
+    fn drop_oldest(&mut self) -> bool {
+        let control = self.control.iter().position(|frame| frame.droppable);
+        let bulk = self.bulk.iter().position(|frame| frame.droppable);
+        let frame = match (control, bulk) {
+            (Some(c), Some(b)) if self.bulk[b].order < self.control[c].order => self.bulk.remove(b),
+            (Some(c), _) => self.control.remove(c),
+            (None, Some(b)) => self.bulk.remove(b),
+            (None, None) => None,
+        };
+        let Some(frame) = frame else {
+            return false;
+        };
+        self.bytes -= frame.message.len();
+        self.dropped += 1;
+        self.handled(&frame);
+        true
+    }
+
+    fn handled(&mut self, frame: &QueuedFrame) {
+        if let Some(seq) = frame.seq {
+            self.handled_seq = self.handled_seq.max(seq);
+        }
+    }
+
+    fn pop(&mut self, bulk: bool) -> Option<Message> {
+        let lane = if bulk {
+            &mut self.bulk
+        } else {
+            &mut self.control
+        };
+        let frame = lane.pop_front()?;
+        self.bytes -= frame.message.len();
+        self.handled(&frame);
+        Some(frame.message)
+    }
+}
+
+/// Outgoing frames of one connection, capped in count and size. Control frames are
+/// taken before bulk ones. When the cap is hit the oldest droppable frames are discarded;
+/// if only frames that must not be dropped are left, the connection counts as stalled.
+pub struct OutgoingQueue {
+    state: Mutex<QueueState>,
+    /// Wakes the single task that sends the frames
+    changed: Notify,
+    /// Wakes the connection handler once the queue has stalled
+    stalled: Notify,
+}
+
+impl Default for OutgoingQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutgoingQueue {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            changed: Notify::new(),
+            stalled: Notify::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queue `message` on the control or bulk lane
+    pub fn push(&self, message: Message, bulk: bool, droppable: bool) -> Result<(), QueueError> {
+        self.push_numbered(message, None, bulk, droppable)
+    }
+
+    /// Queue a frame carrying master broadcast number `seq`. Broadcasts must be queued
+    /// in the order they were numbered.
+    pub fn push_numbered(
+        &self,
+        message: Message,
+        seq: Option<u64>,
+        bulk: bool,
+        droppable: bool,
+    ) -> Result<(), QueueError> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(QueueError::Closed);
+        }
+        let frame = QueuedFrame {
+            order: state.next_order,
+            message,
+            seq,
+            droppable,
+        };
+        state.next_order += 1;
+        state.bytes += frame.message.len();
+        if bulk {
+            state.bulk.push_back(frame);
+        } else {
+            state.control.push_back(frame);
+        }
+
+        while state.is_full() {
+            if !state.drop_oldest() {
+                // Nothing left to give up; free the memory and end the connection
+                state.control.clear();
+                state.bulk.clear();
+                state.bytes = 0;
+                state.closed = true;
+                drop(state);
+                self.changed.notify_one();
+                self.stalled.notify_one();
+                return Err(QueueError::Stalled);
+            }
+        }
+        drop(state);
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    pub fn pop_control(&self) -> Option<Message> {
+        self.lock().pop(false)
+    }
+
+    pub fn pop_bulk(&self) -> Option<Message> {
+        self.lock().pop(true)
+    }
+
+    /// Number of frames discarded since the last call
+    pub fn take_dropped(&self) -> u64 {
+        std::mem::take(&mut self.lock().dropped)
+    }
+
+    /// Highest broadcast number among the frames sent or discarded so far. Once the
+    /// queue has run empty, every broadcast up to it has left one way or the other.
+    pub fn handled_seq(&self) -> u64 {
+        self.lock().handled_seq
+    }
+
+    /// Refuse new frames; those already queued are still handed out
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.changed.notify_one();
+    }
+
+    /// Closed and drained
+    pub fn is_finished(&self) -> bool {
+        let state = self.lock();
+        state.closed && state.len() == 0
+    }
+
+    /// Wait until frames are queued or the queue is closed. Only one task may wait.
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+
+    /// Wait until the queue gives up on the connection. Only one task may wait.
+    pub async fn stalled(&self) {
+        self.stalled.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Message {
+        Message::Text(s.to_string())
+    }
+
+    fn popped(message: Option<Message>) -> Option<String> {
+        message.map(|message| message.into_text().unwrap())
+    }
+
+    #[test]
+    fn oldest_droppable_frame_goes_first_across_lanes() {
+        let queue = OutgoingQueue::new();
+        queue.push(text("old bulk"), true, true).unwrap();
+        queue.push(text("newer control"), false, true).unwrap();
+        queue.push(text("newest bulk"), true, true).unwrap();
+        for _ in 0..MAX_QUEUED_MESSAGES - 3 {
+            queue.push(text("keep"), false, false).unwrap();
+        }
+        assert_eq!(queue.take_dropped(), 0);
+
+        // Each frame past the cap evicts the oldest droppable one, whichever lane it is in
+        queue.push(text("keep"), false, false).unwrap();
+        assert_eq!(queue.take_dropped(), 1);
+        queue.push(text("keep"), false, false).unwrap();
+        assert_eq!(queue.take_dropped(), 1);
+
+        assert_eq!(popped(queue.pop_bulk()).as_deref(), Some("newest bulk"));
+        assert_eq!(popped(queue.pop_bulk()), None);
+        assert_eq!(popped(queue.pop_control()).as_deref(), Some("keep"));
+    }
+
+    #[tokio::test]
+    async fn queue_of_undroppable_frames_stalls_and_closes() {
+        let queue = OutgoingQueue::new();
+        for _ in 0..MAX_QUEUED_MESSAGES {
+            queue.push(text("keep"), false, false).unwrap();
+        }
+
+        assert_eq!(
+            queue.push(text("one too many"), true, false),
+            Err(QueueError::Stalled)
+        );
+        tokio::time::timeout(std::time::Duration::from_secs(1), queue.stalled())
+            .await
+            .expect("stall was not signalled");
+        // Everything queued was freed and nothing more is accepted
+        assert!(queue.is_finished());
+        assert_eq!(popped(queue.pop_control()), None);
+        assert_eq!(
+            queue.push(text("late"), false, true),
+            Err(QueueError::Closed)
+        );
+    }
+
+    #[test]
+    fn single_frame_is_never_full() {
+        let oversized = |order| QueuedFrame {
+            order,
+            message: text("x"),
+            seq: None,
+            droppable: false,
+        };
+        let mut state = QueueState::default();
+        state.bulk.push_back(oversized(0));
+        state.bytes = MAX_QUEUED_BYTES + 1;
+        assert!(!state.is_full());
+
+        state.control.push_back(oversized(1));
+        assert!(state.is_full());
+    }
+
+    #[test]
+    fn closed_queue_still_hands_out_queued_frames() {
+        let queue = OutgoingQueue::new();
+        queue.push(text("bulk"), true, false).unwrap();
+        queue.push(text("control"), false, false).unwrap();
+        queue.close();
+
+        assert_eq!(
+            queue.push(text("late"), false, false),
+            Err(QueueError::Closed)
+        );
+        assert!(!queue.is_finished());
+        assert_eq!(popped(queue.pop_control()).as_deref(), Some("control"));
+        assert_eq!(popped(queue.pop_bulk()).as_deref(), Some("bulk"));
+        assert!(queue.is_finished());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long the slave waits before each reconnection attempt to the master
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ReconnectStrategy {
+    /// Same delay before every attempt; suits stable LANs
+    Fixed { secs: u64 },
+    /// `step * attempt` seconds, at most `cap`
+    Linear { step: u64, cap: u64 },
+    /// `base ^ (attempt - 1)` seconds, at most `cap`; suits flaky WAN links
+    Exponential { base: u64, cap: u64 },
+}
+
+impl Default for ReconnectStrategy {
+    fn default() -> Self {
+        ReconnectStrategy::Exponential { base: 2, cap: 30 }
+    }
+}
+
+impl ReconnectStrategy {
+    /// Delay before reconnection attempt `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let attempt = attempt.max(1);
+        let secs = match *self {
+            ReconnectStrategy::Fixed { secs } => secs,
+            ReconnectStrategy::Linear { step, cap } => step.saturating_mul(attempt as u64).min(cap),
+            ReconnectStrategy::Exponential { base, cap } => base
+                .checked_pow(attempt - 1)
+                .map_or(cap, |secs| secs.min(cap)),
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(strategy: ReconnectStrategy, attempts: u32) -> Vec<u64> {
+        (1..=attempts)
+            .map(|attempt| strategy.delay(attempt).as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn fixed_delay_never_changes() {
+        assert_eq!(
+            delays(ReconnectStrategy::Fixed { secs: 3 }, 5),
+            vec![3, 3, 3, 3, 3]
+        );
+    }
+
+    #[test]
+    fn linear_delay_grows_by_step_up_to_cap() {
+        assert_eq!(
+            delays(ReconnectStrategy::Linear { step: 4, cap: 10 }, 5),
+            vec![4, 8, 10, 10, 10]
+        );
+    }
+
+    #[test]
+    fn exponential_delay_doubles_up_to_cap() {
+        assert_eq!(
+            delays(ReconnectStrategy::default(), 7),
+            vec![1, 2, 4, 8, 16, 30, 30]
+        );
+    }
+
+    #[test]
+    fn delay_saturates_instead_of_overflowing() {
+        let exponential = ReconnectStrategy::Exponential { base: 10, cap: 60 };
+        assert_eq!(exponential.delay(u32::MAX), Duration::from_secs(60));
+        let linear = ReconnectStrategy::Linear {
+            step: u64::MAX,
+            cap: 60,
+        };
+        assert_eq!(linear.delay(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn attempt_zero_is_treated_as_the_first() {
+        let strategy = ReconnectStrategy::Linear { step: 5, cap: 30 };
+        assert_eq!(strategy.delay(0), strategy.delay(1));
+    }
+}
@@ -1,14 +1,28 @@
-use crate::sync::protocol::SyncMessage;
+use super::history::MessageHistory;
+use super::keepalive::{apply_keepalive, KeepaliveConfig};
+use super::queue::{OutgoingQueue, QueueError};
+use super::tls::MasterTls;
+use crate::sync::compression;
+use crate::sync::protocol::{
+    is_protocol_compatible, protocol_mismatch_reason, Capabilities, ClientHelloPayload,
+    ResumeFromPayload, StateSyncRequestPayload, SyncMessage, SyncMessageType,
+    LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
 use anyhow::{Context, Result};
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use subtle::ConstantTimeEq;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Notify, RwLock};
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message, WebSocketStream};
 
 type ClientId = String;
 
@@ -18,9 +32,20 @@ type InitialStateCallback = Arc<
         + Sync,
 >;
 
+/// Receives scene switches and transforms sent up by bidirectional slaves
+type RemoteUpdateCallback = Arc<dyn Fn(ClientId, SyncMessage) + Send + Sync>;
+
+/// Receives the full client list whenever a slave joins or leaves
+type ClientsChangedCallback = Arc<dyn Fn(Vec<ClientInfo>) + Send + Sync>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ClientInfo {
+    /// The slave's machine id, or its socket address for slaves that don't send one
     pub id: String,
+    /// Name the slave's user gave the machine
+    #[serde(default)]
+    pub display_name: Option<String>,
     pub ip_address: String,
     pub connected_at: i64,
     pub last_activity: i64,
@@ -32,30 +57,515 @@ pub struct SlaveStatus {
     pub is_synced: bool,
     pub desync_details: Vec<serde_json::Value>,
     pub last_report_time: i64,
+    /// Delay between the slave sending its last report and the master receiving it,
+    /// corrected for the slave's clock offset; None until the offset is known
+    #[serde(default)]
+    pub report_latency_ms: Option<i64>,
+    /// Apply errors the slave reported since its previous report
+    #[serde(default)]
+    pub apply_errors: Vec<String>,
+    /// Rolling round trip of the master's heartbeat pings to this slave
+    #[serde(default)]
+    pub rtt_ms: Option<f64>,
+}
+
+/// Latency acks for an in-flight probe, keyed by probe id
+type LatencyProbes = HashMap<String, mpsc::UnboundedSender<(ClientId, i64)>>;
+
+/// How long a new connection has to send its Auth frame when a token is required
+const AUTH_TIMEOUT_SECS: u64 = 5;
+
+/// How long the master waits for an ack before resending a message
+const ACK_TIMEOUT_MS: u64 = 2_000;
+
+/// Sends of an acknowledged message (including the first) before giving up
+const ACK_MAX_ATTEMPTS: u32 = 3;
+
+/// How long unacknowledged messages for a disconnected slave wait for it to return
+const PENDING_ACK_TTL_SECS: u64 = 60;
+
+/// Weight of the newest heartbeat round trip in a slave's rolling RTT
+const RTT_SMOOTHING: f64 = 0.25;
+
+/// Master clock minus the slave's, from a slave heartbeat stamped `client_time` that
+/// arrived at `now`, taking the one-way delay as half the link's round trip
+fn clock_offset_sample(now: i64, client_time: i64, rtt_ms: f64) -> f64 {
+    (now - client_time) as f64 - rtt_ms / 2.0
+}
+
+/// How long a report stamped `sent_at` on the slave's clock took to arrive at `now`;
+/// unknown until heartbeats have given an estimate of the slave's clock offset
+fn report_latency_ms(now: i64, sent_at: i64, clock_offset_ms: Option<f64>) -> Option<i64> {
+    let offset = clock_offset_ms?;
+    Some(((now - sent_at) as f64 - offset).round().max(0.0) as i64)
+}
+
+/// Messages that can run to megabytes: full state and image data. They are queued
+/// behind control messages and paced to the bandwidth limit.
+fn is_bulk(message_type: &SyncMessageType) -> bool {
+    matches!(
+        message_type,
+        SyncMessageType::StateSync | SyncMessageType::ImageUpdate
+    )
+}
+
+/// Messages a slave that falls behind can do without: they are superseded by later
+/// ones or are only measurements, and the StateSync it gets once it has caught up
+/// restores anything they carried. Everything else, notably StateSync and SceneChange,
+/// is never dropped.
+fn is_droppable(message_type: &SyncMessageType) -> bool {
+    matches!(
+        message_type,
+        SyncMessageType::TransformUpdate
+            | SyncMessageType::TransformBatch
+            | SyncMessageType::ImageUpdate
+            | SyncMessageType::FilterUpdate
+            | SyncMessageType::SourceSettingsUpdate
+            | SyncMessageType::VolumeUpdate
+            | SyncMessageType::MuteUpdate
+            | SyncMessageType::ReorderItems
+            | SyncMessageType::Heartbeat
+            | SyncMessageType::LatencyProbe
+            | SyncMessageType::RawEvent
+    )
+}
+
+/// Outgoing queue of one connection. Control messages such as scene changes and
+/// heartbeats are always sent before queued bulk messages.
+#[derive(Clone)]
+struct ClientSender {
+    queue: Arc<OutgoingQueue>,
+}
+
+impl ClientSender {
+    fn send(&self, message: Message) -> Result<(), QueueError> {
+        self.queue.push(message, false, false)
+    }
+
+    /// Queue a serialized sync message on the lane its type belongs to. `seq` is the
+    /// broadcast number it carries; resends and targeted messages pass None.
+    fn send_sync(
+        &self,
+        message_type: &SyncMessageType,
+        seq: Option<u64>,
+        json: String,
+    ) -> Result<(), QueueError> {
+        self.queue.push_numbered(
+            Message::Text(json),
+            seq,
+            is_bulk(message_type),
+            is_droppable(message_type),
+        )
+    }
+
+    fn same_channel(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.queue, &other.queue)
+    }
+}
+
+/// Paces bulk messages to all slaves together, since it is the master's uplink that
+/// a large image would otherwise saturate
+#[derive(Default)]
+struct BandwidthLimiter {
+    /// Kilobits per second; 0 for unlimited
+    limit_kbps: AtomicU32,
+    /// When the bulk data reserved so far will have gone out at the limited rate
+    next_free: Mutex<Option<tokio::time::Instant>>,
+}
+
+impl BandwidthLimiter {
+    /// When a bulk message of `bytes` may be sent, reserving its share of the bandwidth
+    fn reserve(&self, bytes: usize) -> tokio::time::Instant {
+        let now = tokio::time::Instant::now();
+        let limit_kbps = self.limit_kbps.load(Ordering::Relaxed);
+        let mut next_free = self
+            .next_free
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if limit_kbps == 0 {
+            *next_free = None;
+            return now;
+        }
+        let start = next_free.map_or(now, |t| t.max(now));
+        let duration = tokio::time::Duration::from_secs_f64(
+            bytes as f64 * 8.0 / (f64::from(limit_kbps) * 1000.0),
+        );
+        *next_free = Some(start + duration);
+        start
+    }
+}
+
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_HEARTBEAT_TIMEOUT_SECS: u64 = 15;
+
+/// Heartbeats the master sends to every slave, and how long a slave may stay silent
+/// before it is considered gone. Slaves ping every 5 seconds on their own, so the
+/// timeout should be comfortably above that.
+#[derive(Debug, Clone, Copy)]
+pub struct HeartbeatConfig {
+    pub interval_secs: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: DEFAULT_HEARTBEAT_INTERVAL_SECS,
+            timeout_secs: DEFAULT_HEARTBEAT_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// A sent message that the slave has not acknowledged yet
+struct PendingAck {
+    message_type: SyncMessageType,
+    /// What the message sets on the slave; see `supersede_key`
+    key: String,
+    json: String,
+    attempts: u32,
+    queued_at: tokio::time::Instant,
+    last_sent: tokio::time::Instant,
+}
+
+/// Unacknowledged messages keyed by (client, message id)
+type PendingAcks = HashMap<(ClientId, String), PendingAck>;
+
+/// Whether `message` carries image data a client with `capabilities` can't decompress.
+/// Clients that never sent capabilities predate compression.
+fn needs_raw_images(message: &SyncMessage, capabilities: Option<&Capabilities>) -> bool {
+    matches!(
+        message.message_type,
+        SyncMessageType::ImageUpdate | SyncMessageType::StateSync
+    ) && !capabilities.is_some_and(|caps| caps.features.compression)
+}
+
+/// `message` serialized with its image data decompressed, off the async runtime
+async fn with_raw_images(message: &SyncMessage) -> Result<String> {
+    let mut message = message.clone();
+    tokio::task::spawn_blocking(move || {
+        compression::decompress_payload_images(&mut message.payload)?;
+        Ok(serde_json::to_string(&message)?)
+    })
+    .await
+    .context("Image decompression task failed")?
+}
+
+/// What an acknowledged message sets on the slave. A newer message with the same
+/// key makes an unacknowledged older one stale, so it must not be resent over it.
+fn supersede_key(message: &SyncMessage) -> String {
+    let field = |name: &str| message.payload[name].as_str().unwrap_or_default();
+    match message.message_type {
+        SyncMessageType::SceneChange => format!("scene_change/{:?}", message.target_type),
+        SyncMessageType::OutputControl => format!("output/{}", message.payload["output"]),
+        _ => format!(
+            "{:?}/{}/{}/{}/{}",
+            message.message_type,
+            field("scene_name"),
+            field("source_name"),
+            field("input_name"),
+            field("filter_name")
+        ),
+    }
+}
+
+/// Track `message` until `client_id` acknowledges it, dropping the pending messages
+/// it supersedes. A StateSync replaces everything the slave was still waiting for.
+fn track_pending(pending: &mut PendingAcks, client_id: &str, message: &SyncMessage, json: String) {
+    let key = supersede_key(message);
+    let full_state = message.message_type == SyncMessageType::StateSync;
+    pending.retain(|(pending_client, _), entry| {
+        pending_client != client_id || !(full_state || entry.key == key)
+    });
+    let now = tokio::time::Instant::now();
+    pending.insert(
+        (client_id.to_string(), message.message_id.clone()),
+        PendingAck {
+            message_type: message.message_type.clone(),
+            key,
+            json,
+            attempts: 1,
+            queued_at: now,
+            last_sent: now,
+        },
+    );
+}
+
+/// Pick the pending messages due for a resend at `now`, spending an attempt on each.
+/// Messages for a disconnected client wait for it to reconnect, up to
+/// `PENDING_ACK_TTL_SECS`, without using up attempts.
+fn take_due_resends(
+    pending: &mut PendingAcks,
+    is_connected: impl Fn(&str) -> bool,
+    now: tokio::time::Instant,
+) -> Vec<(ClientId, SyncMessageType, String)> {
+    let timeout = tokio::time::Duration::from_millis(ACK_TIMEOUT_MS);
+    let ttl = tokio::time::Duration::from_secs(PENDING_ACK_TTL_SECS);
+    let mut due = Vec::new();
+    pending.retain(|(client_id, message_id), entry| {
+        if !is_connected(client_id) {
+            return now.duration_since(entry.queued_at) < ttl;
+        }
+        if now.duration_since(entry.last_sent) < timeout {
+            return true;
+        }
+        if entry.attempts >= ACK_MAX_ATTEMPTS {
+            eprintln!(
+                "No ack from {} for {:?} {} after {} attempts",
+                client_id, entry.message_type, message_id, entry.attempts
+            );
+            return false;
+        }
+        entry.attempts += 1;
+        entry.last_sent = now;
+        println!(
+            "Resending {:?} {} to {} (attempt {}/{})",
+            entry.message_type, message_id, client_id, entry.attempts, ACK_MAX_ATTEMPTS
+        );
+        due.push((
+            client_id.clone(),
+            entry.message_type.clone(),
+            entry.json.clone(),
+        ));
+        true
+    });
+    due
+}
+
+/// Round trip of a latency probe to one slave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaveLatency {
+    pub client_id: String,
+    /// `None` if the slave did not acknowledge before the timeout
+    pub round_trip_ms: Option<i64>,
+}
+
+/// Statuses older than this are flagged as stale in `SlaveDetail`
+const SLAVE_STATUS_STALE_MS: i64 = 30_000;
+
+/// Everything the master knows about a single connected slave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaveDetail {
+    pub info: ClientInfo,
+    pub status: Option<SlaveStatus>,
+    /// Age of the latest status, if any
+    pub status_age_ms: Option<i64>,
+    pub is_stale: bool,
+    pub capabilities: Option<Capabilities>,
+}
+
+/// Sync state of a slave as last reported
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SlaveSyncState {
+    Synced,
+    Desynced,
+    /// Connected but hasn't sent a StateReport yet
+    Pending,
+}
+
+/// One row of the per-slave health table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaveDesyncSummary {
+    pub client_id: String,
+    pub ip_address: String,
+    pub state: SlaveSyncState,
+    pub critical_count: usize,
+    pub warning_count: usize,
+    /// `None` while pending
+    pub last_report_time: Option<i64>,
+}
+
+/// One connected slave in a fleet report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetReportEntry {
+    pub client_id: String,
+    pub display_name: Option<String>,
+    pub ip_address: String,
+    pub connected_at: i64,
+    /// `None` if the slave hasn't reported yet
+    pub last_report_time: Option<i64>,
+    pub is_synced: Option<bool>,
+    pub is_stale: bool,
+    pub report_latency_ms: Option<i64>,
+    pub desync_details: Vec<serde_json::Value>,
+    pub apply_errors: Vec<String>,
+}
+
+/// Snapshot of every connected slave, written by `export_fleet_report` for post-show summaries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetReport {
+    pub generated_at: i64,
+    pub master_obs_version: Option<String>,
+    pub clients: Vec<FleetReportEntry>,
+}
+
+impl FleetReport {
+    /// One row per client, each repeating when the report was made and the master's OBS
+    /// version; desync details are flattened to their descriptions
+    pub fn to_csv(&self) -> String {
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+        fn optional<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+        }
+
+        let mut csv = String::from("generated_at,master_obs_version,client_id,display_name,ip_address,connected_at,last_report_time,is_synced,is_stale,report_latency_ms,desync_details,apply_errors\n");
+        let master_obs_version = field(self.master_obs_version.as_deref().unwrap_or_default());
+        for client in &self.clients {
+            let desyncs: Vec<&str> = client
+                .desync_details
+                .iter()
+                .filter_map(|detail| detail["description"].as_str())
+                .collect();
+            let row = [
+                self.generated_at.to_string(),
+                master_obs_version.clone(),
+                field(&client.client_id),
+                field(client.display_name.as_deref().unwrap_or_default()),
+                field(&client.ip_address),
+                client.connected_at.to_string(),
+                optional(&client.last_report_time),
+                optional(&client.is_synced),
+                client.is_stale.to_string(),
+                optional(&client.report_latency_ms),
+                field(&desyncs.join("; ")),
+                field(&client.apply_errors.join("; ")),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Optional gate applied to the WebSocket handshake before the upgrade.
+/// Disabled by default; when enabled, every non-empty rule must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HandshakeGuard {
+    pub enabled: bool,
+    pub allowed_origins: Vec<String>,
+    pub allowed_hosts: Vec<String>,
+    pub path_token: Option<String>,
+}
+
+impl HandshakeGuard {
+    pub fn check(&self, request: &Request) -> std::result::Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        };
+
+        if !self.allowed_origins.is_empty() {
+            let origin = header("origin");
+            if !self.allowed_origins.iter().any(|o| o == origin) {
+                return Err(format!("Origin '{}' is not allowed", origin));
+            }
+        }
+
+        if !self.allowed_hosts.is_empty() {
+            let host = header("host");
+            let hostname = host.split(':').next().unwrap_or("");
+            if !self
+                .allowed_hosts
+                .iter()
+                .any(|h| h == host || h == hostname)
+            {
+                return Err(format!("Host '{}' is not allowed", host));
+            }
+        }
+
+        if let Some(token) = self.path_token.as_deref().filter(|t| !t.is_empty()) {
+            if request.uri().path().trim_start_matches('/') != token {
+                return Err("Invalid path token".to_string());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub struct MasterServer {
-    clients: Arc<RwLock<HashMap<ClientId, mpsc::UnboundedSender<Message>>>>,
+    clients: Arc<RwLock<HashMap<ClientId, ClientSender>>>,
     client_info: Arc<RwLock<HashMap<ClientId, ClientInfo>>>,
     slave_statuses: Arc<RwLock<HashMap<ClientId, SlaveStatus>>>,
+    /// Rolling heartbeat round trip per slave, in milliseconds
+    link_rtts: Arc<RwLock<HashMap<ClientId, f64>>>,
+    /// Master hashes of the images each slave said it still has
+    held_images: Arc<RwLock<HashMap<ClientId, HashSet<String>>>>,
     port: u16,
     shutdown: Arc<AtomicBool>,
+    shutdown_notify: Arc<Notify>,
+    broadcast_task: Arc<RwLock<Option<JoinHandle<()>>>>,
     tasks: Arc<RwLock<Vec<JoinHandle<()>>>>,
     initial_state_callback: Arc<RwLock<Option<InitialStateCallback>>>,
     listener: Arc<RwLock<Option<TcpListener>>>,
+    handshake_guard: Arc<RwLock<HandshakeGuard>>,
+    client_capabilities: Arc<RwLock<HashMap<ClientId, Capabilities>>>,
+    keepalive: Arc<RwLock<KeepaliveConfig>>,
+    heartbeat: Arc<RwLock<HeartbeatConfig>>,
+    latency_probes: Arc<RwLock<LatencyProbes>>,
+    pending_acks: Arc<RwLock<PendingAcks>>,
+    /// Serve wss:// instead of ws:// when set
+    tls: Arc<RwLock<Option<MasterTls>>>,
+    /// Shared token slaves must send in an Auth frame before they are accepted
+    auth_token: Option<String>,
+    /// Unset unless the master accepts changes from bidirectional slaves
+    remote_update_callback: Arc<RwLock<Option<RemoteUpdateCallback>>>,
+    clients_changed_callback: Arc<RwLock<Option<ClientsChangedCallback>>>,
+    /// Set by `start`; messages sent to single clients are recorded like broadcasts
+    performance_monitor: Arc<RwLock<Option<Arc<crate::commands::PerformanceMonitor>>>>,
+    bandwidth: Arc<BandwidthLimiter>,
+    /// Recent broadcasts, replayed to slaves that reconnect after a short drop
+    history: Arc<RwLock<MessageHistory>>,
 }
 
 impl MasterServer {
-    pub fn new(port: u16) -> Self {
+    /// `auth_token`: when set (and non-empty), slaves must authenticate with it
+    pub fn new(port: u16, auth_token: Option<String>) -> Self {
         Self {
             clients: Arc::new(RwLock::new(HashMap::new())),
             client_info: Arc::new(RwLock::new(HashMap::new())),
             slave_statuses: Arc::new(RwLock::new(HashMap::new())),
+            link_rtts: Arc::new(RwLock::new(HashMap::new())),
+            held_images: Arc::new(RwLock::new(HashMap::new())),
             port,
             shutdown: Arc::new(AtomicBool::new(false)),
+            shutdown_notify: Arc::new(Notify::new()),
+            broadcast_task: Arc::new(RwLock::new(None)),
             tasks: Arc::new(RwLock::new(Vec::new())),
             initial_state_callback: Arc::new(RwLock::new(None)),
             listener: Arc::new(RwLock::new(None)),
+            handshake_guard: Arc::new(RwLock::new(HandshakeGuard::default())),
+            client_capabilities: Arc::new(RwLock::new(HashMap::new())),
+            keepalive: Arc::new(RwLock::new(KeepaliveConfig::default())),
+            heartbeat: Arc::new(RwLock::new(HeartbeatConfig::default())),
+            latency_probes: Arc::new(RwLock::new(HashMap::new())),
+            pending_acks: Arc::new(RwLock::new(HashMap::new())),
+            tls: Arc::new(RwLock::new(None)),
+            auth_token: auth_token.filter(|t| !t.is_empty()),
+            remote_update_callback: Arc::new(RwLock::new(None)),
+            clients_changed_callback: Arc::new(RwLock::new(None)),
+            performance_monitor: Arc::new(RwLock::new(None)),
+            bandwidth: Arc::new(BandwidthLimiter::default()),
+            history: Arc::new(RwLock::new(MessageHistory::new())),
         }
     }
 
@@ -71,9 +581,69 @@ impl MasterServer {
         *self.initial_state_callback.write().await = Some(wrapped);
     }
 
+    pub async fn set_remote_update_callback<F>(&self, callback: F)
+    where
+        F: Fn(ClientId, SyncMessage) + Send + Sync + 'static,
+    {
+        *self.remote_update_callback.write().await = Some(Arc::new(callback));
+    }
+
+    /// Called with the updated client list after a slave connects or disconnects
+    pub async fn set_clients_changed_callback<F>(&self, callback: F)
+    where
+        F: Fn(Vec<ClientInfo>) + Send + Sync + 'static,
+    {
+        *self.clients_changed_callback.write().await = Some(Arc::new(callback));
+    }
+
+    pub async fn set_handshake_guard(&self, guard: HandshakeGuard) {
+        *self.handshake_guard.write().await = guard;
+    }
+
+    pub async fn set_keepalive(&self, keepalive: KeepaliveConfig) {
+        *self.keepalive.write().await = keepalive;
+    }
+
+    pub async fn set_heartbeat(&self, heartbeat: HeartbeatConfig) {
+        *self.heartbeat.write().await = heartbeat;
+    }
+
+    /// Cap state and image traffic to all slaves together, in kilobits per second;
+    /// 0 lifts the cap. Takes effect for messages not yet sent.
+    pub fn set_bandwidth_limit_kbps(&self, limit_kbps: u32) {
+        self.bandwidth
+            .limit_kbps
+            .store(limit_kbps, Ordering::Relaxed);
+    }
+
+    /// What this master advertises: TLS and auth as configured
+    pub async fn capabilities(&self) -> Capabilities {
+        Capabilities::for_connection(self.tls.read().await.is_some(), self.auth_token.is_some())
+    }
+
+    pub async fn set_tls(&self, tls: Option<MasterTls>) {
+        *self.tls.write().await = tls;
+    }
+
+    /// Certificate fingerprint slaves should pin, if serving wss://
+    pub async fn tls_fingerprint(&self) -> Option<String> {
+        self.tls
+            .read()
+            .await
+            .as_ref()
+            .map(|tls| tls.fingerprint.clone())
+    }
+
     pub async fn stop(&self) {
-        // Signal shutdown
+        // Signal shutdown; the broadcast task wakes up even if no message is pending
         self.shutdown.store(true, Ordering::SeqCst);
+        self.shutdown_notify.notify_one();
+
+        // Stop accepting, resending and pinging. The accept loop holds the listener
+        // while it waits for a connection, so it has to go before the listener can.
+        for task in self.tasks.write().await.drain(..) {
+            task.abort();
+        }
 
         // Close TcpListener to stop accepting new connections
         {
@@ -83,16 +653,54 @@ impl MasterServer {
             }
         }
 
-        // Abort all tasks
-        let tasks = self.tasks.write().await;
-        for task in tasks.iter() {
-            task.abort();
+        // Tell connected slaves we're going away instead of just dropping them.
+        // Slaves that understand ServerShutdown stop reconnecting.
+        let close_sent = {
+            let clients = self.clients.read().await;
+            let capabilities = self.client_capabilities.read().await;
+            let shutdown_json = serde_json::to_string(&SyncMessage::server_shutdown()).ok();
+            for (client_id, tx) in clients.iter() {
+                if let Some(json) = &shutdown_json {
+                    if capabilities
+                        .get(client_id)
+                        .is_some_and(|caps| caps.supports(&SyncMessageType::ServerShutdown))
+                    {
+                        let _ = tx.send(Message::Text(json.clone()));
+                    }
+                }
+                let _ = tx.send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Away,
+                    reason: "Master shutting down".into(),
+                })));
+            }
+            !clients.is_empty()
+        };
+        if close_sent {
+            // Give the per-client send tasks a moment to flush the Close frames
+            let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_millis(500);
+            while !self.clients.read().await.is_empty() && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+            }
+        }
+
+        // Let the broadcast task finish its current message and exit on its own
+        if let Some(mut task) = self.broadcast_task.write().await.take() {
+            let timeout = tokio::time::Duration::from_millis(500);
+            if tokio::time::timeout(timeout, &mut task).await.is_err() {
+                eprintln!("Broadcast task did not stop in time, aborting");
+                task.abort();
+            }
         }
 
         // Clear clients
         self.clients.write().await.clear();
         self.client_info.write().await.clear();
         self.slave_statuses.write().await.clear();
+        self.link_rtts.write().await.clear();
+        self.held_images.write().await.clear();
+        self.client_capabilities.write().await.clear();
+        self.pending_acks.write().await.clear();
+        notify_clients_changed(&self.client_info, &self.clients_changed_callback).await;
 
         println!("Master server stopped");
     }
@@ -106,6 +714,7 @@ impl MasterServer {
         let listener = TcpListener::bind(&addr)
             .await
             .context(format!("Failed to bind to {}", addr))?;
+        *self.performance_monitor.write().await = performance_monitor.clone();
 
         // Store listener for cleanup
         *self.listener.write().await = Some(listener);
@@ -113,17 +722,27 @@ impl MasterServer {
         println!("Master server listening on: {}", addr);
 
         let clients = self.clients.clone();
-        let shutdown = self.shutdown.clone();
+        let client_capabilities = self.client_capabilities.clone();
+        let shutdown_notify = self.shutdown_notify.clone();
         let listener_for_accept = self.listener.clone();
+        let pending_acks = self.pending_acks.clone();
+        let performance_monitor_for_acks = performance_monitor.clone();
+        let history = self.history.clone();
 
         // Broadcast sync messages to all connected clients
         let broadcast_task = tokio::spawn(async move {
-            while let Some(message) = sync_rx.recv().await {
-                if shutdown.load(Ordering::SeqCst) {
-                    break;
-                }
+            loop {
+                let mut message = tokio::select! {
+                    biased;
+                    _ = shutdown_notify.notified() => break,
+                    message = sync_rx.recv() => match message {
+                        Some(message) => message,
+                        None => break,
+                    },
+                };
 
-                let json = match serde_json::to_string(&message) {
+                // Numbered before any client can receive it; see `serve_connection`
+                let json = match history.write().await.record(&mut message) {
                     Ok(j) => j,
                     Err(e) => {
                         eprintln!("Failed to serialize sync message: {}", e);
@@ -131,33 +750,127 @@ impl MasterServer {
                     }
                 };
 
-                // Record performance metric (send time)
+                // Record performance metric (send time); acks complete the latency
                 if let Some(ref monitor) = performance_monitor {
-                    let message_type_str = format!("{:?}", message.message_type);
-                    let metric = crate::commands::SyncMetric {
-                        timestamp: message.timestamp,
-                        message_type: message_type_str,
-                        latency_ms: 0.0, // Latency is calculated on slave side
-                        message_size_bytes: json.len(),
-                    };
-                    monitor.record_metric(metric).await;
+                    monitor.record_send(&message, json.len()).await;
                 }
 
                 let clients_lock = clients.read().await;
+                let capabilities = client_capabilities.read().await;
+                // Made once, when the first client that can't decompress images needs it
+                let mut raw_json: Option<Option<String>> = None;
                 for (client_id, tx) in clients_lock.iter() {
-                    if let Err(e) = tx.send(Message::Text(json.clone())) {
+                    // Skip message types the client did not advertise
+                    if let Some(caps) = capabilities.get(client_id) {
+                        if !caps.supports(&message.message_type) {
+                            // Older slaves get a batch as the updates it carries
+                            if message.message_type == SyncMessageType::TransformBatch
+                                && caps.supports(&SyncMessageType::TransformUpdate)
+                            {
+                                for update in message.split_transform_batch().unwrap_or_default() {
+                                    if let Ok(json) = serde_json::to_string(&update) {
+                                        let _ = tx.send_sync(&update.message_type, None, json);
+                                    }
+                                }
+                            }
+                            continue;
+                        }
+                    }
+                    let json =
+                        if needs_raw_images(&message, capabilities.get(client_id)) {
+                            if raw_json.is_none() {
+                                raw_json = Some(with_raw_images(&message).await.map_err(|e| {
+                                eprintln!("Failed to decompress images for older slaves: {:#}", e)
+                            }).ok());
+                            }
+                            match raw_json.clone().flatten() {
+                                Some(raw) => raw,
+                                None => continue,
+                            }
+                        } else {
+                            json.clone()
+                        };
+                    if let Err(e) = tx.send_sync(&message.message_type, message.seq, json.clone()) {
                         eprintln!("Failed to send message to client {}: {}", client_id, e);
+                    } else if message.requires_ack
+                        && capabilities
+                            .get(client_id)
+                            .is_some_and(|caps| caps.supports(&SyncMessageType::Ack))
+                    {
+                        track_pending(
+                            &mut *pending_acks.write().await,
+                            client_id,
+                            &message,
+                            json.clone(),
+                        );
                     }
                 }
             }
         });
 
+        // Resend unacknowledged messages until the slave confirms or attempts run out
+        let clients_for_retry = self.clients.clone();
+        let pending_acks_for_retry = self.pending_acks.clone();
+        let retry_task = tokio::spawn(async move {
+            let timeout = tokio::time::Duration::from_millis(ACK_TIMEOUT_MS);
+            loop {
+                tokio::time::sleep(timeout / 4).await;
+                let clients = clients_for_retry.read().await;
+                let due = take_due_resends(
+                    &mut *pending_acks_for_retry.write().await,
+                    |client_id| clients.contains_key(client_id),
+                    tokio::time::Instant::now(),
+                );
+                for (client_id, message_type, json) in due {
+                    if let Some(tx) = clients.get(&client_id) {
+                        let _ = tx.send_sync(&message_type, None, json);
+                    }
+                }
+            }
+        });
+
+        // Ping every slave so it can tell a silent master from a dead link
+        let heartbeat = *self.heartbeat.read().await;
+        let clients_for_heartbeat = self.clients.clone();
+        let heartbeat_task = tokio::spawn(async move {
+            let interval = tokio::time::Duration::from_secs(heartbeat.interval_secs.max(1));
+            loop {
+                tokio::time::sleep(interval).await;
+                let Ok(json) = serde_json::to_string(&SyncMessage::heartbeat()) else {
+                    continue;
+                };
+                // Slaves answer the ping by themselves; its pong gives the round trip
+                let sent_at = chrono::Utc::now().timestamp_millis().to_be_bytes().to_vec();
+                for tx in clients_for_heartbeat.read().await.values() {
+                    let _ = tx.send(Message::Text(json.clone()));
+                    let _ = tx.send(Message::Ping(sent_at.clone()));
+                }
+            }
+        });
+
         // Accept incoming connections
-        let clients_for_accept = self.clients.clone();
-        let client_info_for_accept = self.client_info.clone();
+        let context_for_accept = ConnectionContext {
+            clients: self.clients.clone(),
+            client_info: self.client_info.clone(),
+            slave_statuses: self.slave_statuses.clone(),
+            link_rtts: self.link_rtts.clone(),
+            held_images: self.held_images.clone(),
+            callback: self.initial_state_callback.clone(),
+            client_capabilities: self.client_capabilities.clone(),
+            latency_probes: self.latency_probes.clone(),
+            pending_acks: self.pending_acks.clone(),
+            auth_token: self.auth_token.clone(),
+            remote_update_callback: self.remote_update_callback.clone(),
+            clients_changed_callback: self.clients_changed_callback.clone(),
+            performance_monitor: performance_monitor_for_acks,
+            bandwidth: self.bandwidth.clone(),
+            history: self.history.clone(),
+            heartbeat_timeout: tokio::time::Duration::from_secs(heartbeat.timeout_secs.max(1)),
+        };
         let shutdown_for_accept = self.shutdown.clone();
-        let callback_for_accept = self.initial_state_callback.clone();
-        let slave_statuses_for_accept = self.slave_statuses.clone();
+        let handshake_guard_for_accept = self.handshake_guard.clone();
+        let keepalive_for_accept = self.keepalive.clone();
+        let tls_for_accept = self.tls.clone();
         let accept_task = tokio::spawn(async move {
             loop {
                 if shutdown_for_accept.load(Ordering::SeqCst) {
@@ -176,17 +889,20 @@ impl MasterServer {
                 match accept_result {
                     Some(Ok((stream, addr))) => {
                         println!("New connection from: {}", addr);
-                        let clients = clients_for_accept.clone();
-                        let client_info = client_info_for_accept.clone();
-                        let slave_statuses = slave_statuses_for_accept.clone();
-                        let callback = callback_for_accept.clone();
+                        let handshake_guard = handshake_guard_for_accept.read().await.clone();
+                        let keepalive = keepalive_for_accept.read().await.clone();
+                        let tls_acceptor = tls_for_accept
+                            .read()
+                            .await
+                            .as_ref()
+                            .map(|tls| tls.acceptor.clone());
                         tokio::spawn(handle_connection(
                             stream,
                             addr.to_string(),
-                            clients,
-                            client_info,
-                            slave_statuses,
-                            callback,
+                            context_for_accept.clone(),
+                            handshake_guard,
+                            keepalive,
+                            tls_acceptor,
                         ));
                     }
                     Some(Err(e)) => {
@@ -203,12 +919,66 @@ impl MasterServer {
 
         // Store task handles
         let mut tasks = self.tasks.write().await;
-        tasks.push(broadcast_task);
         tasks.push(accept_task);
+        tasks.push(retry_task);
+        tasks.push(heartbeat_task);
+        *self.broadcast_task.write().await = Some(broadcast_task);
 
         Ok(())
     }
 
+    /// Send a message to one client only, e.g. a resync for a single desynced slave.
+    /// Acks and metrics are tracked the same way as for broadcasts.
+    pub async fn send_to_client(&self, client_id: &str, message: SyncMessage) -> Result<()> {
+        let mut json =
+            serde_json::to_string(&message).context("Failed to serialize sync message")?;
+        let clients = self.clients.read().await;
+        let tx = clients
+            .get(client_id)
+            .with_context(|| format!("Client {} is not connected", client_id))?;
+
+        // Clients that never sent capabilities get everything, as with broadcasts
+        let (supports_ack, raw_images) = match self.client_capabilities.read().await.get(client_id)
+        {
+            Some(caps) if !caps.supports(&message.message_type) => {
+                anyhow::bail!(
+                    "Client {} does not support {:?}",
+                    client_id,
+                    message.message_type
+                );
+            }
+            caps => (
+                caps.is_some_and(|caps| caps.supports(&SyncMessageType::Ack)),
+                needs_raw_images(&message, caps),
+            ),
+        };
+        if raw_images {
+            json = with_raw_images(&message).await?;
+        }
+
+        if let Some(monitor) = self.performance_monitor.read().await.as_ref() {
+            monitor.record_send(&message, json.len()).await;
+        }
+
+        tx.send_sync(&message.message_type, None, json.clone())
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to send message to client {}: {}", client_id, e)
+            })?;
+        if message.requires_ack && supports_ack {
+            track_pending(
+                &mut *self.pending_acks.write().await,
+                client_id,
+                &message,
+                json,
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn is_client_connected(&self, client_id: &str) -> bool {
+        self.clients.read().await.contains_key(client_id)
+    }
+
     pub async fn get_connected_clients_count(&self) -> usize {
         self.clients.read().await.len()
     }
@@ -218,27 +988,382 @@ impl MasterServer {
         info.values().cloned().collect()
     }
 
+    /// Master hashes of the images `client_id` reported having, so its StateSync
+    /// can leave their bytes out
+    pub async fn held_images(&self, client_id: &str) -> HashSet<String> {
+        self.held_images
+            .read()
+            .await
+            .get(client_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub async fn get_slave_statuses(&self) -> Vec<SlaveStatus> {
         let statuses = self.slave_statuses.read().await;
-        statuses.values().cloned().collect()
+        let rtts = self.link_rtts.read().await;
+        statuses
+            .values()
+            .map(|status| SlaveStatus {
+                rtt_ms: rtts.get(&status.client_id).copied(),
+                ..status.clone()
+            })
+            .collect()
+    }
+
+    pub async fn get_slave_detail(&self, client_id: &str) -> Option<SlaveDetail> {
+        let info = self.client_info.read().await.get(client_id).cloned()?;
+        let rtt_ms = self.link_rtts.read().await.get(client_id).copied();
+        let status = self
+            .slave_statuses
+            .read()
+            .await
+            .get(client_id)
+            .map(|status| SlaveStatus {
+                rtt_ms,
+                ..status.clone()
+            });
+        let capabilities = self
+            .client_capabilities
+            .read()
+            .await
+            .get(client_id)
+            .cloned();
+
+        let status_age_ms = status
+            .as_ref()
+            .map(|s| chrono::Utc::now().timestamp_millis() - s.last_report_time);
+        Some(SlaveDetail {
+            info,
+            is_stale: status_age_ms
+                .map(|age| age > SLAVE_STATUS_STALE_MS)
+                .unwrap_or(true),
+            status,
+            status_age_ms,
+            capabilities,
+        })
+    }
+
+    /// Desync counts of every connected slave, oldest connection first. Slaves that haven't
+    /// reported yet are listed as pending.
+    pub async fn desync_summary(&self) -> Vec<SlaveDesyncSummary> {
+        let mut infos = self.get_connected_clients_info().await;
+        infos.sort_by_key(|info| info.connected_at);
+        let statuses = self.slave_statuses.read().await;
+
+        infos
+            .into_iter()
+            .map(|info| {
+                let status = statuses.get(&info.id);
+                let count = |severity: &str| {
+                    status.map_or(0, |s| {
+                        s.desync_details
+                            .iter()
+                            .filter(|detail| detail["severity"].as_str() == Some(severity))
+                            .count()
+                    })
+                };
+                let state = match status {
+                    None => SlaveSyncState::Pending,
+                    Some(s) if s.is_synced => SlaveSyncState::Synced,
+                    Some(_) => SlaveSyncState::Desynced,
+                };
+                SlaveDesyncSummary {
+                    critical_count: count("Critical"),
+                    warning_count: count("Warning"),
+                    state,
+                    last_report_time: status.map(|s| s.last_report_time),
+                    client_id: info.id,
+                    ip_address: info.ip_address,
+                }
+            })
+            .collect()
+    }
+
+    /// Combine client info and the latest status of every connected slave, oldest connection first
+    pub async fn fleet_report(&self, master_obs_version: Option<String>) -> FleetReport {
+        let mut infos = self.get_connected_clients_info().await;
+        infos.sort_by_key(|info| info.connected_at);
+
+        let mut clients = Vec::with_capacity(infos.len());
+        for info in infos {
+            let Some(detail) = self.get_slave_detail(&info.id).await else {
+                continue;
+            };
+            let status = detail.status;
+            clients.push(FleetReportEntry {
+                client_id: detail.info.id,
+                display_name: detail.info.display_name,
+                ip_address: detail.info.ip_address,
+                connected_at: detail.info.connected_at,
+                last_report_time: status.as_ref().map(|s| s.last_report_time),
+                is_synced: status.as_ref().map(|s| s.is_synced),
+                is_stale: detail.is_stale,
+                report_latency_ms: status.as_ref().and_then(|s| s.report_latency_ms),
+                desync_details: status
+                    .as_ref()
+                    .map(|s| s.desync_details.clone())
+                    .unwrap_or_default(),
+                apply_errors: status.map(|s| s.apply_errors).unwrap_or_default(),
+            });
+        }
+
+        FleetReport {
+            generated_at: chrono::Utc::now().timestamp_millis(),
+            master_obs_version,
+            clients,
+        }
+    }
+
+    /// Send a tagged probe to every connected slave and wait up to `timeout` for each to
+    /// acknowledge it. Slaves that don't answer in time get `round_trip_ms: None`.
+    pub async fn measure_latency(&self, timeout: tokio::time::Duration) -> Vec<SlaveLatency> {
+        let probe_id = uuid::Uuid::new_v4().to_string();
+        let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+        self.latency_probes
+            .write()
+            .await
+            .insert(probe_id.clone(), ack_tx);
+
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        let mut round_trips: HashMap<ClientId, Option<i64>> = HashMap::new();
+        if let Ok(json) = serde_json::to_string(&SyncMessage::latency_probe(&probe_id)) {
+            for (client_id, tx) in self.clients.read().await.iter() {
+                if tx.send(Message::Text(json.clone())).is_ok() {
+                    round_trips.insert(client_id.clone(), None);
+                }
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut pending = round_trips.len();
+        while pending > 0 {
+            match tokio::time::timeout_at(deadline, ack_rx.recv()).await {
+                Ok(Some((client_id, received_at))) => {
+                    if let Some(entry @ None) = round_trips.get_mut(&client_id) {
+                        *entry = Some(received_at - sent_at);
+                        pending -= 1;
+                    }
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        self.latency_probes.write().await.remove(&probe_id);
+
+        round_trips
+            .into_iter()
+            .map(|(client_id, round_trip_ms)| SlaveLatency {
+                client_id,
+                round_trip_ms,
+            })
+            .collect()
+    }
+
+    /// Insert a status directly, bypassing StateReport (used by `simulate_desync`)
+    pub async fn set_slave_status(&self, status: SlaveStatus) {
+        self.slave_statuses
+            .write()
+            .await
+            .insert(status.client_id.clone(), status);
     }
 }
 
-async fn handle_connection(
-    stream: TcpStream,
-    client_id: ClientId,
-    clients: Arc<RwLock<HashMap<ClientId, mpsc::UnboundedSender<Message>>>>,
+/// Shared server state handed to each connection task
+#[derive(Clone)]
+struct ConnectionContext {
+    clients: Arc<RwLock<HashMap<ClientId, ClientSender>>>,
     client_info: Arc<RwLock<HashMap<ClientId, ClientInfo>>>,
     slave_statuses: Arc<RwLock<HashMap<ClientId, SlaveStatus>>>,
+    link_rtts: Arc<RwLock<HashMap<ClientId, f64>>>,
+    held_images: Arc<RwLock<HashMap<ClientId, HashSet<String>>>>,
     callback: Arc<RwLock<Option<InitialStateCallback>>>,
+    client_capabilities: Arc<RwLock<HashMap<ClientId, Capabilities>>>,
+    latency_probes: Arc<RwLock<LatencyProbes>>,
+    pending_acks: Arc<RwLock<PendingAcks>>,
+    auth_token: Option<String>,
+    remote_update_callback: Arc<RwLock<Option<RemoteUpdateCallback>>>,
+    clients_changed_callback: Arc<RwLock<Option<ClientsChangedCallback>>>,
+    performance_monitor: Option<Arc<crate::commands::PerformanceMonitor>>,
+    /// Connections silent for this long are dropped as stale
+    heartbeat_timeout: tokio::time::Duration,
+    bandwidth: Arc<BandwidthLimiter>,
+    history: Arc<RwLock<MessageHistory>>,
+}
+
+/// Wait for the slave's Auth frame; true if it arrives in time and carries `expected`
+async fn authenticate<S>(ws_stream: &mut WebSocketStream<S>, expected: &str) -> bool
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let timeout = tokio::time::Duration::from_secs(AUTH_TIMEOUT_SECS);
+    let Ok(Some(Ok(Message::Text(text)))) = tokio::time::timeout(timeout, ws_stream.next()).await
+    else {
+        return false;
+    };
+    serde_json::from_str::<SyncMessage>(&text).is_ok_and(|msg| {
+        msg.message_type == SyncMessageType::Auth
+            && msg
+                .payload
+                .get("token")
+                .and_then(|v| v.as_str())
+                .is_some_and(|token| token_matches(token, expected))
+    })
+}
+
+/// Compare in constant time so the token can't be guessed from response timing
+fn token_matches(token: &str, expected: &str) -> bool {
+    token.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+/// What a new connection sent before it was registered as a client
+enum FirstFrame {
+    Hello(ClientHelloPayload),
+    /// Older slaves skip ClientHello; this frame is processed like any later one
+    Other(Message),
+    /// Nothing within the timeout: v1 slaves wait for a StateSync before sending anything
+    Silent,
+    /// The peer closed, as probes do
+    Gone,
+}
+
+/// Read the slave's first frame, which current slaves make their ClientHello
+async fn receive_first_frame<S>(ws_stream: &mut WebSocketStream<S>) -> FirstFrame
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let timeout = tokio::time::Duration::from_secs(AUTH_TIMEOUT_SECS);
+    match tokio::time::timeout(timeout, ws_stream.next()).await {
+        Err(_) => FirstFrame::Silent,
+        Ok(Some(Ok(Message::Close(_)))) | Ok(Some(Err(_))) | Ok(None) => FirstFrame::Gone,
+        Ok(Some(Ok(Message::Text(text)))) => match serde_json::from_str::<SyncMessage>(&text) {
+            Ok(msg) if msg.message_type == SyncMessageType::ClientHello => {
+                match serde_json::from_value::<ClientHelloPayload>(msg.payload) {
+                    Ok(hello) => FirstFrame::Hello(hello),
+                    Err(_) => FirstFrame::Other(Message::Text(text)),
+                }
+            }
+            _ => FirstFrame::Other(Message::Text(text)),
+        },
+        Ok(Some(Ok(other))) => FirstFrame::Other(other),
+    }
+}
+
+/// Hand the current client list to the clients-changed callback, if one is set.
+/// Call after `client_info` has been updated so the list is accurate. The callback
+/// runs under the read lock, so concurrent changes are reported in order.
+async fn notify_clients_changed(
+    client_info: &RwLock<HashMap<ClientId, ClientInfo>>,
+    callback: &RwLock<Option<ClientsChangedCallback>>,
+) {
+    let Some(callback) = callback.read().await.clone() else {
+        return;
+    };
+    let info = client_info.read().await;
+    callback(info.values().cloned().collect());
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    client_id: ClientId,
+    context: ConnectionContext,
+    handshake_guard: HandshakeGuard,
+    keepalive: KeepaliveConfig,
+    tls_acceptor: Option<tokio_rustls::TlsAcceptor>,
 ) {
+    if let Err(e) = apply_keepalive(&stream, &keepalive) {
+        eprintln!("Failed to enable TCP keepalive for {}: {}", client_id, e);
+    }
     let peer_addr = stream.peer_addr().ok();
     let ip_address = peer_addr
         .map(|a| a.ip().to_string())
         .unwrap_or_else(|| "unknown".to_string());
+
+    match tls_acceptor {
+        Some(acceptor) => match acceptor.accept(stream).await {
+            Ok(tls_stream) => {
+                serve_connection(
+                    tls_stream,
+                    client_id,
+                    ip_address,
+                    context,
+                    handshake_guard,
+                    true,
+                )
+                .await
+            }
+            Err(e) => eprintln!("TLS handshake failed for {}: {}", client_id, e),
+        },
+        None => {
+            serve_connection(
+                stream,
+                client_id,
+                ip_address,
+                context,
+                handshake_guard,
+                false,
+            )
+            .await
+        }
+    }
+}
+
+async fn is_current_connection(
+    clients: &RwLock<HashMap<ClientId, ClientSender>>,
+    client_id: &str,
+    tx: &ClientSender,
+) -> bool {
+    clients
+        .read()
+        .await
+        .get(client_id)
+        .is_some_and(|current| current.same_channel(tx))
+}
+
+/// WebSocket session with one slave over a plain or TLS stream
+async fn serve_connection<S>(
+    stream: S,
+    client_id: ClientId,
+    ip_address: String,
+    context: ConnectionContext,
+    handshake_guard: HandshakeGuard,
+    tls: bool,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let ConnectionContext {
+        clients,
+        client_info,
+        slave_statuses,
+        link_rtts,
+        held_images,
+        callback,
+        client_capabilities,
+        latency_probes,
+        pending_acks,
+        auth_token,
+        remote_update_callback,
+        clients_changed_callback,
+        performance_monitor,
+        heartbeat_timeout,
+        bandwidth,
+        history,
+    } = context;
     let connected_at = chrono::Utc::now().timestamp_millis();
 
-    let ws_stream = match accept_async(stream).await {
+    // ErrorResponse size is dictated by tungstenite's handshake callback signature
+    #[allow(clippy::result_large_err)]
+    let check_handshake =
+        |request: &Request, response: Response| match handshake_guard.check(request) {
+            Ok(()) => Ok(response),
+            Err(reason) => {
+                eprintln!("Rejected handshake from {}: {}", client_id, reason);
+                let mut error = ErrorResponse::new(Some(reason));
+                *error.status_mut() = StatusCode::FORBIDDEN;
+                Err(error)
+            }
+        };
+
+    let mut ws_stream = match accept_hdr_async(stream, check_handshake).await {
         Ok(ws) => ws,
         Err(e) => {
             eprintln!("WebSocket handshake failed for {}: {}", client_id, e);
@@ -246,11 +1371,137 @@ async fn handle_connection(
         }
     };
 
+    // Say who we are first so probes can tell an obs-sync master from another service
+    if let Ok(json) = serde_json::to_string(&SyncMessage::server_hello(auth_token.is_some())) {
+        if ws_stream.send(Message::Text(json)).await.is_err() {
+            return;
+        }
+    }
+
+    // Unauthenticated sockets never become clients, so they get no state
+    if let Some(expected) = auth_token.as_deref() {
+        if !authenticate(&mut ws_stream, expected).await {
+            eprintln!("Rejected {}: authentication failed", client_id);
+            let _ = ws_stream
+                .close(Some(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: "Authentication failed".into(),
+                }))
+                .await;
+            return;
+        }
+    }
+
+    // Slaves that name their machine are keyed by it, so a reconnect reuses their entry
+    let mut first_frame = None;
+    let mut display_name = None;
+    let mut resumable = false;
+    let mut resume_point = None;
+    let mut hello_images = Vec::new();
+    let client_id = match receive_first_frame(&mut ws_stream).await {
+        FirstFrame::Gone => return,
+        FirstFrame::Silent => {
+            println!(
+                "{} sent nothing yet, treating it as a protocol v{} slave",
+                client_id, LEGACY_PROTOCOL_VERSION
+            );
+            client_id
+        }
+        FirstFrame::Other(message) => {
+            println!(
+                "{} sent no ClientHello, treating it as a protocol v{} slave",
+                client_id, LEGACY_PROTOCOL_VERSION
+            );
+            first_frame = Some(message);
+            client_id
+        }
+        FirstFrame::Hello(hello) => {
+            if !is_protocol_compatible(hello.protocol_version) {
+                let reason = protocol_mismatch_reason(PROTOCOL_VERSION, hello.protocol_version);
+                eprintln!("Rejected {}: {}", client_id, reason);
+                let _ = ws_stream
+                    .close(Some(CloseFrame {
+                        code: CloseCode::Protocol,
+                        reason: reason.into(),
+                    }))
+                    .await;
+                return;
+            }
+            display_name = hello.display_name;
+            resumable = hello.resumable;
+            resume_point = hello.resume;
+            hello_images = hello.held_images;
+            hello
+                .machine_id
+                .filter(|id| !id.is_empty())
+                .unwrap_or(client_id)
+        }
+    };
+
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-    let (tx, mut rx) = mpsc::unbounded_channel();
+    let queue = Arc::new(OutgoingQueue::new());
+    let tx = ClientSender {
+        queue: queue.clone(),
+    };
 
-    // Add client to the list
-    clients.write().await.insert(client_id.clone(), tx.clone());
+    // Add client to the list, taking over from a stale connection of the same machine.
+    // Broadcasts are numbered before they are sent, and sending waits for this lock, so
+    // each one is either in the replay or sent afterwards; the slave drops duplicates.
+    let resumed = {
+        let mut clients = clients.write().await;
+        if let Some(previous) = clients.insert(client_id.clone(), tx.clone()) {
+            println!("{} reconnected, closing its previous connection", client_id);
+            let _ = previous.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "Replaced by a new connection".into(),
+            })));
+        }
+        if resumable {
+            let history = history.read().await;
+            let point = resume_point.filter(|point| point.history_id == history.id());
+            let replay = point.as_ref().and_then(|point| history.since(point));
+            let payload = ResumeFromPayload {
+                history_id: history.id().to_string(),
+                last_seq: match (&point, &replay) {
+                    (Some(point), Some(_)) => point.last_seq,
+                    _ => history.last_seq(),
+                },
+                replayed: replay.is_some(),
+            };
+            if let Ok(json) = serde_json::to_string(&SyncMessage::resume_from(payload)) {
+                let _ = tx.send(Message::Text(json));
+            }
+            match (point, replay) {
+                (Some(point), Some(replay)) => {
+                    println!(
+                        "Resuming {} after #{}, replaying {} messages",
+                        client_id,
+                        point.last_seq,
+                        replay.len()
+                    );
+                    for (seq, message_type, json) in replay {
+                        let _ = tx.send_sync(&message_type, Some(seq), json);
+                    }
+                    true
+                }
+                (Some(point), None) => {
+                    println!(
+                        "Can't resume {} after #{}, the gap is no longer in the history",
+                        client_id, point.last_seq
+                    );
+                    false
+                }
+                (None, _) => false,
+            }
+        } else {
+            false
+        }
+    };
+
+    held_images
+        .write()
+        .await
+        .insert(client_id.clone(), hello_images.into_iter().collect());
 
     // Add client info
     {
@@ -259,18 +1510,25 @@ async fn handle_connection(
             client_id.clone(),
             ClientInfo {
                 id: client_id.clone(),
+                display_name: display_name.clone(),
                 ip_address: ip_address.clone(),
                 connected_at,
                 last_activity: connected_at,
             },
         );
     }
+    notify_clients_changed(&client_info, &clients_changed_callback).await;
 
-    println!("Client connected: {} from {}", client_id, ip_address);
+    println!(
+        "Client connected: {} ({}) from {}",
+        client_id,
+        display_name.as_deref().unwrap_or("unnamed"),
+        ip_address
+    );
 
-    // Call initial state callback for new client
+    // Call initial state callback for new client, unless the replay caught it up
     let callback_lock = callback.read().await;
-    if let Some(cb) = callback_lock.as_ref() {
+    if let Some(cb) = callback_lock.as_ref().filter(|_| !resumed) {
         let client_id_clone = client_id.clone();
         let future = cb(client_id_clone);
         drop(callback_lock); // Release lock before awaiting
@@ -278,18 +1536,107 @@ async fn handle_connection(
         println!("Triggered initial state sync for client: {}", client_id);
     }
 
-    // Forward messages from tx to WebSocket
+    // Forward messages from the queue to the WebSocket. Control messages go out as soon
+    // as the socket is free; a bulk message waits for its bandwidth share, and control
+    // messages queued meanwhile overtake it. One already being written is not interrupted.
+    let callback_for_send = callback.clone();
+    let client_id_for_send = client_id.clone();
+    let history_for_send = history.clone();
     let send_task = tokio::spawn(async move {
-        while let Some(message) = rx.recv().await {
+        let mut waiting_bulk: Option<(Message, tokio::time::Instant)> = None;
+        loop {
+            let message = if let Some(message) = queue.pop_control() {
+                message
+            } else if let Some(send_at) = waiting_bulk.as_ref().map(|(_, send_at)| *send_at) {
+                tokio::select! {
+                    biased;
+                    _ = queue.changed() => continue,
+                    _ = tokio::time::sleep_until(send_at) => match waiting_bulk.take() {
+                        Some((message, _)) => message,
+                        None => continue,
+                    },
+                }
+            } else if let Some(message) = queue.pop_bulk() {
+                let send_at = bandwidth.reserve(message.len());
+                waiting_bulk = Some((message, send_at));
+                continue;
+            } else if queue.is_finished() {
+                break;
+            } else {
+                // Caught up after falling behind: replace what was dropped with a full state
+                let dropped = queue.take_dropped();
+                if dropped > 0 {
+                    println!(
+                        "{} caught up after {} messages were dropped, resending full state",
+                        client_id_for_send, dropped
+                    );
+                    // The full state stands in for the dropped broadcasts, so the slave
+                    // moves its resume point past them instead of waiting for them
+                    if resumable {
+                        let payload = ResumeFromPayload {
+                            history_id: history_for_send.read().await.id().to_string(),
+                            last_seq: queue.handled_seq(),
+                            replayed: false,
+                        };
+                        if let Ok(json) = serde_json::to_string(&SyncMessage::resume_from(payload))
+                        {
+                            if ws_sender.send(Message::Text(json)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    let callback_lock = callback_for_send.read().await;
+                    if let Some(cb) = callback_lock.as_ref() {
+                        tokio::spawn(cb(client_id_for_send.clone()));
+                    }
+                }
+                queue.changed().await;
+                continue;
+            };
             if ws_sender.send(message).await.is_err() {
                 break;
             }
         }
     });
 
-    // Handle incoming messages from client (heartbeats, etc.)
+    // Handle incoming messages from client (heartbeats, etc.). Slaves send heartbeats
+    // regularly, so a silent connection is treated as dead rather than waiting for
+    // TCP to notice, which can take minutes behind a NAT.
     let client_info_for_update = client_info.clone();
-    while let Some(msg) = ws_receiver.next().await {
+    // Master clock minus the slave's, smoothed over its heartbeats
+    let mut clock_offset_ms: Option<f64> = None;
+    loop {
+        let next = match first_frame.take() {
+            Some(message) => Ok(Some(Ok(message))),
+            None => tokio::select! {
+                next = tokio::time::timeout(heartbeat_timeout, ws_receiver.next()) => next,
+                _ = tx.queue.stalled() => {
+                    eprintln!(
+                        "{} is not reading fast enough and its queue is full, dropping it",
+                        client_id
+                    );
+                    break;
+                }
+            },
+        };
+        let msg = match next {
+            Ok(Some(msg)) => msg,
+            Ok(None) => break,
+            Err(_) => {
+                eprintln!(
+                    "No activity from {} for {}s, dropping stale client",
+                    client_id,
+                    heartbeat_timeout.as_secs()
+                );
+                break;
+            }
+        };
+
+        // A newer connection from the same machine owns the entry now
+        if !is_current_connection(&clients, &client_id, &tx).await {
+            break;
+        }
+
         // Update last activity time
         {
             let mut info = client_info_for_update.write().await;
@@ -300,6 +1647,18 @@ async fn handle_connection(
 
         match msg {
             Ok(Message::Close(_)) => break,
+            Ok(Message::Pong(data)) => {
+                // Heartbeat pings carry the time they were queued
+                if let Ok(sent) = <[u8; 8]>::try_from(data.as_slice()) {
+                    let rtt = (chrono::Utc::now().timestamp_millis() - i64::from_be_bytes(sent))
+                        .max(0) as f64;
+                    let mut rtts = link_rtts.write().await;
+                    let smoothed = rtts
+                        .get(&client_id)
+                        .map_or(rtt, |previous| previous + RTT_SMOOTHING * (rtt - previous));
+                    rtts.insert(client_id.clone(), smoothed);
+                }
+            }
             Ok(Message::Ping(data)) => {
                 // Send pong
                 if let Some(tx) = clients.read().await.get(&client_id) {
@@ -312,6 +1671,12 @@ async fn handle_connection(
                     match sync_msg.message_type {
                         crate::sync::protocol::SyncMessageType::StateSyncRequest => {
                             println!("Received StateSyncRequest from {}", client_id);
+                            let request: StateSyncRequestPayload =
+                                serde_json::from_value(sync_msg.payload).unwrap_or_default();
+                            held_images.write().await.insert(
+                                client_id.clone(),
+                                request.held_images.into_iter().collect(),
+                            );
                             // Trigger initial state callback
                             let callback_lock = callback.read().await;
                             if let Some(cb) = callback_lock.as_ref() {
@@ -331,17 +1696,137 @@ async fn handle_connection(
                                     .get("desync_details")
                                     .and_then(|v| v.as_array()),
                             ) {
+                                let now = chrono::Utc::now().timestamp_millis();
+                                let apply_errors = sync_msg
+                                    .payload
+                                    .get("apply_errors")
+                                    .and_then(|v| v.as_array())
+                                    .map(|errors| {
+                                        errors
+                                            .iter()
+                                            .filter_map(|e| e.as_str().map(String::from))
+                                            .collect()
+                                    })
+                                    .unwrap_or_default();
                                 statuses.insert(
                                     client_id.clone(),
                                     SlaveStatus {
                                         client_id: client_id.clone(),
                                         is_synced,
                                         desync_details: desync_details.clone(),
-                                        last_report_time: chrono::Utc::now().timestamp_millis(),
+                                        last_report_time: now,
+                                        report_latency_ms: report_latency_ms(
+                                            now,
+                                            sync_msg.timestamp,
+                                            clock_offset_ms,
+                                        ),
+                                        apply_errors,
+                                        rtt_ms: None,
                                     },
                                 );
                             }
                         }
+                        crate::sync::protocol::SyncMessageType::Heartbeat => {
+                            // Echo the slave's clock alongside ours so it can estimate the offset
+                            if let Some(client_time) =
+                                sync_msg.payload.get("client_time").and_then(|v| v.as_i64())
+                            {
+                                let rtt = link_rtts.read().await.get(&client_id).copied();
+                                if let Some(rtt) = rtt {
+                                    let now = chrono::Utc::now().timestamp_millis();
+                                    let sample = clock_offset_sample(now, client_time, rtt);
+                                    clock_offset_ms =
+                                        Some(clock_offset_ms.map_or(sample, |previous| {
+                                            previous + RTT_SMOOTHING * (sample - previous)
+                                        }));
+                                }
+                                if let Ok(json) = serde_json::to_string(
+                                    &SyncMessage::heartbeat_reply(client_time),
+                                ) {
+                                    if let Some(tx) = clients.read().await.get(&client_id) {
+                                        let _ = tx.send(Message::Text(json));
+                                    }
+                                }
+                            }
+                        }
+                        crate::sync::protocol::SyncMessageType::Capabilities => {
+                            match serde_json::from_value::<Capabilities>(sync_msg.payload) {
+                                Ok(remote) => {
+                                    let own =
+                                        Capabilities::for_connection(tls, auth_token.is_some());
+                                    let negotiated = own.intersect(&remote);
+                                    println!(
+                                        "Negotiated capabilities with {}: {:?}",
+                                        client_id, negotiated
+                                    );
+                                    client_capabilities
+                                        .write()
+                                        .await
+                                        .insert(client_id.clone(), negotiated);
+
+                                    // Reply with our own capabilities
+                                    if let Ok(json) =
+                                        serde_json::to_string(&SyncMessage::capabilities(&own))
+                                    {
+                                        if let Some(tx) = clients.read().await.get(&client_id) {
+                                            let _ = tx.send(Message::Text(json));
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!("Invalid capabilities from {}: {}", client_id, e);
+                                }
+                            }
+                        }
+                        crate::sync::protocol::SyncMessageType::LatencyAck => {
+                            let received_at = chrono::Utc::now().timestamp_millis();
+                            if let Some(probe_id) =
+                                sync_msg.payload.get("probe_id").and_then(|v| v.as_str())
+                            {
+                                if let Some(ack_tx) = latency_probes.read().await.get(probe_id) {
+                                    let _ = ack_tx.send((client_id.clone(), received_at));
+                                }
+                            }
+                        }
+                        crate::sync::protocol::SyncMessageType::SceneChange
+                        | crate::sync::protocol::SyncMessageType::TransformUpdate
+                        | crate::sync::protocol::SyncMessageType::TransformBatch => {
+                            // Local changes of a bidirectional slave
+                            let callback = remote_update_callback.read().await.clone();
+                            match callback {
+                                Some(_) if sync_msg.origin_id.is_empty() => {
+                                    eprintln!(
+                                        "Ignoring {:?} from {} without origin id",
+                                        sync_msg.message_type, client_id
+                                    );
+                                }
+                                Some(cb) => cb(client_id.clone(), sync_msg),
+                                None => {
+                                    println!(
+                                        "Ignoring {:?} from {} (slave updates are not accepted)",
+                                        sync_msg.message_type, client_id
+                                    );
+                                }
+                            }
+                        }
+                        crate::sync::protocol::SyncMessageType::Ack => {
+                            if let Some(message_id) =
+                                sync_msg.payload.get("message_id").and_then(|v| v.as_str())
+                            {
+                                let acked = pending_acks
+                                    .write()
+                                    .await
+                                    .remove(&(client_id.clone(), message_id.to_string()));
+                                if let Some(monitor) = &performance_monitor {
+                                    let now = chrono::Utc::now().timestamp_millis();
+                                    // Timed from the send this ack answers, not the first one
+                                    let last_sent = acked.map(|entry| {
+                                        now - entry.last_sent.elapsed().as_millis() as i64
+                                    });
+                                    monitor.record_ack(message_id, now, last_sent).await;
+                                }
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -354,10 +1839,624 @@ async fn handle_connection(
         }
     }
 
-    // Remove client from the list
-    clients.write().await.remove(&client_id);
-    client_info.write().await.remove(&client_id);
-    slave_statuses.write().await.remove(&client_id);
-    send_task.abort();
+    // Remove client from the list, unless a reconnect of the same machine has taken it over
+    let replaced = {
+        let mut clients = clients.write().await;
+        let replaced = clients
+            .get(&client_id)
+            .is_some_and(|current| !current.same_channel(&tx));
+        if !replaced {
+            clients.remove(&client_id);
+        }
+        replaced
+    };
+    if !replaced {
+        client_info.write().await.remove(&client_id);
+        slave_statuses.write().await.remove(&client_id);
+        link_rtts.write().await.remove(&client_id);
+        held_images.write().await.remove(&client_id);
+        client_capabilities.write().await.remove(&client_id);
+        // Pending acks stay: a slave that reconnects under the same id gets them resent
+        notify_clients_changed(&client_info, &clients_changed_callback).await;
+    }
+    // Flush what is still queued (such as a rejection's Close frame), then stop
+    tx.queue.close();
+    let mut send_task = send_task;
+    let flush_timeout = tokio::time::Duration::from_millis(500);
+    if tokio::time::timeout(flush_timeout, &mut send_task)
+        .await
+        .is_err()
+    {
+        send_task.abort();
+    }
     println!("Client disconnected: {}", client_id);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::protocol::SyncTargetType;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+    /// A port nothing listens on right now
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    /// Master on a free port; the returned sender keeps its broadcast task alive.
+    /// The server is left to the test runtime's shutdown rather than stopped.
+    async fn start_master(
+        guard: HandshakeGuard,
+    ) -> (MasterServer, u16, mpsc::UnboundedSender<SyncMessage>) {
+        let port = free_port();
+        let server = MasterServer::new(port, None);
+        server.set_handshake_guard(guard).await;
+        let (tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+        (server, port, tx)
+    }
+
+    async fn connect_with_origin(
+        port: u16,
+        origin: &str,
+    ) -> tokio_tungstenite::tungstenite::Result<()> {
+        let mut request = format!("ws://127.0.0.1:{}", port)
+            .into_client_request()
+            .unwrap();
+        request
+            .headers_mut()
+            .insert("origin", HeaderValue::from_str(origin).unwrap());
+        tokio_tungstenite::connect_async(request).await.map(|_| ())
+    }
+
+    /// Slave that says hello as `machine_id` and acknowledges latency probes after
+    /// `ack_delay`, or never if it is `None`
+    async fn spawn_mock_slave(
+        port: u16,
+        machine_id: &str,
+        ack_delay: Option<tokio::time::Duration>,
+    ) {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let (mut sink, mut stream) = ws_stream.split();
+        let hello = SyncMessage::client_hello(Some(machine_id.to_string()), None, None, Vec::new());
+        sink.send(Message::Text(serde_json::to_string(&hello).unwrap()))
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            while let Some(Ok(frame)) = stream.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let Ok(message) = serde_json::from_str::<SyncMessage>(&text) else {
+                    continue;
+                };
+                if message.message_type != SyncMessageType::LatencyProbe {
+                    continue;
+                }
+                if let Some(delay) = ack_delay {
+                    tokio::time::sleep(delay).await;
+                    let probe_id = message.payload["probe_id"].as_str().unwrap();
+                    let ack = SyncMessage::latency_ack(probe_id);
+                    let _ = sink
+                        .send(Message::Text(serde_json::to_string(&ack).unwrap()))
+                        .await;
+                }
+            }
+        });
+    }
+
+    async fn wait_for_clients(server: &MasterServer, count: usize) {
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+            while server.get_connected_clients_count().await < count {
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("mock slaves did not connect");
+    }
+
+    #[tokio::test]
+    async fn advertised_auth_follows_the_configured_token() {
+        let open = MasterServer::new(0, None).capabilities().await;
+        assert!(!open.features.auth && !open.features.tls);
+        let guarded = MasterServer::new(0, Some("secret".to_string()))
+            .capabilities()
+            .await;
+        assert!(guarded.features.auth);
+        // An empty token means no auth
+        let empty = MasterServer::new(0, Some(String::new()))
+            .capabilities()
+            .await;
+        assert!(!empty.features.auth);
+    }
+
+    #[tokio::test]
+    async fn handshake_guard_checks_origin() {
+        let guard = HandshakeGuard {
+            enabled: true,
+            allowed_origins: vec!["http://studio.local".to_string()],
+            ..Default::default()
+        };
+        let (_server, port, _tx) = start_master(guard).await;
+
+        match connect_with_origin(port, "http://evil.example").await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::FORBIDDEN)
+            }
+            other => panic!("disallowed origin was not rejected: {:?}", other),
+        }
+        connect_with_origin(port, "http://studio.local")
+            .await
+            .expect("allowed origin should connect");
+    }
+
+    #[tokio::test]
+    async fn latency_probe_waits_for_delayed_slaves_and_times_out_silent_ones() {
+        let (server, port, _tx) = start_master(HandshakeGuard::default()).await;
+        spawn_mock_slave(
+            port,
+            "delayed",
+            Some(tokio::time::Duration::from_millis(200)),
+        )
+        .await;
+        spawn_mock_slave(port, "silent", None).await;
+        wait_for_clients(&server, 2).await;
+
+        let started = tokio::time::Instant::now();
+        let latencies = server
+            .measure_latency(tokio::time::Duration::from_millis(1000))
+            .await;
+        let elapsed = started.elapsed();
+
+        let round_trip = |id: &str| {
+            latencies
+                .iter()
+                .find(|latency| latency.client_id == id)
+                .unwrap_or_else(|| panic!("no result for {}", id))
+                .round_trip_ms
+        };
+        let delayed = round_trip("delayed").expect("delayed slave acknowledged in time");
+        assert!((200..1000).contains(&delayed), "round trip {}ms", delayed);
+        assert_eq!(round_trip("silent"), None);
+        // The silent slave holds the probe open until the timeout
+        assert!(elapsed >= tokio::time::Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn latency_probe_returns_once_every_slave_answered() {
+        let (server, port, _tx) = start_master(HandshakeGuard::default()).await;
+        spawn_mock_slave(port, "first", Some(tokio::time::Duration::from_millis(50))).await;
+        spawn_mock_slave(
+            port,
+            "second",
+            Some(tokio::time::Duration::from_millis(100)),
+        )
+        .await;
+        wait_for_clients(&server, 2).await;
+
+        let started = tokio::time::Instant::now();
+        let latencies = server
+            .measure_latency(tokio::time::Duration::from_secs(10))
+            .await;
+
+        assert_eq!(latencies.len(), 2);
+        assert!(latencies
+            .iter()
+            .all(|latency| latency.round_trip_ms.is_some()));
+        assert!(started.elapsed() < tokio::time::Duration::from_secs(5));
+    }
+
+    fn acked(
+        message_type: SyncMessageType,
+        target: SyncTargetType,
+        payload: serde_json::Value,
+    ) -> SyncMessage {
+        SyncMessage::new(message_type, target, payload).with_ack()
+    }
+
+    #[test]
+    fn newer_messages_supersede_pending_ones_for_the_same_target() {
+        let mut pending = PendingAcks::new();
+        let start = acked(
+            SyncMessageType::OutputControl,
+            SyncTargetType::Output,
+            serde_json::json!({"output": "record", "active": true}),
+        );
+        let stream = acked(
+            SyncMessageType::OutputControl,
+            SyncTargetType::Output,
+            serde_json::json!({"output": "stream", "active": true}),
+        );
+        let stop = acked(
+            SyncMessageType::OutputControl,
+            SyncTargetType::Output,
+            serde_json::json!({"output": "record", "active": false}),
+        );
+        track_pending(&mut pending, "slave", &start, String::new());
+        track_pending(&mut pending, "other", &start, String::new());
+        track_pending(&mut pending, "slave", &stream, String::new());
+        track_pending(&mut pending, "slave", &stop, String::new());
+
+        // "start recording" must never be resent after "stop"
+        assert!(!pending.contains_key(&("slave".to_string(), start.message_id.clone())));
+        assert!(pending.contains_key(&("slave".to_string(), stream.message_id.clone())));
+        assert!(pending.contains_key(&("slave".to_string(), stop.message_id.clone())));
+        assert!(pending.contains_key(&("other".to_string(), start.message_id.clone())));
+
+        let state = acked(
+            SyncMessageType::StateSync,
+            SyncTargetType::Program,
+            serde_json::json!({}),
+        );
+        track_pending(&mut pending, "slave", &state, String::new());
+        let left: Vec<_> = pending.keys().map(|(client, _)| client.as_str()).collect();
+        assert_eq!(left.iter().filter(|client| **client == "slave").count(), 1);
+        assert!(pending.contains_key(&("slave".to_string(), state.message_id.clone())));
+    }
+
+    #[test]
+    fn pending_messages_wait_for_a_reconnect_without_using_attempts() {
+        let mut pending = PendingAcks::new();
+        let message = acked(
+            SyncMessageType::SceneChange,
+            SyncTargetType::Program,
+            serde_json::json!({"scene_name": "Live"}),
+        );
+        track_pending(&mut pending, "slave", &message, "json".to_string());
+        let start = tokio::time::Instant::now();
+        let timeout = tokio::time::Duration::from_millis(ACK_TIMEOUT_MS);
+
+        // Disconnected through several timeouts: kept, nothing resent
+        for step in 1..=5 {
+            let due = take_due_resends(&mut pending, |_| false, start + timeout * step);
+            assert!(due.is_empty());
+        }
+        assert_eq!(pending.len(), 1);
+
+        // Back online: resent at once, then given up after the remaining attempts
+        let now = start + timeout * 6;
+        let due = take_due_resends(&mut pending, |_| true, now);
+        assert_eq!(
+            due,
+            vec![(
+                "slave".to_string(),
+                SyncMessageType::SceneChange,
+                "json".to_string()
+            )]
+        );
+        let due = take_due_resends(&mut pending, |_| true, now + timeout);
+        assert_eq!(due.len(), 1);
+        let due = take_due_resends(&mut pending, |_| true, now + timeout * 2);
+        assert!(due.is_empty());
+        assert!(pending.is_empty());
+
+        // A slave that never comes back is forgotten after the TTL
+        track_pending(&mut pending, "gone", &message, String::new());
+        let ttl = tokio::time::Duration::from_secs(PENDING_ACK_TTL_SECS);
+        take_due_resends(&mut pending, |_| false, tokio::time::Instant::now() + ttl);
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn silent_legacy_slave_is_registered_and_sent_state() {
+        let (server, port, _tx) = start_master(HandshakeGuard::default()).await;
+        let (state_tx, mut state_rx) = mpsc::unbounded_channel();
+        server
+            .set_initial_state_callback(move |client_id| {
+                let state_tx = state_tx.clone();
+                async move {
+                    let _ = state_tx.send(client_id);
+                }
+            })
+            .await;
+
+        // A v1 slave sends nothing until it has received a StateSync
+        let (_ws_stream, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let client_id = tokio::time::timeout(
+            tokio::time::Duration::from_secs(AUTH_TIMEOUT_SECS + 5),
+            state_rx.recv(),
+        )
+        .await
+        .expect("silent slave got no initial state")
+        .unwrap();
+        assert!(client_id.starts_with("127.0.0.1"));
+        assert_eq!(server.get_connected_clients_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn slaves_without_compression_get_raw_image_bytes() {
+        let image = vec![7u8; 64 * 1024];
+        let engine = &base64::engine::general_purpose::STANDARD;
+        let compressed = compression::compress(&image).unwrap();
+        let message = SyncMessage::new(
+            SyncMessageType::ImageUpdate,
+            SyncTargetType::Source,
+            serde_json::json!({
+                "source_name": "Logo",
+                "image_data": base64::Engine::encode(engine, &compressed),
+                "compression": compression::GZIP,
+            }),
+        );
+
+        let current = Capabilities::current();
+        let mut older = current.clone();
+        older.features.compression = false;
+        assert!(!needs_raw_images(&message, Some(&current)));
+        assert!(needs_raw_images(&message, Some(&older)));
+        assert!(needs_raw_images(&message, None));
+
+        let raw: SyncMessage =
+            serde_json::from_str(&with_raw_images(&message).await.unwrap()).unwrap();
+        assert!(raw.payload["compression"].is_null());
+        let data = raw.payload["image_data"].as_str().unwrap();
+        assert_eq!(base64::Engine::decode(engine, data).unwrap(), image);
+        assert_eq!(raw.message_id, message.message_id);
+    }
+
+    #[tokio::test]
+    async fn tls_slave_receives_state_sync_by_pin_or_by_ca() {
+        use super::super::tls;
+        use tokio_tungstenite::Connector;
+
+        let dir = std::env::temp_dir().join(format!("obs-sync-tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (cert_path, key_path) = (dir.join("cert.pem"), dir.join("key.pem"));
+        tls::ensure_self_signed(&cert_path, &key_path, tls::local_names()).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let master_tls = MasterTls::load(&cert_path, &key_path).unwrap();
+        let fingerprint = master_tls.fingerprint.clone();
+
+        let port = free_port();
+        let server = MasterServer::new(port, None);
+        server.set_tls(Some(master_tls)).await;
+        let (tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+
+        // Pinned by fingerprint, and verified against the certificate by IP address
+        let configs = [
+            tls::client_config(None, Some(&fingerprint)).unwrap(),
+            tls::client_config(Some(&cert_path), None).unwrap(),
+        ];
+        let mut connected = Vec::new();
+        for (slaves, config) in configs.into_iter().enumerate() {
+            let tcp = tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+            let (ws_stream, _) = tokio_tungstenite::client_async_tls_with_config(
+                format!("wss://127.0.0.1:{}", port),
+                tcp,
+                None,
+                Some(Connector::Rustls(config)),
+            )
+            .await
+            .unwrap();
+            let (mut sink, mut stream) = ws_stream.split();
+            let hello = SyncMessage::client_hello(None, None, None, Vec::new());
+            sink.send(Message::Text(serde_json::to_string(&hello).unwrap()))
+                .await
+                .unwrap();
+            wait_for_clients(&server, slaves + 1).await;
+
+            let state = SyncMessage::new(
+                SyncMessageType::StateSync,
+                SyncTargetType::Program,
+                serde_json::json!({ "scenes": [], "current_program_scene": "Main" }),
+            );
+            tx.send(state.clone()).unwrap();
+            let received = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+                while let Some(Ok(frame)) = stream.next().await {
+                    let Message::Text(text) = frame else {
+                        continue;
+                    };
+                    let message: SyncMessage = serde_json::from_str(&text).unwrap();
+                    if message.message_type == SyncMessageType::StateSync {
+                        return message;
+                    }
+                }
+                panic!("connection closed before the StateSync");
+            })
+            .await
+            .expect("no StateSync over TLS");
+            assert_eq!(received.message_id, state.message_id);
+            connected.push((sink, stream));
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn token_check_needs_the_exact_token() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cre", "s3cret"));
+        assert!(!token_matches("s3cret!", "s3cret"));
+        assert!(!token_matches("", "s3cret"));
+    }
+
+    /// Next sync message on `stream`, skipping pings and other non-text frames
+    async fn next_sync_message<S>(stream: &mut S) -> SyncMessage
+    where
+        S: futures::Stream<Item = tokio_tungstenite::tungstenite::Result<Message>> + Unpin,
+    {
+        loop {
+            let frame = tokio::time::timeout(tokio::time::Duration::from_secs(10), stream.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            if let Message::Text(text) = frame {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn slave_resumes_after_broadcasts_dropped_on_its_way() {
+        use super::super::history::ReceivedSeqs;
+        use crate::sync::protocol::ResumePoint;
+
+        let port = free_port();
+        let server = MasterServer::new(port, None);
+        let (tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+
+        let hello = |resume| {
+            let hello =
+                SyncMessage::client_hello(Some("stage".to_string()), None, resume, Vec::new());
+            Message::Text(serde_json::to_string(&hello).unwrap())
+        };
+        let (ws_stream, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let (mut sink, mut stream) = ws_stream.split();
+        sink.send(hello(None)).await.unwrap();
+        let mut start = next_sync_message(&mut stream).await;
+        while start.message_type != SyncMessageType::ResumeFrom {
+            start = next_sync_message(&mut stream).await;
+        }
+        let start: ResumeFromPayload = serde_json::from_value(start.payload).unwrap();
+        let mut received = ReceivedSeqs::new(start.history_id.clone(), start.last_seq);
+
+        // More than the slave's queue holds while it isn't reading
+        const BROADCASTS: u64 = 8_000;
+        let padding = "x".repeat(16 * 1024);
+        for _ in 0..BROADCASTS {
+            let volume = SyncMessage::new(
+                SyncMessageType::VolumeUpdate,
+                SyncTargetType::Source,
+                serde_json::json!({ "input_name": "Mic", "volume_db": -3.0, "padding": padding }),
+            );
+            tx.send(volume).unwrap();
+        }
+        while server.history.read().await.last_seq() < BROADCASTS {
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        }
+
+        // Read until the master marks where the dropped broadcasts end
+        let mut gaps = false;
+        loop {
+            let message = next_sync_message(&mut stream).await;
+            if message.message_type == SyncMessageType::ResumeFrom {
+                let marker: ResumeFromPayload = serde_json::from_value(message.payload).unwrap();
+                assert!(!marker.replayed);
+                assert_eq!(marker.history_id, start.history_id);
+                assert!(gaps, "nothing was dropped");
+                assert!(received.resume_point().last_seq < marker.last_seq);
+                received.skip_to(marker.last_seq);
+                break;
+            }
+            if let Some(seq) = message.seq {
+                gaps |= seq > received.resume_point().last_seq + 1;
+                received.receive(seq);
+            }
+        }
+        assert_eq!(received.resume_point().last_seq, BROADCASTS);
+        drop((sink, stream));
+
+        // Two changes while disconnected; the reconnect replays just those
+        for _ in 0..2 {
+            let mute = SyncMessage::new(
+                SyncMessageType::MuteUpdate,
+                SyncTargetType::Source,
+                serde_json::json!({ "input_name": "Mic", "muted": true }),
+            );
+            tx.send(mute).unwrap();
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        let (ws_stream, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let (mut sink, mut stream) = ws_stream.split();
+        let point: ResumePoint = received.resume_point();
+        sink.send(hello(Some(point))).await.unwrap();
+        let mut resume = next_sync_message(&mut stream).await;
+        while resume.message_type != SyncMessageType::ResumeFrom {
+            resume = next_sync_message(&mut stream).await;
+        }
+        let resume: ResumeFromPayload = serde_json::from_value(resume.payload).unwrap();
+        assert!(resume.replayed);
+        assert_eq!(resume.last_seq, BROADCASTS);
+        let mut replayed = Vec::new();
+        while replayed.len() < 2 {
+            let message = next_sync_message(&mut stream).await;
+            if message.message_type == SyncMessageType::MuteUpdate {
+                replayed.push(message.seq.unwrap());
+            }
+        }
+        assert_eq!(replayed, vec![BROADCASTS + 1, BROADCASTS + 2]);
+    }
+
+    #[test]
+    fn report_latency_is_corrected_for_the_slave_clock() {
+        // The slave's clock runs 60s behind; its heartbeat left 10ms before arriving
+        let rtt = 20.0;
+        let offset = clock_offset_sample(1_000_000, 1_000_000 - 60_000 - 10, rtt);
+        assert_eq!(offset, 60_000.0);
+
+        // A report stamped 940_000 on the slave's clock is 940_000 + 60s on ours
+        assert_eq!(
+            report_latency_ms(1_000_015, 940_000, Some(offset)),
+            Some(15)
+        );
+        // Without an offset the two clocks can't be compared
+        assert_eq!(report_latency_ms(1_000_015, 940_000, None), None);
+    }
+
+    #[test]
+    fn fleet_csv_is_a_plain_table() {
+        let report = FleetReport {
+            generated_at: 1_700_000_000_000,
+            master_obs_version: Some("30.1.2".to_string()),
+            clients: vec![FleetReportEntry {
+                client_id: "stage".to_string(),
+                display_name: Some("Stage, left".to_string()),
+                ip_address: "10.0.0.2".to_string(),
+                connected_at: 1_699_999_000_000,
+                last_report_time: None,
+                is_synced: Some(true),
+                is_stale: false,
+                report_latency_ms: Some(12),
+                desync_details: Vec::new(),
+                apply_errors: Vec::new(),
+            }],
+        };
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("generated_at,master_obs_version,client_id,"));
+        assert_eq!(
+            lines[1],
+            "1700000000000,30.1.2,stage,\"Stage, left\",10.0.0.2,1699999000000,,true,false,12,,"
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_master_stops_promptly() {
+        let port = free_port();
+        let server = MasterServer::new(port, None);
+        let (_tx, rx) = mpsc::unbounded_channel();
+        server.start(rx, None).await.unwrap();
+
+        let started = tokio::time::Instant::now();
+        tokio::time::timeout(tokio::time::Duration::from_secs(2), server.stop())
+            .await
+            .expect("stop hung");
+        // The broadcast task left on the shutdown signal instead of running into the abort
+        assert!(started.elapsed() < tokio::time::Duration::from_millis(400));
+        assert!(server.broadcast_task.read().await.is_none());
+        assert!(server.listener.read().await.is_none());
+    }
+}
@@ -0,0 +1,41 @@
+use super::client::ReconnectionStatus;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Connection or sync problem worth showing on screen, not just in the log.
+/// Sent to the frontend as the `connection-status` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusEvent {
+    pub level: StatusLevel,
+    pub message: String,
+    pub timestamp: i64,
+    /// Slave reconnection state at the time of the event
+    pub reconnection: Option<ReconnectionStatus>,
+}
+
+impl StatusEvent {
+    pub fn new(level: StatusLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            reconnection: None,
+        }
+    }
+
+    pub fn with_reconnection(mut self, reconnection: ReconnectionStatus) -> Self {
+        self.reconnection = Some(reconnection);
+        self
+    }
+}
+
+pub type StatusCallback = Arc<dyn Fn(StatusEvent) + Send + Sync>;
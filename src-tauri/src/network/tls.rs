@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{
+    ring::default_provider, verify_tls12_signature, verify_tls13_signature,
+    WebPkiSupportedAlgorithms,
+};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    ClientConfig, DigitallySignedStruct, Error as TlsError, RootCertStore, ServerConfig,
+    SignatureScheme,
+};
+use tokio_rustls::TlsAcceptor;
+
+/// SHA-256 of a DER certificate as colon-separated uppercase hex, the form shown by browsers
+pub fn fingerprint(cert: &[u8]) -> String {
+    Sha256::digest(cert)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Lowercase hex without separators, so pins can be pasted in either form
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let pem = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let certs = rustls_pemfile::certs(&mut pem.as_slice())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid certificate in {:?}", path))?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificate found in {:?}", path);
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    let pem = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    rustls_pemfile::private_key(&mut pem.as_slice())
+        .with_context(|| format!("Invalid private key in {:?}", path))?
+        .with_context(|| format!("No private key found in {:?}", path))
+}
+
+/// Names slaves may reach this machine by: localhost, its host name and the address
+/// it uses on the network
+pub fn local_names() -> Vec<String> {
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+    let host_name = ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    if let Some(host_name) = host_name {
+        names.push(format!("{}.local", host_name));
+        names.push(host_name);
+    }
+    // Connecting a UDP socket picks the outgoing interface without sending anything
+    let lan_address = std::net::UdpSocket::bind("0.0.0.0:0").and_then(|socket| {
+        socket.connect("192.0.2.1:9")?;
+        socket.local_addr()
+    });
+    if let Ok(address) = lan_address {
+        names.push(address.ip().to_string());
+    }
+    names.dedup();
+    names
+}
+
+/// Write the private key readable by the current user only
+fn write_private_key(path: &Path, pem: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(pem.as_bytes())
+}
+
+/// Write a self-signed certificate for `names` (host names or IP addresses) and its key
+/// to the given paths, unless both already exist
+pub fn ensure_self_signed(cert_path: &Path, key_path: &Path, names: Vec<String>) -> Result<()> {
+    if cert_path.exists() && key_path.exists() {
+        return Ok(());
+    }
+    let cert = rcgen::generate_simple_self_signed(names)
+        .context("Failed to generate self-signed certificate")?;
+    let cert_pem = cert
+        .serialize_pem()
+        .context("Failed to encode self-signed certificate")?;
+    std::fs::write(cert_path, cert_pem)
+        .with_context(|| format!("Failed to write {:?}", cert_path))?;
+    write_private_key(key_path, &cert.serialize_private_key_pem())
+        .with_context(|| format!("Failed to write {:?}", key_path))?;
+    println!("Generated self-signed master certificate: {:?}", cert_path);
+    Ok(())
+}
+
+/// TLS identity the master serves wss:// with
+#[derive(Clone)]
+pub struct MasterTls {
+    pub acceptor: TlsAcceptor,
+    /// Fingerprint of the master certificate, for pinning on slaves
+    pub fingerprint: String,
+}
+
+impl MasterTls {
+    pub fn load(cert_path: &Path, key_path: &Path) -> Result<Self> {
+        let certs = load_certs(cert_path)?;
+        let key = load_key(key_path)?;
+        let cert_fingerprint = fingerprint(&certs[0]);
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("Certificate and key don't form a valid TLS identity")?;
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            fingerprint: cert_fingerprint,
+        })
+    }
+}
+
+/// Accepts exactly the server certificate with the pinned fingerprint; chain, expiry and
+/// host name are not checked, so self-signed master certificates work on any address
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: String,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, TlsError> {
+        if normalize_fingerprint(&fingerprint(end_entity)) == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(TlsError::General(
+                "Master certificate does not match the pinned fingerprint".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// Client config for slaves. A pinned fingerprint takes precedence; otherwise the master
+/// certificate must chain to `ca_cert_path` when given, or to the public web roots.
+pub fn client_config(
+    ca_cert_path: Option<&Path>,
+    pinned_fingerprint: Option<&str>,
+) -> Result<Arc<ClientConfig>> {
+    if let Some(pin) = pinned_fingerprint.filter(|p| !p.trim().is_empty()) {
+        let verifier = PinnedCertVerifier {
+            fingerprint: normalize_fingerprint(pin),
+            algorithms: default_provider().signature_verification_algorithms,
+        };
+        let config = ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        return Ok(Arc::new(config));
+    }
+
+    let mut roots = RootCertStore::empty();
+    match ca_cert_path {
+        Some(path) => {
+            for cert in load_certs(path)? {
+                roots
+                    .add(cert)
+                    .with_context(|| format!("Unusable certificate in {:?}", path))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
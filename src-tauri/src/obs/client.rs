@@ -1,8 +1,16 @@
+use crate::network::reconnect::ReconnectStrategy;
 use anyhow::{Context, Result};
+use futures_util::StreamExt;
 use obws::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+
+/// Delay between attempts to reach OBS again; a restarted OBS is usually back within seconds
+const RECONNECT_STRATEGY: ReconnectStrategy = ReconnectStrategy::Exponential { base: 2, cap: 5 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OBSConnectionConfig {
@@ -17,38 +25,133 @@ pub struct OBSConnectionStatus {
     pub connected: bool,
     pub obs_version: Option<String>,
     pub obs_websocket_version: Option<String>,
+    /// The connection dropped and is being re-established
+    #[serde(default)]
+    pub reconnecting: bool,
+}
+
+/// Change of the OBS connection noticed while connected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OBSConnectionEvent {
+    /// OBS closed the connection; reconnection attempts follow
+    Lost,
+    /// Connected again with the stored config
+    Reconnected { attempts: u32 },
 }
 
 #[derive(Clone)]
 pub struct OBSClient {
     client: Arc<RwLock<Option<Client>>>,
     config: Arc<RwLock<Option<OBSConnectionConfig>>>,
+    /// Notices a dropped connection and reconnects; runs while connected
+    watchdog: Arc<Mutex<Option<JoinHandle<()>>>>,
+    reconnecting: Arc<AtomicBool>,
+    connection_events: broadcast::Sender<OBSConnectionEvent>,
 }
 
 impl OBSClient {
     pub fn new() -> Self {
+        let (connection_events, _) = broadcast::channel(16);
         Self {
             client: Arc::new(RwLock::new(None)),
             config: Arc::new(RwLock::new(None)),
+            watchdog: Arc::new(Mutex::new(None)),
+            reconnecting: Arc::new(AtomicBool::new(false)),
+            connection_events,
         }
     }
 
     pub async fn connect(&self, config: OBSConnectionConfig) -> Result<()> {
-        let host = config.host.clone();
-        let port = config.port;
-        let password = config.password.clone();
+        self.stop_watchdog();
 
-        let client = Client::connect(host, port, password)
+        let client = Self::open(&config)
             .await
             .context("Failed to connect to OBS WebSocket")?;
 
         *self.client.write().await = Some(client);
         *self.config.write().await = Some(config);
 
+        let watchdog = tokio::spawn(self.clone().watch());
+        *self.watchdog.lock().unwrap_or_else(PoisonError::into_inner) = Some(watchdog);
+
         Ok(())
     }
 
+    async fn open(config: &OBSConnectionConfig) -> Result<Client, obws::error::Error> {
+        Client::connect(config.host.clone(), config.port, config.password.clone()).await
+    }
+
+    /// Drops and reconnects while connected. Receivers must re-subscribe to OBS events
+    /// after `Reconnected`, as event streams end with the connection they came from.
+    pub fn subscribe(&self) -> broadcast::Receiver<OBSConnectionEvent> {
+        self.connection_events.subscribe()
+    }
+
+    fn stop_watchdog(&self) {
+        if let Some(watchdog) = self
+            .watchdog
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            watchdog.abort();
+        }
+        self.reconnecting.store(false, Ordering::SeqCst);
+    }
+
+    /// Wait for the connection to drop, then reconnect with the stored config until it works
+    async fn watch(self) {
+        loop {
+            self.wait_for_drop().await;
+            *self.client.write().await = None;
+            self.reconnecting.store(true, Ordering::SeqCst);
+            println!("Lost connection to OBS, reconnecting");
+            let _ = self.connection_events.send(OBSConnectionEvent::Lost);
+
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                tokio::time::sleep(RECONNECT_STRATEGY.delay(attempts)).await;
+                let Some(config) = self.config.read().await.clone() else {
+                    return;
+                };
+                match Self::open(&config).await {
+                    Ok(client) => {
+                        *self.client.write().await = Some(client);
+                        break;
+                    }
+                    Err(e) => eprintln!("OBS reconnection attempt {} failed: {}", attempts, e),
+                }
+            }
+
+            self.reconnecting.store(false, Ordering::SeqCst);
+            println!("Reconnected to OBS after {} attempt(s)", attempts);
+            let _ = self
+                .connection_events
+                .send(OBSConnectionEvent::Reconnected { attempts });
+        }
+    }
+
+    /// Returns once the current connection is gone. obws ends every event stream when
+    /// its connection closes, but also when a slow reader lags, so a stream ending is
+    /// only taken as a drop if no new one can be had.
+    async fn wait_for_drop(&self) {
+        loop {
+            let events = match self.client.read().await.as_ref() {
+                Some(client) => client.events(),
+                None => return,
+            };
+            let Ok(events) = events else {
+                return;
+            };
+            tokio::pin!(events);
+            while events.next().await.is_some() {}
+        }
+    }
+
     pub async fn disconnect(&self) -> Result<()> {
+        self.stop_watchdog();
         let mut client_lock = self.client.write().await;
         if let Some(client) = client_lock.take() {
             drop(client);
@@ -71,6 +174,7 @@ impl OBSClient {
                     connected: true,
                     obs_version: Some(version.obs_version.to_string()),
                     obs_websocket_version: Some(version.obs_web_socket_version.to_string()),
+                    reconnecting: false,
                 };
             }
         }
@@ -79,6 +183,7 @@ impl OBSClient {
             connected: false,
             obs_version: None,
             obs_websocket_version: None,
+            reconnecting: self.reconnecting.load(Ordering::SeqCst),
         }
     }
 
@@ -92,3 +197,124 @@ impl Default for OBSClient {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+    use serde_json::json;
+    use tokio::sync::Notify;
+    use tokio::time::{timeout, Duration};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Minimal obs-websocket 5 server: it does the handshake, answers GetVersion and
+    /// closes its current connection, as a quitting OBS would, whenever `quit` is notified
+    async fn spawn_mock_obs(quit: Arc<Notify>) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let quit = quit.clone();
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    let hello = json!({ "op": 0, "d": { "obsWebSocketVersion": "5.5.0", "rpcVersion": 1 } });
+                    if ws.send(Message::Text(hello.to_string())).await.is_err() {
+                        return;
+                    }
+                    loop {
+                        let frame = tokio::select! {
+                            _ = quit.notified() => {
+                                let _ = ws.close(None).await;
+                                return;
+                            }
+                            frame = ws.next() => frame,
+                        };
+                        let Some(Ok(Message::Text(text))) = frame else {
+                            return;
+                        };
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let reply = match request["op"].as_u64() {
+                            // Identify
+                            Some(1) => json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } }),
+                            // Request; only GetVersion is sent while connecting
+                            Some(6) => json!({ "op": 7, "d": {
+                                "requestType": request["d"]["requestType"],
+                                "requestId": request["d"]["requestId"],
+                                "requestStatus": { "result": true, "code": 100 },
+                                "responseData": {
+                                    "obsVersion": "30.2.0",
+                                    "obsWebSocketVersion": "5.5.0",
+                                    "rpcVersion": 1,
+                                    "availableRequests": [],
+                                    "supportedImageFormats": [],
+                                    "platform": "linux",
+                                    "platformDescription": "mock",
+                                },
+                            } }),
+                            _ => continue,
+                        };
+                        if ws.send(Message::Text(reply.to_string())).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    fn config(port: u16) -> OBSConnectionConfig {
+        OBSConnectionConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            password: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn watchdog_reports_a_drop_and_reconnects() {
+        let quit = Arc::new(Notify::new());
+        let port = spawn_mock_obs(quit.clone()).await;
+        let obs = OBSClient::new();
+        let mut events = obs.subscribe();
+        obs.connect(config(port)).await.unwrap();
+        assert!(obs.is_connected().await);
+
+        quit.notify_one();
+        let lost = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(lost, Ok(OBSConnectionEvent::Lost)));
+        assert!(!obs.is_connected().await);
+
+        let reconnected = timeout(Duration::from_secs(10), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            reconnected,
+            Ok(OBSConnectionEvent::Reconnected { attempts: 1 })
+        ));
+        assert!(obs.is_connected().await);
+        obs.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn disconnecting_stops_the_watchdog() {
+        let quit = Arc::new(Notify::new());
+        let port = spawn_mock_obs(quit).await;
+        let obs = OBSClient::new();
+        let mut events = obs.subscribe();
+        obs.connect(config(port)).await.unwrap();
+
+        obs.disconnect().await.unwrap();
+        // The connection closing is our own doing, so it isn't reported as lost
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(matches!(
+            events.try_recv(),
+            Err(broadcast::error::TryRecvError::Empty)
+        ));
+        assert!(!obs.is_connected().await);
+    }
+}
@@ -1,9 +1,40 @@
 use anyhow::{Context, Result};
+use obws::responses::scene_items::{SceneItem, SourceType};
 use obws::Client;
 
+/// Kind reported for scene items that are groups
+pub const GROUP_SOURCE_KIND: &str = "group";
+/// Kind reported for scenes nested in another scene
+pub const SCENE_SOURCE_KIND: &str = "scene";
+/// Kind of OBS's built-in Cut transition
+const CUT_TRANSITION_KIND: &str = "cut_transition";
+
 pub struct OBSCommands;
 
 impl OBSCommands {
+    /// Input kind of a scene item. OBS leaves `input_kind` empty for groups and nested
+    /// scenes, which are classified explicitly; other items are looked up as inputs
+    /// before falling back to "unknown".
+    pub async fn resolve_source_kind(client: &Client, item: &SceneItem) -> String {
+        if let Some(kind) = &item.input_kind {
+            return kind.clone();
+        }
+        if item.is_group == Some(true) {
+            return GROUP_SOURCE_KIND.to_string();
+        }
+        match item.source_type {
+            SourceType::Scene => SCENE_SOURCE_KIND.to_string(),
+            _ => client
+                .inputs()
+                .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(
+                    &item.source_name,
+                ))
+                .await
+                .map(|input| input.kind)
+                .unwrap_or_else(|_| "unknown".to_string()),
+        }
+    }
+
     pub async fn set_current_program_scene(client: &Client, scene_name: &str) -> Result<()> {
         client
             .scenes()
@@ -13,6 +44,237 @@ impl OBSCommands {
         Ok(())
     }
 
+    /// Set the preview scene, turning studio mode on first if it is off, since OBS
+    /// only has a preview in studio mode
+    pub async fn set_current_preview_scene(client: &Client, scene_name: &str) -> Result<()> {
+        let studio_mode = client
+            .ui()
+            .studio_mode_enabled()
+            .await
+            .context("Failed to get studio mode state")?;
+        if !studio_mode {
+            client
+                .ui()
+                .set_studio_mode_enabled(true)
+                .await
+                .context("Failed to enable studio mode")?;
+            println!("Enabled studio mode to sync the preview scene");
+        }
+        client
+            .scenes()
+            .set_current_preview_scene(scene_name)
+            .await
+            .context("Failed to set current preview scene")?;
+        Ok(())
+    }
+
+    /// Current scene transition and its duration (None if the transition is fixed)
+    pub async fn current_transition(client: &Client) -> Result<(String, Option<u64>)> {
+        let transition = client
+            .transitions()
+            .current()
+            .await
+            .context("Failed to get current scene transition")?;
+        let duration_ms = transition
+            .duration
+            .filter(|_| !transition.fixed)
+            .map(|d| d.whole_milliseconds().max(0) as u64);
+        Ok((transition.id.name, duration_ms))
+    }
+
+    /// Make `name` the current scene transition, with `duration_ms` where the
+    /// transition allows it. If this OBS has no transition by that name the Cut
+    /// transition is selected instead and `false` is returned.
+    pub async fn use_transition(
+        client: &Client,
+        name: &str,
+        duration_ms: Option<u64>,
+    ) -> Result<bool> {
+        let list = client
+            .transitions()
+            .list()
+            .await
+            .context("Failed to list scene transitions")?;
+        let Some(transition) = list.transitions.iter().find(|t| t.id.name == name) else {
+            if let Some(cut) = list
+                .transitions
+                .iter()
+                .find(|t| t.kind == CUT_TRANSITION_KIND)
+            {
+                client
+                    .transitions()
+                    .set_current(&cut.id.name)
+                    .await
+                    .context("Failed to set current scene transition")?;
+            }
+            return Ok(false);
+        };
+        client
+            .transitions()
+            .set_current(name)
+            .await
+            .context("Failed to set current scene transition")?;
+        if let (Some(ms), false) = (duration_ms, transition.fixed) {
+            let duration = std::time::Duration::from_millis(ms)
+                .try_into()
+                .context("Invalid transition duration")?;
+            client
+                .transitions()
+                .set_current_duration(duration)
+                .await
+                .context("Failed to set scene transition duration")?;
+        }
+        Ok(true)
+    }
+
+    /// Push the preview scene to program with the current transition ("Take").
+    /// Fails if studio mode is off.
+    pub async fn trigger_studio_transition(client: &Client) -> Result<()> {
+        let studio_mode = client
+            .ui()
+            .studio_mode_enabled()
+            .await
+            .context("Failed to get studio mode state")?;
+        if !studio_mode {
+            anyhow::bail!("Studio mode is not enabled");
+        }
+        client
+            .transitions()
+            .trigger()
+            .await
+            .context("Failed to trigger studio mode transition")?;
+        Ok(())
+    }
+
+    /// Create an empty scene unless one with that name already exists
+    pub async fn create_scene(client: &Client, scene_name: &str) -> Result<()> {
+        let scenes = client
+            .scenes()
+            .list()
+            .await
+            .context("Failed to list scenes")?;
+        if scenes.scenes.iter().any(|s| s.id.name == scene_name) {
+            return Ok(());
+        }
+        client
+            .scenes()
+            .create(scene_name)
+            .await
+            .context("Failed to create scene")?;
+        Ok(())
+    }
+
+    /// Add a filter to a source unless it already has one by that name, then move it
+    /// to `index` so filter order matches the master
+    pub async fn create_filter(
+        client: &Client,
+        source_name: &str,
+        filter_name: &str,
+        filter_kind: &str,
+        settings: Option<&serde_json::Value>,
+        index: Option<u32>,
+    ) -> Result<()> {
+        let source = obws::requests::sources::SourceId::Name(source_name);
+        let filters = client
+            .filters()
+            .list(source)
+            .await
+            .context("Failed to list filters")?;
+        if filters.iter().any(|f| f.name == filter_name) {
+            return Ok(());
+        }
+        client
+            .filters()
+            .create(obws::requests::filters::Create {
+                source,
+                filter: filter_name,
+                kind: filter_kind,
+                settings,
+            })
+            .await
+            .context("Failed to create filter")?;
+        if let Some(index) = index {
+            client
+                .filters()
+                .set_index(obws::requests::filters::SetIndex {
+                    source,
+                    filter: filter_name,
+                    index,
+                })
+                .await
+                .context("Failed to move filter")?;
+        }
+        Ok(())
+    }
+
+    /// Remove a filter from a source; a filter that is already gone is not an error
+    pub async fn remove_filter(
+        client: &Client,
+        source_name: &str,
+        filter_name: &str,
+    ) -> Result<()> {
+        let source = obws::requests::sources::SourceId::Name(source_name);
+        let filters = client
+            .filters()
+            .list(source)
+            .await
+            .context("Failed to list filters")?;
+        if !filters.iter().any(|f| f.name == filter_name) {
+            return Ok(());
+        }
+        client
+            .filters()
+            .remove(source, filter_name)
+            .await
+            .context("Failed to remove filter")?;
+        Ok(())
+    }
+
+    /// Remove a scene. If it's the current program or preview scene, another scene is
+    /// switched to first; the last remaining scene is never removed, as OBS needs one.
+    pub async fn remove_scene(client: &Client, scene_name: &str) -> Result<()> {
+        let scenes = client
+            .scenes()
+            .list()
+            .await
+            .context("Failed to list scenes")?;
+        if !scenes.scenes.iter().any(|s| s.id.name == scene_name) {
+            return Ok(());
+        }
+        let fallback = scenes
+            .scenes
+            .iter()
+            .map(|s| s.id.name.as_str())
+            .find(|name| *name != scene_name)
+            .context("Cannot remove the only scene")?;
+
+        let is_program = scenes
+            .current_program_scene
+            .as_ref()
+            .is_some_and(|s| s.name == scene_name);
+        if is_program {
+            Self::set_current_program_scene(client, fallback).await?;
+        }
+        let is_preview = scenes
+            .current_preview_scene
+            .as_ref()
+            .is_some_and(|s| s.name == scene_name);
+        if is_preview {
+            client
+                .scenes()
+                .set_current_preview_scene(fallback)
+                .await
+                .context("Failed to set current preview scene")?;
+        }
+
+        client
+            .scenes()
+            .remove(obws::requests::scenes::SceneId::Name(scene_name))
+            .await
+            .context("Failed to remove scene")?;
+        Ok(())
+    }
+
     pub async fn create_scene_item(
         client: &Client,
         scene_name: &str,
@@ -77,4 +339,203 @@ impl OBSCommands {
 
         Ok(())
     }
+
+    pub async fn set_scene_item_locked(
+        client: &Client,
+        scene_name: &str,
+        scene_item_id: i64,
+        locked: bool,
+    ) -> Result<()> {
+        let scene_id: obws::requests::scenes::SceneId =
+            obws::requests::scenes::SceneId::Name(scene_name);
+
+        use obws::requests::scene_items::SetLocked;
+        client
+            .scene_items()
+            .set_locked(SetLocked {
+                scene: scene_id,
+                item_id: scene_item_id,
+                locked,
+            })
+            .await
+            .context("Failed to set scene item lock state")?;
+
+        Ok(())
+    }
+
+    pub async fn set_scene_item_blend_mode(
+        client: &Client,
+        scene_name: &str,
+        scene_item_id: i64,
+        blend_mode: &str,
+    ) -> Result<()> {
+        let scene_id: obws::requests::scenes::SceneId =
+            obws::requests::scenes::SceneId::Name(scene_name);
+        let mode: obws::common::BlendMode =
+            serde_json::from_value(serde_json::Value::String(blend_mode.to_string()))
+                .with_context(|| format!("Unknown blend mode {}", blend_mode))?;
+
+        use obws::requests::scene_items::SetBlendMode;
+        client
+            .scene_items()
+            .set_blend_mode(SetBlendMode {
+                scene: scene_id,
+                item_id: scene_item_id,
+                mode,
+            })
+            .await
+            .context("Failed to set scene item blend mode")?;
+
+        Ok(())
+    }
+
+    /// Restack the items of a scene so `order` (bottom first) holds. Items not in `order`
+    /// keep their slots; the others are moved into the remaining ones. Returns how many
+    /// items were moved.
+    pub async fn set_scene_item_order(
+        client: &Client,
+        scene_name: &str,
+        order: &[i64],
+    ) -> Result<usize> {
+        let scene_id: obws::requests::scenes::SceneId =
+            obws::requests::scenes::SceneId::Name(scene_name);
+
+        let mut items = client
+            .scene_items()
+            .list(scene_id)
+            .await
+            .context("Failed to list scene items")?;
+        items.sort_by_key(|item| item.index);
+        let mut current: Vec<i64> = items.into_iter().map(|item| item.id).collect();
+
+        let mut ordered = order.iter().filter(|id| current.contains(id));
+        let desired: Vec<i64> = current
+            .iter()
+            .map(|id| {
+                if order.contains(id) {
+                    ordered.next().copied().unwrap_or(*id)
+                } else {
+                    *id
+                }
+            })
+            .collect();
+
+        use obws::requests::scene_items::SetIndex;
+        let mut moved = 0;
+        for (index, &id) in desired.iter().enumerate() {
+            let Some(position) = current.iter().position(|&local| local == id) else {
+                continue;
+            };
+            if position == index {
+                continue;
+            }
+            client
+                .scene_items()
+                .set_index(SetIndex {
+                    scene: scene_id,
+                    item_id: id,
+                    index: index as u32,
+                })
+                .await
+                .context("Failed to set scene item index")?;
+            let item = current.remove(position);
+            current.insert(index, item);
+            moved += 1;
+        }
+
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obs::mock::MockObs;
+
+    #[tokio::test]
+    async fn studio_transition_needs_studio_mode() {
+        let obs = MockObs::start(|request_type, _| match request_type {
+            "GetStudioModeEnabled" => Ok(serde_json::json!({ "studioModeEnabled": false })),
+            _ => Ok(serde_json::Value::Null),
+        })
+        .await;
+        let client = obs.client().await;
+
+        let error = OBSCommands::trigger_studio_transition(&client)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Studio mode is not enabled");
+        assert_eq!(obs.request_types(), vec!["GetStudioModeEnabled"]);
+    }
+
+    #[tokio::test]
+    async fn studio_transition_triggers_in_studio_mode() {
+        let obs = MockObs::start(|request_type, _| match request_type {
+            "GetStudioModeEnabled" => Ok(serde_json::json!({ "studioModeEnabled": true })),
+            _ => Ok(serde_json::Value::Null),
+        })
+        .await;
+        let client = obs.client().await;
+
+        OBSCommands::trigger_studio_transition(&client)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            obs.request_types(),
+            vec!["GetStudioModeEnabled", "TriggerStudioModeTransition"]
+        );
+    }
+
+    #[tokio::test]
+    async fn items_without_an_input_kind_are_classified() {
+        let obs =
+            MockObs::start(
+                |request_type, data| match (request_type, data["inputName"].as_str()) {
+                    ("GetInputSettings", Some("Mic")) => Ok(serde_json::json!({
+                        "inputSettings": {},
+                        "inputKind": "wasapi_input_capture",
+                    })),
+                    _ => Err("No source was found".to_string()),
+                },
+            )
+            .await;
+        let client = obs.client().await;
+        let item = |name: &str, source_type: &str, is_group: Option<bool>| -> SceneItem {
+            serde_json::from_value(serde_json::json!({
+                "sceneItemId": 1,
+                "sceneItemIndex": 0,
+                "sourceName": name,
+                "sourceType": source_type,
+                "inputKind": null,
+                "isGroup": is_group,
+            }))
+            .unwrap()
+        };
+
+        let group = item("Lower third", "OBS_SOURCE_TYPE_SCENE", Some(true));
+        let nested = item("Intermission", "OBS_SOURCE_TYPE_SCENE", Some(false));
+        assert_eq!(
+            OBSCommands::resolve_source_kind(&client, &group).await,
+            GROUP_SOURCE_KIND
+        );
+        assert_eq!(
+            OBSCommands::resolve_source_kind(&client, &nested).await,
+            SCENE_SOURCE_KIND
+        );
+        // Groups and scenes are classified without asking OBS
+        assert!(obs.request_types().is_empty());
+
+        let mic = item("Mic", "OBS_SOURCE_TYPE_INPUT", None);
+        let gone = item("Gone", "OBS_SOURCE_TYPE_INPUT", None);
+        assert_eq!(
+            OBSCommands::resolve_source_kind(&client, &mic).await,
+            "wasapi_input_capture"
+        );
+        assert_eq!(
+            OBSCommands::resolve_source_kind(&client, &gone).await,
+            "unknown"
+        );
+    }
 }
@@ -1,8 +1,9 @@
 use futures_util::StreamExt;
-use obws::events::Event;
+use obws::events::{Event, OutputState};
 use obws::Client;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
@@ -41,20 +42,141 @@ pub enum OBSEvent {
         scene_item_id: i64,
         enabled: bool,
     },
+    SceneItemLockStateChanged {
+        scene_name: String,
+        scene_item_id: i64,
+        locked: bool,
+    },
+    /// OBS has no event for this; the master finds changes by polling
+    SceneItemBlendModeChanged {
+        scene_name: String,
+        scene_item_id: i64,
+        blend_mode: String,
+    },
+    /// Items of a scene were restacked; `item_ids` lists them bottom first
+    SceneItemsReordered {
+        scene_name: String,
+        item_ids: Vec<i64>,
+    },
+    SourceFilterCreated {
+        source_name: String,
+        filter_name: String,
+        filter_kind: String,
+        filter_index: u32,
+        filter_settings: serde_json::Value,
+    },
+    SourceFilterRemoved {
+        source_name: String,
+        filter_name: String,
+    },
+    SceneCreated {
+        scene_name: String,
+    },
+    SceneRemoved {
+        scene_name: String,
+    },
+    InputVolumeChanged {
+        input_name: String,
+        volume_db: f64,
+        volume_mul: f64,
+    },
+    InputMuteStateChanged {
+        input_name: String,
+        muted: bool,
+    },
+    RecordStateChanged {
+        active: bool,
+    },
+    StreamStateChanged {
+        active: bool,
+    },
+    /// Serialized obws event forwarded as-is for custom slaves
+    RawEvent {
+        event_type: String,
+        event_data: serde_json::Value,
+    },
+}
+
+/// Opt-in forwarding of whitelisted raw OBS events
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RawEventPassthrough {
+    pub enabled: bool,
+    /// obws event type names, e.g. "InputMuteStateChanged"
+    pub event_types: Vec<String>,
+}
+
+impl RawEventPassthrough {
+    /// Wrap the event as a `RawEvent` if passthrough is enabled and its type is whitelisted
+    pub fn capture(&self, event: &Event) -> Option<OBSEvent> {
+        if !self.enabled || self.event_types.is_empty() {
+            return None;
+        }
+        let value = serde_json::to_value(event).ok()?;
+        let event_type = value.get("eventType")?.as_str()?.to_string();
+        if !self.event_types.contains(&event_type) {
+            return None;
+        }
+        Some(OBSEvent::RawEvent {
+            event_type,
+            event_data: value.get("eventData").cloned().unwrap_or_default(),
+        })
+    }
+}
+
+impl OBSEvent {
+    /// Scene the event refers to, if any
+    pub fn scene_name(&self) -> Option<&str> {
+        let name = match self {
+            OBSEvent::SceneChanged { scene_name }
+            | OBSEvent::CurrentPreviewSceneChanged { scene_name }
+            | OBSEvent::SceneItemTransformChanged { scene_name, .. }
+            | OBSEvent::SceneItemFilterChanged { scene_name, .. }
+            | OBSEvent::SceneItemCreated { scene_name, .. }
+            | OBSEvent::SceneItemRemoved { scene_name, .. }
+            | OBSEvent::SceneItemEnableStateChanged { scene_name, .. }
+            | OBSEvent::SceneItemLockStateChanged { scene_name, .. }
+            | OBSEvent::SceneItemBlendModeChanged { scene_name, .. }
+            | OBSEvent::SceneItemsReordered { scene_name, .. }
+            | OBSEvent::SceneCreated { scene_name }
+            | OBSEvent::SceneRemoved { scene_name } => scene_name,
+            OBSEvent::InputSettingsChanged { .. }
+            | OBSEvent::SourceFilterCreated { .. }
+            | OBSEvent::SourceFilterRemoved { .. }
+            | OBSEvent::InputVolumeChanged { .. }
+            | OBSEvent::InputMuteStateChanged { .. }
+            | OBSEvent::RecordStateChanged { .. }
+            | OBSEvent::StreamStateChanged { .. }
+            | OBSEvent::RawEvent { .. } => return None,
+        };
+        Some(name.as_str()).filter(|n| !n.is_empty())
+    }
 }
 
 pub struct OBSEventHandler {
     event_tx: mpsc::UnboundedSender<OBSEvent>,
+    raw_passthrough: Arc<RwLock<RawEventPassthrough>>,
 }
 
 impl OBSEventHandler {
     pub fn new() -> (Self, mpsc::UnboundedReceiver<OBSEvent>) {
         let (tx, rx) = mpsc::unbounded_channel();
-        (Self { event_tx: tx }, rx)
+        (
+            Self {
+                event_tx: tx,
+                raw_passthrough: Arc::new(RwLock::new(RawEventPassthrough::default())),
+            },
+            rx,
+        )
+    }
+
+    pub async fn set_raw_passthrough(&self, config: RawEventPassthrough) {
+        *self.raw_passthrough.write().await = config;
     }
 
     pub async fn start_listening(&self, client: &Client) -> anyhow::Result<()> {
         let tx = self.event_tx.clone();
+        let raw_passthrough = self.raw_passthrough.clone();
 
         // Get event stream from obws client
         let events = client
@@ -67,9 +189,16 @@ impl OBSEventHandler {
         tokio::spawn(async move {
             tokio::pin!(events);
             while let Some(event) = events.next().await {
+                if let Some(raw_event) = raw_passthrough.read().await.capture(&event) {
+                    if let Err(e) = tx.send(raw_event) {
+                        eprintln!("Failed to send RawEvent: {}", e);
+                        break;
+                    }
+                }
+
                 match event {
                     Event::CurrentProgramSceneChanged { id } => {
-                        let scene_name = format!("{:?}", id);
+                        let scene_name = id.name;
                         let obs_event = OBSEvent::SceneChanged { scene_name };
                         if let Err(e) = tx.send(obs_event) {
                             eprintln!("Failed to send SceneChanged event: {}", e);
@@ -77,7 +206,7 @@ impl OBSEventHandler {
                         }
                     }
                     Event::CurrentPreviewSceneChanged { id } => {
-                        let scene_name = format!("{:?}", id);
+                        let scene_name = id.name;
                         let obs_event = OBSEvent::CurrentPreviewSceneChanged { scene_name };
                         if let Err(e) = tx.send(obs_event) {
                             eprintln!("Failed to send CurrentPreviewSceneChanged event: {}", e);
@@ -86,7 +215,7 @@ impl OBSEventHandler {
                     }
                     Event::SceneItemTransformChanged { scene, item_id, .. } => {
                         let obs_event = OBSEvent::SceneItemTransformChanged {
-                            scene_name: format!("{:?}", scene),
+                            scene_name: scene.name,
                             scene_item_id: item_id as i64,
                         };
                         if let Err(e) = tx.send(obs_event) {
@@ -95,13 +224,78 @@ impl OBSEventHandler {
                         }
                     }
                     Event::InputSettingsChanged { id, .. } => {
-                        let input_name = format!("{:?}", id);
+                        let input_name = id.name;
                         let obs_event = OBSEvent::InputSettingsChanged { input_name };
                         if let Err(e) = tx.send(obs_event) {
                             eprintln!("Failed to send InputSettingsChanged event: {}", e);
                             break;
                         }
                     }
+                    // Groups are synced as scene items, not as scenes
+                    Event::SceneCreated { id, is_group } if !is_group => {
+                        let obs_event = OBSEvent::SceneCreated {
+                            scene_name: id.name,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SceneCreated event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::SceneRemoved { id, is_group } if !is_group => {
+                        let obs_event = OBSEvent::SceneRemoved {
+                            scene_name: id.name,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SceneRemoved event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::InputVolumeChanged { id, mul, db } => {
+                        let obs_event = OBSEvent::InputVolumeChanged {
+                            input_name: id.name,
+                            volume_db: db,
+                            volume_mul: mul,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send InputVolumeChanged event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::InputMuteStateChanged { id, muted } => {
+                        let obs_event = OBSEvent::InputMuteStateChanged {
+                            input_name: id.name,
+                            muted,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send InputMuteStateChanged event: {}", e);
+                            break;
+                        }
+                    }
+                    // Only settled states are synced, so each start/stop is sent once
+                    Event::RecordStateChanged {
+                        state: state @ (OutputState::Started | OutputState::Stopped),
+                        ..
+                    } => {
+                        let obs_event = OBSEvent::RecordStateChanged {
+                            active: state == OutputState::Started,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send RecordStateChanged event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::StreamStateChanged {
+                        state: state @ (OutputState::Started | OutputState::Stopped),
+                        ..
+                    } => {
+                        let obs_event = OBSEvent::StreamStateChanged {
+                            active: state == OutputState::Started,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send StreamStateChanged event: {}", e);
+                            break;
+                        }
+                    }
                     // Note: SourceFilterSettingsChanged is not available in obws 0.11
                     // Filter changes will need to be detected through polling or manual triggers
                     // For now, we skip filter change events as they're not properly supported in this obws version
@@ -110,6 +304,36 @@ impl OBSEventHandler {
                         // are not directly available as events in obws 0.11
                         // TODO: Implement filter change detection via polling or upgrade obws version
                     }
+                    Event::SourceFilterCreated {
+                        source,
+                        filter,
+                        kind,
+                        index,
+                        settings,
+                        ..
+                    } => {
+                        let obs_event = OBSEvent::SourceFilterCreated {
+                            source_name: source,
+                            filter_name: filter,
+                            filter_kind: kind,
+                            filter_index: index,
+                            filter_settings: settings,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SourceFilterCreated event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::SourceFilterRemoved { source, filter } => {
+                        let obs_event = OBSEvent::SourceFilterRemoved {
+                            source_name: source,
+                            filter_name: filter,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SourceFilterRemoved event: {}", e);
+                            break;
+                        }
+                    }
                     Event::SceneItemCreated {
                         scene,
                         item_id,
@@ -117,9 +341,9 @@ impl OBSEventHandler {
                         ..
                     } => {
                         let obs_event = OBSEvent::SceneItemCreated {
-                            scene_name: format!("{:?}", scene),
+                            scene_name: scene.name,
                             scene_item_id: item_id as i64,
-                            source_name: format!("{:?}", source),
+                            source_name: source.name,
                         };
                         if let Err(e) = tx.send(obs_event) {
                             eprintln!("Failed to send SceneItemCreated event: {}", e);
@@ -133,9 +357,9 @@ impl OBSEventHandler {
                         ..
                     } => {
                         let obs_event = OBSEvent::SceneItemRemoved {
-                            scene_name: format!("{:?}", scene),
+                            scene_name: scene.name,
                             scene_item_id: item_id as i64,
-                            source_name: format!("{:?}", source),
+                            source_name: source.name,
                         };
                         if let Err(e) = tx.send(obs_event) {
                             eprintln!("Failed to send SceneItemRemoved event: {}", e);
@@ -149,7 +373,7 @@ impl OBSEventHandler {
                         ..
                     } => {
                         let obs_event = OBSEvent::SceneItemEnableStateChanged {
-                            scene_name: format!("{:?}", scene),
+                            scene_name: scene.name,
                             scene_item_id: item_id as i64,
                             enabled,
                         };
@@ -158,6 +382,32 @@ impl OBSEventHandler {
                             break;
                         }
                     }
+                    Event::SceneItemLockStateChanged {
+                        scene,
+                        item_id,
+                        locked,
+                    } => {
+                        let obs_event = OBSEvent::SceneItemLockStateChanged {
+                            scene_name: scene.name,
+                            scene_item_id: item_id as i64,
+                            locked,
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SceneItemLockStateChanged event: {}", e);
+                            break;
+                        }
+                    }
+                    Event::SceneItemListReindexed { scene, mut items } => {
+                        items.sort_by_key(|item| item.index);
+                        let obs_event = OBSEvent::SceneItemsReordered {
+                            scene_name: scene.name,
+                            item_ids: items.into_iter().map(|item| item.id as i64).collect(),
+                        };
+                        if let Err(e) = tx.send(obs_event) {
+                            eprintln!("Failed to send SceneItemsReordered event: {}", e);
+                            break;
+                        }
+                    }
                     _ => {
                         // Ignore other events
                     }
@@ -175,3 +425,32 @@ impl Default for OBSEventHandler {
         Self::new().0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_whitelisted_raw_events_are_forwarded() {
+        let mut passthrough = RawEventPassthrough {
+            enabled: true,
+            event_types: vec!["StudioModeStateChanged".to_string()],
+        };
+        let studio_mode = Event::StudioModeStateChanged { enabled: true };
+
+        let Some(OBSEvent::RawEvent {
+            event_type,
+            event_data,
+        }) = passthrough.capture(&studio_mode)
+        else {
+            panic!("whitelisted event was not forwarded");
+        };
+        assert_eq!(event_type, "StudioModeStateChanged");
+        assert_eq!(event_data["studioModeEnabled"], true);
+        assert!(passthrough.capture(&Event::ExitStarted).is_none());
+
+        // The whitelist alone forwards nothing while passthrough is off
+        passthrough.enabled = false;
+        assert!(passthrough.capture(&studio_mode).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+//! obs-websocket stand-in for tests that need a connected obws client
+
+use super::client::{OBSClient, OBSConnectionConfig};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Answer to one request: its response data, or `Err(comment)` for a failed request
+pub type MockResponse = Result<Value, String>;
+
+pub struct MockObs {
+    pub port: u16,
+    /// Type and data of every request received after the handshake, in order
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl MockObs {
+    /// Serve obs-websocket on a free port, answering requests with `respond`.
+    /// GetVersion is answered here, so obws accepts the connection.
+    pub async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&str, &Value) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond = Arc::new(respond);
+
+        let requests_for_server = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                tokio::spawn(Self::serve(
+                    socket,
+                    respond.clone(),
+                    requests_for_server.clone(),
+                ));
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    async fn serve<F>(
+        socket: tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+        respond: Arc<F>,
+        requests: Arc<Mutex<Vec<(String, Value)>>>,
+    ) where
+        F: Fn(&str, &Value) -> MockResponse + Send + Sync + 'static,
+    {
+        let (mut sink, mut stream) = socket.split();
+        let hello = json!({ "op": 0, "d": { "obsWebSocketVersion": "5.5.0", "rpcVersion": 1 } });
+        if sink.send(Message::Text(hello.to_string())).await.is_err() {
+            return;
+        }
+
+        while let Some(Ok(Message::Text(text))) = stream.next().await {
+            let Ok(message) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            let reply = match message["op"].as_u64() {
+                // Identify
+                Some(1) => json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } }),
+                // Request
+                Some(6) => {
+                    let data = &message["d"];
+                    let request_type = data["requestType"].as_str().unwrap_or("").to_string();
+                    let request_data = data["requestData"].clone();
+                    let response = if request_type == "GetVersion" {
+                        Ok(json!({
+                            "obsVersion": "30.2.0",
+                            "obsWebSocketVersion": "5.5.0",
+                            "rpcVersion": 1,
+                            "availableRequests": [],
+                            "supportedImageFormats": [],
+                            "platform": "test",
+                            "platformDescription": "test",
+                        }))
+                    } else {
+                        requests
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .push((request_type.clone(), request_data.clone()));
+                        respond(&request_type, &request_data)
+                    };
+                    let (status, response_data) = match response {
+                        Ok(response_data) => {
+                            (json!({ "result": true, "code": 100 }), response_data)
+                        }
+                        Err(comment) => (
+                            json!({ "result": false, "code": 600, "comment": comment }),
+                            Value::Null,
+                        ),
+                    };
+                    json!({
+                        "op": 7,
+                        "d": {
+                            "requestType": request_type,
+                            "requestId": data["requestId"],
+                            "requestStatus": status,
+                            "responseData": response_data,
+                        },
+                    })
+                }
+                _ => continue,
+            };
+            if sink.send(Message::Text(reply.to_string())).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// obws client connected to this mock
+    pub async fn client(&self) -> obws::Client {
+        obws::Client::connect("127.0.0.1", self.port, None::<String>)
+            .await
+            .unwrap()
+    }
+
+    /// `OBSClient` connected to this mock
+    pub async fn obs_client(&self) -> Arc<OBSClient> {
+        let obs_client = Arc::new(OBSClient::new());
+        obs_client
+            .connect(OBSConnectionConfig {
+                host: "127.0.0.1".to_string(),
+                port: self.port,
+                password: None,
+            })
+            .await
+            .unwrap();
+        obs_client
+    }
+
+    /// Types of the requests received so far, in order
+    pub fn request_types(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(request_type, _)| request_type.clone())
+            .collect()
+    }
+
+    /// Data of every received request of `request_type`, in order
+    pub fn requests_of(&self, request_type: &str) -> Vec<Value> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(received, _)| received == request_type)
+            .map(|(_, data)| data.clone())
+            .collect()
+    }
+}
@@ -1,5 +1,33 @@
 pub mod client;
 pub mod commands;
 pub mod events;
+#[cfg(test)]
+pub mod mock;
 
 pub use client::OBSClient;
+
+use obws::responses::scenes::{CurrentPreviewScene, CurrentProgramScene, SceneId};
+
+/// Plain name of an obws scene reference. Debug-formatting these gives
+/// `SceneId { name: "Camera 1", uuid: .. }`, which matches no scene in OBS.
+pub trait SceneName {
+    fn scene_name(&self) -> &str;
+}
+
+impl SceneName for SceneId {
+    fn scene_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl SceneName for CurrentProgramScene {
+    fn scene_name(&self) -> &str {
+        &self.id.name
+    }
+}
+
+impl SceneName for CurrentPreviewScene {
+    fn scene_name(&self) -> &str {
+        &self.id.name
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Canvas point a source stays pinned to on slaves with a different canvas size.
+/// Edge anchors keep the master's pixel distance to that edge; `Proportional`
+/// keeps the position as a fraction of the canvas.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TransformAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    Proportional,
+}
+
+/// Anchor per source name (master side)
+pub type TransformAnchors = HashMap<String, TransformAnchor>;
+
+#[derive(Debug, Clone, Copy)]
+enum AxisAnchor {
+    Start,
+    Middle,
+    End,
+    Proportional,
+}
+
+impl TransformAnchor {
+    /// Horizontal and vertical anchoring
+    fn axes(&self) -> (AxisAnchor, AxisAnchor) {
+        use AxisAnchor::*;
+        match self {
+            TransformAnchor::TopLeft => (Start, Start),
+            TransformAnchor::Top => (Middle, Start),
+            TransformAnchor::TopRight => (End, Start),
+            TransformAnchor::Left => (Start, Middle),
+            TransformAnchor::Center => (Middle, Middle),
+            TransformAnchor::Right => (End, Middle),
+            TransformAnchor::BottomLeft => (Start, End),
+            TransformAnchor::Bottom => (Middle, End),
+            TransformAnchor::BottomRight => (End, End),
+            TransformAnchor::Proportional => (Proportional, Proportional),
+        }
+    }
+}
+
+/// The master's position as a fraction of its canvas, sent alongside the absolute position
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct AnchoredPosition {
+    pub anchor: TransformAnchor,
+    pub normalized_x: f64,
+    pub normalized_y: f64,
+    pub canvas_width: f64,
+    pub canvas_height: f64,
+}
+
+impl AnchoredPosition {
+    /// `None` if the master canvas size is unusable
+    pub fn new(
+        anchor: TransformAnchor,
+        position_x: f64,
+        position_y: f64,
+        canvas: (f64, f64),
+    ) -> Option<Self> {
+        let (canvas_width, canvas_height) = canvas;
+        if canvas_width <= 0.0 || canvas_height <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            anchor,
+            normalized_x: position_x / canvas_width,
+            normalized_y: position_y / canvas_height,
+            canvas_width,
+            canvas_height,
+        })
+    }
+
+    /// Absolute position on a canvas of `canvas` size
+    pub fn resolve(&self, canvas: (f64, f64)) -> (f64, f64) {
+        let (horizontal, vertical) = self.anchor.axes();
+        (
+            Self::resolve_axis(horizontal, self.normalized_x, self.canvas_width, canvas.0),
+            Self::resolve_axis(vertical, self.normalized_y, self.canvas_height, canvas.1),
+        )
+    }
+
+    fn resolve_axis(anchor: AxisAnchor, normalized: f64, master_size: f64, size: f64) -> f64 {
+        let master_position = normalized * master_size;
+        match anchor {
+            AxisAnchor::Start => master_position,
+            AxisAnchor::Middle => size / 2.0 + (master_position - master_size / 2.0),
+            AxisAnchor::End => size - (master_size - master_position),
+            AxisAnchor::Proportional => normalized * size,
+        }
+    }
+}
+
+/// Base (canvas) resolution of the connected OBS instance
+pub async fn canvas_size(client: &obws::Client) -> Result<(f64, f64)> {
+    let video = client
+        .config()
+        .video_settings()
+        .await
+        .context("Failed to get video settings")?;
+    Ok((video.base_width as f64, video.base_height as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchored_positions_follow_the_slave_canvas() {
+        let master = (1920.0, 1080.0);
+        // A logo 100px in from the right and bottom edges, and a centered title
+        let logo =
+            AnchoredPosition::new(TransformAnchor::BottomRight, 1820.0, 980.0, master).unwrap();
+        let title = AnchoredPosition::new(TransformAnchor::Center, 960.0, 540.0, master).unwrap();
+        let ticker =
+            AnchoredPosition::new(TransformAnchor::Proportional, 480.0, 270.0, master).unwrap();
+
+        for (canvas, logo_at, title_at, ticker_at) in [
+            (
+                (1280.0, 720.0),
+                (1180.0, 620.0),
+                (640.0, 360.0),
+                (320.0, 180.0),
+            ),
+            (
+                (3840.0, 2160.0),
+                (3740.0, 2060.0),
+                (1920.0, 1080.0),
+                (960.0, 540.0),
+            ),
+        ] {
+            assert_eq!(logo.resolve(canvas), logo_at);
+            assert_eq!(title.resolve(canvas), title_at);
+            assert_eq!(ticker.resolve(canvas), ticker_at);
+        }
+        // The master's own canvas gets its positions back unchanged
+        assert_eq!(logo.resolve(master), (1820.0, 980.0));
+        assert!(AnchoredPosition::new(TransformAnchor::TopLeft, 0.0, 0.0, (0.0, 1080.0)).is_none());
+    }
+}
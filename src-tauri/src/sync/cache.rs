@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Hashes of the items a slave last applied, persisted so a resync after a
+/// transient drop can skip items the master reports as unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppliedStateCache {
+    /// Scene collection the cached items belong to
+    pub scene_collection: Option<String>,
+    /// scene name -> source name -> item hash
+    pub items: HashMap<String, HashMap<String, String>>,
+}
+
+impl AppliedStateCache {
+    /// Load the cache from disk, falling back to an empty cache
+    pub async fn load(path: &Path) -> Self {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Ignoring unreadable slave cache {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize slave cache")?;
+        tokio::fs::write(path, json)
+            .await
+            .context("Failed to write slave cache")?;
+        Ok(())
+    }
+
+    /// Drop all entries if they were recorded for a different scene collection
+    pub fn ensure_collection(&mut self, collection: &str) {
+        if self.scene_collection.as_deref() != Some(collection) {
+            if self.scene_collection.is_some() {
+                println!("Scene collection changed, invalidating slave cache");
+            }
+            self.items.clear();
+            self.scene_collection = Some(collection.to_string());
+        }
+    }
+
+    pub fn is_unchanged(&self, scene_name: &str, source_name: &str, hash: &str) -> bool {
+        self.items
+            .get(scene_name)
+            .and_then(|sources| sources.get(source_name))
+            .map(|cached| cached == hash)
+            .unwrap_or(false)
+    }
+
+    pub fn record(&mut self, scene_name: &str, source_name: &str, hash: &str) {
+        self.items
+            .entry(scene_name.to_string())
+            .or_default()
+            .insert(source_name.to_string(), hash.to_string());
+    }
+
+    /// Drop one item's entry so the next StateSync applies it again
+    pub fn forget(&mut self, scene_name: &str, source_name: &str) -> bool {
+        self.items
+            .get_mut(scene_name)
+            .map(|sources| sources.remove(source_name).is_some())
+            .unwrap_or(false)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.scene_collection = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_items_are_skipped_and_changed_ones_applied() {
+        let mut cache = AppliedStateCache::default();
+        cache.ensure_collection("Main");
+        cache.record("Scene", "Camera", "hash-a");
+
+        assert!(cache.is_unchanged("Scene", "Camera", "hash-a"));
+        assert!(!cache.is_unchanged("Scene", "Camera", "hash-b"));
+        assert!(!cache.is_unchanged("Scene", "Overlay", "hash-a"));
+        assert!(!cache.is_unchanged("Other", "Camera", "hash-a"));
+    }
+
+    #[test]
+    fn forgotten_items_are_applied_again() {
+        let mut cache = AppliedStateCache::default();
+        cache.record("Scene", "Camera", "hash-a");
+        cache.record("Scene", "Overlay", "hash-b");
+
+        assert!(cache.forget("Scene", "Camera"));
+        assert!(!cache.forget("Scene", "Camera"));
+        assert!(!cache.is_unchanged("Scene", "Camera", "hash-a"));
+        assert!(cache.is_unchanged("Scene", "Overlay", "hash-b"));
+    }
+
+    #[test]
+    fn switching_scene_collection_invalidates_entries() {
+        let mut cache = AppliedStateCache::default();
+        cache.ensure_collection("Main");
+        cache.record("Scene", "Camera", "hash-a");
+
+        cache.ensure_collection("Main");
+        assert!(cache.is_unchanged("Scene", "Camera", "hash-a"));
+
+        cache.ensure_collection("Backup");
+        assert!(!cache.is_unchanged("Scene", "Camera", "hash-a"));
+        assert_eq!(cache.scene_collection.as_deref(), Some("Backup"));
+    }
+
+    #[tokio::test]
+    async fn cache_survives_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "obs-sync-applied-cache-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        let mut cache = AppliedStateCache::default();
+        cache.ensure_collection("Main");
+        cache.record("Scene", "Camera", "hash-a");
+        cache.save(&path).await.unwrap();
+
+        let loaded = AppliedStateCache::load(&path).await;
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded.scene_collection.as_deref(), Some("Main"));
+        assert!(loaded.is_unchanged("Scene", "Camera", "hash-a"));
+    }
+}
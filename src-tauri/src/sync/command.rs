@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use obws::requests::inputs::InputId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// High-level action a master asks slaves to perform, independent of state sync.
+/// New actions are added as variants together with their arm in `execute`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum SemanticCommand {
+    /// Take preview to program with the current transition (studio mode only)
+    StudioTransition,
+    SetInputMute {
+        input_name: String,
+        muted: bool,
+    },
+    ToggleInputMute {
+        input_name: String,
+    },
+    SetProgramScene {
+        scene_name: String,
+    },
+    /// Fire an OBS hotkey by its internal name on the slave
+    TriggerHotkey {
+        hotkey_name: String,
+    },
+}
+
+/// Semantic command broadcast when a master hotkey (by OBS hotkey name) is triggered
+pub type HotkeyCommands = HashMap<String, SemanticCommand>;
+
+impl SemanticCommand {
+    /// Run the action against the local OBS instance
+    pub async fn execute(&self, client: &obws::Client) -> Result<()> {
+        match self {
+            SemanticCommand::StudioTransition => {
+                crate::obs::commands::OBSCommands::trigger_studio_transition(client).await
+            }
+            SemanticCommand::SetInputMute { input_name, muted } => client
+                .inputs()
+                .set_muted(InputId::Name(input_name), *muted)
+                .await
+                .with_context(|| format!("Failed to set mute on {}", input_name)),
+            SemanticCommand::ToggleInputMute { input_name } => client
+                .inputs()
+                .toggle_mute(InputId::Name(input_name))
+                .await
+                .map(|_| ())
+                .with_context(|| format!("Failed to toggle mute on {}", input_name)),
+            SemanticCommand::SetProgramScene { scene_name } => {
+                crate::obs::commands::OBSCommands::set_current_program_scene(client, scene_name)
+                    .await
+            }
+            SemanticCommand::TriggerHotkey { hotkey_name } => client
+                .hotkeys()
+                .trigger_by_name(hotkey_name, None)
+                .await
+                .with_context(|| format!("Failed to trigger hotkey {}", hotkey_name)),
+        }
+    }
+}
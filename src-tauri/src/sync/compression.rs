@@ -0,0 +1,79 @@
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// Value of the payload `compression` field for gzip-compressed image data
+pub const GZIP: &str = "gzip";
+
+/// Payloads smaller than this are sent raw; the gzip overhead isn't worth it
+const MIN_COMPRESS_BYTES: usize = 16 * 1024;
+
+/// Gzip `data` for sending. Returns `None` when the data is small or doesn't
+/// shrink (already-compressed formats like JPEG often don't).
+pub fn compress(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < MIN_COMPRESS_BYTES {
+        return None;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    if let Err(e) = encoder.write_all(data) {
+        eprintln!("Failed to compress image data: {}", e);
+        return None;
+    }
+    match encoder.finish() {
+        Ok(compressed) => (compressed.len() < data.len()).then_some(compressed),
+        Err(e) => {
+            eprintln!("Failed to compress image data: {}", e);
+            None
+        }
+    }
+}
+
+/// Undo `compress` according to the payload's `compression` field.
+/// An absent field means the data was sent raw (older masters never compress).
+pub fn decompress(data: Vec<u8>, compression: Option<&str>) -> Result<Vec<u8>> {
+    match compression {
+        None => Ok(data),
+        Some(GZIP) => {
+            let mut decompressed = Vec::new();
+            GzDecoder::new(data.as_slice())
+                .read_to_end(&mut decompressed)
+                .context("Failed to decompress gzip image data")?;
+            Ok(decompressed)
+        }
+        Some(other) => bail!("Unsupported image compression: {}", other),
+    }
+}
+
+/// Replace gzip-compressed image data in an ImageUpdate or StateSync payload with the
+/// raw bytes, for slaves that don't support compression
+pub fn decompress_payload_images(payload: &mut serde_json::Value) -> Result<()> {
+    fn raw(image: &mut serde_json::Value, data_key: &str) -> Result<()> {
+        let Some(compression) = image["compression"].as_str().map(String::from) else {
+            return Ok(());
+        };
+        let engine = &base64::engine::general_purpose::STANDARD;
+        let data = image[data_key].as_str().unwrap_or_default();
+        let data = base64::Engine::decode(engine, data).context("Invalid image data")?;
+        let data = decompress(data, Some(&compression))?;
+        image[data_key] = base64::Engine::encode(engine, data).into();
+        image["compression"] = serde_json::Value::Null;
+        Ok(())
+    }
+
+    // ImageUpdate carries one image at the top level, StateSync one per item
+    raw(payload, "image_data")?;
+    if let Some(scenes) = payload["scenes"].as_array_mut() {
+        for scene in scenes {
+            if let Some(items) = scene["items"].as_array_mut() {
+                for item in items {
+                    if item["image_data"].is_object() {
+                        raw(&mut item["image_data"], "data")?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
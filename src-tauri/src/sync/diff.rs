@@ -1,4 +1,7 @@
+use super::protocol::TransformFieldMask;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
 pub struct StateDifference {
@@ -9,11 +12,15 @@ pub struct StateDifference {
     pub severity: DiffSeverity,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DiffCategory {
     SceneMismatch,
     SourceMissing,
+    /// Source exists but is shown/hidden differently than on the master
+    EnabledStateMismatch,
     TransformMismatch,
+    /// Source is cropped differently than on the master
+    CropMismatch,
 }
 
 #[derive(Debug, Clone)]
@@ -22,12 +29,158 @@ pub enum DiffSeverity {
     Warning,  // Transform or settings differ
 }
 
+/// Allowed drift before a transform counts as mismatched
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffTolerance {
+    pub position: f64,
+    pub scale: f64,
+    /// In degrees
+    pub rotation: f64,
+    /// In whole pixels, per edge
+    #[serde(default)]
+    pub crop: u32,
+}
+
+impl Default for DiffTolerance {
+    fn default() -> Self {
+        Self {
+            position: 0.5,
+            scale: 0.01,
+            rotation: 0.5,
+            crop: 0,
+        }
+    }
+}
+
+/// Overall outcome of a desync report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DesyncVerdict {
+    InSync,
+    MinorDrift,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesyncIssue {
+    pub category: String,
+    pub description: String,
+    pub severity: String,
+    pub suggested_action: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceDesyncReport {
+    /// Empty for scene-level issues
+    pub source_name: String,
+    pub issues: Vec<DesyncIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneDesyncReport {
+    pub scene_name: String,
+    pub sources: Vec<SourceDesyncReport>,
+}
+
+/// UI-ready projection of a set of differences, grouped by scene and then source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesyncReport {
+    pub verdict: DesyncVerdict,
+    pub summary: String,
+    pub total_issues: usize,
+    pub scenes: Vec<SceneDesyncReport>,
+}
+
+impl DesyncReport {
+    pub fn from_differences(diffs: &[StateDifference]) -> Self {
+        let critical = diffs
+            .iter()
+            .filter(|d| matches!(d.severity, DiffSeverity::Critical))
+            .count();
+        let verdict = if diffs.is_empty() {
+            DesyncVerdict::InSync
+        } else if critical > 0 {
+            DesyncVerdict::Critical
+        } else {
+            DesyncVerdict::MinorDrift
+        };
+
+        let mut grouped: BTreeMap<&str, BTreeMap<&str, Vec<DesyncIssue>>> = BTreeMap::new();
+        for diff in diffs {
+            grouped
+                .entry(diff.scene_name.as_str())
+                .or_default()
+                .entry(diff.source_name.as_str())
+                .or_default()
+                .push(DesyncIssue {
+                    category: format!("{:?}", diff.category),
+                    description: diff.description.clone(),
+                    severity: format!("{:?}", diff.severity),
+                    suggested_action: Self::suggested_action(&diff.category).to_string(),
+                });
+        }
+        let scenes: Vec<SceneDesyncReport> = grouped
+            .into_iter()
+            .map(|(scene_name, sources)| SceneDesyncReport {
+                scene_name: scene_name.to_string(),
+                sources: sources
+                    .into_iter()
+                    .map(|(source_name, issues)| SourceDesyncReport {
+                        source_name: source_name.to_string(),
+                        issues,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let summary = match verdict {
+            DesyncVerdict::InSync => "In sync with master".to_string(),
+            DesyncVerdict::MinorDrift => format!(
+                "Minor drift: {} difference(s) in {} scene(s)",
+                diffs.len(),
+                scenes.len()
+            ),
+            DesyncVerdict::Critical => format!(
+                "Critical: {} difference(s) in {} scene(s), {} critical",
+                diffs.len(),
+                scenes.len(),
+                critical
+            ),
+        };
+
+        Self {
+            verdict,
+            summary,
+            total_issues: diffs.len(),
+            scenes,
+        }
+    }
+
+    fn suggested_action(category: &DiffCategory) -> &'static str {
+        match category {
+            DiffCategory::SceneMismatch => "Switch to the expected scene or run resync",
+            DiffCategory::SourceMissing => "Source missing — create it or run resync",
+            DiffCategory::EnabledStateMismatch => "Toggle the source's visibility or run resync",
+            DiffCategory::TransformMismatch => "Run resync to restore the transform",
+            DiffCategory::CropMismatch => "Run resync to restore the crop",
+        }
+    }
+}
+
 pub struct DiffDetector;
 
 impl DiffDetector {
-    const TRANSFORM_TOLERANCE: f64 = 0.5; // Tolerance for position/scale differences
-
-    pub fn detect_differences(local_state: &Value, expected_state: &Value) -> Vec<StateDifference> {
+    pub fn detect_differences(
+        local_state: &Value,
+        expected_state: &Value,
+        tolerance: &DiffTolerance,
+        mask: &TransformFieldMask,
+    ) -> Vec<StateDifference> {
         let mut diffs = Vec::new();
 
         // Compare current scene
@@ -81,11 +234,36 @@ impl DiffDetector {
                     if let Some(local_source) = local_sources.iter().find(|s| {
                         s.get("name").and_then(|v| v.as_str()).unwrap_or("") == expected_name
                     }) {
+                        let local_enabled = local_source.get("enabled").and_then(|v| v.as_bool());
+                        let expected_enabled =
+                            expected_source.get("enabled").and_then(|v| v.as_bool());
+                        if let (Some(local_enabled), Some(expected_enabled)) =
+                            (local_enabled, expected_enabled)
+                        {
+                            if local_enabled != expected_enabled {
+                                let state =
+                                    |enabled: bool| if enabled { "enabled" } else { "disabled" };
+                                diffs.push(StateDifference {
+                                    category: DiffCategory::EnabledStateMismatch,
+                                    scene_name: local_scene.to_string(),
+                                    source_name: expected_name.to_string(),
+                                    description: format!(
+                                        "Source '{}' is {} but expected {}",
+                                        expected_name,
+                                        state(local_enabled),
+                                        state(expected_enabled)
+                                    ),
+                                    severity: DiffSeverity::Warning,
+                                });
+                            }
+                        }
                         if let Some(transform_diffs) = Self::compare_transforms(
                             local_source,
                             expected_source,
                             local_scene,
                             expected_name,
+                            tolerance,
+                            mask,
                         ) {
                             diffs.extend(transform_diffs);
                         }
@@ -102,6 +280,8 @@ impl DiffDetector {
         expected_source: &Value,
         scene_name: &str,
         source_name: &str,
+        tolerance: &DiffTolerance,
+        mask: &TransformFieldMask,
     ) -> Option<Vec<StateDifference>> {
         let local_transform = local_source.get("transform")?;
         let expected_transform = expected_source.get("transform")?;
@@ -126,8 +306,10 @@ impl DiffDetector {
             .and_then(|v| v.as_f64())
             .unwrap_or(0.0);
 
-        if (local_x - expected_x).abs() > Self::TRANSFORM_TOLERANCE
-            || (local_y - expected_y).abs() > Self::TRANSFORM_TOLERANCE
+        // Fields the slave doesn't take from the master are expected to differ
+        if mask.sync_position
+            && ((local_x - expected_x).abs() > tolerance.position
+                || (local_y - expected_y).abs() > tolerance.position)
         {
             diffs.push(StateDifference {
                 category: DiffCategory::TransformMismatch,
@@ -159,8 +341,9 @@ impl DiffDetector {
             .and_then(|v| v.as_f64())
             .unwrap_or(1.0);
 
-        if (local_scale_x - expected_scale_x).abs() > 0.01
-            || (local_scale_y - expected_scale_y).abs() > 0.01
+        if mask.sync_scale
+            && ((local_scale_x - expected_scale_x).abs() > tolerance.scale
+                || (local_scale_y - expected_scale_y).abs() > tolerance.scale)
         {
             diffs.push(StateDifference {
                 category: DiffCategory::TransformMismatch,
@@ -174,6 +357,64 @@ impl DiffDetector {
             });
         }
 
+        // Compare rotation; 359.9° and 0.1° are 0.2° apart
+        let local_rotation = local_transform
+            .get("rotation")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        let expected_rotation = expected_transform
+            .get("rotation")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        let rotation_delta = (local_rotation - expected_rotation).rem_euclid(360.0);
+        let rotation_delta = rotation_delta.min(360.0 - rotation_delta);
+
+        if mask.sync_rotation && rotation_delta > tolerance.rotation {
+            diffs.push(StateDifference {
+                category: DiffCategory::TransformMismatch,
+                scene_name: scene_name.to_string(),
+                source_name: source_name.to_string(),
+                description: format!(
+                    "Rotation mismatch: local={:.1}°, expected={:.1}°",
+                    local_rotation, expected_rotation
+                ),
+                severity: DiffSeverity::Warning,
+            });
+        }
+
+        // Compare crop (whole pixels; a tolerance of 0 requires an exact match)
+        let crop = |transform: &Value| -> [u64; 4] {
+            ["crop_left", "crop_top", "crop_right", "crop_bottom"]
+                .map(|field| transform.get(field).and_then(|v| v.as_u64()).unwrap_or(0))
+        };
+        let local_crop = crop(local_transform);
+        let expected_crop = crop(expected_transform);
+
+        if mask.sync_crop
+            && local_crop
+                .iter()
+                .zip(expected_crop)
+                .any(|(local, expected)| local.abs_diff(expected) > u64::from(tolerance.crop))
+        {
+            diffs.push(StateDifference {
+                category: DiffCategory::CropMismatch,
+                scene_name: scene_name.to_string(),
+                source_name: source_name.to_string(),
+                description: format!(
+                    "Crop mismatch (left, top, right, bottom): local=({}, {}, {}, {}), expected=({}, {}, {}, {})",
+                    local_crop[0],
+                    local_crop[1],
+                    local_crop[2],
+                    local_crop[3],
+                    expected_crop[0],
+                    expected_crop[1],
+                    expected_crop[2],
+                    expected_crop[3]
+                ),
+                severity: DiffSeverity::Warning,
+            });
+        }
+
         if diffs.is_empty() {
             None
         } else {
@@ -181,3 +422,167 @@ impl DiffDetector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(transform: Value) -> Value {
+        json!({
+            "current_scene": "Scene",
+            "sources": [{ "name": "Camera", "transform": transform }],
+        })
+    }
+
+    #[test]
+    fn masked_out_fields_are_not_reported() {
+        let local = state(json!({
+            "position_x": 0.0, "position_y": 0.0,
+            "scale_x": 1.0, "scale_y": 1.0,
+            "rotation": 0.0,
+            "crop_left": 0, "crop_top": 0, "crop_right": 0, "crop_bottom": 0,
+        }));
+        let expected = state(json!({
+            "position_x": 100.0, "position_y": 0.0,
+            "scale_x": 2.0, "scale_y": 1.0,
+            "rotation": 90.0,
+            "crop_left": 8, "crop_top": 0, "crop_right": 0, "crop_bottom": 0,
+        }));
+
+        for bits in 0..16u8 {
+            let mask = TransformFieldMask {
+                sync_position: bits & 1 != 0,
+                sync_scale: bits & 2 != 0,
+                sync_rotation: bits & 4 != 0,
+                sync_crop: bits & 8 != 0,
+            };
+            let diffs = DiffDetector::detect_differences(
+                &local,
+                &expected,
+                &DiffTolerance::default(),
+                &mask,
+            );
+            let reported = |prefix: &str| diffs.iter().any(|d| d.description.starts_with(prefix));
+
+            assert_eq!(reported("Position"), mask.sync_position, "{:?}", mask);
+            assert_eq!(reported("Scale"), mask.sync_scale, "{:?}", mask);
+            assert_eq!(reported("Rotation"), mask.sync_rotation, "{:?}", mask);
+            assert_eq!(reported("Crop"), mask.sync_crop, "{:?}", mask);
+        }
+    }
+
+    #[test]
+    fn desync_report_groups_by_scene_then_source() {
+        let diff = |category, scene: &str, source: &str, severity| StateDifference {
+            category,
+            scene_name: scene.to_string(),
+            source_name: source.to_string(),
+            description: format!("{} / {}", scene, source),
+            severity,
+        };
+        let diffs = vec![
+            diff(
+                DiffCategory::TransformMismatch,
+                "Main",
+                "Camera",
+                DiffSeverity::Warning,
+            ),
+            diff(
+                DiffCategory::SourceMissing,
+                "Break",
+                "Logo",
+                DiffSeverity::Warning,
+            ),
+            diff(
+                DiffCategory::CropMismatch,
+                "Main",
+                "Camera",
+                DiffSeverity::Warning,
+            ),
+            diff(
+                DiffCategory::EnabledStateMismatch,
+                "Main",
+                "Alert",
+                DiffSeverity::Warning,
+            ),
+        ];
+
+        let report = DesyncReport::from_differences(&diffs);
+
+        assert_eq!(report.verdict, DesyncVerdict::MinorDrift);
+        assert_eq!(report.total_issues, 4);
+        assert_eq!(report.summary, "Minor drift: 4 difference(s) in 2 scene(s)");
+        let scenes: Vec<&str> = report
+            .scenes
+            .iter()
+            .map(|s| s.scene_name.as_str())
+            .collect();
+        assert_eq!(scenes, vec!["Break", "Main"]);
+        let main = &report.scenes[1].sources;
+        let sources: Vec<&str> = main.iter().map(|s| s.source_name.as_str()).collect();
+        assert_eq!(sources, vec!["Alert", "Camera"]);
+        assert_eq!(main[1].issues.len(), 2);
+        assert_eq!(
+            report.scenes[0].sources[0].issues[0].suggested_action,
+            "Source missing — create it or run resync"
+        );
+
+        // One scene mismatch makes the whole report critical
+        let mut diffs = diffs;
+        diffs.push(diff(
+            DiffCategory::SceneMismatch,
+            "Main",
+            "",
+            DiffSeverity::Critical,
+        ));
+        let report = DesyncReport::from_differences(&diffs);
+        assert_eq!(report.verdict, DesyncVerdict::Critical);
+        assert!(report.summary.ends_with("1 critical"));
+        assert_eq!(
+            DesyncReport::from_differences(&[]).verdict,
+            DesyncVerdict::InSync
+        );
+    }
+
+    #[test]
+    fn disabled_sources_are_not_reported_as_missing() {
+        let scene = |sources: Value| json!({ "current_scene": "Scene", "sources": sources });
+        let expected = scene(json!([
+            { "name": "Camera", "enabled": true },
+            { "name": "Logo", "enabled": true },
+            { "name": "Alert", "enabled": false },
+            { "name": "Clock", "enabled": true },
+        ]));
+        // Camera matches, Logo is hidden, Alert is shown, Clock doesn't exist
+        let local = scene(json!([
+            { "name": "Camera", "enabled": true },
+            { "name": "Logo", "enabled": false },
+            { "name": "Alert", "enabled": true },
+        ]));
+
+        let diffs = DiffDetector::detect_differences(
+            &local,
+            &expected,
+            &DiffTolerance::default(),
+            &TransformFieldMask::default(),
+        );
+        let reported: Vec<(&str, &DiffCategory)> = diffs
+            .iter()
+            .map(|d| (d.source_name.as_str(), &d.category))
+            .collect();
+
+        assert_eq!(
+            reported,
+            vec![
+                ("Logo", &DiffCategory::EnabledStateMismatch),
+                ("Alert", &DiffCategory::EnabledStateMismatch),
+                ("Clock", &DiffCategory::SourceMissing),
+            ]
+        );
+        assert_eq!(
+            diffs[0].description,
+            "Source 'Logo' is disabled but expected enabled"
+        );
+    }
+}
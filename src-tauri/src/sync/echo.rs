@@ -0,0 +1,160 @@
+use super::protocol::{SyncMessage, SyncMessageType};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+
+/// How long after applying a remote change the local OBS events it triggers count as echoes
+const ECHO_WINDOW_MS: u64 = 1_000;
+
+/// Largest difference in a transform number that still counts as the applied value;
+/// OBS rounds what it is given
+const ECHO_TOLERANCE: f64 = 0.01;
+
+/// A remote change applied locally
+struct AppliedChange {
+    at: Instant,
+    origin: String,
+    /// What it set: the scene name or the transform
+    value: Value,
+}
+
+/// Changes recently applied on behalf of another peer, keyed by what they touched.
+/// In bidirectional sync the OBS events these applies trigger locally must not be
+/// mistaken for new local changes, or every change would bounce between peers.
+pub struct EchoGuard {
+    applied: HashMap<String, AppliedChange>,
+}
+
+impl EchoGuard {
+    pub fn new() -> Self {
+        Self {
+            applied: HashMap::new(),
+        }
+    }
+
+    /// What a message changes and the value it sets; only scene switches and transforms
+    /// are synced both ways. Transforms are matched by source name, since item ids differ
+    /// between peers; without one there is nothing to match.
+    fn key(message: &SyncMessage) -> Option<(String, Value)> {
+        match message.message_type {
+            SyncMessageType::SceneChange => Some((
+                format!("scene/{:?}", message.target_type),
+                message.payload["scene_name"].clone(),
+            )),
+            SyncMessageType::TransformUpdate => {
+                let scene_name = message.payload["scene_name"].as_str()?;
+                let source_name = message.payload["source_name"].as_str()?;
+                Some((
+                    format!("transform/{}/{}", scene_name, source_name),
+                    message.payload["transform"].clone(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `local` is what applying `applied` left in OBS. Anchored positions are
+    /// derived from each peer's own canvas, so they are left out of the comparison.
+    fn same_value(applied: &Value, local: &Value) -> bool {
+        let (Some(applied_fields), Some(local_fields)) = (applied.as_object(), local.as_object())
+        else {
+            return applied == local;
+        };
+        let anchored = !applied["anchor"].is_null();
+        applied_fields.iter().all(|(field, value)| {
+            if anchored && matches!(field.as_str(), "position_x" | "position_y" | "anchor") {
+                return true;
+            }
+            match (
+                value.as_f64(),
+                local_fields.get(field).and_then(Value::as_f64),
+            ) {
+                (Some(applied), Some(local)) => (applied - local).abs() <= ECHO_TOLERANCE,
+                _ => local_fields.get(field).unwrap_or(&Value::Null) == value,
+            }
+        })
+    }
+
+    /// Remember a remote message that is about to be applied locally
+    pub fn record(&mut self, message: &SyncMessage) {
+        if let Some((key, value)) = Self::key(message) {
+            self.applied.insert(
+                key,
+                AppliedChange {
+                    at: Instant::now(),
+                    origin: message.origin_id.clone(),
+                    value,
+                },
+            );
+        }
+    }
+
+    /// Origin of the remote change a locally produced message echoes, if any. A local
+    /// change to the same target with a different value is a new edit, not an echo.
+    /// A transform batch echoes a change if any of its updates does.
+    pub fn origin_of(&mut self, message: &SyncMessage) -> Option<String> {
+        if message.message_type == SyncMessageType::TransformBatch {
+            return message
+                .split_transform_batch()
+                .ok()?
+                .iter()
+                .find_map(|update| self.origin_of(update));
+        }
+        let window = Duration::from_millis(ECHO_WINDOW_MS);
+        self.applied
+            .retain(|_, change| change.at.elapsed() < window);
+        let (key, value) = Self::key(message)?;
+        self.applied
+            .get(&key)
+            .filter(|change| Self::same_value(&change.value, &value))
+            .map(|change| change.origin.clone())
+    }
+}
+
+impl Default for EchoGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::protocol::SyncTargetType;
+
+    fn moved(source_name: Option<&str>, x: f64, origin: &str) -> SyncMessage {
+        let mut message = SyncMessage::new(
+            SyncMessageType::TransformUpdate,
+            SyncTargetType::Source,
+            serde_json::json!({
+                "scene_name": "Main",
+                "scene_item_id": 3,
+                "source_name": source_name,
+                "transform": { "position_x": x, "position_y": 10.0, "bounds_type": null },
+            }),
+        );
+        message.origin_id = origin.to_string();
+        message
+    }
+
+    #[test]
+    fn only_the_applied_value_counts_as_an_echo() {
+        let mut guard = EchoGuard::new();
+        guard.record(&moved(Some("Cam"), 100.0, "slave-a"));
+
+        // OBS reports what was applied, give or take rounding
+        assert_eq!(
+            guard.origin_of(&moved(Some("Cam"), 100.004, "master")),
+            Some("slave-a".to_string())
+        );
+        // A genuine edit to the same source right after isn't attributed to the slave
+        assert_eq!(guard.origin_of(&moved(Some("Cam"), 240.0, "master")), None);
+    }
+
+    #[test]
+    fn transforms_without_a_source_name_are_not_guarded() {
+        let mut guard = EchoGuard::new();
+        guard.record(&moved(None, 100.0, "slave-a"));
+        assert_eq!(guard.origin_of(&moved(None, 100.0, "master")), None);
+    }
+}
@@ -0,0 +1,147 @@
+use super::protocol::SyncMessageType;
+use anyhow::Result;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+/// Filter kinds accepted by `SyncFilters::set`
+pub const FILTER_KINDS: [&str; 5] = [
+    "scenes",
+    "sources",
+    "includeSources",
+    "inputKinds",
+    "messageTypes",
+];
+
+/// Everything the master leaves out of sync, in one place
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFilters {
+    /// Scene names never sent to slaves
+    #[serde(default)]
+    pub scenes: Vec<String>,
+    /// Source names never sent to slaves; glob patterns (`*`, `?`, `[abc]`) match several
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// When set, only sources matching one of these names or patterns are sent to slaves;
+    /// `sources` still wins. Nested scenes are scene items too, so they need an entry
+    /// of their own.
+    #[serde(default)]
+    pub include_sources: Vec<String>,
+    /// OBS input kinds (e.g. "browser_source") never sent to slaves
+    #[serde(default)]
+    pub input_kinds: Vec<String>,
+    /// Live update message types the master doesn't broadcast
+    #[serde(default)]
+    pub message_types: Vec<SyncMessageType>,
+}
+
+/// Both source lists of `SyncFilters` at once, as set by `set_source_sync_rules`:
+/// `include` is `include_sources` and `exclude` is `sources`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceSyncRules {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Whether `source_name` is `entry` or matches it as a glob pattern. Names that aren't
+/// valid patterns, such as "Cam [", still match themselves.
+fn source_matches(entry: &str, source_name: &str) -> bool {
+    entry == source_name || Pattern::new(entry).is_ok_and(|glob| glob.matches(source_name))
+}
+
+impl SyncFilters {
+    /// Replace one filter list; `kind` must be one of `FILTER_KINDS`
+    pub fn set(&mut self, kind: &str, list: Vec<String>) -> Result<()> {
+        match kind {
+            "scenes" => self.scenes = list,
+            "sources" => self.sources = list,
+            "includeSources" => self.include_sources = list,
+            "inputKinds" => self.input_kinds = list,
+            "messageTypes" => {
+                self.message_types = list
+                    .into_iter()
+                    .map(|name| {
+                        serde_json::from_value(serde_json::Value::String(name.clone()))
+                            .map_err(|_| anyhow::anyhow!("Unknown message type: {}", name))
+                    })
+                    .collect::<Result<_>>()?;
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unknown filter kind '{}' (expected one of: {})",
+                    kind,
+                    FILTER_KINDS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_scene_excluded(&self, scene_name: &str) -> bool {
+        self.scenes.iter().any(|s| s == scene_name)
+    }
+
+    /// Replace both source lists
+    pub fn set_source_rules(&mut self, rules: SourceSyncRules) {
+        self.include_sources = rules.include;
+        self.sources = rules.exclude;
+    }
+
+    pub fn is_source_excluded(&self, source_name: &str) -> bool {
+        let matches = |entry: &String| source_matches(entry, source_name);
+        self.sources.iter().any(matches)
+            || (!self.include_sources.is_empty() && !self.include_sources.iter().any(matches))
+    }
+
+    /// Whether any filter applies per source (and needs a source name lookup)
+    pub fn filters_sources(&self) -> bool {
+        !self.sources.is_empty() || !self.include_sources.is_empty()
+    }
+
+    pub fn is_input_kind_excluded(&self, input_kind: &str) -> bool {
+        self.input_kinds.iter().any(|k| k == input_kind)
+    }
+
+    pub fn is_message_type_excluded(&self, message_type: &SyncMessageType) -> bool {
+        self.message_types.contains(message_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_rules_and_source_filter_are_one_list() {
+        let mut filters = SyncFilters::default();
+        filters
+            .set("sources", vec!["Private cam".to_string()])
+            .unwrap();
+        filters.set_source_rules(SourceSyncRules {
+            include: vec!["Stage *".to_string()],
+            exclude: vec!["Stage private*".to_string()],
+        });
+        // The rules replaced the plain exclude list rather than adding a second one
+        assert_eq!(filters.sources, vec!["Stage private*".to_string()]);
+        assert!(!filters.is_source_excluded("Stage cam"));
+        assert!(filters.is_source_excluded("Stage private cam"));
+        assert!(filters.is_source_excluded("Lobby cam"));
+
+        // Changing another filter leaves the source lists alone
+        filters
+            .set("scenes", vec!["Backstage".to_string()])
+            .unwrap();
+        assert_eq!(filters.include_sources, vec!["Stage *".to_string()]);
+    }
+
+    #[test]
+    fn names_that_are_not_valid_patterns_match_themselves() {
+        let mut filters = SyncFilters::default();
+        filters.set("sources", vec!["Cam [".to_string()]).unwrap();
+        assert!(filters.is_source_excluded("Cam ["));
+        assert!(!filters.is_source_excluded("Cam"));
+    }
+}
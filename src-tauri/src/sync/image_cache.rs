@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long and how much of the received image temp files a slave keeps around
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageCacheLimits {
+    /// Files unused for longer than this are deleted
+    pub max_age_secs: u64,
+    /// Least recently used files are deleted once all files together exceed this
+    pub max_bytes: u64,
+}
+
+impl Default for ImageCacheLimits {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 24 * 60 * 60,
+            max_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Name of the temp directory under the system temp dir that holds received images
+const IMAGE_DIR_NAME: &str = "obs-sync";
+
+/// Directory received images are written to
+pub fn image_dir() -> PathBuf {
+    std::env::temp_dir().join(IMAGE_DIR_NAME)
+}
+
+/// Hex SHA-256 of decoded image bytes; also the stem of the file holding them
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Content hash a cache file name encodes, if it is one of ours
+fn hash_of_file(path: &Path) -> Option<&str> {
+    let stem = path.file_stem()?.to_str()?;
+    (stem.len() == 64 && stem.bytes().all(|b| b.is_ascii_hexdigit())).then_some(stem)
+}
+
+struct CachedImage {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+/// Temp files written for received images, keyed by the hash of their decoded bytes,
+/// so identical images share one file however often or for whichever source they arrive.
+/// The master's hash of an image is remembered as an alias so a repeat can be
+/// recognised before decoding it.
+pub struct ImageFileCache {
+    limits: ImageCacheLimits,
+    total_bytes: u64,
+    entries: HashMap<String, CachedImage>,
+    /// Master-side image hash -> content hash
+    aliases: HashMap<String, String>,
+    /// Source name -> content hash of the image it currently shows; never evicted
+    in_use: HashMap<String, String>,
+}
+
+impl ImageFileCache {
+    pub fn new(limits: ImageCacheLimits) -> Self {
+        Self {
+            limits,
+            total_bytes: 0,
+            entries: HashMap::new(),
+            aliases: HashMap::new(),
+            in_use: HashMap::new(),
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: ImageCacheLimits) {
+        self.limits = limits;
+    }
+
+    /// Path of the file for a content hash or master hash, marking it as recently used
+    pub fn get(&mut self, hash: &str) -> Option<PathBuf> {
+        let key = self.aliases.get(hash).map(String::as_str).unwrap_or(hash);
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = SystemTime::now();
+        Some(entry.path.clone())
+    }
+
+    /// Remember that the master's `master_hash` decodes to `content_hash`
+    pub fn alias(&mut self, master_hash: &str, content_hash: &str) {
+        if master_hash != content_hash {
+            self.aliases
+                .insert(master_hash.to_string(), content_hash.to_string());
+        }
+    }
+
+    /// Record that `source_name` now shows the file at `path`, which keeps it from being evicted
+    pub fn mark_in_use(&mut self, source_name: &str, path: &Path) {
+        if let Some(hash) = hash_of_file(path) {
+            self.in_use
+                .insert(source_name.to_string(), hash.to_string());
+        }
+    }
+
+    /// Forget an entry whose file has gone missing
+    pub fn remove(&mut self, hash: &str) {
+        let key = self
+            .aliases
+            .get(hash)
+            .cloned()
+            .unwrap_or_else(|| hash.to_string());
+        if let Some(entry) = self.entries.remove(&key) {
+            self.total_bytes -= entry.size;
+        }
+        self.aliases.retain(|_, target| *target != key);
+    }
+
+    /// Record a file holding the image with `content_hash`. Returns the files evicted to
+    /// stay within the size cap; the caller deletes them. The new file and files shown by a
+    /// source are never evicted.
+    pub fn insert(&mut self, content_hash: String, path: PathBuf, size: u64) -> Vec<PathBuf> {
+        self.track(content_hash.clone(), path, size, SystemTime::now());
+        self.evict(Some(&content_hash), SystemTime::now())
+    }
+
+    /// Track a file found in the image directory, e.g. one left by an earlier run.
+    /// Returns false for files the cache can't account for: those not named by their
+    /// content hash, and duplicates of a tracked image under another extension.
+    pub fn adopt(&mut self, path: &Path, size: u64, modified: SystemTime) -> bool {
+        let Some(hash) = hash_of_file(path) else {
+            return false;
+        };
+        match self.entries.get(hash) {
+            Some(entry) => entry.path == path,
+            None => {
+                self.track(hash.to_string(), path.to_path_buf(), size, modified);
+                true
+            }
+        }
+    }
+
+    /// Master hashes of the images that still have a file
+    pub fn master_hashes(&self) -> Vec<String> {
+        self.aliases
+            .iter()
+            .filter(|(_, content_hash)| self.entries.contains_key(*content_hash))
+            .map(|(master_hash, _)| master_hash.clone())
+            .collect()
+    }
+
+    pub fn limits(&self) -> ImageCacheLimits {
+        self.limits
+    }
+
+    /// Drop files unused for longer than the age limit, then trim to the size cap.
+    /// Returns the files to delete.
+    pub fn sweep(&mut self) -> Vec<PathBuf> {
+        self.evict(None, SystemTime::now())
+    }
+
+    fn track(&mut self, hash: String, path: PathBuf, size: u64, last_used: SystemTime) {
+        if let Some(previous) = self.entries.insert(
+            hash,
+            CachedImage {
+                path,
+                size,
+                last_used,
+            },
+        ) {
+            self.total_bytes -= previous.size;
+        }
+        self.total_bytes += size;
+    }
+
+    fn is_evictable(&self, key: &str, keep: Option<&str>) -> bool {
+        Some(key) != keep && !self.in_use.values().any(|hash| hash == key)
+    }
+
+    fn evict(&mut self, keep: Option<&str>, now: SystemTime) -> Vec<PathBuf> {
+        let max_age = Duration::from_secs(self.limits.max_age_secs);
+        let mut stale: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(key, entry)| {
+                self.is_evictable(key, keep)
+                    && now
+                        .duration_since(entry.last_used)
+                        .is_ok_and(|age| age > max_age)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut remaining = self.total_bytes;
+        for key in &stale {
+            remaining -= self.entries[key].size;
+        }
+        let mut by_age: Vec<(&String, &CachedImage)> = self
+            .entries
+            .iter()
+            .filter(|(key, _)| self.is_evictable(key, keep) && !stale.contains(key))
+            .collect();
+        by_age.sort_by_key(|(_, entry)| entry.last_used);
+        for (key, entry) in by_age {
+            if remaining <= self.limits.max_bytes {
+                break;
+            }
+            remaining -= entry.size;
+            stale.push(key.clone());
+        }
+
+        let mut evicted = Vec::new();
+        for key in stale {
+            if let Some(entry) = self.entries.remove(&key) {
+                self.total_bytes -= entry.size;
+                evicted.push(entry.path);
+            }
+            self.aliases.retain(|_, target| *target != key);
+        }
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_images_go_first() {
+        let mut cache = ImageFileCache::new(ImageCacheLimits {
+            max_age_secs: 3600,
+            max_bytes: 250,
+        });
+        let hash = |n: u8| content_hash(&[n]);
+        let path = |n: u8| PathBuf::from(format!("{}.png", hash(n)));
+
+        assert!(cache.insert(hash(1), path(1), 100).is_empty());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.insert(hash(2), path(2), 100).is_empty());
+        std::thread::sleep(Duration::from_millis(5));
+        // Using the first image again makes the second the oldest
+        assert_eq!(cache.get(&hash(1)), Some(path(1)));
+
+        assert_eq!(cache.insert(hash(3), path(3), 100), vec![path(2)]);
+        assert!(cache.get(&hash(2)).is_none());
+        assert_eq!(cache.get(&hash(1)), Some(path(1)));
+    }
+}
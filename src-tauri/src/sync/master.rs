@@ -1,16 +1,289 @@
+use super::anchor::{canvas_size, AnchoredPosition, TransformAnchors};
+use super::command::SemanticCommand;
+use super::compression;
+use super::echo::EchoGuard;
+use super::filters::{SourceSyncRules, SyncFilters};
+use super::profile::SyncTuning;
 use super::protocol::{
-    SceneChangePayload, SourceUpdateAction, SourceUpdatePayload, SyncMessage, SyncMessageType,
-    SyncTargetType, TransformData, TransformUpdatePayload,
+    default_sync_targets, targets_for_scene, FilterLifecycleAction, FilterLifecyclePayload,
+    InputAudioData, MuteUpdatePayload, OutputControlPayload, OutputKind, ReorderItemsPayload,
+    ReorderedItem, SceneChangePayload, SceneItemSettings, SceneLifecycleAction,
+    SceneLifecyclePayload, SceneTargetOverrides, SceneTransition, SourceSettingsPayload,
+    SourceUpdateAction, SourceUpdatePayload, SyncMessage, SyncMessageType, SyncTargetType,
+    TransformData, TransformUpdatePayload, VolumeUpdatePayload, MACHINE_LOCAL_SETTINGS,
 };
-use crate::obs::{events::OBSEvent, OBSClient};
-use anyhow::Result;
+use super::reencode::ImageReencode;
+use crate::obs::{commands::OBSCommands, events::OBSEvent, OBSClient, SceneName};
+use anyhow::{Context, Result};
+use obws::common::Alignment;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+/// Error of a state collection stopped by `MasterSync::cancel_resync`
+#[derive(Debug)]
+pub struct ResyncCancelled;
+
+impl std::fmt::Display for ResyncCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "State sync was cancelled")
+    }
+}
+
+impl std::error::Error for ResyncCancelled {}
+
+/// How far a `send_initial_state` has got, reported as each scene and item is collected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncProgress {
+    /// Slave being caught up; None when the state goes to all slaves
+    pub target: Option<String>,
+    pub scene_name: String,
+    /// 1-based position of the scene among all scenes, filtered ones included
+    pub scene_index: usize,
+    pub scene_count: usize,
+    /// 1-based position of the item in its scene; 0 when the scene starts
+    pub item_index: usize,
+    pub item_count: usize,
+    /// Set on the last event of a run, whether it was sent, cancelled or failed.
+    /// That event carries no scene and zero counts.
+    pub done: bool,
+}
+
+type ResyncProgressCallback = Arc<dyn Fn(ResyncProgress) + Send + Sync>;
+
+/// One `send_initial_state`: its view of the cancel generation, cancelled once that
+/// moves on, and where its progress goes
+struct ResyncRun {
+    generation: Arc<AtomicU64>,
+    started: u64,
+    target: Option<String>,
+    progress: Option<ResyncProgressCallback>,
+}
+
+impl ResyncRun {
+    fn check(run: Option<&ResyncRun>) -> Result<()> {
+        match run {
+            Some(run) if run.generation.load(Ordering::SeqCst) != run.started => {
+                Err(ResyncCancelled.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn report(
+        run: Option<&ResyncRun>,
+        scene_name: &str,
+        (scene_index, scene_count): (usize, usize),
+        (item_index, item_count): (usize, usize),
+    ) {
+        if let Some(progress) = run.and_then(|run| run.progress.as_ref()) {
+            progress(ResyncProgress {
+                target: run.and_then(|run| run.target.clone()),
+                scene_name: scene_name.to_string(),
+                scene_index,
+                scene_count,
+                item_index,
+                item_count,
+                done: false,
+            });
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(progress) = &self.progress {
+            progress(ResyncProgress {
+                target: self.target.clone(),
+                scene_name: String::new(),
+                scene_index: 0,
+                scene_count: 0,
+                item_index: 0,
+                item_count: 0,
+                done: true,
+            });
+        }
+    }
+}
+
+/// Scene left out of a state collection because its items couldn't be read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedScene {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpedImage {
+    pub source_name: String,
+    pub file: String,
+    pub encoded_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDumpSummary {
+    pub generated_at: i64,
+    pub collection_ms: u64,
+    pub scene_count: usize,
+    pub item_count: usize,
+    pub images: Vec<DumpedImage>,
+    pub skipped_scenes: Vec<SkippedScene>,
+}
+
+/// Image file prepared for an ImageUpdate or StateSync payload
+struct EncodedImage {
+    /// Base64 of the (possibly compressed) file bytes
+    data: String,
+    /// File extension to use when the image was re-encoded
+    format: Option<&'static str>,
+    /// Payload `compression` value; `None` when the bytes are sent raw
+    compression: Option<&'static str>,
+}
+
+/// Lowest volume sent to slaves in dB; OBS reports silence as -inf
+const MIN_VOLUME_DB: f64 = -100.0;
+
+/// How often coalesced transform changes are sent to slaves. Dragging a source fires
+/// transform events far faster than this; only each item's latest transform is sent.
+const TRANSFORM_BATCH_INTERVAL_MS: u64 = 100;
+
+/// How often scene item blend modes are read to find changes
+const BLEND_MODE_POLL_MS: u64 = 2_000;
+
+/// Blend mode of every scene item, by (scene, item id)
+type BlendModes = HashMap<(String, i64), String>;
+
+/// Items whose blend mode differs between two polls. Items not in `previous` are new
+/// to the master, not changed, so they are left out.
+fn blend_mode_changes(previous: &BlendModes, current: &BlendModes) -> Vec<OBSEvent> {
+    current
+        .iter()
+        .filter(|(key, mode)| previous.get(*key).is_some_and(|old| old != *mode))
+        .map(
+            |((scene_name, scene_item_id), blend_mode)| OBSEvent::SceneItemBlendModeChanged {
+                scene_name: scene_name.clone(),
+                scene_item_id: *scene_item_id,
+                blend_mode: blend_mode.clone(),
+            },
+        )
+        .collect()
+}
+
+/// Source added to one scene more than once; name-based matching can't tell its items apart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmbiguousSource {
+    pub scene_name: String,
+    pub source_name: String,
+    pub scene_item_ids: Vec<i64>,
+}
+
+type AmbiguousSourcesCallback = Arc<dyn Fn(Vec<AmbiguousSource>) + Send + Sync>;
+
+/// Delivers a message to a single slave (by client id) instead of broadcasting it
+type ClientSender = Arc<
+    dyn Fn(
+            String,
+            SyncMessage,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// File layout written by `dump_state`: the summary first, then the exact StateSync message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDump {
+    pub summary: StateDumpSummary,
+    pub message: SyncMessage,
+}
+
+impl StateDump {
+    /// Summarize a collected StateSync payload and wrap it in the message that would be sent
+    fn new(
+        payload: serde_json::Value,
+        skipped_scenes: Vec<SkippedScene>,
+        collection_ms: u64,
+    ) -> Self {
+        let items: Vec<&serde_json::Value> = payload["scenes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|scene| scene["items"].as_array())
+            .flatten()
+            .collect();
+        let images: Vec<DumpedImage> = items
+            .iter()
+            .filter_map(|item| {
+                let image = item["image_data"].as_object()?;
+                Some(DumpedImage {
+                    source_name: item["source_name"].as_str().unwrap_or("").to_string(),
+                    file: image
+                        .get("file")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    encoded_size: image
+                        .get("data")
+                        .and_then(|v| v.as_str())
+                        .map(|d| d.len())
+                        .unwrap_or(0),
+                })
+            })
+            .collect();
+
+        let summary = StateDumpSummary {
+            generated_at: chrono::Utc::now().timestamp_millis(),
+            collection_ms,
+            scene_count: payload["scenes"].as_array().map(|s| s.len()).unwrap_or(0),
+            item_count: items.len(),
+            images,
+            skipped_scenes,
+        };
+        Self {
+            summary,
+            message: SyncMessage::new(SyncMessageType::StateSync, SyncTargetType::Program, payload),
+        }
+    }
+}
 
 pub struct MasterSync {
     obs_client: Arc<OBSClient>,
     message_tx: mpsc::UnboundedSender<SyncMessage>,
     active_targets: Arc<RwLock<Vec<SyncTargetType>>>,
+    scene_target_overrides: Arc<RwLock<SceneTargetOverrides>>,
+    /// Last broadcast image hash per source name
+    image_hashes: Arc<RwLock<HashMap<String, String>>>,
+    tuning: Arc<RwLock<SyncTuning>>,
+    /// Items with unsent transform changes, with when each last changed
+    pending_transforms: Arc<RwLock<HashMap<(String, i64), Instant>>>,
+    monitor_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Flushes `pending_transforms` to slaves; runs alongside `monitor_task`
+    transform_batch_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Polls blend modes for `monitor_task`, since OBS sends no event when one changes
+    blend_mode_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    filters: Arc<RwLock<SyncFilters>>,
+    image_reencode: Arc<RwLock<ImageReencode>>,
+    transform_anchors: Arc<RwLock<TransformAnchors>>,
+    /// While set, OBS events and state requests are not sent to slaves
+    paused: Arc<AtomicBool>,
+    /// Bumped by `cancel_resync`; state collections started before then stop
+    resync_generation: Arc<AtomicU64>,
+    /// Duplicated sources seen during state collection, keyed by (scene, source)
+    ambiguous_sources: Arc<RwLock<HashMap<(String, String), AmbiguousSource>>>,
+    /// Notified once with each newly found batch of ambiguous sources
+    ambiguous_sources_callback: Arc<RwLock<Option<AmbiguousSourcesCallback>>>,
+    resync_progress_callback: Arc<RwLock<Option<ResyncProgressCallback>>>,
+    /// Changes applied from bidirectional slaves, so their echoes are tagged with the slave
+    echo_guard: Arc<RwLock<EchoGuard>>,
+    /// Used for state sent to one slave; unset until the server is wired up
+    client_sender: Arc<RwLock<Option<ClientSender>>>,
 }
 
 impl MasterSync {
@@ -20,33 +293,963 @@ impl MasterSync {
             Self {
                 obs_client,
                 message_tx: tx,
-                active_targets: Arc::new(RwLock::new(vec![
-                    SyncTargetType::Program,
-                    SyncTargetType::Source,
-                ])),
+                active_targets: Arc::new(RwLock::new(default_sync_targets())),
+                scene_target_overrides: Arc::new(RwLock::new(HashMap::new())),
+                image_hashes: Arc::new(RwLock::new(HashMap::new())),
+                tuning: Arc::new(RwLock::new(SyncTuning::default())),
+                pending_transforms: Arc::new(RwLock::new(HashMap::new())),
+                monitor_task: Arc::new(RwLock::new(None)),
+                transform_batch_task: Arc::new(RwLock::new(None)),
+                blend_mode_task: Arc::new(RwLock::new(None)),
+                filters: Arc::new(RwLock::new(SyncFilters::default())),
+                image_reencode: Arc::new(RwLock::new(ImageReencode::default())),
+                transform_anchors: Arc::new(RwLock::new(HashMap::new())),
+                paused: Arc::new(AtomicBool::new(false)),
+                resync_generation: Arc::new(AtomicU64::new(0)),
+                ambiguous_sources: Arc::new(RwLock::new(HashMap::new())),
+                ambiguous_sources_callback: Arc::new(RwLock::new(None)),
+                resync_progress_callback: Arc::new(RwLock::new(None)),
+                echo_guard: Arc::new(RwLock::new(EchoGuard::new())),
+                client_sender: Arc::new(RwLock::new(None)),
             },
             rx,
         )
     }
 
+    /// Targets an event is synced for: its scene's override, or the global targets
+    fn event_targets(
+        global: &[SyncTargetType],
+        overrides: &SceneTargetOverrides,
+        event: &OBSEvent,
+    ) -> Vec<SyncTargetType> {
+        match event.scene_name() {
+            Some(scene_name) => targets_for_scene(overrides, scene_name, global),
+            None => global.to_vec(),
+        }
+    }
+
     pub async fn set_active_targets(&self, targets: Vec<SyncTargetType>) {
         *self.active_targets.write().await = targets;
     }
 
-    pub async fn start_monitoring(&self, mut obs_event_rx: mpsc::UnboundedReceiver<OBSEvent>) {
-        let message_tx = self.message_tx.clone();
+    pub async fn set_scene_target_overrides(&self, overrides: SceneTargetOverrides) {
+        *self.scene_target_overrides.write().await = overrides;
+    }
+
+    pub async fn set_tuning(&self, tuning: SyncTuning) {
+        *self.tuning.write().await = tuning;
+    }
+
+    pub async fn set_filters(&self, filters: SyncFilters) {
+        *self.filters.write().await = filters;
+    }
+
+    /// Swap in new source sync rules. Scene items whose source the new rules exclude
+    /// are removed from slaves, since no further updates will reach them.
+    /// Returns how many removals were sent.
+    pub async fn set_source_rules(&self, rules: SourceSyncRules) -> Result<usize> {
+        let previous = {
+            let mut filters = self.filters.write().await;
+            let previous = filters.clone();
+            filters.set_source_rules(rules);
+            previous
+        };
+        let filters = self.filters.read().await.clone();
+
+        let client_arc = self.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let Some(client) = client_lock.as_ref() else {
+            return Ok(0);
+        };
+        let scenes = client
+            .scenes()
+            .list()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get scenes list: {}", e))?;
+
+        let mut removed = 0;
+        for scene in scenes.scenes {
+            let scene_name = scene.id.scene_name().to_string();
+            if filters.is_scene_excluded(&scene_name) {
+                continue;
+            }
+            let items = match client.scene_items().list(scene.id.clone().into()).await {
+                Ok(items) => items,
+                Err(e) => {
+                    eprintln!("Failed to get scene items for {}: {}", scene_name, e);
+                    continue;
+                }
+            };
+            for item in items {
+                if previous.is_source_excluded(&item.source_name)
+                    || !filters.is_source_excluded(&item.source_name)
+                {
+                    continue;
+                }
+                println!(
+                    "Source {} is now excluded, removing it from slaves in {}",
+                    item.source_name, scene_name
+                );
+                let payload = SourceUpdatePayload {
+                    scene_name: scene_name.clone(),
+                    scene_item_id: item.id,
+                    source_name: item.source_name,
+                    action: SourceUpdateAction::Removed,
+                    source_type: None,
+                    scene_item_enabled: None,
+                    transform: None,
+                    item_settings: None,
+                };
+                let msg = SyncMessage::new(
+                    SyncMessageType::SourceUpdate,
+                    SyncTargetType::Source,
+                    serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+                )
+                .with_ack();
+                self.message_tx
+                    .send(msg)
+                    .context("Failed to queue source removal")?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub async fn set_image_reencode(&self, reencode: ImageReencode) {
+        *self.image_reencode.write().await = reencode;
+    }
+
+    pub async fn set_transform_anchors(&self, anchors: TransformAnchors) {
+        *self.transform_anchors.write().await = anchors;
+    }
+
+    pub async fn set_ambiguous_sources_callback<F>(&self, callback: F)
+    where
+        F: Fn(Vec<AmbiguousSource>) + Send + Sync + 'static,
+    {
+        *self.ambiguous_sources_callback.write().await = Some(Arc::new(callback));
+    }
+
+    /// Called as `send_initial_state` works through the scenes and items, and once at the end
+    pub async fn set_resync_progress_callback<F>(&self, callback: F)
+    where
+        F: Fn(ResyncProgress) + Send + Sync + 'static,
+    {
+        *self.resync_progress_callback.write().await = Some(Arc::new(callback));
+    }
+
+    pub async fn set_client_sender<F, Fut>(&self, sender: F)
+    where
+        F: Fn(String, SyncMessage) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        let wrapped = Arc::new(move |client_id: String, message: SyncMessage| {
+            Box::pin(sender(client_id, message))
+                as std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send>>
+        });
+        *self.client_sender.write().await = Some(wrapped);
+    }
+
+    /// Sources that appear more than once in a scene, as of the last state collection
+    pub async fn ambiguous_sources(&self) -> Vec<AmbiguousSource> {
+        self.ambiguous_sources
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Sources occurring in more than one item of the same scene in a collected state
+    fn find_duplicate_sources(state: &serde_json::Value) -> Vec<AmbiguousSource> {
+        let mut duplicates = Vec::new();
+        for scene in state["scenes"].as_array().into_iter().flatten() {
+            let Some(scene_name) = scene["name"].as_str() else {
+                continue;
+            };
+            let mut item_ids: HashMap<&str, Vec<i64>> = HashMap::new();
+            for item in scene["items"].as_array().into_iter().flatten() {
+                if let (Some(source_name), Some(id)) =
+                    (item["source_name"].as_str(), item["scene_item_id"].as_i64())
+                {
+                    item_ids.entry(source_name).or_default().push(id);
+                }
+            }
+            duplicates.extend(item_ids.into_iter().filter(|(_, ids)| ids.len() > 1).map(
+                |(source_name, scene_item_ids)| AmbiguousSource {
+                    scene_name: scene_name.to_string(),
+                    source_name: source_name.to_string(),
+                    scene_item_ids,
+                },
+            ));
+        }
+        duplicates
+    }
+
+    /// Record duplicated sources in `state`, warning once about each newly found one
+    async fn record_ambiguous_sources(&self, state: &serde_json::Value) {
+        let duplicates = Self::find_duplicate_sources(state);
+        let mut newly_found = Vec::new();
+        {
+            let mut known = self.ambiguous_sources.write().await;
+            let mut current = HashMap::new();
+            for duplicate in duplicates {
+                let key = (duplicate.scene_name.clone(), duplicate.source_name.clone());
+                if !known.contains_key(&key) {
+                    newly_found.push(duplicate.clone());
+                }
+                current.insert(key, duplicate);
+            }
+            *known = current;
+        }
+        if newly_found.is_empty() {
+            return;
+        }
+
+        for duplicate in &newly_found {
+            eprintln!(
+                "Warning: source {} appears {} times in scene {}; name-based sync may pick the wrong item there",
+                duplicate.source_name,
+                duplicate.scene_item_ids.len(),
+                duplicate.scene_name
+            );
+        }
+        let callback = self.ambiguous_sources_callback.read().await.clone();
+        if let Some(callback) = callback {
+            callback(newly_found);
+        }
+    }
+
+    /// Broadcast a semantic command for slaves to execute
+    pub fn send_command(&self, command: &SemanticCommand) -> Result<()> {
+        self.message_tx
+            .send(SyncMessage::command(command))
+            .context("Failed to queue command")
+    }
+
+    /// Tell slaves to reconnect to the master at `new_host:new_port`
+    pub fn send_handoff(&self, new_host: &str, new_port: u16) -> Result<()> {
+        self.message_tx
+            .send(SyncMessage::master_handoff(new_host, new_port))
+            .context("Failed to queue master handoff")
+    }
+
+    /// Stop pushing local OBS changes to slaves until `resume` is called
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Stop every `send_initial_state` still collecting state, for all slaves or one.
+    /// They stop at the next scene or item and send nothing.
+    pub fn cancel_resync(&self) {
+        self.resync_generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Canvas-relative position for `source_name` if it has an anchor configured
+    async fn anchored_position(
+        client: &obws::Client,
+        anchors: &TransformAnchors,
+        source_name: &str,
+        position_x: f64,
+        position_y: f64,
+    ) -> Option<AnchoredPosition> {
+        let anchor = *anchors.get(source_name)?;
+        match canvas_size(client).await {
+            Ok(canvas) => AnchoredPosition::new(anchor, position_x, position_y, canvas),
+            Err(e) => {
+                throttled_eprintln!("Failed to anchor {}: {}", source_name, e);
+                None
+            }
+        }
+    }
+
+    fn send_scene_lifecycle(
+        message_tx: &mpsc::UnboundedSender<SyncMessage>,
+        scene_name: String,
+        action: SceneLifecycleAction,
+    ) {
+        println!(
+            "Sending scene lifecycle update: {} {:?}",
+            scene_name, action
+        );
+        let payload = SceneLifecyclePayload { scene_name, action };
+        let msg = SyncMessage::new(
+            SyncMessageType::SceneLifecycle,
+            SyncTargetType::Source,
+            serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+        )
+        .with_ack();
+        let _ = message_tx.send(msg);
+    }
+
+    /// Broadcast an input's settings minus the paths that only make sense on this machine
+    fn send_source_settings(
+        message_tx: &mpsc::UnboundedSender<SyncMessage>,
+        source_name: &str,
+        input_kind: String,
+        settings: &serde_json::Value,
+    ) {
+        if let Some(msg) = Self::source_settings_message(source_name, input_kind, settings) {
+            println!("Sending settings update for {}", source_name);
+            let _ = message_tx.send(msg);
+        }
+    }
+
+    /// SourceSettingsUpdate for an input; None when nothing is left once the
+    /// machine-local paths are removed
+    fn source_settings_message(
+        source_name: &str,
+        input_kind: String,
+        settings: &serde_json::Value,
+    ) -> Option<SyncMessage> {
+        let mut settings = settings.clone();
+        if let Some(object) = settings.as_object_mut() {
+            for key in MACHINE_LOCAL_SETTINGS {
+                object.remove(key);
+            }
+            if object.is_empty() {
+                return None;
+            }
+        }
+        let payload = SourceSettingsPayload {
+            source_name: source_name.to_string(),
+            input_kind,
+            settings,
+        };
+        Some(SyncMessage::new(
+            SyncMessageType::SourceSettingsUpdate,
+            SyncTargetType::Source,
+            serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+        ))
+    }
+
+    fn send_filter_lifecycle(
+        message_tx: &mpsc::UnboundedSender<SyncMessage>,
+        payload: FilterLifecyclePayload,
+    ) {
+        println!(
+            "Sending filter lifecycle update: {}/{} {:?}",
+            payload.source_name, payload.filter_name, payload.action
+        );
+        let msg = SyncMessage::new(
+            SyncMessageType::FilterLifecycle,
+            SyncTargetType::Source,
+            serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+        )
+        .with_ack();
+        let _ = message_tx.send(msg);
+    }
+
+    fn send_reorder_items(
+        message_tx: &mpsc::UnboundedSender<SyncMessage>,
+        payload: ReorderItemsPayload,
+    ) {
+        println!(
+            "Sending item order of {} ({} items)",
+            payload.scene_name,
+            payload.items.len()
+        );
+        let msg = SyncMessage::new(
+            SyncMessageType::ReorderItems,
+            SyncTargetType::Source,
+            serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+        )
+        .with_ack();
+        let _ = message_tx.send(msg);
+    }
+
+    fn send_output_control(
+        message_tx: &mpsc::UnboundedSender<SyncMessage>,
+        output: OutputKind,
+        active: bool,
+    ) {
+        let payload = OutputControlPayload { output, active };
+        let msg = SyncMessage::new(
+            SyncMessageType::OutputControl,
+            SyncTargetType::Output,
+            serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+        )
+        .with_ack();
+        let _ = message_tx.send(msg);
+    }
+
+    /// Message type a live OBS event is broadcast as
+    fn message_type_for(event: &OBSEvent) -> SyncMessageType {
+        match event {
+            OBSEvent::SceneChanged { .. } | OBSEvent::CurrentPreviewSceneChanged { .. } => {
+                SyncMessageType::SceneChange
+            }
+            OBSEvent::SceneItemTransformChanged { .. } => SyncMessageType::TransformUpdate,
+            OBSEvent::InputSettingsChanged { .. } => SyncMessageType::ImageUpdate,
+            OBSEvent::SceneItemFilterChanged { .. } => SyncMessageType::FilterUpdate,
+            OBSEvent::SceneItemsReordered { .. } => SyncMessageType::ReorderItems,
+            OBSEvent::SourceFilterCreated { .. } | OBSEvent::SourceFilterRemoved { .. } => {
+                SyncMessageType::FilterLifecycle
+            }
+            OBSEvent::SceneItemCreated { .. }
+            | OBSEvent::SceneItemRemoved { .. }
+            | OBSEvent::SceneItemEnableStateChanged { .. }
+            | OBSEvent::SceneItemLockStateChanged { .. }
+            | OBSEvent::SceneItemBlendModeChanged { .. } => SyncMessageType::SourceUpdate,
+            OBSEvent::SceneCreated { .. } | OBSEvent::SceneRemoved { .. } => {
+                SyncMessageType::SceneLifecycle
+            }
+            OBSEvent::InputVolumeChanged { .. } => SyncMessageType::VolumeUpdate,
+            OBSEvent::InputMuteStateChanged { .. } => SyncMessageType::MuteUpdate,
+            OBSEvent::RecordStateChanged { .. } | OBSEvent::StreamStateChanged { .. } => {
+                SyncMessageType::OutputControl
+            }
+            OBSEvent::RawEvent { .. } => SyncMessageType::RawEvent,
+        }
+    }
+
+    /// Whether an event is dropped by the sync filters. Source names and input kinds are
+    /// only looked up in OBS when the corresponding list is non-empty.
+    async fn is_event_filtered(
+        obs_client: &OBSClient,
+        filters: &SyncFilters,
+        event: &OBSEvent,
+    ) -> bool {
+        let type_excluded = match event {
+            // A settings change can produce both an image and a settings update
+            OBSEvent::InputSettingsChanged { .. } => {
+                filters.is_message_type_excluded(&SyncMessageType::ImageUpdate)
+                    && filters.is_message_type_excluded(&SyncMessageType::SourceSettingsUpdate)
+            }
+            _ => filters.is_message_type_excluded(&Self::message_type_for(event)),
+        };
+        if type_excluded {
+            return true;
+        }
+        if event
+            .scene_name()
+            .map(|scene| filters.is_scene_excluded(scene))
+            .unwrap_or(false)
+        {
+            return true;
+        }
+        if !filters.filters_sources() && filters.input_kinds.is_empty() {
+            return false;
+        }
+
+        let client_arc = obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let Some(client) = client_lock.as_ref() else {
+            return false;
+        };
+        let source_name = match event {
+            OBSEvent::InputSettingsChanged { input_name }
+            | OBSEvent::InputVolumeChanged { input_name, .. }
+            | OBSEvent::InputMuteStateChanged { input_name, .. } => Some(input_name.clone()),
+            OBSEvent::SceneItemCreated { source_name, .. }
+            | OBSEvent::SceneItemRemoved { source_name, .. }
+            | OBSEvent::SourceFilterCreated { source_name, .. }
+            | OBSEvent::SourceFilterRemoved { source_name, .. } => Some(source_name.clone()),
+            OBSEvent::SceneItemTransformChanged {
+                scene_name,
+                scene_item_id,
+            }
+            | OBSEvent::SceneItemFilterChanged {
+                scene_name,
+                scene_item_id,
+                ..
+            }
+            | OBSEvent::SceneItemEnableStateChanged {
+                scene_name,
+                scene_item_id,
+                ..
+            }
+            | OBSEvent::SceneItemLockStateChanged {
+                scene_name,
+                scene_item_id,
+                ..
+            }
+            | OBSEvent::SceneItemBlendModeChanged {
+                scene_name,
+                scene_item_id,
+                ..
+            } => client
+                .scene_items()
+                .source(obws::requests::scene_items::Source {
+                    scene: scene_name.as_str().into(),
+                    item_id: *scene_item_id,
+                })
+                .await
+                .ok()
+                .map(|source| source.name),
+            OBSEvent::SceneChanged { .. }
+            | OBSEvent::CurrentPreviewSceneChanged { .. }
+            | OBSEvent::SceneItemsReordered { .. }
+            | OBSEvent::SceneCreated { .. }
+            | OBSEvent::SceneRemoved { .. }
+            | OBSEvent::RecordStateChanged { .. }
+            | OBSEvent::StreamStateChanged { .. }
+            | OBSEvent::RawEvent { .. } => None,
+        };
+        let Some(source_name) = source_name else {
+            return false;
+        };
+        if filters.is_source_excluded(&source_name) {
+            return true;
+        }
+        if filters.input_kinds.is_empty() {
+            return false;
+        }
+        // Scenes and groups have no input kind and are never excluded by kind
+        match client
+            .inputs()
+            .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(&source_name))
+            .await
+        {
+            Ok(input) => filters.is_input_kind_excluded(&input.kind),
+            Err(_) => false,
+        }
+    }
+
+    /// Apply a scene switch or transform a bidirectional slave made in its own OBS.
+    /// The OBS event this causes here is broadcast tagged with the slave's origin,
+    /// so the other slaves apply it and the originating slave skips it.
+    pub async fn apply_remote_update(&self, message: &SyncMessage) -> Result<()> {
+        if message.message_type == SyncMessageType::TransformBatch {
+            for update in message.split_transform_batch()? {
+                Box::pin(self.apply_remote_update(&update)).await?;
+            }
+            return Ok(());
+        }
+        self.echo_guard.write().await.record(message);
+
+        let client_arc = self.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().context("OBS client not connected")?;
+
+        match message.message_type {
+            SyncMessageType::SceneChange => {
+                let payload: SceneChangePayload = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse scene change")?;
+                if message.target_type == SyncTargetType::Preview {
+                    client
+                        .scenes()
+                        .set_current_preview_scene(payload.scene_name.as_str())
+                        .await
+                        .context("Failed to set current preview scene")?;
+                } else {
+                    if let Some(transition) = &payload.transition {
+                        if !OBSCommands::use_transition(
+                            client,
+                            &transition.name,
+                            transition.duration_ms,
+                        )
+                        .await?
+                        {
+                            eprintln!(
+                                "Transition '{}' not found, switching to '{}' with a cut",
+                                transition.name, payload.scene_name
+                            );
+                        }
+                    }
+                    OBSCommands::set_current_program_scene(client, &payload.scene_name).await?;
+                }
+            }
+            SyncMessageType::TransformUpdate => {
+                let payload: TransformUpdatePayload =
+                    serde_json::from_value(message.payload.clone())
+                        .context("Failed to parse transform update")?;
+                let scene_id = obws::requests::scenes::SceneId::Name(&payload.scene_name);
+
+                // Item ids differ between machines; the source name identifies the item
+                let mut item_id = payload.scene_item_id;
+                if let Some(source_name) = &payload.source_name {
+                    let items = client
+                        .scene_items()
+                        .list(scene_id)
+                        .await
+                        .context("Failed to list scene items")?;
+                    if let Some(item) = items.iter().find(|i| &i.source_name == source_name) {
+                        item_id = item.id;
+                    }
+                }
+
+                let mut transform = client
+                    .scene_items()
+                    .transform(scene_id, item_id)
+                    .await
+                    .context("Failed to get transform")?;
+                let remote = &payload.transform;
+                transform.position_x = remote.position_x as f32;
+                transform.position_y = remote.position_y as f32;
+                transform.rotation = remote.rotation as f32;
+                transform.scale_x = remote.scale_x as f32;
+                transform.scale_y = remote.scale_y as f32;
+                transform.crop_left = remote.crop_left;
+                transform.crop_right = remote.crop_right;
+                transform.crop_top = remote.crop_top;
+                transform.crop_bottom = remote.crop_bottom;
+                if let Some(alignment) = remote.alignment.and_then(Alignment::from_bits) {
+                    transform.alignment = alignment;
+                }
+                if let Some(bounds_type) = remote.bounds_type.as_deref().and_then(|bounds| {
+                    serde_json::from_value(serde_json::Value::String(bounds.to_string())).ok()
+                }) {
+                    transform.bounds_type = bounds_type;
+                }
+                if let Some(alignment) = remote.bounds_alignment.and_then(Alignment::from_bits) {
+                    transform.bounds_alignment = alignment;
+                }
+                transform.bounds_width = remote
+                    .bounds_width
+                    .map_or(transform.bounds_width, |width| width as f32);
+                transform.bounds_height = remote
+                    .bounds_height
+                    .map_or(transform.bounds_height, |height| height as f32);
+                client
+                    .scene_items()
+                    .set_transform(obws::requests::scene_items::SetTransform {
+                        scene: scene_id,
+                        item_id,
+                        transform: transform.into(),
+                    })
+                    .await
+                    .context("Failed to set transform")?;
+            }
+            _ => anyhow::bail!("Slaves can't send {:?}", message.message_type),
+        }
+        Ok(())
+    }
+
+    /// Abort the OBS event monitoring task (no-op if it isn't running)
+    pub async fn stop_monitoring(&self) {
+        if let Some(task) = self.monitor_task.write().await.take() {
+            task.abort();
+        }
+        if let Some(task) = self.transform_batch_task.write().await.take() {
+            task.abort();
+        }
+        if let Some(task) = self.blend_mode_task.write().await.take() {
+            task.abort();
+        }
+    }
+
+    /// Send pending transform changes every `TRANSFORM_BATCH_INTERVAL_MS`, each item's
+    /// latest transform once. Items still changing within the tuning's debounce window
+    /// wait for a later flush. Several items go out as one TransformBatch.
+    /// Nothing is sent while paused; changes still pending at that point are dropped.
+    async fn run_transform_batches(
+        obs_client: Arc<OBSClient>,
+        paused: Arc<AtomicBool>,
+        pending_transforms: Arc<RwLock<HashMap<(String, i64), Instant>>>,
+        tuning: Arc<RwLock<SyncTuning>>,
+        transform_anchors: Arc<RwLock<TransformAnchors>>,
+        message_tx: mpsc::UnboundedSender<SyncMessage>,
+    ) {
+        let interval = Duration::from_millis(TRANSFORM_BATCH_INTERVAL_MS);
+        loop {
+            tokio::time::sleep(interval).await;
+            if paused.load(Ordering::SeqCst) {
+                pending_transforms.write().await.clear();
+                continue;
+            }
+            let debounce = Duration::from_millis(tuning.read().await.transform_debounce_ms);
+            let ready: Vec<(String, i64)> = {
+                let mut pending = pending_transforms.write().await;
+                let ready: Vec<_> = pending
+                    .iter()
+                    .filter(|(_, changed_at)| changed_at.elapsed() >= debounce)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in &ready {
+                    pending.remove(key);
+                }
+                ready
+            };
+            if ready.is_empty() {
+                continue;
+            }
+
+            let anchors = transform_anchors.read().await.clone();
+            let client_arc = obs_client.get_client_arc();
+            let client_lock = client_arc.read().await;
+            let Some(client) = client_lock.as_ref() else {
+                continue;
+            };
+            let mut updates = Vec::with_capacity(ready.len());
+            for (scene_name, scene_item_id) in ready {
+                match Self::read_transform_update(client, &anchors, scene_name, scene_item_id).await
+                {
+                    Ok(update) => updates.push(update),
+                    Err(e) => {
+                        throttled_eprintln!(
+                            "Failed to get transform for item {}: {}",
+                            scene_item_id,
+                            e
+                        );
+                    }
+                }
+            }
+
+            let msg = match updates.len() {
+                0 => continue,
+                1 => {
+                    let update = &updates[0];
+                    println!(
+                        "Sent transform update for scene item {} in {}",
+                        update.scene_item_id, update.scene_name
+                    );
+                    SyncMessage::new(
+                        SyncMessageType::TransformUpdate,
+                        SyncTargetType::Source,
+                        serde_json::to_value(update).unwrap_or(serde_json::Value::Null),
+                    )
+                }
+                count => {
+                    println!("Sent transform batch of {} items", count);
+                    SyncMessage::transform_batch(updates)
+                }
+            };
+            if message_tx.send(msg).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Transition in use for a program scene switch, so slaves can play the same one
+    async fn read_scene_transition(obs_client: &OBSClient) -> Option<SceneTransition> {
+        let client_arc = obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref()?;
+        match OBSCommands::current_transition(client).await {
+            Ok((name, duration_ms)) => Some(SceneTransition { name, duration_ms }),
+            Err(e) => {
+                throttled_eprintln!("Failed to read current scene transition: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Current transform of a scene item as it is sent to slaves
+    async fn read_transform_update(
+        client: &obws::Client,
+        anchors: &TransformAnchors,
+        scene_name: String,
+        scene_item_id: i64,
+    ) -> Result<TransformUpdatePayload> {
+        let scene_id = obws::requests::scenes::SceneId::Name(&scene_name);
+        let transform = client
+            .scene_items()
+            .transform(scene_id, scene_item_id)
+            .await?;
+        let source_name = client
+            .scene_items()
+            .source(obws::requests::scene_items::Source {
+                scene: scene_id,
+                item_id: scene_item_id,
+            })
+            .await
+            .ok()
+            .map(|source| source.name);
+        let anchor = match source_name.as_deref() {
+            Some(name) => {
+                Self::anchored_position(
+                    client,
+                    anchors,
+                    name,
+                    transform.position_x as f64,
+                    transform.position_y as f64,
+                )
+                .await
+            }
+            None => None,
+        };
+        Ok(TransformUpdatePayload {
+            scene_name,
+            scene_item_id,
+            source_name,
+            transform: Self::transform_data(&transform, anchor),
+        })
+    }
+
+    /// Protocol form of an OBS transform, including its bounds and alignment
+    fn transform_data(
+        t: &obws::responses::scene_items::SceneItemTransform,
+        anchor: Option<AnchoredPosition>,
+    ) -> TransformData {
+        TransformData {
+            position_x: t.position_x as f64,
+            position_y: t.position_y as f64,
+            rotation: t.rotation as f64,
+            scale_x: t.scale_x as f64,
+            scale_y: t.scale_y as f64,
+            width: t.width as f64,
+            height: t.height as f64,
+            crop_left: t.crop_left,
+            crop_right: t.crop_right,
+            crop_top: t.crop_top,
+            crop_bottom: t.crop_bottom,
+            alignment: Some(t.alignment.bits()),
+            bounds_type: serde_json::to_value(t.bounds_type)
+                .ok()
+                .and_then(|bounds| bounds.as_str().map(String::from)),
+            bounds_alignment: Some(t.bounds_alignment.bits()),
+            bounds_width: Some(t.bounds_width as f64),
+            bounds_height: Some(t.bounds_height as f64),
+            anchor,
+        }
+    }
+
+    /// Read every scene item's blend mode each `BLEND_MODE_POLL_MS` and report the ones
+    /// that changed as events. Nothing is compared while paused; polling starts afresh
+    /// on resume.
+    async fn run_blend_mode_watch(
+        obs_client: Arc<OBSClient>,
+        paused: Arc<AtomicBool>,
+        event_tx: mpsc::UnboundedSender<OBSEvent>,
+    ) {
+        let interval = Duration::from_millis(BLEND_MODE_POLL_MS);
+        let mut known = BlendModes::new();
+        loop {
+            tokio::time::sleep(interval).await;
+            if paused.load(Ordering::SeqCst) {
+                known.clear();
+                continue;
+            }
+            let client_arc = obs_client.get_client_arc();
+            let client_lock = client_arc.read().await;
+            let Some(client) = client_lock.as_ref() else {
+                known.clear();
+                continue;
+            };
+            let scenes = match client.scenes().list().await {
+                Ok(scenes) => scenes.scenes,
+                Err(e) => {
+                    throttled_eprintln!("Failed to get scenes for blend modes: {}", e);
+                    continue;
+                }
+            };
+            let mut current = BlendModes::new();
+            for scene in scenes {
+                let scene_name = scene.id.scene_name().to_string();
+                let Ok(items) = client.scene_items().list(scene.id.clone().into()).await else {
+                    continue;
+                };
+                for item in items {
+                    let blend_mode = client
+                        .scene_items()
+                        .blend_mode(scene.id.clone().into(), item.id)
+                        .await
+                        .ok()
+                        .and_then(|mode| serde_json::to_value(mode).ok())
+                        .and_then(|mode| mode.as_str().map(String::from));
+                    if let Some(blend_mode) = blend_mode {
+                        current.insert((scene_name.clone(), item.id), blend_mode);
+                    }
+                }
+            }
+            drop(client_lock);
+
+            for event in blend_mode_changes(&known, &current) {
+                if event_tx.send(event).is_err() {
+                    return;
+                }
+            }
+            known = current;
+        }
+    }
+
+    pub async fn start_monitoring(&self, obs_event_rx: mpsc::UnboundedReceiver<OBSEvent>) {
+        // Blend mode changes found by polling join the events OBS sends
+        let (event_tx, mut obs_event_rx) = {
+            let (event_tx, merged_rx) = mpsc::unbounded_channel();
+            let forward_tx = event_tx.clone();
+            let mut obs_event_rx = obs_event_rx;
+            tokio::spawn(async move {
+                while let Some(event) = obs_event_rx.recv().await {
+                    if forward_tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+            (event_tx, merged_rx)
+        };
+        // Messages for local OBS events pass the echo guard on their way to the slaves.
+        // Those still being prepared when sync is paused are dropped here.
+        let (message_tx, mut event_message_rx) = mpsc::unbounded_channel::<SyncMessage>();
+        let outgoing_tx = self.message_tx.clone();
+        let echo_guard = self.echo_guard.clone();
+        let paused_for_relay = self.paused.clone();
+        tokio::spawn(async move {
+            while let Some(message) = event_message_rx.recv().await {
+                if paused_for_relay.load(Ordering::SeqCst) {
+                    continue;
+                }
+                let origin_id = echo_guard.write().await.origin_of(&message);
+                let message = match origin_id {
+                    Some(origin_id) => message.with_origin(origin_id),
+                    None => message,
+                };
+                if outgoing_tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
         let active_targets = self.active_targets.clone();
+        let scene_target_overrides = self.scene_target_overrides.clone();
         let obs_client = self.obs_client.clone();
+        let image_hashes = self.image_hashes.clone();
+        let tuning = self.tuning.clone();
+        let pending_transforms = self.pending_transforms.clone();
+        let filters = self.filters.clone();
+        let image_reencode = self.image_reencode.clone();
+        let transform_anchors = self.transform_anchors.clone();
+        let paused = self.paused.clone();
 
-        tokio::spawn(async move {
+        let batch_task = tokio::spawn(Self::run_transform_batches(
+            obs_client.clone(),
+            paused.clone(),
+            pending_transforms.clone(),
+            tuning.clone(),
+            transform_anchors.clone(),
+            message_tx.clone(),
+        ));
+        if let Some(previous) = self.transform_batch_task.write().await.replace(batch_task) {
+            previous.abort();
+        }
+        let blend_mode_task = tokio::spawn(Self::run_blend_mode_watch(
+            obs_client.clone(),
+            paused.clone(),
+            event_tx,
+        ));
+        if let Some(previous) = self.blend_mode_task.write().await.replace(blend_mode_task) {
+            previous.abort();
+        }
+
+        let task = tokio::spawn(async move {
             while let Some(event) = obs_event_rx.recv().await {
-                let targets = active_targets.read().await.clone();
+                if paused.load(Ordering::SeqCst) {
+                    continue;
+                }
+                if Self::is_event_filtered(&obs_client, &*filters.read().await, &event).await {
+                    continue;
+                }
+
+                let targets = Self::event_targets(
+                    &active_targets.read().await,
+                    &*scene_target_overrides.read().await,
+                    &event,
+                );
 
                 match event {
                     OBSEvent::SceneChanged { scene_name } => {
                         if targets.contains(&SyncTargetType::Program) {
                             let payload = SceneChangePayload {
                                 scene_name: scene_name.clone(),
+                                transition: Self::read_scene_transition(&obs_client).await,
                             };
                             let payload_json =
                                 serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null);
@@ -54,7 +1257,8 @@ impl MasterSync {
                                 SyncMessageType::SceneChange,
                                 SyncTargetType::Program,
                                 payload_json,
-                            );
+                            )
+                            .with_ack();
                             let _ = message_tx.send(msg);
                         }
                     }
@@ -62,6 +1266,7 @@ impl MasterSync {
                         if targets.contains(&SyncTargetType::Preview) {
                             let payload = SceneChangePayload {
                                 scene_name: scene_name.clone(),
+                                transition: None,
                             };
                             let payload_json =
                                 serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null);
@@ -69,7 +1274,8 @@ impl MasterSync {
                                 SyncMessageType::SceneChange,
                                 SyncTargetType::Preview,
                                 payload_json,
-                            );
+                            )
+                            .with_ack();
                             let _ = message_tx.send(msg);
                         }
                     }
@@ -78,60 +1284,11 @@ impl MasterSync {
                         scene_item_id,
                     } => {
                         if targets.contains(&SyncTargetType::Source) {
-                            // Get the full transform data
-                            let obs_client_clone = obs_client.clone();
-                            let message_tx_clone = message_tx.clone();
-                            let scene_name_clone = scene_name.clone();
-
-                            tokio::spawn(async move {
-                                let client_arc = obs_client_clone.get_client_arc();
-                                let client_lock = client_arc.read().await;
-
-                                if let Some(client) = client_lock.as_ref() {
-                                    let scene_id: obws::requests::scenes::SceneId =
-                                        obws::requests::scenes::SceneId::Name(&scene_name_clone);
-                                    match client
-                                        .scene_items()
-                                        .transform(scene_id, scene_item_id)
-                                        .await
-                                    {
-                                        Ok(transform) => {
-                                            let payload = TransformUpdatePayload {
-                                                scene_name: scene_name_clone.clone(),
-                                                scene_item_id,
-                                                transform: TransformData {
-                                                    position_x: transform.position_x as f64,
-                                                    position_y: transform.position_y as f64,
-                                                    rotation: transform.rotation as f64,
-                                                    scale_x: transform.scale_x as f64,
-                                                    scale_y: transform.scale_y as f64,
-                                                    width: transform.width as f64,
-                                                    height: transform.height as f64,
-                                                },
-                                            };
-                                            let payload_json = serde_json::to_value(&payload)
-                                                .unwrap_or(serde_json::Value::Null);
-
-                                            let msg = SyncMessage::new(
-                                                SyncMessageType::TransformUpdate,
-                                                SyncTargetType::Source,
-                                                payload_json,
-                                            );
-                                            let _ = message_tx_clone.send(msg);
-                                            println!(
-                                                "Sent transform update for scene item {} in {}",
-                                                scene_item_id, scene_name_clone
-                                            );
-                                        }
-                                        Err(e) => {
-                                            eprintln!(
-                                                "Failed to get transform for item {}: {}",
-                                                scene_item_id, e
-                                            );
-                                        }
-                                    }
-                                }
-                            });
+                            // Coalesced per item and sent by the transform batch task
+                            pending_transforms
+                                .write()
+                                .await
+                                .insert((scene_name, scene_item_id), Instant::now());
                         }
                     }
                     OBSEvent::SceneItemFilterChanged {
@@ -176,9 +1333,10 @@ impl MasterSync {
                                                     }
                                                 }
                                                 Err(e) => {
-                                                    eprintln!(
+                                                    throttled_eprintln!(
                                                         "Failed to get scene items for {}: {}",
-                                                        scene_name_clone, e
+                                                        scene_name_clone,
+                                                        e
                                                     );
                                                     (None, None, None)
                                                 }
@@ -201,7 +1359,7 @@ impl MasterSync {
                                                                     match client.filters().list(obws::requests::sources::SourceId::Name(&item.source_name)).await {
                                                 Ok(filters) => {
                                                     if filters.iter().any(|f| f.name == filter_name_clone) {
-                                                        found = Some((format!("{:?}", scene.id), item.id, item.source_name.clone()));
+                                                        found = Some((scene.id.scene_name().to_string(), item.id, item.source_name.clone()));
                                                         break 'outer;
                                                     }
                                                 }
@@ -283,14 +1441,25 @@ impl MasterSync {
                             let obs_client_clone = obs_client.clone();
                             let message_tx_clone = message_tx.clone();
                             let input_name_clone = input_name.clone();
+                            let image_hashes_clone = image_hashes.clone();
+                            let image_reencode_clone = image_reencode.clone();
+                            let (send_settings, send_image) = {
+                                let filters = filters.read().await;
+                                (
+                                    !filters.is_message_type_excluded(
+                                        &SyncMessageType::SourceSettingsUpdate,
+                                    ),
+                                    !filters
+                                        .is_message_type_excluded(&SyncMessageType::ImageUpdate),
+                                )
+                            };
 
-                            // Spawn task to get image data
+                            // Spawn task to get the settings (and image data)
                             tokio::spawn(async move {
                                 let client_arc = obs_client_clone.get_client_arc();
                                 let client_lock = client_arc.read().await;
 
                                 if let Some(client) = client_lock.as_ref() {
-                                    // Get input settings first to check if it's an image source
                                     match client
                                         .inputs()
                                         .settings::<serde_json::Value>(
@@ -301,18 +1470,22 @@ impl MasterSync {
                                         .await
                                     {
                                         Ok(settings) => {
+                                            if send_settings {
+                                                Self::send_source_settings(
+                                                    &message_tx_clone,
+                                                    &input_name_clone,
+                                                    settings.kind.clone(),
+                                                    &settings.settings,
+                                                );
+                                            }
+
+                                            // Image sources also send the file's bytes
                                             let file_path = settings
                                                 .settings
                                                 .get("file")
                                                 .and_then(|v| v.as_str())
                                                 .unwrap_or("");
-
-                                            // Only process if it has a file path (likely an image source)
-                                            if file_path.is_empty() {
-                                                println!(
-                                                    "Skipping InputSettingsChanged for {} - no file path found",
-                                                    input_name_clone
-                                                );
+                                            if file_path.is_empty() || !send_image {
                                                 return;
                                             }
 
@@ -322,34 +1495,41 @@ impl MasterSync {
                                             );
 
                                             // Read and encode image if file path exists
-                                            let image_data = if !file_path.is_empty() {
-                                                match tokio::fs::read(file_path).await {
-                                                    Ok(data) => {
-                                                        let encoded = base64::Engine::encode(
-                                                            &base64::engine::general_purpose::STANDARD,
-                                                            &data
-                                                        );
-                                                        println!(
-                                                            "Encoded image: {} ({} bytes)",
-                                                            file_path,
-                                                            data.len()
-                                                        );
-                                                        Some(encoded)
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!("Failed to read image: {}", e);
-                                                        None
-                                                    }
+                                            let reencode =
+                                                image_reencode_clone.read().await.clone();
+                                            let encoded =
+                                                Self::read_and_encode_image(file_path, &reencode)
+                                                    .await;
+                                            let image_format =
+                                                encoded.as_ref().and_then(|e| e.format);
+                                            let compression =
+                                                encoded.as_ref().and_then(|e| e.compression);
+                                            let image_data = encoded.map(|e| e.data);
+
+                                            // Skip broadcasting if the image content is unchanged
+                                            let image_hash =
+                                                image_data.as_deref().map(Self::content_hash);
+                                            if let Some(hash) = image_hash.as_ref() {
+                                                let mut hashes = image_hashes_clone.write().await;
+                                                if hashes.get(&input_name_clone) == Some(hash) {
+                                                    println!(
+                                                        "Image for {} unchanged, skipping ImageUpdate",
+                                                        input_name_clone
+                                                    );
+                                                    return;
                                                 }
-                                            } else {
-                                                None
-                                            };
+                                                hashes
+                                                    .insert(input_name_clone.clone(), hash.clone());
+                                            }
 
                                             let payload = serde_json::json!({
                                                 "scene_name": "",
                                                 "source_name": input_name_clone,
-                                                "file": file_path,
-                                                "image_data": image_data
+                                                "file": Self::file_name_for_slaves(file_path),
+                                                "image_data": image_data,
+                                                "image_hash": image_hash,
+                                                "image_format": image_format,
+                                                "compression": compression,
                                             });
 
                                             let msg = SyncMessage::new(
@@ -360,7 +1540,10 @@ impl MasterSync {
                                             let _ = message_tx_clone.send(msg);
                                         }
                                         Err(e) => {
-                                            eprintln!("Failed to get input settings: {}", e);
+                                            throttled_eprintln!(
+                                                "Failed to get input settings: {}",
+                                                e
+                                            );
                                         }
                                     }
                                 }
@@ -377,6 +1560,7 @@ impl MasterSync {
                             let message_tx_clone = message_tx.clone();
                             let scene_name_clone = scene_name.clone();
                             let source_name_clone = source_name.clone();
+                            let anchors = transform_anchors.read().await.clone();
 
                             tokio::spawn(async move {
                                 let client_arc = obs_client_clone.get_client_arc();
@@ -393,20 +1577,24 @@ impl MasterSync {
                                                 items.iter().find(|i| i.id == scene_item_id)
                                             {
                                                 // Get transform if available
-                                                let transform = client
+                                                let transform = match client
                                                     .scene_items()
                                                     .transform(scene_id, scene_item_id)
                                                     .await
-                                                    .ok()
-                                                    .map(|t| super::protocol::TransformData {
-                                                        position_x: t.position_x as f64,
-                                                        position_y: t.position_y as f64,
-                                                        rotation: t.rotation as f64,
-                                                        scale_x: t.scale_x as f64,
-                                                        scale_y: t.scale_y as f64,
-                                                        width: t.width as f64,
-                                                        height: t.height as f64,
-                                                    });
+                                                {
+                                                    Ok(t) => {
+                                                        let anchor = Self::anchored_position(
+                                                            client,
+                                                            &anchors,
+                                                            &source_name_clone,
+                                                            t.position_x as f64,
+                                                            t.position_y as f64,
+                                                        )
+                                                        .await;
+                                                        Some(Self::transform_data(&t, anchor))
+                                                    }
+                                                    Err(_) => None,
+                                                };
 
                                                 // Get enabled state separately since SceneItem doesn't have it
                                                 let enabled_state = client
@@ -416,7 +1604,8 @@ impl MasterSync {
                                                     .ok();
 
                                                 let source_type =
-                                                    item.input_kind.clone().unwrap_or_default();
+                                                    OBSCommands::resolve_source_kind(client, item)
+                                                        .await;
 
                                                 let payload = SourceUpdatePayload {
                                                     scene_name: scene_name_clone.clone(),
@@ -426,6 +1615,7 @@ impl MasterSync {
                                                     source_type: Some(source_type),
                                                     scene_item_enabled: enabled_state,
                                                     transform,
+                                                    item_settings: None,
                                                 };
 
                                                 let payload_json = serde_json::to_value(&payload)
@@ -444,9 +1634,10 @@ impl MasterSync {
                                             }
                                         }
                                         Err(e) => {
-                                            eprintln!(
+                                            throttled_eprintln!(
                                                 "Failed to get scene items for {}: {}",
-                                                scene_name_clone, e
+                                                scene_name_clone,
+                                                e
                                             );
                                         }
                                     }
@@ -469,6 +1660,7 @@ impl MasterSync {
                                 source_type: None,
                                 scene_item_enabled: None,
                                 transform: None,
+                                item_settings: None,
                             };
 
                             let payload_json =
@@ -478,7 +1670,8 @@ impl MasterSync {
                                 SyncMessageType::SourceUpdate,
                                 SyncTargetType::Source,
                                 payload_json,
-                            );
+                            )
+                            .with_ack();
                             let _ = message_tx.send(msg);
                             println!(
                                 "Sent source removed update for item {} in {}",
@@ -486,276 +1679,1411 @@ impl MasterSync {
                             );
                         }
                     }
-                    OBSEvent::SceneItemEnableStateChanged {
-                        scene_name,
-                        scene_item_id,
-                        enabled,
+                    OBSEvent::SceneCreated { scene_name } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            Self::send_scene_lifecycle(
+                                &message_tx,
+                                scene_name,
+                                SceneLifecycleAction::Created,
+                            );
+                        }
+                    }
+                    OBSEvent::SceneRemoved { scene_name } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            Self::send_scene_lifecycle(
+                                &message_tx,
+                                scene_name,
+                                SceneLifecycleAction::Removed,
+                            );
+                        }
+                    }
+                    OBSEvent::SceneItemEnableStateChanged {
+                        scene_name,
+                        scene_item_id,
+                        enabled,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let obs_client_clone = obs_client.clone();
+                            let message_tx_clone = message_tx.clone();
+                            let scene_name_clone = scene_name.clone();
+
+                            tokio::spawn(async move {
+                                let client_arc = obs_client_clone.get_client_arc();
+                                let client_lock = client_arc.read().await;
+
+                                if let Some(client) = client_lock.as_ref() {
+                                    let scene_id: obws::requests::scenes::SceneId =
+                                        obws::requests::scenes::SceneId::Name(&scene_name_clone);
+
+                                    // Get scene item to find source name
+                                    match client.scene_items().list(scene_id).await {
+                                        Ok(items) => {
+                                            if let Some(item) =
+                                                items.iter().find(|i| i.id == scene_item_id)
+                                            {
+                                                let scene_name_for_payload =
+                                                    scene_name_clone.clone();
+                                                let payload = SourceUpdatePayload {
+                                                    scene_name: scene_name_clone,
+                                                    scene_item_id,
+                                                    source_name: item.source_name.clone(),
+                                                    action: SourceUpdateAction::EnabledStateChanged,
+                                                    source_type: None,
+                                                    scene_item_enabled: Some(enabled),
+                                                    transform: None,
+                                                    item_settings: None,
+                                                };
+
+                                                let payload_json = serde_json::to_value(&payload)
+                                                    .unwrap_or(serde_json::Value::Null);
+
+                                                let msg = SyncMessage::new(
+                                                    SyncMessageType::SourceUpdate,
+                                                    SyncTargetType::Source,
+                                                    payload_json,
+                                                );
+                                                let _ = message_tx_clone.send(msg);
+                                                println!(
+                                                    "Sent source enable state changed update for item {} in {}",
+                                                    scene_item_id, scene_name_for_payload
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            throttled_eprintln!(
+                                                "Failed to get scene items for {}: {}",
+                                                scene_name_clone,
+                                                e
+                                            );
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    }
+                    OBSEvent::SceneItemLockStateChanged {
+                        scene_name,
+                        scene_item_id,
+                        locked,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let obs_client_clone = obs_client.clone();
+                            let message_tx_clone = message_tx.clone();
+
+                            tokio::spawn(async move {
+                                let client_arc = obs_client_clone.get_client_arc();
+                                let client_lock = client_arc.read().await;
+                                let Some(client) = client_lock.as_ref() else {
+                                    return;
+                                };
+                                let scene_id: obws::requests::scenes::SceneId =
+                                    scene_name.as_str().into();
+
+                                let source_name = match client
+                                    .scene_items()
+                                    .source(obws::requests::scene_items::Source {
+                                        scene: scene_id,
+                                        item_id: scene_item_id,
+                                    })
+                                    .await
+                                {
+                                    Ok(source) => source.name,
+                                    Err(e) => {
+                                        throttled_eprintln!(
+                                            "Failed to get source for item {} in {}: {}",
+                                            scene_item_id,
+                                            scene_name,
+                                            e
+                                        );
+                                        return;
+                                    }
+                                };
+                                // Blend mode has no change event; send it along with the lock state
+                                let blend_mode = client
+                                    .scene_items()
+                                    .blend_mode(scene_id, scene_item_id)
+                                    .await
+                                    .ok()
+                                    .and_then(|mode| serde_json::to_value(mode).ok())
+                                    .and_then(|mode| mode.as_str().map(String::from));
+
+                                let payload = SourceUpdatePayload {
+                                    scene_name: scene_name.clone(),
+                                    scene_item_id,
+                                    source_name,
+                                    action: SourceUpdateAction::SettingsChanged,
+                                    source_type: None,
+                                    scene_item_enabled: None,
+                                    transform: None,
+                                    item_settings: Some(SceneItemSettings {
+                                        locked: Some(locked),
+                                        blend_mode,
+                                    }),
+                                };
+                                let msg = SyncMessage::new(
+                                    SyncMessageType::SourceUpdate,
+                                    SyncTargetType::Source,
+                                    serde_json::to_value(&payload)
+                                        .unwrap_or(serde_json::Value::Null),
+                                );
+                                let _ = message_tx_clone.send(msg);
+                                println!(
+                                    "Sent item settings update for item {} in {}",
+                                    scene_item_id, scene_name
+                                );
+                            });
+                        }
+                    }
+                    OBSEvent::SceneItemBlendModeChanged {
+                        scene_name,
+                        scene_item_id,
+                        blend_mode,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let obs_client_clone = obs_client.clone();
+                            let message_tx_clone = message_tx.clone();
+
+                            tokio::spawn(async move {
+                                let client_arc = obs_client_clone.get_client_arc();
+                                let client_lock = client_arc.read().await;
+                                let Some(client) = client_lock.as_ref() else {
+                                    return;
+                                };
+                                let source_name = match client
+                                    .scene_items()
+                                    .source(obws::requests::scene_items::Source {
+                                        scene: scene_name.as_str().into(),
+                                        item_id: scene_item_id,
+                                    })
+                                    .await
+                                {
+                                    Ok(source) => source.name,
+                                    Err(e) => {
+                                        throttled_eprintln!(
+                                            "Failed to get source for item {} in {}: {}",
+                                            scene_item_id,
+                                            scene_name,
+                                            e
+                                        );
+                                        return;
+                                    }
+                                };
+
+                                let payload = SourceUpdatePayload {
+                                    scene_name: scene_name.clone(),
+                                    scene_item_id,
+                                    source_name,
+                                    action: SourceUpdateAction::SettingsChanged,
+                                    source_type: None,
+                                    scene_item_enabled: None,
+                                    transform: None,
+                                    item_settings: Some(SceneItemSettings {
+                                        locked: None,
+                                        blend_mode: Some(blend_mode),
+                                    }),
+                                };
+                                let msg = SyncMessage::new(
+                                    SyncMessageType::SourceUpdate,
+                                    SyncTargetType::Source,
+                                    serde_json::to_value(&payload)
+                                        .unwrap_or(serde_json::Value::Null),
+                                );
+                                let _ = message_tx_clone.send(msg);
+                                println!(
+                                    "Sent blend mode update for item {} in {}",
+                                    scene_item_id, scene_name
+                                );
+                            });
+                        }
+                    }
+                    OBSEvent::SceneItemsReordered {
+                        scene_name,
+                        item_ids,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let obs_client_clone = obs_client.clone();
+                            let message_tx_clone = message_tx.clone();
+
+                            tokio::spawn(async move {
+                                let client_arc = obs_client_clone.get_client_arc();
+                                let client_lock = client_arc.read().await;
+                                let Some(client) = client_lock.as_ref() else {
+                                    return;
+                                };
+                                // The event only carries ids; slaves need names to match items
+                                let source_names: HashMap<i64, String> = match client
+                                    .scene_items()
+                                    .list(scene_name.as_str().into())
+                                    .await
+                                {
+                                    Ok(items) => items
+                                        .into_iter()
+                                        .map(|item| (item.id, item.source_name))
+                                        .collect(),
+                                    Err(e) => {
+                                        throttled_eprintln!(
+                                            "Failed to get scene items for {}: {}",
+                                            scene_name,
+                                            e
+                                        );
+                                        return;
+                                    }
+                                };
+                                let items = item_ids
+                                    .into_iter()
+                                    .filter_map(|id| {
+                                        Some(ReorderedItem {
+                                            scene_item_id: id,
+                                            source_name: source_names.get(&id)?.clone(),
+                                        })
+                                    })
+                                    .collect();
+                                Self::send_reorder_items(
+                                    &message_tx_clone,
+                                    ReorderItemsPayload { scene_name, items },
+                                );
+                            });
+                        }
+                    }
+                    OBSEvent::InputVolumeChanged {
+                        input_name,
+                        volume_db,
+                        volume_mul,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let payload = VolumeUpdatePayload {
+                                input_name,
+                                // -inf dB (silence) isn't representable in JSON
+                                volume_db: volume_db.max(MIN_VOLUME_DB),
+                                volume_mul,
+                            };
+                            let msg = SyncMessage::new(
+                                SyncMessageType::VolumeUpdate,
+                                SyncTargetType::Source,
+                                serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+                            );
+                            let _ = message_tx.send(msg);
+                        }
+                    }
+                    OBSEvent::InputMuteStateChanged { input_name, muted } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            let payload = MuteUpdatePayload { input_name, muted };
+                            let msg = SyncMessage::new(
+                                SyncMessageType::MuteUpdate,
+                                SyncTargetType::Source,
+                                serde_json::to_value(&payload).unwrap_or(serde_json::Value::Null),
+                            );
+                            let _ = message_tx.send(msg);
+                        }
+                    }
+                    OBSEvent::SourceFilterCreated {
+                        source_name,
+                        filter_name,
+                        filter_kind,
+                        filter_index,
+                        filter_settings,
+                    } => {
+                        if targets.contains(&SyncTargetType::Source) {
+                            Self::send_filter_lifecycle(
+                                &message_tx,
+                                FilterLifecyclePayload {
+                                    source_name,
+                                    filter_name,
+                                    action: FilterLifecycleAction::Created,
+                                    filter_kind: Some(filter_kind),
+                                    filter_index: Some(filter_index),
+                                    filter_settings: Some(filter_settings),
+                                },
+                            );
+                        }
+                    }
+                    OBSEvent::SourceFilterRemoved {
+                        source_name,
+                        filter_name,
                     } => {
                         if targets.contains(&SyncTargetType::Source) {
-                            let obs_client_clone = obs_client.clone();
-                            let message_tx_clone = message_tx.clone();
-                            let scene_name_clone = scene_name.clone();
-
-                            tokio::spawn(async move {
-                                let client_arc = obs_client_clone.get_client_arc();
-                                let client_lock = client_arc.read().await;
-
-                                if let Some(client) = client_lock.as_ref() {
-                                    let scene_id: obws::requests::scenes::SceneId =
-                                        obws::requests::scenes::SceneId::Name(&scene_name_clone);
-
-                                    // Get scene item to find source name
-                                    match client.scene_items().list(scene_id).await {
-                                        Ok(items) => {
-                                            if let Some(item) =
-                                                items.iter().find(|i| i.id == scene_item_id)
-                                            {
-                                                let scene_name_for_payload =
-                                                    scene_name_clone.clone();
-                                                let payload = SourceUpdatePayload {
-                                                    scene_name: scene_name_clone,
-                                                    scene_item_id,
-                                                    source_name: item.source_name.clone(),
-                                                    action: SourceUpdateAction::EnabledStateChanged,
-                                                    source_type: None,
-                                                    scene_item_enabled: Some(enabled),
-                                                    transform: None,
-                                                };
-
-                                                let payload_json = serde_json::to_value(&payload)
-                                                    .unwrap_or(serde_json::Value::Null);
-
-                                                let msg = SyncMessage::new(
-                                                    SyncMessageType::SourceUpdate,
-                                                    SyncTargetType::Source,
-                                                    payload_json,
-                                                );
-                                                let _ = message_tx_clone.send(msg);
-                                                println!(
-                                                    "Sent source enable state changed update for item {} in {}",
-                                                    scene_item_id, scene_name_for_payload
-                                                );
-                                            }
-                                        }
-                                        Err(e) => {
-                                            eprintln!(
-                                                "Failed to get scene items for {}: {}",
-                                                scene_name_clone, e
-                                            );
-                                        }
-                                    }
-                                }
-                            });
+                            Self::send_filter_lifecycle(
+                                &message_tx,
+                                FilterLifecyclePayload {
+                                    source_name,
+                                    filter_name,
+                                    action: FilterLifecycleAction::Removed,
+                                    filter_kind: None,
+                                    filter_index: None,
+                                    filter_settings: None,
+                                },
+                            );
+                        }
+                    }
+                    OBSEvent::RecordStateChanged { active } => {
+                        if targets.contains(&SyncTargetType::Output) {
+                            Self::send_output_control(&message_tx, OutputKind::Record, active);
+                        }
+                    }
+                    OBSEvent::StreamStateChanged { active } => {
+                        if targets.contains(&SyncTargetType::Output) {
+                            Self::send_output_control(&message_tx, OutputKind::Stream, active);
                         }
                     }
+                    OBSEvent::RawEvent {
+                        event_type,
+                        event_data,
+                    } => {
+                        let msg = SyncMessage::new(
+                            SyncMessageType::RawEvent,
+                            SyncTargetType::Program,
+                            serde_json::json!({
+                                "event_type": event_type,
+                                "event_data": event_data,
+                            }),
+                        );
+                        let _ = message_tx.send(msg);
+                    }
                 }
             }
         });
+        *self.monitor_task.write().await = Some(task);
     }
 
-    /// Read image file and encode to base64
-    async fn read_and_encode_image(file_path: &str) -> Option<String> {
+    /// Read image file, re-encode it if configured, gzip it when that helps,
+    /// and encode to base64.
+    async fn read_and_encode_image(
+        file_path: &str,
+        reencode: &ImageReencode,
+    ) -> Option<EncodedImage> {
         match tokio::fs::read(file_path).await {
             Ok(data) => {
-                let encoded =
-                    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data);
+                // Decoding, re-encoding and gzip are CPU-bound; keep them off the async runtime
+                let original_size = data.len();
+                let reencode = reencode.clone();
+                let (encoded, size, sent_size, compressed, format) =
+                    tokio::task::spawn_blocking(move || {
+                        let (data, format) = match reencode.apply(&data) {
+                            Some(reencoded) => (reencoded, Some(reencode.format.extension())),
+                            None => (data, None),
+                        };
+                        let compressed = compression::compress(&data);
+                        let sent = compressed.as_deref().unwrap_or(&data);
+                        let encoded = base64::Engine::encode(
+                            &base64::engine::general_purpose::STANDARD,
+                            sent,
+                        );
+                        (
+                            encoded,
+                            data.len(),
+                            sent.len(),
+                            compressed.is_some(),
+                            format,
+                        )
+                    })
+                    .await
+                    .ok()?;
+                if let Some(format) = format {
+                    println!(
+                        "Re-encoded image {} as {} ({} -> {} bytes)",
+                        file_path, format, original_size, size
+                    );
+                }
                 println!(
-                    "Encoded image: {} ({} bytes -> {} chars)",
+                    "Encoded image: {} ({} bytes, {} compressed -> {} chars)",
                     file_path,
-                    data.len(),
+                    size,
+                    sent_size,
                     encoded.len()
                 );
-                Some(encoded)
+                // The server decompresses it for slaves that don't support compression
+                Some(EncodedImage {
+                    data: encoded,
+                    format,
+                    compression: compressed.then_some(compression::GZIP),
+                })
             }
             Err(e) => {
-                eprintln!("Failed to read image file {}: {}", file_path, e);
+                throttled_eprintln!("Failed to read image file {}: {}", file_path, e);
                 None
             }
         }
     }
 
-    /// Get image source settings from OBS and encode the file
-    pub async fn get_image_data_for_source(&self, input_name: &str) -> Option<(String, String)> {
-        let client_arc = self.obs_client.get_client_arc();
-        let client_lock = client_arc.read().await;
-
-        if let Some(client) = client_lock.as_ref() {
-            // Get input settings to find the file path
-            match client
-                .inputs()
-                .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(input_name))
-                .await
-            {
-                Ok(settings) => {
-                    // Try to get file path from settings
-                    if let Some(file_path) = settings.settings.get("file").and_then(|v| v.as_str())
-                    {
-                        println!("Found image file for {}: {}", input_name, file_path);
+    /// Get image source settings from OBS and encode the file.
+    /// Returns the file path and the encoded image.
+    async fn read_image_data(
+        client: &obws::Client,
+        input_name: &str,
+        reencode: &ImageReencode,
+    ) -> Option<(String, EncodedImage)> {
+        // Get input settings to find the file path
+        match client
+            .inputs()
+            .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(input_name))
+            .await
+        {
+            Ok(settings) => {
+                // Try to get file path from settings
+                if let Some(file_path) = settings.settings.get("file").and_then(|v| v.as_str()) {
+                    println!("Found image file for {}: {}", input_name, file_path);
 
-                        // Read and encode the image
-                        if let Some(encoded_data) = Self::read_and_encode_image(file_path).await {
-                            return Some((file_path.to_string(), encoded_data));
-                        }
-                    } else {
-                        println!("No file path found in settings for {}", input_name);
+                    // Read and encode the image
+                    if let Some(encoded) = Self::read_and_encode_image(file_path, reencode).await {
+                        return Some((Self::file_name_for_slaves(file_path), encoded));
                     }
-                }
-                Err(e) => {
-                    eprintln!("Failed to get settings for {}: {}", input_name, e);
+                } else {
+                    println!("No file path found in settings for {}", input_name);
                 }
             }
+            Err(e) => {
+                throttled_eprintln!("Failed to get settings for {}: {}", input_name, e);
+            }
         }
 
         None
     }
 
-    /// Send initial state to newly connected slave
-    pub async fn send_initial_state(&self) -> Result<()> {
-        println!("Collecting full OBS state for new slave...");
-        let client_arc = self.obs_client.get_client_arc();
+    /// The file name of an image without the master's directories, which slaves refuse;
+    /// they only take the extension from it
+    fn file_name_for_slaves(file_path: &str) -> String {
+        file_path
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(file_path)
+            .to_string()
+    }
+
+    /// SHA-256 hex digest used to compare images and items without shipping them
+    pub fn content_hash(data: &str) -> String {
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(data.as_bytes());
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Collect the full OBS state (scenes, items, transforms, images, filters)
+    /// in the StateSync payload shape
+    pub async fn collect_state(
+        obs_client: &OBSClient,
+        filters: &SyncFilters,
+        image_reencode: &ImageReencode,
+        anchors: &TransformAnchors,
+    ) -> Result<serde_json::Value> {
+        Self::collect_state_with_skipped(obs_client, filters, image_reencode, anchors, None)
+            .await
+            .map(|(state, _)| state)
+    }
+
+    /// Same as `collect_state`, also returning the scenes that had to be skipped and why.
+    /// With `run`, progress is reported per scene and item, and the collection fails with
+    /// `ResyncCancelled` between them once the run is cancelled.
+    async fn collect_state_with_skipped(
+        obs_client: &OBSClient,
+        filters: &SyncFilters,
+        image_reencode: &ImageReencode,
+        anchors: &TransformAnchors,
+        run: Option<&ResyncRun>,
+    ) -> Result<(serde_json::Value, Vec<SkippedScene>)> {
+        let client_arc = obs_client.get_client_arc();
         let client_lock = client_arc.read().await;
+        let client = client_lock
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("OBS client not connected"))?;
 
-        if let Some(client) = client_lock.as_ref() {
-            // Get current program scene
-            let current_program_scene = match client.scenes().current_program_scene().await {
-                Ok(scene) => scene,
-                Err(e) => {
-                    eprintln!("Failed to get current scene: {}", e);
-                    return Ok(());
-                }
-            };
+        // Get current program scene
+        let current_program_scene = client
+            .scenes()
+            .current_program_scene()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get current scene: {}", e))?;
 
-            // Get preview scene if in studio mode
-            let current_preview_scene = client.scenes().current_preview_scene().await.ok();
+        // Get preview scene if in studio mode
+        let current_preview_scene = client.scenes().current_preview_scene().await.ok();
 
-            // Get all scenes
-            let scenes_list = match client.scenes().list().await {
-                Ok(scenes) => scenes,
-                Err(e) => {
-                    eprintln!("Failed to get scenes list: {}", e);
-                    return Ok(());
-                }
-            };
+        // Get all scenes
+        let scenes_list = client
+            .scenes()
+            .list()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get scenes list: {}", e))?;
 
-            let mut scenes_data = Vec::new();
-
-            // For each scene, get all items
-            for scene in scenes_list.scenes {
-                let scene_id: obws::requests::scenes::SceneId = scene.id.clone().into();
-                println!("Processing scene: {:?}", scene.id);
-
-                match client.scene_items().list(scene_id).await {
-                    Ok(items) => {
-                        let mut scene_items_data = Vec::new();
-
-                        for item in items {
-                            println!("  - Item: {} (id: {})", item.source_name, item.id);
-
-                            // Get transform for this item
-                            let transform =
-                                match client.scene_items().transform(scene_id, item.id).await {
-                                    Ok(t) => Some(serde_json::json!({
-                                        "position_x": t.position_x,
-                                        "position_y": t.position_y,
-                                        "rotation": t.rotation,
-                                        "scale_x": t.scale_x,
-                                        "scale_y": t.scale_y,
-                                        "width": t.width,
-                                        "height": t.height,
-                                    })),
-                                    Err(e) => {
-                                        eprintln!(
-                                            "Failed to get transform for {}: {}",
-                                            item.source_name, e
-                                        );
-                                        None
-                                    }
-                                };
+        let mut scenes_data = Vec::new();
+        let mut skipped_scenes = Vec::new();
+        // Filtered sources are named so slaves can tell them from sources the master lacks
+        let mut skipped_sources = std::collections::BTreeSet::new();
 
-                            // Get source type from item
-                            let source_type = item
-                                .input_kind
-                                .clone()
-                                .unwrap_or_else(|| "unknown".to_string());
+        // For each scene, get all items
+        let scene_count = scenes_list.scenes.len();
+        for (scene_index, scene) in scenes_list.scenes.into_iter().enumerate() {
+            ResyncRun::check(run)?;
+            let scene_id: obws::requests::scenes::SceneId = scene.id.clone().into();
+            let scene_name = scene.id.scene_name().to_string();
+            let scene_position = (scene_index + 1, scene_count);
+            if filters.is_scene_excluded(&scene_name) {
+                skipped_scenes.push(SkippedScene {
+                    name: scene_name,
+                    reason: "Excluded by sync filter".to_string(),
+                });
+                continue;
+            }
+            println!("Processing scene: {}", scene_name);
 
-                            // If it's an image source, get the image data
-                            let image_data = if source_type.contains("image") {
-                                self.get_image_data_for_source(&item.source_name).await.map(
-                                    |(path, data)| {
-                                        serde_json::json!({
-                                            "file": path,
-                                            "data": data
-                                        })
-                                    },
-                                )
-                            } else {
-                                None
-                            };
+            match client.scene_items().list(scene_id).await {
+                Ok(items) => {
+                    let mut scene_items_data = Vec::new();
+                    let item_count = items.len();
+                    ResyncRun::report(run, &scene_name, scene_position, (0, item_count));
 
-                            // Get filters for this source
-                            let mut filters_data = Vec::new();
-                            match client
-                                .filters()
-                                .list(obws::requests::sources::SourceId::Name(&item.source_name))
-                                .await
-                            {
-                                Ok(filters) => {
-                                    for filter in filters {
-                                        filters_data.push(serde_json::json!({
-                                            "name": filter.name,
-                                            "enabled": filter.enabled,
-                                            "settings": filter.settings
-                                        }));
-                                    }
+                    for (item_index, item) in items.into_iter().enumerate() {
+                        ResyncRun::check(run)?;
+                        ResyncRun::report(
+                            run,
+                            &scene_name,
+                            scene_position,
+                            (item_index + 1, item_count),
+                        );
+                        let source_type = OBSCommands::resolve_source_kind(client, &item).await;
+                        if filters.is_source_excluded(&item.source_name)
+                            || filters.is_input_kind_excluded(&source_type)
+                        {
+                            println!("  - Skipping filtered item: {}", item.source_name);
+                            skipped_sources.insert(item.source_name.clone());
+                            continue;
+                        }
+                        println!("  - Item: {} (id: {})", item.source_name, item.id);
+
+                        // Get transform for this item
+                        let transform =
+                            match client.scene_items().transform(scene_id, item.id).await {
+                                Ok(t) => {
+                                    let anchor = Self::anchored_position(
+                                        client,
+                                        anchors,
+                                        &item.source_name,
+                                        t.position_x as f64,
+                                        t.position_y as f64,
+                                    )
+                                    .await;
+                                    serde_json::to_value(Self::transform_data(&t, anchor)).ok()
                                 }
                                 Err(e) => {
                                     eprintln!(
-                                        "Failed to get filters for source {}: {}",
+                                        "Failed to get transform for {}: {}",
                                         item.source_name, e
                                     );
+                                    None
                                 }
-                            }
+                            };
+
+                        let enabled = client.scene_items().enabled(scene_id, item.id).await.ok();
+                        let locked = client.scene_items().locked(scene_id, item.id).await.ok();
 
-                            scene_items_data.push(serde_json::json!({
-                                "source_name": item.source_name,
-                                "scene_item_id": item.id,
-                                "source_type": source_type,
-                                "transform": transform,
-                                "image_data": image_data,
-                                "filters": filters_data,
-                            }));
+                        // If it's an image source, get the image data
+                        let image_data = if source_type.contains("image") {
+                            Self::read_image_data(client, &item.source_name, image_reencode)
+                                .await
+                                .map(|(path, encoded)| {
+                                    serde_json::json!({
+                                        "file": path,
+                                        "hash": Self::content_hash(&encoded.data),
+                                        "format": encoded.format,
+                                        "compression": encoded.compression,
+                                        "data": encoded.data
+                                    })
+                                })
+                        } else {
+                            None
+                        };
+
+                        // Get filters for this source
+                        let mut filters_data = Vec::new();
+                        match client
+                            .filters()
+                            .list(obws::requests::sources::SourceId::Name(&item.source_name))
+                            .await
+                        {
+                            Ok(filters) => {
+                                for filter in filters {
+                                    filters_data.push(serde_json::json!({
+                                        "name": filter.name,
+                                        "enabled": filter.enabled,
+                                        "settings": filter.settings
+                                    }));
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!(
+                                    "Failed to get filters for source {}: {}",
+                                    item.source_name, e
+                                );
+                            }
                         }
 
-                        // Use scene.id for name (SceneId doesn't implement Display)
-                        let scene_name = format!("{:?}", scene.id);
-                        scenes_data.push(serde_json::json!({
-                            "name": scene_name.clone(),
-                            "items": scene_items_data,
-                        }));
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to get items for scene {:?}: {}", scene.id, e);
+                        let mut item_data = serde_json::json!({
+                            "source_name": item.source_name,
+                            "scene_item_id": item.id,
+                            "scene_item_index": item.index,
+                            "source_type": source_type,
+                            "enabled": enabled,
+                            "locked": locked,
+                            "transform": transform,
+                            "image_data": image_data,
+                            "filters": filters_data,
+                        });
+                        // Hash everything except the raw image bytes (the image
+                        // hash already covers them) so slaves can skip unchanged items
+                        let mut hashed = item_data.clone();
+                        if let Some(image) = hashed["image_data"].as_object_mut() {
+                            image.remove("data");
+                        }
+                        item_data["item_hash"] =
+                            serde_json::Value::String(Self::content_hash(&hashed.to_string()));
+                        scene_items_data.push(item_data);
                     }
+
+                    scenes_data.push(serde_json::json!({
+                        "name": scene_name,
+                        "items": scene_items_data,
+                    }));
+                }
+                Err(e) => {
+                    eprintln!("Failed to get items for scene {}: {}", scene_name, e);
+                    skipped_scenes.push(SkippedScene {
+                        name: scene_name,
+                        reason: e.to_string(),
+                    });
                 }
             }
+        }
 
-            // Create comprehensive initial state payload
-            let payload = serde_json::json!({
-                "current_program_scene": current_program_scene,
-                "current_preview_scene": current_preview_scene,
+        // Create comprehensive initial state payload
+        Ok((
+            serde_json::json!({
+                "current_program_scene": current_program_scene.scene_name(),
+                "current_preview_scene": current_preview_scene.as_ref().map(|s| s.scene_name()),
                 "scenes": scenes_data,
+                "skipped_scenes": skipped_scenes.iter().map(|scene| &scene.name).collect::<Vec<_>>(),
+                "skipped_sources": skipped_sources,
+                "inputs": Self::collect_input_audio(client, filters).await,
+            }),
+            skipped_scenes,
+        ))
+    }
+
+    /// Volume and mute of every input with audio; inputs without audio fail the
+    /// volume request and are left out
+    async fn collect_input_audio(
+        client: &obws::Client,
+        filters: &SyncFilters,
+    ) -> Vec<InputAudioData> {
+        let inputs = match client.inputs().list(None).await {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                eprintln!("Failed to list inputs for audio state: {}", e);
+                return Vec::new();
+            }
+        };
+        let mut audio = Vec::new();
+        for input in inputs {
+            let input_name = input.id.name;
+            if filters.is_source_excluded(&input_name)
+                || filters.is_input_kind_excluded(&input.kind)
+            {
+                continue;
+            }
+            let input_id = obws::requests::inputs::InputId::Name(&input_name);
+            let Ok(volume) = client.inputs().volume(input_id).await else {
+                continue;
+            };
+            let muted = client.inputs().muted(input_id).await.ok();
+            audio.push(InputAudioData {
+                volume_db: (volume.db as f64).max(MIN_VOLUME_DB),
+                volume_mul: volume.mul as f64,
+                muted,
+                input_name,
             });
+        }
+        audio
+    }
+
+    /// Build a diagram-friendly scene graph from a collected state payload.
+    /// Image bytes are replaced by their hash so the result stays small.
+    pub fn scene_graph_from_state(state: &serde_json::Value) -> serde_json::Value {
+        let scenes: Vec<serde_json::Value> = state["scenes"]
+            .as_array()
+            .map(|scenes| {
+                scenes
+                    .iter()
+                    .map(|scene| {
+                        let items: Vec<serde_json::Value> = scene["items"]
+                            .as_array()
+                            .map(|items| {
+                                items
+                                    .iter()
+                                    .map(|item| {
+                                        let image = item["image_data"].as_object().map(|image| {
+                                            let data = image
+                                                .get("data")
+                                                .and_then(|v| v.as_str())
+                                                .unwrap_or("");
+                                            serde_json::json!({
+                                                "file": image.get("file").cloned(),
+                                                "hash": image
+                                                    .get("hash")
+                                                    .and_then(|v| v.as_str())
+                                                    .map(String::from)
+                                                    .unwrap_or_else(|| Self::content_hash(data)),
+                                                "encoded_size": data.len(),
+                                            })
+                                        });
+                                        serde_json::json!({
+                                            "source": item["source_name"],
+                                            "scene_item_id": item["scene_item_id"],
+                                            "source_type": item["source_type"],
+                                            "index": item["scene_item_index"],
+                                            "enabled": item["enabled"],
+                                            "locked": item["locked"],
+                                            "transform": item["transform"],
+                                            "filters": item["filters"],
+                                            "image": image,
+                                        })
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        serde_json::json!({
+                            "name": scene["name"],
+                            "items": items,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
 
-            let msg =
-                SyncMessage::new(SyncMessageType::StateSync, SyncTargetType::Program, payload);
+        serde_json::json!({
+            "current_program_scene": state["current_program_scene"],
+            "current_preview_scene": state["current_preview_scene"],
+            "scenes": scenes,
+        })
+    }
 
-            self.message_tx.send(msg)?;
+    /// Export the current scene graph without image payloads
+    pub async fn export_scene_graph(
+        obs_client: &OBSClient,
+        filters: &SyncFilters,
+    ) -> Result<serde_json::Value> {
+        let state = Self::collect_state(
+            obs_client,
+            filters,
+            &ImageReencode::default(),
+            &TransformAnchors::new(),
+        )
+        .await?;
+        Ok(Self::scene_graph_from_state(&state))
+    }
+
+    /// Snapshot of this machine's OBS: the scene graph with transforms and filters,
+    /// the current program and preview scenes, and input audio. Nothing is filtered
+    /// out, so dumps taken on a master and a slave can be diffed offline.
+    pub async fn dump_obs_state(obs_client: &OBSClient) -> Result<serde_json::Value> {
+        let state = Self::collect_state(
+            obs_client,
+            &SyncFilters::default(),
+            &ImageReencode::default(),
+            &TransformAnchors::new(),
+        )
+        .await?;
+        let mut dump = Self::scene_graph_from_state(&state);
+        dump["inputs"] = state["inputs"].clone();
+        dump["captured_at"] = serde_json::json!(chrono::Utc::now().to_rfc3339());
+        Ok(dump)
+    }
+
+    /// Run the initial-state collection and write the StateSync message that would be
+    /// broadcast to `path`, preceded by a summary, instead of sending it
+    pub async fn dump_state(
+        obs_client: &OBSClient,
+        filters: &SyncFilters,
+        image_reencode: &ImageReencode,
+        anchors: &TransformAnchors,
+        path: &Path,
+    ) -> Result<StateDumpSummary> {
+        let started = std::time::Instant::now();
+        let (payload, skipped_scenes) =
+            Self::collect_state_with_skipped(obs_client, filters, image_reencode, anchors, None)
+                .await?;
+        let collection_ms = started.elapsed().as_millis() as u64;
+        let dump = StateDump::new(payload, skipped_scenes, collection_ms);
+        let summary = dump.summary.clone();
+
+        let json = serde_json::to_string_pretty(&dump)?;
+        tokio::fs::write(path, json)
+            .await
+            .with_context(|| format!("Failed to write state dump to {:?}", path))?;
+        println!(
+            "Dumped master state to {:?} ({} scenes, {} items, {} images, {} skipped) in {}ms",
+            path,
+            summary.scene_count,
+            summary.item_count,
+            summary.images.len(),
+            summary.skipped_scenes.len(),
+            summary.collection_ms
+        );
+        Ok(summary)
+    }
+
+    /// Send the full state to one slave (`target`, by client id), or to all of them.
+    /// The StateSync is marked `force`, so slaves reapply items they think are unchanged.
+    /// Fails while sync is paused.
+    pub async fn send_initial_state(&self, target: Option<&str>) -> Result<()> {
+        if self.is_paused() {
+            anyhow::bail!("Master sync is paused");
+        }
+        self.send_state(target, &HashSet::new(), true).await
+    }
+
+    /// Send the full state to one slave, leaving out the bytes of the images whose master
+    /// hash is in `held_images`; the slave reuses its own files for those
+    pub async fn send_initial_state_to(
+        &self,
+        client_id: &str,
+        held_images: &HashSet<String>,
+    ) -> Result<()> {
+        // Slaves catch up when sync is resumed with a resync
+        if self.is_paused() {
+            println!("Master sync is paused, not sending state to {}", client_id);
+            return Ok(());
+        }
+        self.send_state(Some(client_id), held_images, false).await
+    }
+
+    async fn send_state(
+        &self,
+        target: Option<&str>,
+        held_images: &HashSet<String>,
+        force: bool,
+    ) -> Result<()> {
+        println!("Collecting full OBS state for new slave...");
+        let filters = self.filters.read().await.clone();
+        let image_reencode = self.image_reencode.read().await.clone();
+        let anchors = self.transform_anchors.read().await.clone();
+        let run = ResyncRun {
+            generation: self.resync_generation.clone(),
+            started: self.resync_generation.load(Ordering::SeqCst),
+            target: target.map(str::to_string),
+            progress: self.resync_progress_callback.read().await.clone(),
+        };
+        let collected = Self::collect_state_with_skipped(
+            &self.obs_client,
+            &filters,
+            &image_reencode,
+            &anchors,
+            Some(&run),
+        )
+        .await;
+        let result = self
+            .send_collected_state(target, held_images, force, collected)
+            .await;
+        run.finish();
+        result
+    }
+
+    /// Second half of `send_initial_state`
+    async fn send_collected_state(
+        &self,
+        target: Option<&str>,
+        held_images: &HashSet<String>,
+        force: bool,
+        collected: Result<(serde_json::Value, Vec<SkippedScene>)>,
+    ) -> Result<()> {
+        let mut payload = match collected {
+            Ok((payload, _)) => payload,
+            Err(e) if e.is::<ResyncCancelled>() => {
+                println!("State sync cancelled before anything was sent");
+                return Err(e);
+            }
+            Err(e) => {
+                throttled_eprintln!("Failed to collect OBS state: {}", e);
+                return Ok(());
+            }
+        };
+        let scene_count = payload["scenes"].as_array().map(|s| s.len()).unwrap_or(0);
+        self.record_ambiguous_sources(&payload).await;
+
+        // Remember what the slaves now have so later ImageUpdates only carry real changes.
+        // A single slave catching up says nothing about what the others have.
+        if target.is_none() {
+            let mut hashes = self.image_hashes.write().await;
+            for item in payload["scenes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|scene| scene["items"].as_array())
+                .flatten()
+            {
+                if let (Some(source_name), Some(hash)) = (
+                    item["source_name"].as_str(),
+                    item["image_data"]["hash"].as_str(),
+                ) {
+                    hashes.insert(source_name.to_string(), hash.to_string());
+                }
+            }
+        }
+
+        if !held_images.is_empty() {
+            let (sent, left_out) = Self::leave_out_held_images(&mut payload, held_images);
             println!(
-                "✓ Sent complete initial state to slave ({} scenes)",
-                scenes_data.len()
+                "Sending {} images, leaving out {} the slave already has",
+                sent, left_out
             );
         }
 
+        if force {
+            payload["force"] = serde_json::json!(true);
+        }
+
+        let msg = SyncMessage::new(SyncMessageType::StateSync, SyncTargetType::Program, payload)
+            .with_ack();
+
+        match target {
+            Some(client_id) => {
+                let sender = self
+                    .client_sender
+                    .read()
+                    .await
+                    .clone()
+                    .context("No way to send to a single slave")?;
+                sender(client_id.to_string(), msg).await?;
+                println!(
+                    "✓ Sent complete state to slave {} ({} scenes)",
+                    client_id, scene_count
+                );
+            }
+            None => {
+                self.message_tx.send(msg)?;
+                println!(
+                    "✓ Sent complete state to all slaves ({} scenes)",
+                    scene_count
+                );
+            }
+        }
+
         Ok(())
     }
+
+    /// Drop the bytes of the StateSync images whose hash is in `held_images`, keeping
+    /// the hash so the slave can find its copy. Returns how many images still carry
+    /// their bytes and how many were left out.
+    fn leave_out_held_images(
+        payload: &mut serde_json::Value,
+        held_images: &HashSet<String>,
+    ) -> (usize, usize) {
+        let (mut sent, mut left_out) = (0, 0);
+        let images = payload["scenes"]
+            .as_array_mut()
+            .into_iter()
+            .flatten()
+            .filter_map(|scene| scene["items"].as_array_mut())
+            .flatten()
+            .filter_map(|item| item["image_data"].as_object_mut());
+        for image in images {
+            let held = image
+                .get("hash")
+                .and_then(|v| v.as_str())
+                .is_some_and(|hash| held_images.contains(hash));
+            if held {
+                image.remove("data");
+                left_out += 1;
+            } else if image.contains_key("data") {
+                sent += 1;
+            }
+        }
+        (sent, left_out)
+    }
+
+    /// Re-send one source's current state to all slaves: its transform in every scene
+    /// that contains it, its input settings, its image and its filters. Corrects a
+    /// single desynced source without a full StateSync. Returns the number of messages sent.
+    pub async fn push_source(&self, source_name: &str) -> Result<usize> {
+        if self.is_paused() {
+            anyhow::bail!("Master sync is paused");
+        }
+        let filters = self.filters.read().await.clone();
+        if filters.is_source_excluded(source_name) {
+            anyhow::bail!("{} is excluded by sync filters", source_name);
+        }
+        let image_reencode = self.image_reencode.read().await.clone();
+        let anchors = self.transform_anchors.read().await.clone();
+
+        let client_arc = self.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("OBS client not connected"))?;
+
+        let mut messages = Vec::new();
+        let mut first_item = None;
+
+        // Transforms, in every scene the source appears in
+        let scenes = client
+            .scenes()
+            .list()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get scenes list: {}", e))?;
+        for scene in scenes.scenes {
+            let scene_id: obws::requests::scenes::SceneId = scene.id.clone().into();
+            let scene_name = scene.id.scene_name().to_string();
+            if filters.is_scene_excluded(&scene_name) {
+                continue;
+            }
+            let items = match client.scene_items().list(scene_id).await {
+                Ok(items) => items,
+                Err(e) => {
+                    eprintln!("Failed to get items for scene {}: {}", scene_name, e);
+                    continue;
+                }
+            };
+            for item in items.iter().filter(|item| item.source_name == source_name) {
+                first_item.get_or_insert((scene_name.clone(), item.id));
+                if filters.is_message_type_excluded(&SyncMessageType::TransformUpdate) {
+                    continue;
+                }
+                match Self::read_transform_update(client, &anchors, scene_name.clone(), item.id)
+                    .await
+                {
+                    Ok(update) => messages.push(SyncMessage::new(
+                        SyncMessageType::TransformUpdate,
+                        SyncTargetType::Source,
+                        serde_json::to_value(&update).unwrap_or(serde_json::Value::Null),
+                    )),
+                    Err(e) => eprintln!(
+                        "Failed to get transform for {} in {}: {}",
+                        source_name, scene_name, e
+                    ),
+                }
+            }
+        }
+        let Some((scene_name, scene_item_id)) = first_item else {
+            anyhow::bail!("{} is not in any synced scene", source_name);
+        };
+
+        // Input settings and image; scenes used as sources have neither
+        if let Ok(settings) = client
+            .inputs()
+            .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(source_name))
+            .await
+        {
+            if !filters.is_input_kind_excluded(&settings.kind) {
+                if !filters.is_message_type_excluded(&SyncMessageType::SourceSettingsUpdate) {
+                    messages.extend(Self::source_settings_message(
+                        source_name,
+                        settings.kind.clone(),
+                        &settings.settings,
+                    ));
+                }
+                let file_path = settings
+                    .settings
+                    .get("file")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                if !file_path.is_empty()
+                    && !filters.is_message_type_excluded(&SyncMessageType::ImageUpdate)
+                {
+                    if let Some(encoded) =
+                        Self::read_and_encode_image(file_path, &image_reencode).await
+                    {
+                        let image_hash = Self::content_hash(&encoded.data);
+                        self.image_hashes
+                            .write()
+                            .await
+                            .insert(source_name.to_string(), image_hash.clone());
+                        messages.push(SyncMessage::new(
+                            SyncMessageType::ImageUpdate,
+                            SyncTargetType::Source,
+                            serde_json::json!({
+                                "scene_name": "",
+                                "source_name": source_name,
+                                "file": Self::file_name_for_slaves(file_path),
+                                "image_data": encoded.data,
+                                "image_hash": image_hash,
+                                "image_format": encoded.format,
+                                "compression": encoded.compression,
+                            }),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Filters
+        if !filters.is_message_type_excluded(&SyncMessageType::FilterUpdate) {
+            match client
+                .filters()
+                .list(obws::requests::sources::SourceId::Name(source_name))
+                .await
+            {
+                Ok(source_filters) => {
+                    for filter in source_filters {
+                        messages.push(SyncMessage::new(
+                            SyncMessageType::FilterUpdate,
+                            SyncTargetType::Source,
+                            serde_json::json!({
+                                "scene_name": scene_name,
+                                "scene_item_id": scene_item_id,
+                                "source_name": source_name,
+                                "filter_name": filter.name,
+                                "filter_settings": filter.settings
+                            }),
+                        ));
+                    }
+                }
+                Err(e) => eprintln!("Failed to get filters for source {}: {}", source_name, e),
+            }
+        }
+
+        let count = messages.len();
+        for msg in messages {
+            self.message_tx.send(msg)?;
+        }
+        println!(
+            "✓ Pushed {} to all slaves ({} messages)",
+            source_name, count
+        );
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_graph_keeps_image_hashes_but_not_bytes() {
+        let data = "aGVsbG8gd29ybGQ=";
+        let state = serde_json::json!({
+            "current_program_scene": "Main",
+            "current_preview_scene": null,
+            "scenes": [{
+                "name": "Main",
+                "items": [
+                    {
+                        "source_name": "Logo",
+                        "scene_item_id": 1,
+                        "scene_item_index": 0,
+                        "source_type": "image_source",
+                        "image_data": {
+                            "file": "/images/logo.png",
+                            "hash": "abc123",
+                            "data": data,
+                        },
+                    },
+                    {
+                        "source_name": "Overlay",
+                        "scene_item_id": 2,
+                        "scene_item_index": 1,
+                        "source_type": "image_source",
+                        "image_data": { "file": "/images/overlay.png", "data": data },
+                    },
+                ],
+            }],
+        });
+
+        let graph = MasterSync::scene_graph_from_state(&state);
+
+        assert!(!graph.to_string().contains(data));
+        let items = graph["scenes"][0]["items"].as_array().unwrap();
+        assert_eq!(items[0]["image"]["hash"], "abc123");
+        assert_eq!(items[0]["image"]["encoded_size"], data.len());
+        // Images collected without a hash get one computed from their bytes
+        assert_eq!(items[1]["image"]["hash"], MasterSync::content_hash(data));
+        assert_eq!(graph["current_program_scene"], "Main");
+    }
+
+    #[test]
+    fn reconnect_state_sync_carries_only_the_changed_image() {
+        let image = |name: &str, hash: &str| {
+            serde_json::json!({
+                "source_name": name,
+                "image_data": { "file": format!("/images/{}.png", name), "hash": hash, "data": "AAAA" },
+            })
+        };
+        let mut payload = serde_json::json!({
+            "scenes": [
+                { "name": "Main", "items": [image("Logo", "logo-v1"), image("Lower third", "lower-v2")] },
+                { "name": "Break", "items": [image("Logo", "logo-v1"), { "source_name": "Camera" }] },
+            ],
+        });
+        // The slave has both images as they were before the lower third changed
+        let held: HashSet<String> = ["logo-v1", "lower-v1"].map(String::from).into();
+
+        let (sent, left_out) = MasterSync::leave_out_held_images(&mut payload, &held);
+
+        assert_eq!((sent, left_out), (1, 2));
+        let main = &payload["scenes"][0]["items"];
+        assert!(main[0]["image_data"].get("data").is_none());
+        assert_eq!(main[0]["image_data"]["hash"], "logo-v1");
+        assert_eq!(main[1]["image_data"]["data"], "AAAA");
+        assert!(payload["scenes"][1]["items"][0]["image_data"]
+            .get("data")
+            .is_none());
+    }
+
+    #[test]
+    fn slaves_get_only_the_image_file_name() {
+        assert_eq!(
+            MasterSync::file_name_for_slaves("/home/obs/images/logo.png"),
+            "logo.png"
+        );
+        assert_eq!(
+            MasterSync::file_name_for_slaves("C:\\Users\\obs\\logo.png"),
+            "logo.png"
+        );
+        assert_eq!(MasterSync::file_name_for_slaves("logo.png"), "logo.png");
+    }
+
+    #[test]
+    fn program_only_override_stops_transform_updates_for_its_scene() {
+        let global = default_sync_targets();
+        let overrides =
+            SceneTargetOverrides::from([("Cam".to_string(), vec![SyncTargetType::Program])]);
+        let moved = |scene: &str| OBSEvent::SceneItemTransformChanged {
+            scene_name: scene.to_string(),
+            scene_item_id: 1,
+        };
+
+        let cam = MasterSync::event_targets(&global, &overrides, &moved("Cam"));
+        assert!(!cam.contains(&SyncTargetType::Source));
+        let main = MasterSync::event_targets(&global, &overrides, &moved("Main"));
+        assert!(main.contains(&SyncTargetType::Source));
+    }
+
+    #[tokio::test]
+    async fn paused_master_sends_nothing_for_obs_events() {
+        let (master_sync, mut outgoing) = MasterSync::new(Arc::new(OBSClient::new()));
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        master_sync.start_monitoring(event_rx).await;
+        let switched = |scene: &str| OBSEvent::SceneChanged {
+            scene_name: scene.to_string(),
+        };
+        let wait = Duration::from_millis(300);
+
+        master_sync.pause();
+        event_tx.send(switched("Experiment")).unwrap();
+        assert!(tokio::time::timeout(wait, outgoing.recv()).await.is_err());
+        // An explicit resync says it didn't happen
+        let error = master_sync.send_initial_state(None).await.unwrap_err();
+        assert!(error.to_string().contains("paused"));
+
+        master_sync.resume();
+        event_tx.send(switched("Live")).unwrap();
+        let message = tokio::time::timeout(wait, outgoing.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.message_type, SyncMessageType::SceneChange);
+        assert_eq!(message.payload["scene_name"], "Live");
+    }
+
+    #[test]
+    fn only_blend_modes_seen_before_count_as_changes() {
+        let previous: BlendModes = HashMap::from([
+            (("Main".to_string(), 1), "OBS_BLEND_NORMAL".to_string()),
+            (("Main".to_string(), 2), "OBS_BLEND_NORMAL".to_string()),
+        ]);
+        let current: BlendModes = HashMap::from([
+            (("Main".to_string(), 1), "OBS_BLEND_NORMAL".to_string()),
+            (("Main".to_string(), 2), "OBS_BLEND_MULTIPLY".to_string()),
+            // Added since the last poll
+            (("Main".to_string(), 3), "OBS_BLEND_SCREEN".to_string()),
+        ]);
+        let changes = blend_mode_changes(&previous, &current);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(
+            &changes[0],
+            OBSEvent::SceneItemBlendModeChanged { scene_name, scene_item_id: 2, blend_mode }
+                if scene_name == "Main" && blend_mode == "OBS_BLEND_MULTIPLY"
+        ));
+    }
+
+    #[test]
+    fn state_dump_reads_back_as_a_state_sync() {
+        let payload = serde_json::json!({
+            "current_program_scene": "Main",
+            "current_preview_scene": "Break",
+            "scenes": [{
+                "name": "Main",
+                "items": [
+                    {
+                        "source_name": "Logo",
+                        "scene_item_id": 1,
+                        "source_type": "image_source",
+                        "image_data": { "file": "logo.png", "hash": "abc123", "data": "AAAA" },
+                    },
+                    {
+                        "source_name": "Camera",
+                        "scene_item_id": 2,
+                        "source_type": "dshow_input",
+                        "image_data": null,
+                    },
+                ],
+            }],
+            "skipped_scenes": ["Broken"],
+            "skipped_sources": [],
+        });
+        let skipped = vec![SkippedScene {
+            name: "Broken".to_string(),
+            reason: "no such scene".to_string(),
+        }];
+
+        let json = serde_json::to_string_pretty(&StateDump::new(payload, skipped, 12)).unwrap();
+        let dump: StateDump = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(dump.message.message_type, SyncMessageType::StateSync);
+        let state: crate::sync::protocol::StateSyncPayload =
+            serde_json::from_value(dump.message.payload).unwrap();
+        assert_eq!(state.current_program_scene, "Main");
+        assert_eq!(state.scenes[0].items.len(), 2);
+        assert_eq!(state.skipped_scenes, vec!["Broken"]);
+        assert_eq!((dump.summary.scene_count, dump.summary.item_count), (1, 2));
+        assert_eq!(dump.summary.images.len(), 1);
+        assert_eq!(dump.summary.images[0].encoded_size, 4);
+        assert_eq!(dump.summary.skipped_scenes[0].name, "Broken");
+    }
+
+    #[tokio::test]
+    async fn duplicated_source_is_reported_once() {
+        let (master_sync, _rx) = MasterSync::new(Arc::new(OBSClient::new()));
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let reported_by_callback = reported.clone();
+        master_sync
+            .set_ambiguous_sources_callback(move |found| {
+                reported_by_callback.lock().unwrap().push(found);
+            })
+            .await;
+        let state = serde_json::json!({
+            "scenes": [
+                { "name": "Main", "items": [
+                    { "source_name": "Camera", "scene_item_id": 1 },
+                    { "source_name": "Logo", "scene_item_id": 2 },
+                    { "source_name": "Camera", "scene_item_id": 5 },
+                ] },
+                // The same source once in each of two scenes is fine
+                { "name": "Break", "items": [
+                    { "source_name": "Logo", "scene_item_id": 1 },
+                ] },
+            ],
+        });
+
+        master_sync.record_ambiguous_sources(&state).await;
+        master_sync.record_ambiguous_sources(&state).await;
+
+        let ambiguous = master_sync.ambiguous_sources().await;
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 1, "the warning repeated");
+        assert_eq!(ambiguous.len(), 1);
+        assert_eq!(
+            (
+                ambiguous[0].scene_name.as_str(),
+                ambiguous[0].source_name.as_str()
+            ),
+            ("Main", "Camera")
+        );
+        assert_eq!(ambiguous[0].scene_item_ids, vec![1, 5]);
+        assert_eq!(reported[0][0].scene_item_ids, vec![1, 5]);
+    }
 }
@@ -1,4 +1,14 @@
+pub mod anchor;
+pub mod cache;
+pub mod command;
+pub mod compression;
 pub mod diff;
+pub mod echo;
+pub mod filters;
+pub mod image_cache;
 pub mod master;
+pub mod profile;
 pub mod protocol;
+pub mod reencode;
+pub mod sanitize;
 pub mod slave;
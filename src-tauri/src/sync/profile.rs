@@ -0,0 +1,113 @@
+use super::diff::DiffTolerance;
+use serde::{Deserialize, Serialize};
+
+/// Preset bundles of sync runtime parameters
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncProfile {
+    #[default]
+    Normal,
+    Aggressive,
+    Relaxed,
+}
+
+/// Runtime parameters governed by a sync profile
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTuning {
+    /// Interval between slave desync checks
+    pub desync_check_interval_secs: u64,
+    /// Tolerances used when comparing local and expected state
+    pub diff_tolerance: DiffTolerance,
+    /// Quiet period before the master sends a transform update (0 = send with the next
+    /// transform batch)
+    pub transform_debounce_ms: u64,
+    /// Consecutive desynced checks before the slave requests a resync (0 = never)
+    pub auto_resync_threshold: u32,
+}
+
+impl SyncProfile {
+    pub fn tuning(&self) -> SyncTuning {
+        match self {
+            SyncProfile::Normal => SyncTuning {
+                desync_check_interval_secs: 5,
+                diff_tolerance: DiffTolerance::default(),
+                transform_debounce_ms: 0,
+                auto_resync_threshold: 0,
+            },
+            SyncProfile::Aggressive => SyncTuning {
+                desync_check_interval_secs: 2,
+                diff_tolerance: DiffTolerance {
+                    position: 0.25,
+                    scale: 0.005,
+                    rotation: 0.25,
+                    crop: 0,
+                },
+                transform_debounce_ms: 0,
+                auto_resync_threshold: 2,
+            },
+            SyncProfile::Relaxed => SyncTuning {
+                desync_check_interval_secs: 15,
+                diff_tolerance: DiffTolerance {
+                    position: 2.0,
+                    scale: 0.05,
+                    rotation: 2.0,
+                    crop: 0,
+                },
+                transform_debounce_ms: 250,
+                auto_resync_threshold: 0,
+            },
+        }
+    }
+}
+
+impl Default for SyncTuning {
+    fn default() -> Self {
+        SyncProfile::default().tuning()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_profile_changes_every_governed_parameter() {
+        // Destructured so a new governed parameter has to be added here too
+        let SyncTuning {
+            desync_check_interval_secs,
+            diff_tolerance,
+            transform_debounce_ms,
+            auto_resync_threshold,
+        } = SyncProfile::Aggressive.tuning();
+
+        let relaxed = SyncProfile::Relaxed.tuning();
+        assert!(relaxed.desync_check_interval_secs > desync_check_interval_secs);
+        assert!(relaxed.diff_tolerance.position > diff_tolerance.position);
+        assert!(relaxed.diff_tolerance.scale > diff_tolerance.scale);
+        assert!(relaxed.diff_tolerance.rotation > diff_tolerance.rotation);
+        assert!(relaxed.transform_debounce_ms > transform_debounce_ms);
+        assert!(relaxed.auto_resync_threshold < auto_resync_threshold);
+    }
+
+    #[test]
+    fn normal_profile_sits_between_aggressive_and_relaxed() {
+        let (aggressive, normal, relaxed) = (
+            SyncProfile::Aggressive.tuning(),
+            SyncProfile::Normal.tuning(),
+            SyncProfile::Relaxed.tuning(),
+        );
+        for (lower, higher) in [(&aggressive, &normal), (&normal, &relaxed)] {
+            assert!(lower.desync_check_interval_secs < higher.desync_check_interval_secs);
+            assert!(lower.diff_tolerance.position < higher.diff_tolerance.position);
+            assert!(lower.diff_tolerance.scale < higher.diff_tolerance.scale);
+            assert!(lower.diff_tolerance.rotation < higher.diff_tolerance.rotation);
+            assert!(lower.transform_debounce_ms <= higher.transform_debounce_ms);
+        }
+    }
+
+    #[test]
+    fn default_tuning_is_the_normal_profile() {
+        assert_eq!(SyncTuning::default(), SyncProfile::Normal.tuning());
+    }
+}
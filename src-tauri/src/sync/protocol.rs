@@ -1,5 +1,29 @@
+use super::anchor::AnchoredPosition;
+use super::command::SemanticCommand;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+
+/// Wire protocol version exchanged in ServerHello/ClientHello. Bump it when payloads
+/// change in a way older peers would misread. Version 1 is every build from before the
+/// hello exchange, so peers that send no hello count as version 1.
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Protocol version assumed for peers that don't send a hello
+pub const LEGACY_PROTOCOL_VERSION: u16 = 1;
+
+/// Peers within one version of each other can talk; both sides apply the same rule
+pub fn is_protocol_compatible(remote_version: u16) -> bool {
+    remote_version.abs_diff(PROTOCOL_VERSION) <= 1
+}
+
+/// Close reason sent to (and shown by) a peer running an incompatible protocol
+pub fn protocol_mismatch_reason(master_version: u16, slave_version: u16) -> String {
+    format!(
+        "Protocol version mismatch: master v{}, slave v{}",
+        master_version, slave_version
+    )
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -13,6 +37,26 @@ pub enum SyncMessageType {
     StateSync,
     StateSyncRequest, // Slave requests initial state from Master
     StateReport,      // Slave reports its current state to Master
+    Capabilities,     // Peers exchange supported features after connecting
+    RawEvent,         // Passthrough of a raw OBS event; ignored by the built-in slave
+    LatencyProbe,     // Master asks slaves to acknowledge a tagged no-op
+    LatencyAck,       // Slave acknowledges a LatencyProbe
+    Command,          // Semantic action (SemanticCommand) for slaves to execute
+    MasterHandoff,    // Outgoing master redirects slaves to its replacement
+    Ack,              // Slave confirms it applied a message sent with `requires_ack`
+    Auth,             // First frame from a slave, carrying the master's shared token
+    VolumeUpdate,
+    MuteUpdate,
+    SceneLifecycle,       // Scene created or removed on the master
+    OutputControl,        // Recording or streaming started/stopped on the master
+    ServerShutdown,       // Master is stopping on purpose; slaves should not reconnect
+    ServerHello,          // First frame from the master on every connection, before auth
+    ClientHello,          // Slave's protocol version, sent right after connecting (and auth)
+    TransformBatch,       // Several TransformUpdates coalesced by the master
+    SourceSettingsUpdate, // Input settings (text, URLs, colors...) changed on the master
+    FilterLifecycle,      // Filter added to or removed from a source on the master
+    ResumeFrom,           // Where in the master's history a new connection picks up
+    ReorderItems,         // Stacking order of a scene's items changed on the master
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -21,6 +65,149 @@ pub enum SyncTargetType {
     Source,
     Preview,
     Program,
+    /// Recording and streaming; opt-in
+    Output,
+}
+
+/// Optional protocol features a build may support
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FeatureFlags {
+    pub tls: bool,
+    pub auth: bool,
+    pub compression: bool,
+    pub msgpack: bool,
+}
+
+/// Message types, targets and features supported by a peer. A newer peer may list
+/// types and targets this build doesn't know; those are left out when parsing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Capabilities {
+    #[serde(deserialize_with = "known_only")]
+    pub message_types: Vec<SyncMessageType>,
+    #[serde(deserialize_with = "known_only")]
+    pub target_types: Vec<SyncTargetType>,
+    #[serde(default)]
+    pub features: FeatureFlags,
+}
+
+/// Deserialize a list, dropping the entries that don't parse as `T`
+fn known_only<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let values = Vec::<Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
+
+impl Capabilities {
+    /// Capabilities of one connection: this build's, with TLS and auth as configured
+    pub fn for_connection(tls: bool, auth: bool) -> Self {
+        let mut capabilities = Self::current();
+        capabilities.features.tls = tls;
+        capabilities.features.auth = auth;
+        capabilities
+    }
+
+    /// Capabilities of this build; TLS and auth depend on the connection
+    pub fn current() -> Self {
+        Self {
+            message_types: vec![
+                SyncMessageType::SourceUpdate,
+                SyncMessageType::TransformUpdate,
+                SyncMessageType::SceneChange,
+                SyncMessageType::ImageUpdate,
+                SyncMessageType::FilterUpdate,
+                SyncMessageType::Heartbeat,
+                SyncMessageType::StateSync,
+                SyncMessageType::StateSyncRequest,
+                SyncMessageType::StateReport,
+                SyncMessageType::Capabilities,
+                SyncMessageType::RawEvent,
+                SyncMessageType::LatencyProbe,
+                SyncMessageType::LatencyAck,
+                SyncMessageType::Command,
+                SyncMessageType::MasterHandoff,
+                SyncMessageType::Ack,
+                SyncMessageType::Auth,
+                SyncMessageType::VolumeUpdate,
+                SyncMessageType::MuteUpdate,
+                SyncMessageType::SceneLifecycle,
+                SyncMessageType::OutputControl,
+                SyncMessageType::ServerShutdown,
+                SyncMessageType::ServerHello,
+                SyncMessageType::ClientHello,
+                SyncMessageType::TransformBatch,
+                SyncMessageType::SourceSettingsUpdate,
+                SyncMessageType::FilterLifecycle,
+                SyncMessageType::ResumeFrom,
+                SyncMessageType::ReorderItems,
+            ],
+            target_types: vec![
+                SyncTargetType::Source,
+                SyncTargetType::Preview,
+                SyncTargetType::Program,
+                SyncTargetType::Output,
+            ],
+            features: FeatureFlags {
+                // Gzip-compressed image payloads (the `compression` payload field)
+                compression: true,
+                ..FeatureFlags::default()
+            },
+        }
+    }
+
+    /// Capabilities supported by both peers
+    pub fn intersect(&self, other: &Capabilities) -> Capabilities {
+        Capabilities {
+            message_types: self
+                .message_types
+                .iter()
+                .filter(|t| other.message_types.contains(t))
+                .cloned()
+                .collect(),
+            target_types: self
+                .target_types
+                .iter()
+                .filter(|t| other.target_types.contains(t))
+                .cloned()
+                .collect(),
+            features: FeatureFlags {
+                tls: self.features.tls && other.features.tls,
+                auth: self.features.auth && other.features.auth,
+                compression: self.features.compression && other.features.compression,
+                msgpack: self.features.msgpack && other.features.msgpack,
+            },
+        }
+    }
+
+    pub fn supports(&self, message_type: &SyncMessageType) -> bool {
+        self.message_types.contains(message_type)
+    }
+}
+
+/// Per-scene sync targets; scenes not listed fall back to the global targets
+pub type SceneTargetOverrides = HashMap<String, Vec<SyncTargetType>>;
+
+/// Targets synced when none were chosen
+pub fn default_sync_targets() -> Vec<SyncTargetType> {
+    vec![SyncTargetType::Program, SyncTargetType::Source]
+}
+
+/// Resolve the effective targets for a scene
+pub fn targets_for_scene(
+    overrides: &SceneTargetOverrides,
+    scene_name: &str,
+    global: &[SyncTargetType],
+) -> Vec<SyncTargetType> {
+    overrides
+        .get(scene_name)
+        .cloned()
+        .unwrap_or_else(|| global.to_vec())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +217,20 @@ pub struct SyncMessage {
     pub timestamp: i64,
     pub target_type: SyncTargetType,
     pub payload: Value,
+    /// Unique per message; identifies it in an `Ack`
+    #[serde(default)]
+    pub message_id: String,
+    /// The master resends the message until the slave acknowledges applying it
+    #[serde(default)]
+    pub requires_ack: bool,
+    /// Peer whose local OBS change produced the message in bidirectional sync;
+    /// empty for the master's own changes
+    #[serde(default)]
+    pub origin_id: String,
+    /// Position in the master's broadcast history, so a slave that reconnects after a
+    /// short drop can ask for only what it missed; unset for messages to one slave
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
 }
 
 impl SyncMessage {
@@ -39,16 +240,209 @@ impl SyncMessage {
             timestamp: chrono::Utc::now().timestamp_millis(),
             target_type,
             payload,
+            message_id: uuid::Uuid::new_v4().to_string(),
+            requires_ack: false,
+            origin_id: String::new(),
+            seq: None,
         }
     }
 
-    pub fn state_sync_request() -> Self {
+    /// Mark a message whose loss would leave slaves wrong until the next full resync
+    pub fn with_ack(self) -> Self {
+        Self {
+            requires_ack: true,
+            ..self
+        }
+    }
+
+    pub fn with_origin(self, origin_id: String) -> Self {
+        Self { origin_id, ..self }
+    }
+
+    pub fn auth(token: &str) -> Self {
+        Self::new(
+            SyncMessageType::Auth,
+            SyncTargetType::Program,
+            serde_json::json!({ "token": token }),
+        )
+    }
+
+    pub fn ack(message_id: &str) -> Self {
+        Self::new(
+            SyncMessageType::Ack,
+            SyncTargetType::Program,
+            serde_json::json!({ "message_id": message_id }),
+        )
+    }
+
+    pub fn state_sync_request(held_images: Vec<String>) -> Self {
         Self::new(
             SyncMessageType::StateSyncRequest,
             SyncTargetType::Program,
-            Value::Object(serde_json::Map::new()),
+            serde_json::to_value(StateSyncRequestPayload { held_images }).unwrap_or(Value::Null),
         )
     }
+
+    pub fn capabilities(capabilities: &Capabilities) -> Self {
+        Self::new(
+            SyncMessageType::Capabilities,
+            SyncTargetType::Program,
+            serde_json::to_value(capabilities).unwrap_or(Value::Null),
+        )
+    }
+
+    /// Heartbeat ping. The master echoes a slave's `client_time` back and sends its own
+    /// pings for liveness, which slaves don't answer.
+    pub fn heartbeat() -> Self {
+        let message = Self::new(
+            SyncMessageType::Heartbeat,
+            SyncTargetType::Program,
+            Value::Null,
+        );
+        Self {
+            payload: serde_json::json!({ "client_time": message.timestamp }),
+            ..message
+        }
+    }
+
+    /// Tagged no-op used by `measure_sync_latency`
+    pub fn latency_probe(probe_id: &str) -> Self {
+        Self::new(
+            SyncMessageType::LatencyProbe,
+            SyncTargetType::Program,
+            serde_json::json!({ "probe_id": probe_id }),
+        )
+    }
+
+    pub fn latency_ack(probe_id: &str) -> Self {
+        Self::new(
+            SyncMessageType::LatencyAck,
+            SyncTargetType::Program,
+            serde_json::json!({ "probe_id": probe_id }),
+        )
+    }
+
+    pub fn command(command: &SemanticCommand) -> Self {
+        Self::new(
+            SyncMessageType::Command,
+            SyncTargetType::Program,
+            serde_json::to_value(command).unwrap_or(Value::Null),
+        )
+    }
+
+    pub fn master_handoff(new_host: &str, new_port: u16) -> Self {
+        Self::new(
+            SyncMessageType::MasterHandoff,
+            SyncTargetType::Program,
+            serde_json::to_value(MasterHandoffPayload {
+                new_host: new_host.to_string(),
+                new_port,
+            })
+            .unwrap_or(Value::Null),
+        )
+    }
+
+    pub fn server_hello(auth_required: bool) -> Self {
+        Self::new(
+            SyncMessageType::ServerHello,
+            SyncTargetType::Program,
+            serde_json::to_value(ServerHelloPayload {
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                protocol_version: PROTOCOL_VERSION,
+                auth_required,
+            })
+            .unwrap_or(Value::Null),
+        )
+    }
+
+    pub fn transform_batch(updates: Vec<TransformUpdatePayload>) -> Self {
+        Self::new(
+            SyncMessageType::TransformBatch,
+            SyncTargetType::Source,
+            serde_json::to_value(TransformBatchPayload { updates }).unwrap_or(Value::Null),
+        )
+    }
+
+    /// The TransformUpdate messages a TransformBatch carries, for code (and peers)
+    /// that handle transforms one at a time. They keep the batch's timestamp and origin.
+    pub fn split_transform_batch(&self) -> anyhow::Result<Vec<SyncMessage>> {
+        let batch: TransformBatchPayload = serde_json::from_value(self.payload.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to parse transform batch: {}", e))?;
+        Ok(batch
+            .updates
+            .into_iter()
+            .map(|update| Self {
+                message_type: SyncMessageType::TransformUpdate,
+                timestamp: self.timestamp,
+                target_type: self.target_type.clone(),
+                payload: serde_json::to_value(update).unwrap_or(Value::Null),
+                message_id: uuid::Uuid::new_v4().to_string(),
+                requires_ack: false,
+                origin_id: self.origin_id.clone(),
+                seq: None,
+            })
+            .collect())
+    }
+
+    pub fn client_hello(
+        machine_id: Option<String>,
+        display_name: Option<String>,
+        resume: Option<ResumePoint>,
+        held_images: Vec<String>,
+    ) -> Self {
+        Self::new(
+            SyncMessageType::ClientHello,
+            SyncTargetType::Program,
+            serde_json::to_value(ClientHelloPayload {
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                protocol_version: PROTOCOL_VERSION,
+                machine_id,
+                display_name,
+                resumable: true,
+                resume,
+                held_images,
+            })
+            .unwrap_or(Value::Null),
+        )
+    }
+
+    pub fn resume_from(payload: ResumeFromPayload) -> Self {
+        Self::new(
+            SyncMessageType::ResumeFrom,
+            SyncTargetType::Program,
+            serde_json::to_value(payload).unwrap_or(Value::Null),
+        )
+    }
+
+    pub fn server_shutdown() -> Self {
+        Self::new(
+            SyncMessageType::ServerShutdown,
+            SyncTargetType::Program,
+            Value::Null,
+        )
+    }
+
+    /// Master's answer to a heartbeat, carrying both clocks for offset estimation
+    pub fn heartbeat_reply(client_time: i64) -> Self {
+        let message = Self::new(
+            SyncMessageType::Heartbeat,
+            SyncTargetType::Program,
+            Value::Null,
+        );
+        Self {
+            payload: serde_json::json!({
+                "client_time": client_time,
+                "server_time": message.timestamp,
+            }),
+            ..message
+        }
+    }
+}
+
+/// Latest transforms of several items, flushed together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformBatchPayload {
+    pub updates: Vec<TransformUpdatePayload>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +450,9 @@ impl SyncMessage {
 pub struct TransformUpdatePayload {
     pub scene_name: String,
     pub scene_item_id: i64,
+    /// Lets slaves that match items by name find the item when ids differ
+    #[serde(default)]
+    pub source_name: Option<String>,
     pub transform: TransformData,
 }
 
@@ -69,12 +466,69 @@ pub struct TransformData {
     pub scale_y: f64,
     pub width: f64,
     pub height: f64,
+    #[serde(default)]
+    pub crop_left: u32,
+    #[serde(default)]
+    pub crop_right: u32,
+    #[serde(default)]
+    pub crop_top: u32,
+    #[serde(default)]
+    pub crop_bottom: u32,
+    /// OBS alignment flags of the point the item is positioned by
+    /// (left 1, right 2, top 4, bottom 8; 0 is centered). Absent from older masters.
+    #[serde(default)]
+    pub alignment: Option<u8>,
+    /// OBS bounds type id, e.g. "OBS_BOUNDS_SCALE_INNER"
+    #[serde(default)]
+    pub bounds_type: Option<String>,
+    /// Alignment flags of the source within its bounding box
+    #[serde(default)]
+    pub bounds_alignment: Option<u8>,
+    #[serde(default)]
+    pub bounds_width: Option<f64>,
+    #[serde(default)]
+    pub bounds_height: Option<f64>,
+    /// Set for anchored sources; slaves derive the position from their own canvas
+    #[serde(default)]
+    pub anchor: Option<AnchoredPosition>,
+}
+
+/// Which transform fields a slave takes from the master; masked-out fields keep the slave's value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformFieldMask {
+    pub sync_position: bool,
+    pub sync_scale: bool,
+    pub sync_rotation: bool,
+    pub sync_crop: bool,
+}
+
+impl Default for TransformFieldMask {
+    fn default() -> Self {
+        Self {
+            sync_position: true,
+            sync_scale: true,
+            sync_rotation: true,
+            sync_crop: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct SceneChangePayload {
     pub scene_name: String,
+    /// Transition the master used for the switch. Absent for preview changes
+    /// and from masters that predate transition sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<SceneTransition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SceneTransition {
+    pub name: String,
+    /// None for transitions with a fixed duration
+    pub duration_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +549,12 @@ pub struct StateSyncPayload {
     pub current_program_scene: String,
     pub current_preview_scene: Option<String>,
     pub scenes: Vec<SceneData>,
+    /// Scenes left out by the sync filters or because their items couldn't be listed
+    #[serde(default)]
+    pub skipped_scenes: Vec<String>,
+    /// Sources left out by the sync filters
+    #[serde(default)]
+    pub skipped_sources: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,8 +569,8 @@ pub struct SceneData {
 pub struct SceneItemData {
     pub source_name: String,
     pub source_type: String,
-    /// Base64 encoded image data for image sources
-    pub image_data: Option<String>,
+    /// File, hash and base64 encoded data for image sources
+    pub image_data: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +582,15 @@ pub enum SourceUpdateAction {
     SettingsChanged,
 }
 
+/// Per-scene-item properties carried by `SourceUpdateAction::SettingsChanged`,
+/// as opposed to the input's own settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneItemSettings {
+    pub locked: Option<bool>,
+    /// OBS blend mode id, e.g. "OBS_BLEND_NORMAL"
+    pub blend_mode: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceUpdatePayload {
     pub scene_name: String,
@@ -131,4 +600,276 @@ pub struct SourceUpdatePayload {
     pub source_type: Option<String>,
     pub scene_item_enabled: Option<bool>,
     pub transform: Option<TransformData>,
+    #[serde(default)]
+    pub item_settings: Option<SceneItemSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneLifecycleAction {
+    Created,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneLifecyclePayload {
+    pub scene_name: String,
+    pub action: SceneLifecycleAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterLifecycleAction {
+    Created,
+    Removed,
+}
+
+/// Filter added to or removed from a source. Kind, index and settings are only
+/// set for `Created`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterLifecyclePayload {
+    pub source_name: String,
+    pub filter_name: String,
+    pub action: FilterLifecycleAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_settings: Option<Value>,
+}
+
+/// One item of a scene in a `ReorderItemsPayload`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderedItem {
+    pub scene_item_id: i64,
+    /// Lets slaves find the item when their ids differ from the master's
+    pub source_name: String,
+}
+
+/// Stacking order of all items of a scene, bottom first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderItemsPayload {
+    pub scene_name: String,
+    pub items: Vec<ReorderedItem>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    Record,
+    Stream,
+}
+
+/// Settings keys holding paths on the master's disk. They are never sent as settings;
+/// image files travel as `ImageUpdate` bytes instead.
+pub const MACHINE_LOCAL_SETTINGS: [&str; 2] = ["file", "local_file"];
+
+/// An input's settings, applied on the slave on top of its own (`overlay`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSettingsPayload {
+    pub source_name: String,
+    /// Slaves skip the update when their input of this name is of another kind
+    pub input_kind: String,
+    /// Settings object without `MACHINE_LOCAL_SETTINGS`
+    pub settings: Value,
+}
+
+/// Desired state of an output on the slaves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputControlPayload {
+    pub output: OutputKind,
+    pub active: bool,
+}
+
+/// Slaves apply `volume_mul`, which also represents silence (-inf dB) exactly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeUpdatePayload {
+    pub input_name: String,
+    pub volume_db: f64,
+    pub volume_mul: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MuteUpdatePayload {
+    pub input_name: String,
+    pub muted: bool,
+}
+
+/// Audio state of one input in a StateSync (`inputs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputAudioData {
+    pub input_name: String,
+    pub volume_db: f64,
+    pub volume_mul: f64,
+    pub muted: Option<bool>,
+}
+
+/// Identifies an obs-sync master to anything that connects, including probes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerHelloPayload {
+    pub app_version: String,
+    pub protocol_version: u16,
+    /// Whether the master expects an auth frame before anything else
+    pub auth_required: bool,
+}
+
+/// Slave's answer to ServerHello; the master drops slaves with an incompatible protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientHelloPayload {
+    pub app_version: String,
+    pub protocol_version: u16,
+    /// Stable id of the slave's machine. The master keys the client by it, so a
+    /// reconnect reuses the same entry; older slaves are keyed by socket address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
+    /// Name the slave's user gave this machine, shown on the master's dashboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Understands sequence numbers and ResumeFrom
+    #[serde(default)]
+    pub resumable: bool,
+    /// Last position received over a previous connection, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume: Option<ResumePoint>,
+    /// Master hashes of the images this slave still has on disk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held_images: Vec<String>,
+}
+
+/// Sent by a slave that wants the master's full state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateSyncRequestPayload {
+    /// Master hashes of the images this slave still has on disk; the StateSync
+    /// leaves their bytes out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held_images: Vec<String>,
+}
+
+/// A position in one run of the master's broadcast history
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResumePoint {
+    /// Changes whenever the master restarts; sequence numbers only compare within one id
+    pub history_id: String,
+    /// Everything up to and including this sequence number was received
+    pub last_seq: u64,
+}
+
+/// Sent to a resumable slave when it is registered. Broadcasts after `last_seq` follow;
+/// when `replayed` is set the slave's own position was kept and the missed ones come
+/// first, otherwise a full StateSync follows instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeFromPayload {
+    pub history_id: String,
+    pub last_seq: u64,
+    pub replayed: bool,
+}
+
+/// Address of the master that takes over from the one sending the handoff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasterHandoffPayload {
+    pub new_host: String,
+    pub new_port: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every message type; the match makes a new variant fail to compile until listed
+    fn all_message_types() -> Vec<SyncMessageType> {
+        use SyncMessageType::*;
+        let all = vec![
+            SourceUpdate,
+            TransformUpdate,
+            SceneChange,
+            ImageUpdate,
+            FilterUpdate,
+            Heartbeat,
+            StateSync,
+            StateSyncRequest,
+            StateReport,
+            Capabilities,
+            RawEvent,
+            LatencyProbe,
+            LatencyAck,
+            Command,
+            MasterHandoff,
+            Ack,
+            Auth,
+            VolumeUpdate,
+            MuteUpdate,
+            SceneLifecycle,
+            OutputControl,
+            ServerShutdown,
+            ServerHello,
+            ClientHello,
+            TransformBatch,
+            SourceSettingsUpdate,
+            FilterLifecycle,
+            ResumeFrom,
+            ReorderItems,
+        ];
+        for message_type in &all {
+            match message_type {
+                SourceUpdate | TransformUpdate | SceneChange | ImageUpdate | FilterUpdate
+                | Heartbeat | StateSync | StateSyncRequest | StateReport | Capabilities
+                | RawEvent | LatencyProbe | LatencyAck | Command | MasterHandoff | Ack | Auth
+                | VolumeUpdate | MuteUpdate | SceneLifecycle | OutputControl | ServerShutdown
+                | ServerHello | ClientHello | TransformBatch | SourceSettingsUpdate
+                | FilterLifecycle | ResumeFrom | ReorderItems => {}
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn advertises_every_message_type_it_handles() {
+        let current = Capabilities::current();
+        for message_type in all_message_types() {
+            assert!(
+                current.supports(&message_type),
+                "{:?} is not advertised",
+                message_type
+            );
+        }
+        assert_eq!(current.message_types.len(), all_message_types().len());
+    }
+
+    #[test]
+    fn advertised_features_follow_the_connection() {
+        // Gzip image payloads are always understood
+        assert!(Capabilities::current().features.compression);
+        assert!(!Capabilities::current().features.msgpack);
+
+        let plain = Capabilities::for_connection(false, false);
+        assert!(!plain.features.tls && !plain.features.auth);
+        let secured = Capabilities::for_connection(true, true);
+        assert!(secured.features.tls && secured.features.auth);
+        assert_eq!(secured.message_types, plain.message_types);
+    }
+
+    #[test]
+    fn capabilities_from_a_newer_peer_still_parse() {
+        let payload = serde_json::json!({
+            "message_types": ["scene_change", "hologram_update", "state_sync"],
+            "target_types": ["program", "metaverse"],
+            "features": { "tls": true, "compression": true, "quantum": true },
+        });
+
+        let remote: Capabilities = serde_json::from_value(payload).unwrap();
+
+        assert_eq!(
+            remote.message_types,
+            vec![SyncMessageType::SceneChange, SyncMessageType::StateSync]
+        );
+        assert_eq!(remote.target_types, vec![SyncTargetType::Program]);
+        assert!(remote.features.tls && remote.features.compression);
+        assert!(!remote.features.auth);
+
+        let negotiated = Capabilities::for_connection(false, false).intersect(&remote);
+        assert!(negotiated.supports(&SyncMessageType::SceneChange));
+        assert!(!negotiated.supports(&SyncMessageType::ImageUpdate));
+        assert!(!negotiated.features.tls);
+    }
 }
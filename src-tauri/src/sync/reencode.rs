@@ -0,0 +1,171 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::codecs::webp::WebPEncoder;
+use image::ImageFormat;
+use serde::{Deserialize, Serialize};
+
+/// Target format for re-encoded images
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReencodeFormat {
+    Png,
+    Jpeg,
+    /// Lossless WebP; `quality` does not apply
+    Webp,
+}
+
+impl ReencodeFormat {
+    /// File extension the slave should write the image with
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReencodeFormat::Png => "png",
+            ReencodeFormat::Jpeg => "jpg",
+            ReencodeFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Optional re-encode of image sources on the master before they are sent to slaves
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageReencode {
+    pub enabled: bool,
+    pub format: ReencodeFormat,
+    /// JPEG quality (1-100)
+    pub quality: u8,
+    /// Files smaller than this are sent as-is
+    pub min_size_bytes: u64,
+}
+
+impl Default for ImageReencode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: ReencodeFormat::Webp,
+            quality: 90,
+            min_size_bytes: 256 * 1024,
+        }
+    }
+}
+
+impl ImageReencode {
+    /// Re-encode `data` into the configured format. Returns `None` when disabled, when the
+    /// file is small or in a format we can't decode, when the target format would lose the
+    /// alpha channel, or when the result isn't smaller than the original.
+    pub fn apply(&self, data: &[u8]) -> Option<Vec<u8>> {
+        if !self.enabled || (data.len() as u64) < self.min_size_bytes {
+            return None;
+        }
+        let source_format = image::guess_format(data).ok()?;
+        if !matches!(
+            source_format,
+            ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP
+        ) {
+            return None;
+        }
+        let decoded = image::load_from_memory_with_format(data, source_format).ok()?;
+
+        let mut encoded = Vec::new();
+        let result = match self.format {
+            ReencodeFormat::Png => decoded.write_with_encoder(PngEncoder::new_with_quality(
+                &mut encoded,
+                CompressionType::Best,
+                FilterType::Adaptive,
+            )),
+            ReencodeFormat::Jpeg => {
+                if decoded.color().has_alpha() {
+                    return None;
+                }
+                decoded
+                    .to_rgb8()
+                    .write_with_encoder(JpegEncoder::new_with_quality(
+                        &mut encoded,
+                        self.quality.clamp(1, 100),
+                    ))
+            }
+            ReencodeFormat::Webp => {
+                let encoder = WebPEncoder::new_lossless(&mut encoded);
+                if decoded.color().has_alpha() {
+                    decoded.to_rgba8().write_with_encoder(encoder)
+                } else {
+                    decoded.to_rgb8().write_with_encoder(encoder)
+                }
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to re-encode image: {}", e);
+            return None;
+        }
+
+        (encoded.len() < data.len()).then_some(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A large, barely compressed PNG like an exported overlay
+    fn sample_png() -> Vec<u8> {
+        let overlay = image::RgbaImage::from_fn(512, 512, |x, y| {
+            if (x / 64 + y / 64) % 2 == 0 {
+                image::Rgba([255, 255, 255, 0])
+            } else {
+                image::Rgba([20, 120, 220, 255])
+            }
+        });
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(overlay)
+            .write_with_encoder(PngEncoder::new_with_quality(
+                &mut png,
+                CompressionType::Fast,
+                FilterType::NoFilter,
+            ))
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn reencoding_a_png_overlay_shrinks_it() {
+        let png = sample_png();
+        let reencode = ImageReencode {
+            enabled: true,
+            min_size_bytes: 0,
+            ..ImageReencode::default()
+        };
+        let webp = reencode
+            .apply(&png)
+            .expect("re-encode should shrink the PNG");
+        println!("PNG {} bytes -> WebP {} bytes", png.len(), webp.len());
+        assert!(webp.len() < png.len());
+        assert_eq!(image::guess_format(&webp).unwrap(), ImageFormat::WebP);
+
+        // Lossless: the pixels survive
+        let before = image::load_from_memory(&png).unwrap().to_rgba8();
+        let after = image::load_from_memory(&webp).unwrap().to_rgba8();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn reencode_is_opt_in_and_skips_small_or_unknown_files() {
+        let png = sample_png();
+        assert!(ImageReencode::default().apply(&png).is_none());
+
+        let enabled = ImageReencode {
+            enabled: true,
+            ..ImageReencode::default()
+        };
+        assert!(enabled.apply(&png[..1024]).is_none());
+        let unknown = ImageReencode {
+            min_size_bytes: 0,
+            ..enabled.clone()
+        };
+        assert!(unknown.apply(b"not an image at all").is_none());
+        // JPEG would drop the transparency
+        let jpeg = ImageReencode {
+            format: ReencodeFormat::Jpeg,
+            ..unknown
+        };
+        assert!(jpeg.apply(&png).is_none());
+    }
+}
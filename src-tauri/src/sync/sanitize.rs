@@ -0,0 +1,157 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// Longest scene, source, input or filter name accepted from the master
+const MAX_NAME_LEN: usize = 256;
+/// Longest file path accepted from the master
+const MAX_PATH_LEN: usize = 4096;
+/// Longest image file extension; received images are saved under it
+const MAX_EXTENSION_LEN: usize = 8;
+
+/// Payload fields holding OBS object names
+const NAME_FIELDS: &[&str] = &["scene_name", "source_name", "input_name", "filter_name"];
+
+/// Reject a name that is too long or contains control characters
+pub fn check_name(field: &str, name: &str) -> Result<()> {
+    if name.len() > MAX_NAME_LEN {
+        bail!(
+            "{} is {} bytes long, the limit is {}",
+            field,
+            name.len(),
+            MAX_NAME_LEN
+        );
+    }
+    if name.chars().any(char::is_control) {
+        bail!("{} contains control characters", field);
+    }
+    Ok(())
+}
+
+/// Reject a file path that is absolute, has `..` components, control characters or an
+/// excessive length. The master sends only the file name; the slave takes the extension
+/// from it.
+pub fn check_file_path(field: &str, path: &str) -> Result<()> {
+    if path.len() > MAX_PATH_LEN {
+        bail!(
+            "{} is {} bytes long, the limit is {}",
+            field,
+            path.len(),
+            MAX_PATH_LEN
+        );
+    }
+    if path.chars().any(char::is_control) {
+        bail!("{} contains control characters", field);
+    }
+    // The master may run on another OS, so split on both separators
+    if path.split(['/', '\\']).any(|component| component == "..") {
+        bail!("{} contains a '..' component: {}", field, path);
+    }
+    let has_drive = matches!(path.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic());
+    if path.starts_with(['/', '\\']) || has_drive {
+        bail!("{} is an absolute path: {}", field, path);
+    }
+    Ok(())
+}
+
+/// Reject an image file extension that isn't a short alphanumeric word,
+/// as it becomes part of a file name in the image directory
+pub fn check_file_extension(extension: &str) -> Result<()> {
+    if extension.is_empty()
+        || extension.len() > MAX_EXTENSION_LEN
+        || !extension.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        bail!("Invalid image file extension: {:?}", extension);
+    }
+    Ok(())
+}
+
+/// Check the names, image file and filter names of one StateSync item in `scene_name`
+pub fn check_state_item(scene_name: &str, item: &Value) -> Result<()> {
+    check_name("scene_name", scene_name)?;
+    if let Some(name) = item["source_name"].as_str() {
+        check_name("source_name", name)?;
+    }
+    if let Some(image) = item["image_data"].as_object() {
+        if let Some(file) = image.get("file").and_then(Value::as_str) {
+            check_file_path("image_data.file", file)?;
+        }
+        if let Some(format) = image.get("format").and_then(Value::as_str) {
+            check_file_extension(format)?;
+        }
+    }
+    for filter in item["filters"].as_array().into_iter().flatten() {
+        if let Some(name) = filter["name"].as_str() {
+            check_name("filter_name", name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check the names, file path and image format at the top level of a sync payload
+pub fn check_payload(payload: &Value) -> Result<()> {
+    for field in NAME_FIELDS {
+        if let Some(name) = payload[*field].as_str() {
+            check_name(field, name)?;
+        }
+    }
+    if let Some(file) = payload["file"].as_str() {
+        check_file_path("file", file)?;
+    }
+    if let Some(format) = payload["image_format"].as_str() {
+        check_file_extension(format)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn file_paths_must_be_relative_and_stay_put() {
+        assert!(check_file_path("file", "logo.png").is_ok());
+        assert!(check_file_path("file", "images/logo.png").is_ok());
+        for path in [
+            "../../etc/cron.d/evil.png",
+            "images\\..\\..\\evil.png",
+            "/etc/passwd",
+            "\\\\server\\share\\logo.png",
+            "C:\\Windows\\logo.png",
+            "c:logo.png",
+            "logo\n.png",
+        ] {
+            assert!(
+                check_file_path("file", path).is_err(),
+                "{:?} accepted",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn crafted_state_sync_items_are_rejected() {
+        let item = |image_data: Value, filters: Value| {
+            json!({
+                "source_name": "Logo",
+                "image_data": image_data,
+                "filters": filters,
+            })
+        };
+        let clean = item(json!({ "file": "logo.png", "format": "png" }), json!([]));
+        assert!(check_state_item("Scene", &clean).is_ok());
+
+        let traversal = item(json!({ "file": "../../etc/cron.d/evil.png" }), json!([]));
+        assert!(check_state_item("Scene", &traversal).is_err());
+        let absolute = item(json!({ "file": "/etc/passwd" }), json!([]));
+        assert!(check_state_item("Scene", &absolute).is_err());
+        let bad_format = item(
+            json!({ "file": "logo.png", "format": "png/../x" }),
+            json!([]),
+        );
+        assert!(check_state_item("Scene", &bad_format).is_err());
+        let bad_filter = item(json!(null), json!([{ "name": "Blur\u{0007}" }]));
+        assert!(check_state_item("Scene", &bad_filter).is_err());
+        assert!(check_state_item(&"S".repeat(MAX_NAME_LEN + 1), &clean).is_err());
+    }
+}
@@ -1,12 +1,32 @@
-use super::diff::{DiffDetector, DiffSeverity};
+use super::anchor::{canvas_size, AnchoredPosition};
+use super::cache::AppliedStateCache;
+use super::command::SemanticCommand;
+use super::compression;
+use super::diff::{DiffCategory, DiffDetector, DiffSeverity, StateDifference};
+use super::echo::EchoGuard;
+use super::image_cache::{self, ImageCacheLimits, ImageFileCache};
+use super::profile::SyncTuning;
 use super::protocol::{
-    SourceUpdateAction, SourceUpdatePayload, SyncMessage, SyncMessageType, SyncTargetType,
+    default_sync_targets, targets_for_scene, FilterLifecycleAction, FilterLifecyclePayload,
+    InputAudioData, MuteUpdatePayload, OutputControlPayload, OutputKind, ReorderItemsPayload,
+    ReorderedItem, SceneLifecycleAction, SceneLifecyclePayload, SceneTargetOverrides,
+    SceneTransition, SourceSettingsPayload, SourceUpdateAction, SourceUpdatePayload, SyncMessage,
+    SyncMessageType, SyncTargetType, TransformFieldMask, TransformUpdatePayload,
+    VolumeUpdatePayload,
 };
-use crate::obs::{commands::OBSCommands, OBSClient};
+use super::sanitize;
+use crate::obs::{commands::OBSCommands, OBSClient, SceneName};
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use obws::common::{Alignment, BoundsType};
+use obws::responses::scene_items::SceneItemTransform;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::fs;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Notify, RwLock};
+use tokio::task::JoinHandle;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +37,28 @@ pub struct DesyncAlert {
     pub source_name: String,
     pub message: String,
     pub severity: AlertSeverity,
+    /// Notice that a previously alerted difference has gone away
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+impl DesyncAlert {
+    pub fn new(
+        scene_name: String,
+        source_name: String,
+        message: String,
+        severity: AlertSeverity,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            scene_name,
+            source_name,
+            message,
+            severity,
+            resolved: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -26,11 +68,528 @@ pub enum AlertSeverity {
     Error,
 }
 
+/// One step of a StateSync that could not be applied
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSyncFailure {
+    pub scene_name: String,
+    pub source_name: String,
+    pub reason: String,
+}
+
+/// Outcome of applying one StateSync, sent to the UI as a single `state-sync-report`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSyncReport {
+    pub timestamp: i64,
+    pub items_applied: usize,
+    /// Items skipped because they match the last clean apply
+    pub items_unchanged: usize,
+    pub items_failed: usize,
+    pub failures: Vec<StateSyncFailure>,
+    /// Strict mode stopped after a scene item or the program scene failed
+    pub aborted: bool,
+    /// A fresh StateSync was requested from the master after aborting
+    pub resync_requested: bool,
+}
+
+impl StateSyncReport {
+    fn fail(&mut self, scene_name: &str, source_name: &str, reason: String) {
+        self.failures.push(StateSyncFailure {
+            scene_name: scene_name.to_string(),
+            source_name: source_name.to_string(),
+            reason,
+        });
+    }
+
+    fn summary(&self) -> String {
+        let total = self.items_applied + self.items_unchanged + self.items_failed;
+        let mut summary = format!(
+            "Initial sync: {}/{} items applied, {} unchanged, {} failed",
+            self.items_applied, total, self.items_unchanged, self.items_failed
+        );
+        if let Some(first) = self.failures.first() {
+            summary.push_str(&format!(
+                "; first failure: {}/{}: {}",
+                first.scene_name, first.source_name, first.reason
+            ));
+        }
+        if self.aborted {
+            summary.push_str(if self.resync_requested {
+                " (aborted, resync requested)"
+            } else {
+                " (aborted)"
+            });
+        }
+        summary
+    }
+}
+
+/// How one scene item of a StateSync went
+enum StateItemOutcome {
+    Applied,
+    Unchanged,
+    Planned,
+    /// Left alone because this slave ignores the source
+    Ignored,
+    /// Reasons of the steps (transform, image, filters) that failed
+    Failed(Vec<String>),
+}
+
+/// Change a dry-run slave would have made to OBS
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAction {
+    pub id: String,
+    pub timestamp: i64,
+    /// What would be done, e.g. "set_transform" or "create_scene_item"
+    pub action: String,
+    pub scene_name: String,
+    pub source_name: String,
+    /// Values the action would apply
+    pub details: serde_json::Value,
+}
+
+impl PlannedAction {
+    fn new(action: &str, scene_name: &str, source_name: &str, details: serde_json::Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            action: action.to_string(),
+            scene_name: scene_name.to_string(),
+            source_name: source_name.to_string(),
+            details,
+        }
+    }
+}
+
+/// Default number of StateSync items applied to OBS at once; 1 keeps writes sequential
+pub const DEFAULT_APPLY_CONCURRENCY: usize = 1;
+
+/// Default number of consecutive checks needed to flip between synced and desynced
+pub const DEFAULT_SYNC_HYSTERESIS: u32 = 2;
+
+/// How often the image temp directory is swept for expired and excess files
+const IMAGE_CLEANUP_INTERVAL_SECS: u64 = 300;
+
+/// Apply errors kept for the next StateReport; older ones are dropped
+const MAX_REPORTED_APPLY_ERRORS: usize = 20;
+
+/// How long after a start request an output must be running before it counts as failed
+const OUTPUT_START_CHECK_MS: u64 = 2_000;
+
+/// Debounces the synced/desynced state reported to the master and the UI, so a slave
+/// hovering right at a tolerance boundary doesn't flap on every check
+#[derive(Debug, Clone, Copy)]
+struct SyncStateHysteresis {
+    synced: bool,
+    /// Consecutive checks disagreeing with `synced`
+    streak: u32,
+}
+
+impl SyncStateHysteresis {
+    fn new() -> Self {
+        Self {
+            synced: true,
+            streak: 0,
+        }
+    }
+
+    /// Feed one check result; the reported state flips after `threshold` disagreeing checks
+    fn observe(&mut self, in_sync: bool, threshold: u32) -> bool {
+        if in_sync == self.synced {
+            self.streak = 0;
+        } else {
+            self.streak += 1;
+            if self.streak >= threshold.max(1) {
+                self.synced = in_sync;
+                self.streak = 0;
+            }
+        }
+        self.synced
+    }
+}
+
+/// A difference that is still present is alerted again only after this long
+const DESYNC_ALERT_COOLDOWN_SECS: u64 = 300;
+
+/// Scene, source and category identifying one reported difference across checks
+type DesyncKey = (String, String, DiffCategory);
+
+fn desync_key(diff: &StateDifference) -> DesyncKey {
+    (
+        diff.scene_name.clone(),
+        diff.source_name.clone(),
+        diff.category.clone(),
+    )
+}
+
+struct ReportedDesync {
+    alerted_at: std::time::Instant,
+    message: String,
+    severity: AlertSeverity,
+}
+
+/// Differences the periodic check has alerted about, so a difference that persists
+/// produces one alert per cooldown instead of one per check, and a "resolved" notice
+/// once it is gone
+#[derive(Default)]
+struct DesyncAlertLog {
+    reported: HashMap<DesyncKey, ReportedDesync>,
+}
+
+impl DesyncAlertLog {
+    /// Alerts for the differences of one check. `alert_new` is false while differences
+    /// are held back by the hysteresis; they are then only used to resolve earlier ones.
+    fn alerts_for(&mut self, diffs: &[StateDifference], alert_new: bool) -> Vec<DesyncAlert> {
+        let now = std::time::Instant::now();
+        let cooldown = std::time::Duration::from_secs(DESYNC_ALERT_COOLDOWN_SECS);
+        let mut alerts = Vec::new();
+        let current: std::collections::HashSet<DesyncKey> = diffs.iter().map(desync_key).collect();
+
+        self.reported.retain(|key, reported| {
+            if current.contains(key) {
+                return true;
+            }
+            let (scene_name, source_name, _) = key;
+            let mut alert = DesyncAlert::new(
+                scene_name.clone(),
+                source_name.clone(),
+                format!("Resolved: {}", reported.message),
+                reported.severity.clone(),
+            );
+            alert.resolved = true;
+            alerts.push(alert);
+            false
+        });
+
+        if !alert_new {
+            return alerts;
+        }
+        for diff in diffs {
+            let key = desync_key(diff);
+            if self
+                .reported
+                .get(&key)
+                .is_some_and(|reported| now.duration_since(reported.alerted_at) < cooldown)
+            {
+                continue;
+            }
+            let severity = match diff.severity {
+                DiffSeverity::Critical => AlertSeverity::Error,
+                _ => AlertSeverity::Warning,
+            };
+            alerts.push(DesyncAlert::new(
+                diff.scene_name.clone(),
+                diff.source_name.clone(),
+                diff.description.clone(),
+                severity.clone(),
+            ));
+            self.reported.insert(
+                key,
+                ReportedDesync {
+                    alerted_at: now,
+                    message: diff.description.clone(),
+                    severity,
+                },
+            );
+        }
+        alerts
+    }
+}
+
+/// Last TransformUpdate timestamp and animation generation per (scene, item)
+type TransformAnimations = HashMap<(String, i64), (i64, u64)>;
+
+/// Optional smoothing of live TransformUpdates that arrive slower than the master's edits
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformInterpolation {
+    pub enabled: bool,
+    /// Upper bound on an animation; the actual length follows the gap between updates
+    pub max_duration_ms: u64,
+    /// Maximum intermediate SetTransform calls per second
+    pub max_rate_hz: u32,
+    /// Moves longer than this (in pixels) are applied immediately
+    pub max_jump_px: f64,
+}
+
+impl Default for TransformInterpolation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_duration_ms: 250,
+            max_rate_hz: 30,
+            max_jump_px: 400.0,
+        }
+    }
+}
+
+/// Whether a local file referenced by an input exists on this machine
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaAvailability {
+    pub source_name: String,
+    pub input_kind: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Kind of change made by `normalize_to_master`
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NormalizeAction {
+    AddScene,
+    RemoveScene,
+    AddItem,
+    RemoveItem,
+    ReorderItem,
+    SetEnabled,
+}
+
+/// One change (or failed attempt) made while reconciling the local collection
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeChange {
+    pub action: NormalizeAction,
+    pub scene_name: String,
+    pub source_name: Option<String>,
+    pub detail: String,
+    /// Set if OBS rejected the change
+    pub error: Option<String>,
+    /// Dry run: the change was only planned, OBS was left alone
+    #[serde(default)]
+    pub planned: bool,
+}
+
+/// Image fields of an ImageUpdate payload or a StateSync item
+struct ReceivedImage<'a> {
+    /// Base64 of the file bytes
+    data: Option<&'a str>,
+    hash: Option<&'a str>,
+    /// File extension when the master re-encoded the image
+    format: Option<&'a str>,
+    /// How the bytes were compressed before base64; absent means raw
+    compression: Option<&'a str>,
+}
+
+/// Scene layout from the master's last StateSync, kept for `normalize_to_master`
+#[derive(Debug, Clone, Default)]
+struct MasterLayout {
+    scenes: Vec<MasterScene>,
+    /// Scenes the master left out on purpose or couldn't list; never removed locally
+    skipped_scenes: HashSet<String>,
+    /// Sources the master's filters left out; never removed locally
+    skipped_sources: HashSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct MasterScene {
+    name: String,
+    items: Vec<MasterItem>,
+}
+
+#[derive(Debug, Clone)]
+struct MasterItem {
+    source_name: String,
+    index: u64,
+    enabled: Option<bool>,
+}
+
+/// A local scene item, in index order, as `normalize_to_master` pairs them
+#[derive(Debug, Clone, PartialEq)]
+struct LocalItem {
+    id: i64,
+    source_name: String,
+}
+
+/// How one scene's items are reconciled with the master's
+#[derive(Debug)]
+struct ItemPlan<'a> {
+    /// Local items the master doesn't have
+    remove: Vec<LocalItem>,
+    /// Master items in order, with the local item each maps to (`None` = create it)
+    paired: Vec<(&'a MasterItem, Option<i64>)>,
+}
+
+impl MasterLayout {
+    fn from_state(payload: &serde_json::Value) -> Self {
+        let names = |key: &str| -> HashSet<String> {
+            payload[key]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.as_str().map(String::from))
+                .collect()
+        };
+        let scenes = payload["scenes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|scene| {
+                let mut items: Vec<MasterItem> = scene["items"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|item| {
+                        Some(MasterItem {
+                            source_name: item["source_name"].as_str()?.to_string(),
+                            index: item["scene_item_index"].as_u64().unwrap_or(0),
+                            enabled: item["enabled"].as_bool(),
+                        })
+                    })
+                    .collect();
+                items.sort_by_key(|item| item.index);
+                Some(MasterScene {
+                    name: scene["name"].as_str()?.to_string(),
+                    items,
+                })
+            })
+            .collect();
+        Self {
+            scenes,
+            skipped_scenes: names("skipped_scenes"),
+            skipped_sources: names("skipped_sources"),
+        }
+    }
+
+    /// Scenes to create and scenes to remove so the local list matches the master's.
+    /// Only scenes for which `synced` holds are touched.
+    fn plan_scenes(
+        &self,
+        local_scenes: &[String],
+        synced: impl Fn(&str) -> bool,
+    ) -> (Vec<String>, Vec<String>) {
+        let add = self
+            .scenes
+            .iter()
+            .filter(|scene| synced(&scene.name) && !local_scenes.contains(&scene.name))
+            .map(|scene| scene.name.clone())
+            .collect();
+        let remove = local_scenes
+            .iter()
+            .filter(|local| {
+                synced(local)
+                    && !self.skipped_scenes.contains(*local)
+                    && !self.scenes.iter().any(|scene| &scene.name == *local)
+            })
+            .cloned()
+            .collect();
+        (add, remove)
+    }
+
+    /// Pair `scene`'s items with `local_items` (in index order) by source name.
+    /// Local items of sources the master filtered out are neither removed nor paired.
+    fn plan_items<'a>(&self, scene: &'a MasterScene, local_items: &[LocalItem]) -> ItemPlan<'a> {
+        let mut unpaired: Vec<&LocalItem> = local_items
+            .iter()
+            .filter(|local| !self.skipped_sources.contains(&local.source_name))
+            .collect();
+        let paired = scene
+            .items
+            .iter()
+            .map(|item| {
+                let id = unpaired
+                    .iter()
+                    .position(|local| local.source_name == item.source_name)
+                    .map(|position| unpaired.remove(position).id);
+                (item, id)
+            })
+            .collect();
+        ItemPlan {
+            remove: unpaired.into_iter().cloned().collect(),
+            paired,
+        }
+    }
+}
+
+/// Moves (`id`, from, to) that put the `desired` ids in that order within `current`,
+/// one OBS index change at a time. Ids not in `desired` keep their relative order.
+fn reorder_moves(current: &[i64], desired: &[i64]) -> Vec<(i64, usize, usize)> {
+    let mut order = current.to_vec();
+    let mut wanted = desired.iter().filter(|id| current.contains(id));
+    let mut moves = Vec::new();
+    for position in 0..order.len() {
+        if !desired.contains(&order[position]) {
+            continue;
+        }
+        let Some(&id) = wanted.next() else {
+            break;
+        };
+        if order[position] != id {
+            if let Some(from) = order.iter().position(|&local| local == id) {
+                order.remove(from);
+                order.insert(position, id);
+                moves.push((id, from, position));
+            }
+        }
+    }
+    moves
+}
+
+/// Run `applies` with at most `limit` (at least 1) in flight; outputs come in completion order
+async fn run_limited<F: std::future::Future>(applies: Vec<F>, limit: usize) -> Vec<F::Output> {
+    stream::iter(applies)
+        .buffer_unordered(limit.max(1))
+        .collect()
+        .await
+}
+
 pub struct SlaveSync {
     obs_client: Arc<OBSClient>,
     alert_tx: mpsc::UnboundedSender<DesyncAlert>,
     expected_state: Arc<RwLock<serde_json::Value>>,
     state_report_tx: Arc<RwLock<Option<mpsc::UnboundedSender<SyncMessage>>>>,
+    /// Hash of the last image successfully applied per source name
+    applied_image_hashes: Arc<RwLock<HashMap<String, String>>>,
+    /// Temp image files by content hash, reused when the same image arrives again
+    image_cache: Arc<RwLock<ImageFileCache>>,
+    image_cleanup_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    tuning: Arc<RwLock<SyncTuning>>,
+    scene_target_overrides: Arc<RwLock<SceneTargetOverrides>>,
+    /// The master's global targets, which scenes without an override follow
+    sync_targets: Arc<RwLock<Vec<SyncTargetType>>>,
+    /// Sources customized locally, by name; sync leaves them alone and doesn't flag them
+    ignored_sources: Arc<RwLock<HashSet<String>>>,
+    /// Item hashes last applied from a StateSync, persisted across restarts
+    applied_cache: Arc<RwLock<AppliedStateCache>>,
+    cache_path: Arc<RwLock<Option<PathBuf>>>,
+    periodic_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    transform_mask: Arc<RwLock<TransformFieldMask>>,
+    interpolation: Arc<RwLock<TransformInterpolation>>,
+    transform_animations: Arc<RwLock<TransformAnimations>>,
+    /// Apply errors since the last StateReport
+    apply_errors: Arc<RwLock<Vec<String>>>,
+    /// Maximum StateSync items applied to OBS at once
+    apply_concurrency: Arc<AtomicUsize>,
+    /// Consecutive checks needed before the reported sync state changes
+    sync_hysteresis: Arc<AtomicU32>,
+    /// Resolve scene items by source name instead of trusting the master's ids
+    prefer_name_matching: Arc<AtomicBool>,
+    master_layout: Arc<RwLock<Option<MasterLayout>>>,
+    /// Notified after each StateSync has been applied
+    state_sync_applied: Arc<Notify>,
+    /// Identifies this slave's own changes in bidirectional sync
+    origin_id: String,
+    /// Changes applied from the master, so their echoes aren't sent back in bidirectional sync
+    echo_guard: Arc<RwLock<EchoGuard>>,
+    /// Observe-only mode: changes are reported as `PlannedAction`s instead of applied
+    dry_run: Arc<AtomicBool>,
+    planned_action_tx: Arc<RwLock<Option<mpsc::UnboundedSender<PlannedAction>>>>,
+    state_sync_report_tx: Arc<RwLock<Option<mpsc::UnboundedSender<StateSyncReport>>>>,
+    /// Abort a StateSync at the first failed item and ask the master for a fresh one
+    strict_state_sync: Arc<AtomicBool>,
+    /// Set while a strict-mode resync is outstanding, so a failure that persists
+    /// doesn't turn into a resync loop
+    strict_resync_requested: Arc<AtomicBool>,
+    /// Set when this slave asks for a resync because its state may have drifted; the
+    /// next StateSync then reapplies every item instead of trusting the applied cache
+    full_resync_pending: Arc<AtomicBool>,
+    /// Master timestamp of the newest SceneChange. The master sends StateSyncs behind
+    /// scene changes, so one can arrive after a switch made since it was taken.
+    last_scene_change_at: Arc<AtomicI64>,
+    /// Differences the periodic check has already alerted about
+    desync_alert_log: Arc<RwLock<DesyncAlertLog>>,
 }
 
 impl SlaveSync {
@@ -42,6 +601,37 @@ impl SlaveSync {
                 alert_tx: tx,
                 expected_state: Arc::new(RwLock::new(serde_json::json!({}))),
                 state_report_tx: Arc::new(RwLock::new(None)),
+                applied_image_hashes: Arc::new(RwLock::new(HashMap::new())),
+                image_cache: Arc::new(RwLock::new(
+                    ImageFileCache::new(ImageCacheLimits::default()),
+                )),
+                image_cleanup_task: Arc::new(RwLock::new(None)),
+                tuning: Arc::new(RwLock::new(SyncTuning::default())),
+                scene_target_overrides: Arc::new(RwLock::new(HashMap::new())),
+                sync_targets: Arc::new(RwLock::new(default_sync_targets())),
+                ignored_sources: Arc::new(RwLock::new(HashSet::new())),
+                applied_cache: Arc::new(RwLock::new(AppliedStateCache::default())),
+                cache_path: Arc::new(RwLock::new(None)),
+                periodic_task: Arc::new(RwLock::new(None)),
+                transform_mask: Arc::new(RwLock::new(TransformFieldMask::default())),
+                interpolation: Arc::new(RwLock::new(TransformInterpolation::default())),
+                transform_animations: Arc::new(RwLock::new(HashMap::new())),
+                apply_errors: Arc::new(RwLock::new(Vec::new())),
+                apply_concurrency: Arc::new(AtomicUsize::new(DEFAULT_APPLY_CONCURRENCY)),
+                sync_hysteresis: Arc::new(AtomicU32::new(DEFAULT_SYNC_HYSTERESIS)),
+                prefer_name_matching: Arc::new(AtomicBool::new(false)),
+                master_layout: Arc::new(RwLock::new(None)),
+                state_sync_applied: Arc::new(Notify::new()),
+                origin_id: uuid::Uuid::new_v4().to_string(),
+                echo_guard: Arc::new(RwLock::new(EchoGuard::new())),
+                dry_run: Arc::new(AtomicBool::new(false)),
+                planned_action_tx: Arc::new(RwLock::new(None)),
+                state_sync_report_tx: Arc::new(RwLock::new(None)),
+                strict_state_sync: Arc::new(AtomicBool::new(false)),
+                strict_resync_requested: Arc::new(AtomicBool::new(false)),
+                full_resync_pending: Arc::new(AtomicBool::new(false)),
+                last_scene_change_at: Arc::new(AtomicI64::new(0)),
+                desync_alert_log: Arc::new(RwLock::new(DesyncAlertLog::default())),
             },
             rx,
         )
@@ -51,44 +641,374 @@ impl SlaveSync {
         *self.state_report_tx.write().await = Some(tx);
     }
 
-    /// Start periodic state checking task
-    pub fn start_periodic_check(&self, interval_secs: u64) {
+    /// Tuning the periodic check reads at its next iteration
+    #[cfg(test)]
+    pub async fn tuning(&self) -> SyncTuning {
+        self.tuning.read().await.clone()
+    }
+
+    pub async fn set_tuning(&self, tuning: SyncTuning) {
+        *self.tuning.write().await = tuning;
+    }
+
+    pub async fn set_scene_target_overrides(&self, overrides: SceneTargetOverrides) {
+        *self.scene_target_overrides.write().await = overrides;
+    }
+
+    pub async fn set_sync_targets(&self, targets: Vec<SyncTargetType>) {
+        *self.sync_targets.write().await = targets;
+    }
+
+    pub async fn set_ignored_sources(&self, names: Vec<String>) {
+        *self.ignored_sources.write().await = names.into_iter().collect();
+    }
+
+    pub async fn set_transform_mask(&self, mask: TransformFieldMask) {
+        *self.transform_mask.write().await = mask;
+    }
+
+    pub async fn set_interpolation(&self, interpolation: TransformInterpolation) {
+        *self.interpolation.write().await = interpolation;
+    }
+
+    pub fn set_apply_concurrency(&self, limit: usize) {
+        self.apply_concurrency.store(limit.max(1), Ordering::SeqCst);
+    }
+
+    pub fn apply_concurrency(&self) -> usize {
+        self.apply_concurrency.load(Ordering::SeqCst)
+    }
+
+    pub fn set_sync_hysteresis(&self, checks: u32) {
+        self.sync_hysteresis.store(checks.max(1), Ordering::SeqCst);
+    }
+
+    pub fn set_dry_run(&self, enabled: bool) {
+        self.dry_run.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::SeqCst)
+    }
+
+    pub async fn set_planned_action_sender(&self, tx: mpsc::UnboundedSender<PlannedAction>) {
+        *self.planned_action_tx.write().await = Some(tx);
+    }
+
+    pub async fn set_state_sync_report_sender(&self, tx: mpsc::UnboundedSender<StateSyncReport>) {
+        *self.state_sync_report_tx.write().await = Some(tx);
+    }
+
+    pub fn set_strict_state_sync(&self, enabled: bool) {
+        self.strict_state_sync.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Report a change skipped because of dry-run mode
+    async fn plan(&self, action: PlannedAction) {
+        println!(
+            "[dry run] {} {}/{}: {}",
+            action.action, action.scene_name, action.source_name, action.details
+        );
+        if let Some(tx) = self.planned_action_tx.read().await.as_ref() {
+            let _ = tx.send(action);
+        }
+    }
+
+    pub fn set_prefer_name_matching(&self, enabled: bool) {
+        self.prefer_name_matching.store(enabled, Ordering::SeqCst);
+    }
+
+    /// The local id of the item the master calls `scene_item_id`. With name matching on,
+    /// a source that appears exactly once in the scene wins; missing or duplicated
+    /// names (and lookup failures) fall back to the incoming id.
+    async fn resolve_scene_item_id(
+        &self,
+        client: &obws::Client,
+        scene_name: &str,
+        source_name: Option<&str>,
+        scene_item_id: i64,
+    ) -> i64 {
+        let source_name = match source_name {
+            Some(name) if !name.is_empty() && self.prefer_name_matching.load(Ordering::SeqCst) => {
+                name
+            }
+            _ => return scene_item_id,
+        };
+        let items = match client
+            .scene_items()
+            .list(obws::requests::scenes::SceneId::Name(scene_name))
+            .await
+        {
+            Ok(items) => items,
+            Err(e) => {
+                throttled_eprintln!(
+                    "Failed to list items of {} for name matching: {}",
+                    scene_name,
+                    e
+                );
+                return scene_item_id;
+            }
+        };
+
+        let id = Self::item_id_by_name(&items, source_name, scene_item_id);
+        if id != scene_item_id {
+            println!(
+                "Matched {} in scene {} by name (id {} -> {})",
+                source_name, scene_name, scene_item_id, id
+            );
+        }
+        id
+    }
+
+    /// Id of the only item in `items` showing `source_name`, else `scene_item_id`
+    fn item_id_by_name(
+        items: &[obws::responses::scene_items::SceneItem],
+        source_name: &str,
+        scene_item_id: i64,
+    ) -> i64 {
+        let mut matches = items.iter().filter(|item| item.source_name == source_name);
+        match (matches.next(), matches.next()) {
+            (Some(item), None) => item.id,
+            _ => scene_item_id,
+        }
+    }
+
+    /// Restack the local items of a scene to match the master's order (bottom first).
+    /// Each master item pairs with the local item of the same id and source, or else the
+    /// first unpaired local item showing that source; local-only and ignored items stay
+    /// where they are.
+    async fn apply_item_order(
+        &self,
+        client: &obws::Client,
+        scene_name: &str,
+        items: &[ReorderedItem],
+    ) -> Result<usize> {
+        let ignored = self.ignored_sources.read().await.clone();
+        let mut local = client
+            .scene_items()
+            .list(obws::requests::scenes::SceneId::Name(scene_name))
+            .await
+            .context("Failed to list scene items")?;
+        let mut order = Vec::with_capacity(items.len());
+        for item in items
+            .iter()
+            .filter(|item| !ignored.contains(&item.source_name))
+        {
+            let position = local
+                .iter()
+                .position(|l| l.id == item.scene_item_id && l.source_name == item.source_name)
+                .or_else(|| local.iter().position(|l| l.source_name == item.source_name));
+            if let Some(position) = position {
+                order.push(local.remove(position).id);
+            }
+        }
+        OBSCommands::set_scene_item_order(client, scene_name, &order).await
+    }
+
+    /// Remember a failed apply so the master sees it in the next StateReport
+    pub async fn record_apply_error(&self, error: String) {
+        let mut errors = self.apply_errors.write().await;
+        if errors.len() >= MAX_REPORTED_APPLY_ERRORS {
+            errors.remove(0);
+        }
+        errors.push(error);
+    }
+
+    /// Master hashes of the received images still on disk
+    pub async fn held_image_hashes(&self) -> Vec<String> {
+        self.image_cache.read().await.master_hashes()
+    }
+
+    pub async fn set_image_cache_limits(&self, limits: ImageCacheLimits) {
+        self.image_cache.write().await.set_limits(limits);
+    }
+
+    /// Start sweeping the image temp directory: files past the age limit or beyond
+    /// the size cap are deleted, including ones left behind by earlier runs
+    pub async fn start_image_cleanup(&self) {
+        let image_cache = self.image_cache.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                Self::clean_image_dir(&image_cache).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(
+                    IMAGE_CLEANUP_INTERVAL_SECS,
+                ))
+                .await;
+            }
+        });
+        if let Some(previous) = self.image_cleanup_task.write().await.replace(task) {
+            previous.abort();
+        }
+    }
+
+    pub async fn stop_image_cleanup(&self) {
+        if let Some(task) = self.image_cleanup_task.write().await.take() {
+            task.abort();
+        }
+    }
+
+    async fn clean_image_dir(image_cache: &RwLock<ImageFileCache>) {
+        // Nothing to clean until the first image has been received
+        let Ok(mut dir) = fs::read_dir(image_cache::image_dir()).await else {
+            return;
+        };
+        let max_age =
+            std::time::Duration::from_secs(image_cache.read().await.limits().max_age_secs);
+
+        let mut orphans = Vec::new();
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let modified = metadata
+                .modified()
+                .unwrap_or_else(|_| std::time::SystemTime::now());
+            if image_cache
+                .write()
+                .await
+                .adopt(&path, metadata.len(), modified)
+            {
+                continue;
+            }
+            // Files from older versions, which named them <source>_<timestamp>.<ext>
+            if modified.elapsed().is_ok_and(|age| age > max_age) {
+                orphans.push(path);
+            }
+        }
+
+        let mut expired = image_cache.write().await.sweep();
+        expired.extend(orphans);
+        if !expired.is_empty() {
+            println!("Removing {} expired image temp files", expired.len());
+            Self::remove_image_files(expired).await;
+        }
+    }
+
+    /// Load the persisted last-known-good cache and keep it up to date at `path`
+    pub async fn set_cache_path(&self, path: PathBuf) {
+        *self.applied_cache.write().await = AppliedStateCache::load(&path).await;
+        *self.cache_path.write().await = Some(path);
+    }
+
+    /// Make the next StateSync reapply every item, for resyncs asked for outside
+    /// `SlaveSync` (e.g. `SlaveClient::request_resync`)
+    pub fn expect_full_resync(&self) {
+        self.full_resync_pending.store(true, Ordering::SeqCst);
+    }
+
+    /// Forget what was applied, e.g. after OBS restarted and may have lost or reverted it.
+    /// The next StateSync then reapplies every item.
+    pub async fn clear_applied_cache(&self) {
+        self.applied_cache.write().await.clear();
+        self.applied_image_hashes.write().await.clear();
+        self.save_applied_cache().await;
+    }
+
+    async fn save_applied_cache(&self) {
+        if let Some(path) = self.cache_path.read().await.as_ref() {
+            if let Err(e) = self.applied_cache.read().await.save(path).await {
+                throttled_eprintln!("Failed to save slave cache: {}", e);
+            }
+        }
+    }
+
+    /// Whether a target is synced for a scene, resolved as the master does
+    fn is_target_allowed(
+        overrides: &SceneTargetOverrides,
+        global: &[SyncTargetType],
+        scene_name: &str,
+        target: &SyncTargetType,
+    ) -> bool {
+        targets_for_scene(overrides, scene_name, global).contains(target)
+    }
+
+    /// Start periodic state checking task.
+    /// The interval is re-read from the tuning on every iteration.
+    pub async fn start_periodic_check(&self) {
         let obs_client = self.obs_client.clone();
         let expected_state = self.expected_state.clone();
         let alert_tx = self.alert_tx.clone();
         let state_report_tx = self.state_report_tx.clone();
+        let tuning = self.tuning.clone();
+        let scene_target_overrides = self.scene_target_overrides.clone();
+        let sync_targets = self.sync_targets.clone();
+        let transform_mask = self.transform_mask.clone();
+        let ignored_sources = self.ignored_sources.clone();
+        let apply_errors = self.apply_errors.clone();
+        let sync_hysteresis = self.sync_hysteresis.clone();
+        let desync_alert_log = self.desync_alert_log.clone();
+        let image_cache = self.image_cache.clone();
+        let applied_cache = self.applied_cache.clone();
+        let applied_image_hashes = self.applied_image_hashes.clone();
+        let cache_path = self.cache_path.clone();
+        let full_resync_pending = self.full_resync_pending.clone();
 
-        tokio::spawn(async move {
-            let mut interval =
-                tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+        let task = tokio::spawn(async move {
+            let mut consecutive_desyncs: u32 = 0;
+            let mut sync_state = SyncStateHysteresis::new();
 
             loop {
-                interval.tick().await;
+                let tuning_snapshot = tuning.read().await.clone();
+                tokio::time::sleep(tokio::time::Duration::from_secs(
+                    tuning_snapshot.desync_check_interval_secs.max(1),
+                ))
+                .await;
 
-                // Get current local OBS state
-                let local_state = match Self::get_current_obs_state(&obs_client).await {
-                    Ok(state) => state,
+                let (local_state, diffs) = match Self::diff_against_expected(
+                    &obs_client,
+                    &expected_state,
+                    &tuning_snapshot,
+                    &scene_target_overrides,
+                    &sync_targets,
+                    &transform_mask,
+                    &ignored_sources,
+                )
+                .await
+                {
+                    Ok(Some(result)) => result,
+                    // No expected state yet, skip check
+                    Ok(None) => continue,
                     Err(e) => {
-                        eprintln!("Failed to get local OBS state: {}", e);
+                        throttled_eprintln!("Failed to get local OBS state: {}", e);
                         continue;
                     }
                 };
 
-                // Compare with expected state
-                let expected = expected_state.read().await;
-                if expected.is_null() || expected.as_object().map(|o| o.is_empty()).unwrap_or(true)
-                {
-                    // No expected state yet, skip check
-                    continue;
-                }
+                let is_synced =
+                    sync_state.observe(diffs.is_empty(), sync_hysteresis.load(Ordering::SeqCst));
 
-                let diffs = DiffDetector::detect_differences(&local_state, &expected);
+                // Drifted items no longer match what was applied, so the next StateSync
+                // must not skip them as unchanged
+                if !diffs.is_empty() {
+                    let mut evicted = false;
+                    {
+                        let mut cache = applied_cache.write().await;
+                        let mut image_hashes = applied_image_hashes.write().await;
+                        for diff in &diffs {
+                            evicted |= cache.forget(&diff.scene_name, &diff.source_name);
+                            image_hashes.remove(&diff.source_name);
+                        }
+                    }
+                    if evicted {
+                        if let Some(path) = cache_path.read().await.as_ref() {
+                            if let Err(e) = applied_cache.read().await.save(path).await {
+                                throttled_eprintln!("Failed to save slave cache: {}", e);
+                            }
+                        }
+                    }
+                }
 
                 // Send state report to Master
                 {
                     let tx = state_report_tx.read().await;
                     if let Some(sender) = tx.as_ref() {
-                        let desync_details: Vec<serde_json::Value> = diffs
+                        let reported_diffs: &[StateDifference] =
+                            if is_synced { &[] } else { &diffs };
+                        let desync_details: Vec<serde_json::Value> = reported_diffs
                             .iter()
                             .map(|diff| {
                                 serde_json::json!({
@@ -100,50 +1020,594 @@ impl SlaveSync {
                                 })
                             })
                             .collect();
+                        let errors = std::mem::take(&mut *apply_errors.write().await);
 
                         let report = SyncMessage::new(
                             SyncMessageType::StateReport,
                             SyncTargetType::Program,
                             serde_json::json!({
-                                "is_synced": diffs.is_empty(),
+                                "is_synced": is_synced,
                                 "desync_details": desync_details,
                                 "current_state": local_state,
+                                "apply_errors": errors,
                             }),
                         );
 
                         if let Err(e) = sender.send(report) {
-                            eprintln!("Failed to send state report: {}", e);
+                            throttled_eprintln!("Failed to send state report: {}", e);
+                        }
+
+                        // Ask the master for a full resync once desync persists
+                        consecutive_desyncs = if diffs.is_empty() {
+                            0
+                        } else {
+                            consecutive_desyncs + 1
+                        };
+                        let threshold = tuning_snapshot.auto_resync_threshold;
+                        if threshold > 0 && consecutive_desyncs >= threshold {
+                            println!(
+                                "Desync persisted for {} checks, requesting resync",
+                                consecutive_desyncs
+                            );
+                            let held_images = image_cache.read().await.master_hashes();
+                            full_resync_pending.store(true, Ordering::SeqCst);
+                            if let Err(e) =
+                                sender.send(SyncMessage::state_sync_request(held_images))
+                            {
+                                eprintln!("Failed to send resync request: {}", e);
+                            }
+                            consecutive_desyncs = 0;
                         }
                     }
                 }
 
-                if !diffs.is_empty() {
+                // Differences seen while still reported as synced are held back until they persist
+                if !is_synced && !diffs.is_empty() {
                     println!("⚠️  Detected {} state difference(s)", diffs.len());
+                }
+                let alerts = desync_alert_log
+                    .write()
+                    .await
+                    .alerts_for(&diffs, !is_synced);
+                for alert in alerts {
+                    if let Err(e) = alert_tx.send(alert) {
+                        throttled_eprintln!("Failed to send desync alert: {}", e);
+                    }
+                }
+            }
+        });
+        if let Some(previous) = self.periodic_task.write().await.replace(task) {
+            previous.abort();
+        }
+    }
 
-                    for diff in diffs {
-                        let severity = match diff.severity {
-                            DiffSeverity::Critical => AlertSeverity::Error,
-                            _ => AlertSeverity::Warning,
-                        };
+    /// Abort the periodic state checking task (no-op if it isn't running)
+    pub async fn stop_periodic_check(&self) {
+        if let Some(task) = self.periodic_task.write().await.take() {
+            task.abort();
+        }
+    }
 
-                        let alert = DesyncAlert {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            timestamp: chrono::Utc::now().timestamp_millis(),
-                            scene_name: diff.scene_name,
-                            source_name: diff.source_name,
-                            message: diff.description,
-                            severity,
-                        };
+    /// Replace the master's position of anchored sources with where the anchor puts them
+    /// on this slave's canvas, as `apply_transform` does
+    async fn resolve_expected_anchors(
+        obs_client: &Arc<OBSClient>,
+        sources: &mut serde_json::Value,
+    ) {
+        let Some(sources) = sources.as_array_mut() else {
+            return;
+        };
+        let mut canvas = None;
+        for source in sources.iter_mut() {
+            let Some(anchor) = source["transform"]
+                .get("anchor")
+                .cloned()
+                .and_then(|v| serde_json::from_value::<Option<AnchoredPosition>>(v).ok())
+                .flatten()
+            else {
+                continue;
+            };
+            if canvas.is_none() {
+                let client_arc = obs_client.get_client_arc();
+                let client_lock = client_arc.read().await;
+                let Some(client) = client_lock.as_ref() else {
+                    return;
+                };
+                match canvas_size(client).await {
+                    Ok(size) => canvas = Some(size),
+                    Err(_) => return,
+                }
+            }
+            let Some(canvas) = canvas else {
+                return;
+            };
+            let (x, y) = anchor.resolve(canvas);
+            source["transform"]["position_x"] = serde_json::json!(x);
+            source["transform"]["position_y"] = serde_json::json!(y);
+        }
+    }
 
-                        if let Err(e) = alert_tx.send(alert) {
-                            eprintln!("Failed to send desync alert: {}", e);
-                        }
-                    }
+    /// Compare local OBS state with the expected state from the master, honouring
+    /// tolerances, the transform mask and scene overrides. None until a StateSync arrived.
+    async fn diff_against_expected(
+        obs_client: &Arc<OBSClient>,
+        expected_state: &RwLock<serde_json::Value>,
+        tuning: &SyncTuning,
+        scene_target_overrides: &RwLock<SceneTargetOverrides>,
+        sync_targets: &RwLock<Vec<SyncTargetType>>,
+        transform_mask: &RwLock<TransformFieldMask>,
+        ignored_sources: &RwLock<HashSet<String>>,
+    ) -> Result<Option<(serde_json::Value, Vec<StateDifference>)>> {
+        let local_state = Self::get_current_obs_state(obs_client).await?;
+
+        let mut expected = expected_state.read().await.clone();
+        if expected.is_null() || expected.as_object().map(|o| o.is_empty()).unwrap_or(true) {
+            return Ok(None);
+        }
+        // Compare the local scene's sources against what the master has in that scene
+        if let Some(sources) = local_state["current_scene"]
+            .as_str()
+            .and_then(|scene| expected["scene_sources"].get(scene))
+            .cloned()
+        {
+            expected["sources"] = sources;
+        }
+        Self::resolve_expected_anchors(obs_client, &mut expected["sources"]).await;
+        let mut diffs = DiffDetector::detect_differences(
+            &local_state,
+            &expected,
+            &tuning.diff_tolerance,
+            &*transform_mask.read().await,
+        );
+
+        // Ignore differences for targets a scene override excludes
+        let overrides = scene_target_overrides.read().await;
+        let global = sync_targets.read().await;
+        diffs.retain(|diff| {
+            let target = match diff.category {
+                DiffCategory::SceneMismatch => SyncTargetType::Program,
+                _ => SyncTargetType::Source,
+            };
+            Self::is_target_allowed(&overrides, &global, &diff.scene_name, &target)
+        });
+        // Sources customized on purpose aren't out of sync
+        let ignored = ignored_sources.read().await;
+        diffs.retain(|diff| !ignored.contains(&diff.source_name));
+
+        Ok(Some((local_state, diffs)))
+    }
+
+    /// Local file paths referenced by an input's settings
+    fn referenced_paths(input_kind: &str, settings: &serde_json::Value) -> Vec<String> {
+        let playlist = |key: &str| -> Vec<String> {
+            settings[key]
+                .as_array()
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| entry["value"].as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let path = |key: &str| settings[key].as_str().map(String::from);
+
+        let paths = match input_kind {
+            // Media sources may point at a URL instead of a local file
+            "ffmpeg_source" => {
+                if settings["is_local_file"].as_bool().unwrap_or(true) {
+                    path("local_file").into_iter().collect()
+                } else {
+                    Vec::new()
+                }
+            }
+            "vlc_source" => playlist("playlist"),
+            "slideshow" | "slideshow_v2" => playlist("files"),
+            _ => path("file")
+                .or_else(|| path("local_file"))
+                .into_iter()
+                .collect(),
+        };
+        paths.into_iter().filter(|p| !p.is_empty()).collect()
+    }
+
+    /// Check every local file referenced by OBS inputs (media, VLC, slideshow, image...)
+    /// so missing files on this machine are found before going live
+    pub async fn validate_media_availability(
+        obs_client: &OBSClient,
+    ) -> Result<Vec<MediaAvailability>> {
+        let client_arc = obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().context("OBS client not connected")?;
+
+        let inputs = client
+            .inputs()
+            .list(None)
+            .await
+            .context("Failed to list inputs")?;
+
+        let mut results = Vec::new();
+        for input in inputs {
+            let settings = match client
+                .inputs()
+                .settings::<serde_json::Value>(obws::requests::inputs::InputId::Name(
+                    &input.id.name,
+                ))
+                .await
+            {
+                Ok(settings) => settings.settings,
+                Err(e) => {
+                    eprintln!("Failed to get settings for {}: {}", input.id.name, e);
+                    continue;
+                }
+            };
+
+            results.extend(
+                Self::media_availability(
+                    &input.id.name,
+                    &input.kind,
+                    &input.unversioned_kind,
+                    &settings,
+                )
+                .await,
+            );
+        }
+        Ok(results)
+    }
+
+    /// Whether each local file referenced by one input exists on this machine
+    async fn media_availability(
+        source_name: &str,
+        input_kind: &str,
+        unversioned_kind: &str,
+        settings: &serde_json::Value,
+    ) -> Vec<MediaAvailability> {
+        let mut results = Vec::new();
+        for path in Self::referenced_paths(unversioned_kind, settings) {
+            let exists = fs::try_exists(&path).await.unwrap_or(false);
+            results.push(MediaAvailability {
+                source_name: source_name.to_string(),
+                input_kind: input_kind.to_string(),
+                path,
+                exists,
+            });
+        }
+        results
+    }
+
+    /// Request a fresh StateSync, then make the local scenes and their items match the
+    /// master's layout: missing scenes and items are created, extra ones removed, items
+    /// reordered and enabled states set. Scenes excluded by a scene override are left alone.
+    /// Destructive; every change (and failed attempt) is returned.
+    pub async fn normalize_to_master(
+        &self,
+        timeout: tokio::time::Duration,
+    ) -> Result<Vec<NormalizeChange>> {
+        let applied = self.state_sync_applied.notified();
+        tokio::pin!(applied);
+        applied.as_mut().enable();
+        {
+            let held_images = self.held_image_hashes().await;
+            let tx = self.state_report_tx.read().await;
+            let sender = tx.as_ref().context("Not connected to a master")?;
+            self.expect_full_resync();
+            sender
+                .send(SyncMessage::state_sync_request(held_images))
+                .context("Failed to request state from master")?;
+        }
+        tokio::time::timeout(timeout, applied)
+            .await
+            .context("Timed out waiting for the master's state")?;
+
+        let overrides = self.scene_target_overrides.read().await.clone();
+        let global = self.sync_targets.read().await.clone();
+        let synced = |name: &str| {
+            Self::is_target_allowed(&overrides, &global, name, &SyncTargetType::Source)
+        };
+        let layout = self
+            .master_layout
+            .read()
+            .await
+            .clone()
+            .context("No state received from master")?;
+        let dry_run = self.is_dry_run();
+
+        let client_arc = self.obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().context("OBS client not connected")?;
+
+        let local_scenes: Vec<String> = client
+            .scenes()
+            .list()
+            .await
+            .context("Failed to list scenes")?
+            .scenes
+            .into_iter()
+            .map(|scene| scene.id.name)
+            .collect();
+        // Guard against wiping a collection that has nothing to do with the master's
+        if !layout
+            .scenes
+            .iter()
+            .any(|scene| local_scenes.contains(&scene.name))
+        {
+            anyhow::bail!(
+                "None of the master's scenes exist locally; refusing to reconcile (wrong scene collection?)"
+            );
+        }
+
+        let mut changes = Vec::new();
+        let (add_scenes, remove_scenes) = layout.plan_scenes(&local_scenes, synced);
+        for scene_name in &add_scenes {
+            let error = if dry_run {
+                None
+            } else {
+                client.scenes().create(scene_name).await.err()
+            };
+            self.record_normalize(
+                &mut changes,
+                NormalizeAction::AddScene,
+                scene_name,
+                None,
+                "created".to_string(),
+                error.map(|e| e.to_string()),
+            )
+            .await;
+        }
+        for scene_name in &remove_scenes {
+            let error = if dry_run {
+                None
+            } else {
+                client
+                    .scenes()
+                    .remove(obws::requests::scenes::SceneId::Name(scene_name))
+                    .await
+                    .err()
+            };
+            self.record_normalize(
+                &mut changes,
+                NormalizeAction::RemoveScene,
+                scene_name,
+                None,
+                "not on master".to_string(),
+                error.map(|e| e.to_string()),
+            )
+            .await;
+        }
+
+        for scene in layout.scenes.iter().filter(|scene| synced(&scene.name)) {
+            self.normalize_scene_items(client, &layout, scene, &mut changes)
+                .await;
+        }
+
+        // Items created above missed the transforms/filters of the StateSync we waited for
+        if !dry_run
+            && changes
+                .iter()
+                .any(|change| change.action == NormalizeAction::AddItem)
+        {
+            let held_images = self.held_image_hashes().await;
+            if let Some(sender) = self.state_report_tx.read().await.as_ref() {
+                self.expect_full_resync();
+                if let Err(e) = sender.send(SyncMessage::state_sync_request(held_images)) {
+                    eprintln!("Failed to request resync after normalizing: {}", e);
                 }
             }
+        }
+
+        println!(
+            "Normalized local scenes to master: {} change(s){}",
+            changes.len(),
+            if dry_run {
+                " (dry run, nothing changed)"
+            } else {
+                ""
+            }
+        );
+        Ok(changes)
+    }
+
+    /// Add a normalize change to the log; in dry-run mode it is also emitted as planned
+    async fn record_normalize(
+        &self,
+        changes: &mut Vec<NormalizeChange>,
+        action: NormalizeAction,
+        scene_name: &str,
+        source_name: Option<&str>,
+        detail: String,
+        error: Option<String>,
+    ) {
+        let planned = self.is_dry_run();
+        if planned {
+            self.plan(PlannedAction::new(
+                "normalize",
+                scene_name,
+                source_name.unwrap_or(""),
+                serde_json::json!({ "action": action, "detail": detail }),
+            ))
+            .await;
+        }
+        changes.push(NormalizeChange {
+            action,
+            scene_name: scene_name.to_string(),
+            source_name: source_name.map(String::from),
+            detail,
+            error,
+            planned,
         });
     }
 
+    /// Reconcile the items of one scene with the master's layout
+    async fn normalize_scene_items(
+        &self,
+        client: &obws::Client,
+        layout: &MasterLayout,
+        scene: &MasterScene,
+        changes: &mut Vec<NormalizeChange>,
+    ) {
+        let dry_run = self.is_dry_run();
+        let scene_id = obws::requests::scenes::SceneId::Name(&scene.name);
+        let list_items = || async {
+            let mut items = client.scene_items().list(scene_id).await?;
+            items.sort_by_key(|item| item.index);
+            Ok::<_, obws::error::Error>(
+                items
+                    .into_iter()
+                    .map(|item| LocalItem {
+                        id: item.id,
+                        source_name: item.source_name,
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let local_items = match list_items().await {
+            Ok(items) => items,
+            // A scene created in a dry run doesn't exist yet
+            Err(_) if dry_run => Vec::new(),
+            Err(e) => {
+                eprintln!("Failed to list items of {}: {}", scene.name, e);
+                return;
+            }
+        };
+        let mut plan = layout.plan_items(scene, &local_items);
+
+        for extra in &plan.remove {
+            let error = if dry_run {
+                None
+            } else {
+                OBSCommands::remove_scene_item(client, &scene.name, extra.id)
+                    .await
+                    .err()
+            };
+            self.record_normalize(
+                changes,
+                NormalizeAction::RemoveItem,
+                &scene.name,
+                Some(&extra.source_name),
+                format!("id {} not on master", extra.id),
+                error.map(|e| e.to_string()),
+            )
+            .await;
+        }
+        for (item, id) in plan.paired.iter_mut().filter(|(_, id)| id.is_none()) {
+            let (detail, error) = if dry_run {
+                ("created".to_string(), None)
+            } else {
+                match OBSCommands::create_scene_item(
+                    client,
+                    &scene.name,
+                    &item.source_name,
+                    item.enabled,
+                )
+                .await
+                {
+                    Ok(new_id) => {
+                        *id = Some(new_id);
+                        (format!("created as id {}", new_id), None)
+                    }
+                    Err(e) => ("create failed".to_string(), Some(e.to_string())),
+                }
+            };
+            self.record_normalize(
+                changes,
+                NormalizeAction::AddItem,
+                &scene.name,
+                Some(&item.source_name),
+                detail,
+                error,
+            )
+            .await;
+        }
+
+        // Put the items that now exist in the master's order, bottom first
+        let current: Vec<i64> = if dry_run {
+            local_items
+                .iter()
+                .filter(|local| !plan.remove.contains(local))
+                .map(|local| local.id)
+                .collect()
+        } else {
+            match list_items().await {
+                Ok(items) => items.into_iter().map(|item| item.id).collect(),
+                Err(e) => {
+                    eprintln!("Failed to list items of {}: {}", scene.name, e);
+                    return;
+                }
+            }
+        };
+        let desired: Vec<i64> = plan.paired.iter().filter_map(|(_, id)| *id).collect();
+        for (id, from, to) in reorder_moves(&current, &desired) {
+            let error = if dry_run {
+                None
+            } else {
+                client
+                    .scene_items()
+                    .set_index(obws::requests::scene_items::SetIndex {
+                        scene: scene_id,
+                        item_id: id,
+                        index: to as u32,
+                    })
+                    .await
+                    .err()
+            };
+            let source_name = plan
+                .paired
+                .iter()
+                .find(|(_, paired)| *paired == Some(id))
+                .map(|(item, _)| item.source_name.as_str());
+            self.record_normalize(
+                changes,
+                NormalizeAction::ReorderItem,
+                &scene.name,
+                source_name,
+                format!("index {} -> {}", from, to),
+                error.map(|e| e.to_string()),
+            )
+            .await;
+        }
+
+        for (item, id) in &plan.paired {
+            let (Some(enabled), Some(id)) = (item.enabled, *id) else {
+                continue;
+            };
+            let local_enabled = client.scene_items().enabled(scene_id, id).await.ok();
+            if local_enabled.is_some_and(|local| local != enabled) {
+                let error = if dry_run {
+                    None
+                } else {
+                    OBSCommands::set_scene_item_enabled(client, &scene.name, id, enabled)
+                        .await
+                        .err()
+                };
+                self.record_normalize(
+                    changes,
+                    NormalizeAction::SetEnabled,
+                    &scene.name,
+                    Some(&item.source_name),
+                    format!("enabled = {}", enabled),
+                    error.map(|e| e.to_string()),
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Run the desync comparison immediately instead of waiting for the periodic check
+    pub async fn compare_now(&self) -> Result<Option<Vec<StateDifference>>> {
+        let tuning = self.tuning.read().await.clone();
+        Ok(Self::diff_against_expected(
+            &self.obs_client,
+            &self.expected_state,
+            &tuning,
+            &self.scene_target_overrides,
+            &self.sync_targets,
+            &self.transform_mask,
+            &self.ignored_sources,
+        )
+        .await?
+        .map(|(_, diffs)| diffs))
+    }
+
     /// Get current OBS state for comparison
     async fn get_current_obs_state(obs_client: &Arc<OBSClient>) -> Result<serde_json::Value> {
         let client_arc = obs_client.get_client_arc();
@@ -157,10 +1621,8 @@ impl SlaveSync {
                 .await
                 .context("Failed to get current scene")?;
 
-            // Convert CurrentProgramScene to SceneId
-            // CurrentProgramScene has a scene_name field that can be converted to SceneId
-            let scene_name = format!("{:?}", current_scene);
-            let scene_id: obws::requests::scenes::SceneId = scene_name.as_str().into();
+            let scene_name = current_scene.scene_name();
+            let scene_id: obws::requests::scenes::SceneId = scene_name.into();
 
             // Get sources in current scene
             let items = client
@@ -172,21 +1634,27 @@ impl SlaveSync {
             let mut sources = Vec::new();
             for item in items {
                 let transform = client.scene_items().transform(scene_id, item.id).await.ok();
+                let enabled = client.scene_items().enabled(scene_id, item.id).await.ok();
 
                 sources.push(serde_json::json!({
                     "name": item.source_name,
+                    "enabled": enabled,
                     "transform": transform.map(|t| serde_json::json!({
                         "position_x": t.position_x,
                         "position_y": t.position_y,
                         "scale_x": t.scale_x,
                         "scale_y": t.scale_y,
                         "rotation": t.rotation,
+                        "crop_left": t.crop_left,
+                        "crop_right": t.crop_right,
+                        "crop_top": t.crop_top,
+                        "crop_bottom": t.crop_bottom,
                     })),
                 }));
             }
 
             Ok(serde_json::json!({
-                "current_scene": format!("{:?}", current_scene),
+                "current_scene": scene_name,
                 "sources": sources,
             }))
         } else {
@@ -194,12 +1662,20 @@ impl SlaveSync {
         }
     }
 
+    /// Whether a StateSync was taken before the newest SceneChange already received
+    fn is_overtaken_by_scene_change(&self, message: &SyncMessage) -> bool {
+        message.timestamp < self.last_scene_change_at.load(Ordering::SeqCst)
+    }
+
     /// Update expected state from sync message
     async fn update_expected_state(&self, message: &SyncMessage) {
         let mut expected = self.expected_state.write().await;
 
         match message.message_type {
-            SyncMessageType::SceneChange => {
+            // The desync check compares the program scene only
+            SyncMessageType::SceneChange if message.target_type != SyncTargetType::Preview => {
+                self.last_scene_change_at
+                    .fetch_max(message.timestamp, Ordering::SeqCst);
                 if let Some(scene_name) = message.payload["scene_name"].as_str() {
                     expected["current_scene"] = serde_json::json!(scene_name);
                 }
@@ -207,18 +1683,218 @@ impl SlaveSync {
             SyncMessageType::StateSync => {
                 // Full state update
                 if let Some(current_scene) = message.payload["current_program_scene"].as_str() {
-                    expected["current_scene"] = serde_json::json!(current_scene);
+                    if !self.is_overtaken_by_scene_change(message) {
+                        expected["current_scene"] = serde_json::json!(current_scene);
+                    }
+                }
+                // Remember which sources each scene contains and whether they're enabled
+                if let Some(scenes) = message.payload["scenes"].as_array() {
+                    let mut scene_sources = serde_json::Map::new();
+                    for scene in scenes {
+                        let (Some(name), Some(items)) =
+                            (scene["name"].as_str(), scene["items"].as_array())
+                        else {
+                            continue;
+                        };
+                        let sources = items
+                            .iter()
+                            .map(|item| {
+                                serde_json::json!({
+                                    "name": item["source_name"],
+                                    "enabled": item["enabled"],
+                                    "transform": item["transform"],
+                                })
+                            })
+                            .collect();
+                        scene_sources.insert(name.to_string(), serde_json::Value::Array(sources));
+                    }
+                    expected["scene_sources"] = serde_json::Value::Object(scene_sources);
+                }
+            }
+            SyncMessageType::SourceUpdate => {
+                let Ok(payload) =
+                    serde_json::from_value::<SourceUpdatePayload>(message.payload.clone())
+                else {
+                    return;
+                };
+                let Some(sources) = expected
+                    .get_mut("scene_sources")
+                    .and_then(|scenes| scenes.get_mut(&payload.scene_name))
+                    .and_then(|sources| sources.as_array_mut())
+                else {
+                    return;
+                };
+                let is_source =
+                    |s: &serde_json::Value| s["name"].as_str() == Some(&payload.source_name);
+                match payload.action {
+                    SourceUpdateAction::Created => sources.push(serde_json::json!({
+                        "name": payload.source_name,
+                        "enabled": payload.scene_item_enabled.unwrap_or(true),
+                        "transform": payload.transform,
+                    })),
+                    SourceUpdateAction::Removed => sources.retain(|s| !is_source(s)),
+                    SourceUpdateAction::EnabledStateChanged => {
+                        for source in sources.iter_mut().filter(|s| is_source(s)) {
+                            source["enabled"] = serde_json::json!(payload.scene_item_enabled);
+                        }
+                    }
+                    SourceUpdateAction::SettingsChanged => {}
+                }
+            }
+            SyncMessageType::TransformUpdate => {
+                let Ok(payload) =
+                    serde_json::from_value::<TransformUpdatePayload>(message.payload.clone())
+                else {
+                    return;
+                };
+                let Some(source_name) = payload.source_name else {
+                    return;
+                };
+                let Some(sources) = expected
+                    .get_mut("scene_sources")
+                    .and_then(|scenes| scenes.get_mut(&payload.scene_name))
+                    .and_then(|sources| sources.as_array_mut())
+                else {
+                    return;
+                };
+                let transform = serde_json::to_value(&payload.transform).unwrap_or_default();
+                for source in sources
+                    .iter_mut()
+                    .filter(|s| s["name"].as_str() == Some(&source_name))
+                {
+                    source["transform"] = transform.clone();
+                }
+            }
+            SyncMessageType::SceneLifecycle => {
+                let Ok(payload) =
+                    serde_json::from_value::<SceneLifecyclePayload>(message.payload.clone())
+                else {
+                    return;
+                };
+                let Some(scenes) = expected
+                    .get_mut("scene_sources")
+                    .and_then(|scenes| scenes.as_object_mut())
+                else {
+                    return;
+                };
+                match payload.action {
+                    SceneLifecycleAction::Created => {
+                        scenes
+                            .entry(payload.scene_name)
+                            .or_insert_with(|| serde_json::json!([]));
+                    }
+                    SceneLifecycleAction::Removed => {
+                        scenes.remove(&payload.scene_name);
+                    }
                 }
-                // Could expand to include full scene data
             }
             _ => {}
         }
     }
 
+    /// Send a scene switch or transform made in this slave's OBS to the master
+    /// (bidirectional sync). Echoes of changes just applied from the master are dropped.
+    pub async fn send_local_update(&self, message: SyncMessage) -> Result<()> {
+        if self.echo_guard.write().await.origin_of(&message).is_some() {
+            return Ok(());
+        }
+        if let Some(sender) = self.state_report_tx.read().await.as_ref() {
+            sender
+                .send(message.with_origin(self.origin_id.clone()))
+                .context("Failed to send local update")?;
+        }
+        Ok(())
+    }
+
+    /// Confirm to the master that a message sent with `requires_ack` was applied
+    pub async fn send_ack(&self, message_id: &str) -> Result<()> {
+        if let Some(sender) = self.state_report_tx.read().await.as_ref() {
+            sender
+                .send(SyncMessage::ack(message_id))
+                .context("Failed to send ack")?;
+        }
+        Ok(())
+    }
+
     pub async fn apply_sync_message(&self, message: SyncMessage) -> Result<()> {
+        // A batch is applied as the updates it carries; one failure doesn't stop the rest
+        if message.message_type == SyncMessageType::TransformBatch {
+            let mut result = Ok(());
+            for update in message.split_transform_batch()? {
+                if let Err(e) = Box::pin(self.apply_sync_message(update)).await {
+                    result = Err(e);
+                }
+            }
+            return result;
+        }
+
+        // Names and paths come from the network; refuse odd ones before they reach OBS or disk
+        if let Err(e) = sanitize::check_payload(&message.payload) {
+            eprintln!("⚠️  Rejected {:?} from master: {}", message.message_type, e);
+            return Err(e.context(format!("Rejected {:?}", message.message_type)));
+        }
+
+        // Skip messages whose target is excluded for the scene they refer to
+        if let Some(scene_name) = message.payload["scene_name"].as_str() {
+            let overrides = self.scene_target_overrides.read().await;
+            let global = self.sync_targets.read().await;
+            if !Self::is_target_allowed(&overrides, &global, scene_name, &message.target_type) {
+                println!(
+                    "Skipping {:?} for scene {} (excluded by scene override)",
+                    message.message_type, scene_name
+                );
+                return Ok(());
+            }
+        }
+
+        // Leave sources customized on this slave alone
+        if let Some(source_name) = ["source_name", "input_name"]
+            .iter()
+            .find_map(|field| message.payload[*field].as_str())
+        {
+            if self.ignored_sources.read().await.contains(source_name) {
+                println!(
+                    "Skipping {:?} for {} (ignored on this slave)",
+                    message.message_type, source_name
+                );
+                return Ok(());
+            }
+        }
+
+        // Latency probes don't touch OBS; answer them right away
+        if message.message_type == SyncMessageType::LatencyProbe {
+            let probe_id = message.payload["probe_id"]
+                .as_str()
+                .context("Invalid probe_id in payload")?;
+            if let Some(sender) = self.state_report_tx.read().await.as_ref() {
+                sender
+                    .send(SyncMessage::latency_ack(probe_id))
+                    .context("Failed to send latency ack")?;
+            }
+            return Ok(());
+        }
+
+        // Only slaves send these; receiving one means the stream is role-confused.
+        // Failing the apply puts the warning on screen rather than only in the log.
+        if matches!(
+            message.message_type,
+            SyncMessageType::StateReport | SyncMessageType::StateSyncRequest
+        ) {
+            return Err(anyhow::anyhow!(
+                "Received master-directed message {:?}; check roles (is this node connected to itself or to another slave?)",
+                message.message_type
+            ));
+        }
+
         // Update expected state first
         self.update_expected_state(&message).await;
 
+        // The master re-broadcasts our own bidirectional changes; OBS already has them
+        if !message.origin_id.is_empty() && message.origin_id == self.origin_id {
+            return Ok(());
+        }
+        self.echo_guard.write().await.record(&message);
+
         let client_arc = self.obs_client.get_client_arc();
         let client_lock = client_arc.read().await;
         let client = client_lock.as_ref().context("OBS client not connected")?;
@@ -229,6 +1905,72 @@ impl SlaveSync {
                     .as_str()
                     .context("Invalid scene_name in payload")?;
 
+                let transition = message
+                    .payload
+                    .get("transition")
+                    .cloned()
+                    .and_then(|t| serde_json::from_value::<SceneTransition>(t).ok());
+
+                // Preview changes never touch the program scene or its transition
+                if message.target_type == SyncTargetType::Preview {
+                    if self.is_dry_run() {
+                        self.plan(PlannedAction::new(
+                            "set_preview_scene",
+                            scene_name,
+                            "",
+                            serde_json::Value::Null,
+                        ))
+                        .await;
+                        return Ok(());
+                    }
+                    if let Err(e) = OBSCommands::set_current_preview_scene(client, scene_name).await
+                    {
+                        self.send_alert(
+                            scene_name.to_string(),
+                            String::new(),
+                            format!("Failed to change preview scene: {}", e),
+                            AlertSeverity::Error,
+                        )?;
+                    }
+                    return Ok(());
+                }
+
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_program_scene",
+                        scene_name,
+                        "",
+                        serde_json::to_value(&transition).unwrap_or(serde_json::Value::Null),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                if let Some(transition) = &transition {
+                    match OBSCommands::use_transition(
+                        client,
+                        &transition.name,
+                        transition.duration_ms,
+                    )
+                    .await
+                    {
+                        Ok(true) => {}
+                        Ok(false) => self.send_alert(
+                            scene_name.to_string(),
+                            String::new(),
+                            format!(
+                                "Transition '{}' does not exist on this machine; switched with a cut",
+                                transition.name
+                            ),
+                            AlertSeverity::Warning,
+                        )?,
+                        Err(e) => self.send_alert(
+                            scene_name.to_string(),
+                            String::new(),
+                            format!("Failed to set transition '{}': {}", transition.name, e),
+                            AlertSeverity::Warning,
+                        )?,
+                    }
+                }
                 if let Err(e) = OBSCommands::set_current_program_scene(client, scene_name).await {
                     self.send_alert(
                         scene_name.to_string(),
@@ -245,11 +1987,38 @@ impl SlaveSync {
                 let scene_item_id = message.payload["scene_item_id"]
                     .as_i64()
                     .context("Invalid scene_item_id")?;
+                let scene_item_id = self
+                    .resolve_scene_item_id(
+                        client,
+                        scene_name,
+                        message.payload["source_name"].as_str(),
+                        scene_item_id,
+                    )
+                    .await;
 
                 // Apply transform if included in payload
                 if let Some(transform) = message.payload["transform"].as_object() {
+                    if self.is_dry_run() {
+                        self.plan(PlannedAction::new(
+                            "set_transform",
+                            scene_name,
+                            message.payload["source_name"].as_str().unwrap_or(""),
+                            serde_json::json!({
+                                "scene_item_id": scene_item_id,
+                                "transform": transform,
+                            }),
+                        ))
+                        .await;
+                        return Ok(());
+                    }
                     if let Err(e) = self
-                        .apply_transform(client, scene_name, scene_item_id, transform)
+                        .apply_transform(
+                            client,
+                            scene_name,
+                            scene_item_id,
+                            transform,
+                            Some(message.timestamp),
+                        )
                         .await
                     {
                         self.send_alert(
@@ -273,11 +2042,31 @@ impl SlaveSync {
                     .as_str()
                     .context("Invalid source_name")?;
                 let file_path = message.payload["file"].as_str().unwrap_or("");
-                let image_data = message.payload["image_data"].as_str();
+                let image = ReceivedImage {
+                    data: message.payload["image_data"].as_str(),
+                    hash: message.payload["image_hash"].as_str(),
+                    format: message.payload["image_format"].as_str(),
+                    compression: message.payload["compression"].as_str(),
+                };
+
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_image",
+                        "",
+                        source_name,
+                        serde_json::json!({
+                            "file": file_path,
+                            "image_hash": image.hash,
+                            "encoded_size": image.data.map(str::len),
+                        }),
+                    ))
+                    .await;
+                    return Ok(());
+                }
 
                 // Handle image update
                 if let Err(e) = self
-                    .handle_image_update(client, source_name, file_path, image_data)
+                    .handle_image_update(client, source_name, file_path, image)
                     .await
                 {
                     self.send_alert(
@@ -288,6 +2077,31 @@ impl SlaveSync {
                     )?;
                 }
             }
+            SyncMessageType::SourceSettingsUpdate => {
+                let payload: SourceSettingsPayload =
+                    serde_json::from_value(message.payload.clone())
+                        .context("Failed to parse SourceSettingsPayload")?;
+
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_input_settings",
+                        "",
+                        &payload.source_name,
+                        payload.settings.clone(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+
+                if let Err(e) = self.apply_source_settings(client, &payload).await {
+                    self.send_alert(
+                        String::new(),
+                        payload.source_name.clone(),
+                        format!("Failed to update settings: {}", e),
+                        AlertSeverity::Warning,
+                    )?;
+                }
+            }
             SyncMessageType::FilterUpdate => {
                 let source_name = message.payload["source_name"]
                     .as_str()
@@ -298,6 +2112,19 @@ impl SlaveSync {
 
                 // Get filter settings from payload
                 if let Some(filter_settings) = message.payload["filter_settings"].as_object() {
+                    if self.is_dry_run() {
+                        self.plan(PlannedAction::new(
+                            "set_filter_settings",
+                            "",
+                            source_name,
+                            serde_json::json!({
+                                "filter_name": filter_name,
+                                "filter_settings": filter_settings,
+                            }),
+                        ))
+                        .await;
+                        return Ok(());
+                    }
                     if let Err(e) = self
                         .apply_filter_settings(client, source_name, filter_name, filter_settings)
                         .await
@@ -320,8 +2147,36 @@ impl SlaveSync {
             }
             SyncMessageType::SourceUpdate => {
                 // Parse SourceUpdatePayload from JSON
-                let payload: SourceUpdatePayload = serde_json::from_value(message.payload.clone())
-                    .context("Failed to parse SourceUpdatePayload")?;
+                let mut payload: SourceUpdatePayload =
+                    serde_json::from_value(message.payload.clone())
+                        .context("Failed to parse SourceUpdatePayload")?;
+                if !matches!(payload.action, SourceUpdateAction::Created) {
+                    payload.scene_item_id = self
+                        .resolve_scene_item_id(
+                            client,
+                            &payload.scene_name,
+                            Some(&payload.source_name),
+                            payload.scene_item_id,
+                        )
+                        .await;
+                }
+
+                if self.is_dry_run() {
+                    let action = match payload.action {
+                        SourceUpdateAction::Created => "create_scene_item",
+                        SourceUpdateAction::Removed => "remove_scene_item",
+                        SourceUpdateAction::EnabledStateChanged => "set_scene_item_enabled",
+                        SourceUpdateAction::SettingsChanged => "set_scene_item_settings",
+                    };
+                    self.plan(PlannedAction::new(
+                        action,
+                        &payload.scene_name,
+                        &payload.source_name,
+                        serde_json::to_value(&payload).unwrap_or_default(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
 
                 match payload.action {
                     SourceUpdateAction::Created => {
@@ -350,6 +2205,16 @@ impl SlaveSync {
                                         "scale_y": transform.scale_y,
                                         "width": transform.width,
                                         "height": transform.height,
+                                        "crop_left": transform.crop_left,
+                                        "crop_right": transform.crop_right,
+                                        "crop_top": transform.crop_top,
+                                        "crop_bottom": transform.crop_bottom,
+                                        "alignment": transform.alignment,
+                                        "bounds_type": transform.bounds_type,
+                                        "bounds_alignment": transform.bounds_alignment,
+                                        "bounds_width": transform.bounds_width,
+                                        "bounds_height": transform.bounds_height,
+                                        "anchor": transform.anchor,
                                     });
 
                                     if let Some(transform_obj) = transform_map.as_object() {
@@ -359,6 +2224,7 @@ impl SlaveSync {
                                                 &payload.scene_name,
                                                 new_item_id,
                                                 transform_obj,
+                                                None,
                                             )
                                             .await
                                         {
@@ -431,11 +2297,48 @@ impl SlaveSync {
                         }
                     }
                     SourceUpdateAction::SettingsChanged => {
-                        // Settings changed - similar to InputSettingsChanged, this might be handled elsewhere
-                        // For now, just log it
+                        // Scene-item properties only; input settings arrive as ImageUpdate
+                        let settings = payload.item_settings.clone().unwrap_or_default();
+                        if let Some(locked) = settings.locked {
+                            if let Err(e) = OBSCommands::set_scene_item_locked(
+                                client,
+                                &payload.scene_name,
+                                payload.scene_item_id,
+                                locked,
+                            )
+                            .await
+                            {
+                                self.send_alert(
+                                    payload.scene_name.clone(),
+                                    payload.source_name.clone(),
+                                    format!("Failed to set scene item lock state: {}", e),
+                                    AlertSeverity::Warning,
+                                )?;
+                            }
+                        }
+                        if let Some(blend_mode) = settings.blend_mode.as_deref() {
+                            if let Err(e) = OBSCommands::set_scene_item_blend_mode(
+                                client,
+                                &payload.scene_name,
+                                payload.scene_item_id,
+                                blend_mode,
+                            )
+                            .await
+                            {
+                                self.send_alert(
+                                    payload.scene_name.clone(),
+                                    payload.source_name.clone(),
+                                    format!("Failed to set scene item blend mode: {}", e),
+                                    AlertSeverity::Warning,
+                                )?;
+                            }
+                        }
                         println!(
-                            "Received settings changed for scene item {} (id: {}) in scene {}",
-                            payload.source_name, payload.scene_item_id, payload.scene_name
+                            "Applied item settings for {} (id: {}) in scene {}: {:?}",
+                            payload.source_name,
+                            payload.scene_item_id,
+                            payload.scene_name,
+                            settings
                         );
                     }
                 }
@@ -443,171 +2346,693 @@ impl SlaveSync {
             SyncMessageType::Heartbeat => {
                 // Just acknowledge heartbeat
             }
+            SyncMessageType::VolumeUpdate => {
+                let payload: VolumeUpdatePayload = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse volume update")?;
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_volume",
+                        "",
+                        &payload.input_name,
+                        serde_json::json!({ "volume_mul": payload.volume_mul }),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                client
+                    .inputs()
+                    .set_volume(
+                        obws::requests::inputs::InputId::Name(&payload.input_name),
+                        obws::requests::inputs::Volume::Mul(payload.volume_mul as f32),
+                    )
+                    .await
+                    .with_context(|| format!("Failed to set volume of {}", payload.input_name))?;
+            }
+            SyncMessageType::MuteUpdate => {
+                let payload: MuteUpdatePayload = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse mute update")?;
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_muted",
+                        "",
+                        &payload.input_name,
+                        serde_json::json!({ "muted": payload.muted }),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                client
+                    .inputs()
+                    .set_muted(
+                        obws::requests::inputs::InputId::Name(&payload.input_name),
+                        payload.muted,
+                    )
+                    .await
+                    .with_context(|| format!("Failed to set mute of {}", payload.input_name))?;
+            }
+            SyncMessageType::SceneLifecycle => {
+                let payload: SceneLifecyclePayload =
+                    serde_json::from_value(message.payload.clone())
+                        .context("Failed to parse scene lifecycle update")?;
+                if self.is_dry_run() {
+                    let action = match payload.action {
+                        SceneLifecycleAction::Created => "create_scene",
+                        SceneLifecycleAction::Removed => "remove_scene",
+                    };
+                    self.plan(PlannedAction::new(
+                        action,
+                        &payload.scene_name,
+                        "",
+                        serde_json::Value::Null,
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                let result = match payload.action {
+                    SceneLifecycleAction::Created => {
+                        OBSCommands::create_scene(client, &payload.scene_name).await
+                    }
+                    SceneLifecycleAction::Removed => {
+                        OBSCommands::remove_scene(client, &payload.scene_name).await
+                    }
+                };
+                if let Err(e) = result {
+                    self.send_alert(
+                        payload.scene_name.clone(),
+                        String::new(),
+                        format!("Failed to sync scene {:?}: {}", payload.action, e),
+                        AlertSeverity::Error,
+                    )?;
+                }
+            }
+            SyncMessageType::FilterLifecycle => {
+                let payload: FilterLifecyclePayload =
+                    serde_json::from_value(message.payload.clone())
+                        .context("Failed to parse filter lifecycle update")?;
+                if self.is_dry_run() {
+                    let action = match payload.action {
+                        FilterLifecycleAction::Created => "create_filter",
+                        FilterLifecycleAction::Removed => "remove_filter",
+                    };
+                    self.plan(PlannedAction::new(
+                        action,
+                        "",
+                        &payload.source_name,
+                        serde_json::to_value(&payload).unwrap_or_default(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                let result = match payload.action {
+                    FilterLifecycleAction::Created => {
+                        let Some(filter_kind) = payload.filter_kind.as_deref() else {
+                            anyhow::bail!("Filter {} created without a kind", payload.filter_name);
+                        };
+                        OBSCommands::create_filter(
+                            client,
+                            &payload.source_name,
+                            &payload.filter_name,
+                            filter_kind,
+                            payload.filter_settings.as_ref(),
+                            payload.filter_index,
+                        )
+                        .await
+                    }
+                    FilterLifecycleAction::Removed => {
+                        OBSCommands::remove_filter(
+                            client,
+                            &payload.source_name,
+                            &payload.filter_name,
+                        )
+                        .await
+                    }
+                };
+                if let Err(e) = result {
+                    self.send_alert(
+                        String::new(),
+                        payload.source_name.clone(),
+                        format!(
+                            "Failed to sync filter {} {:?}: {}",
+                            payload.filter_name, payload.action, e
+                        ),
+                        AlertSeverity::Warning,
+                    )?;
+                }
+            }
+            SyncMessageType::ReorderItems => {
+                let payload: ReorderItemsPayload = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse item order")?;
+                for item in &payload.items {
+                    sanitize::check_name("source_name", &item.source_name)?;
+                }
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "reorder_items",
+                        &payload.scene_name,
+                        "",
+                        serde_json::to_value(&payload.items).unwrap_or_default(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                match self
+                    .apply_item_order(client, &payload.scene_name, &payload.items)
+                    .await
+                {
+                    Ok(moved) => println!(
+                        "✓ Restacked {} items in scene {}",
+                        moved, payload.scene_name
+                    ),
+                    Err(e) => {
+                        self.send_alert(
+                            payload.scene_name.clone(),
+                            String::new(),
+                            format!("Failed to sync item order: {}", e),
+                            AlertSeverity::Warning,
+                        )?;
+                    }
+                }
+            }
+            SyncMessageType::OutputControl => {
+                let payload: OutputControlPayload = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse output control")?;
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "set_output_active",
+                        "",
+                        "",
+                        serde_json::to_value(&payload).unwrap_or_default(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                if let Err(e) = self.apply_output_control(client, &payload).await {
+                    self.send_alert(
+                        String::new(),
+                        String::new(),
+                        format!("Failed to sync {:?} output: {}", payload.output, e),
+                        AlertSeverity::Error,
+                    )?;
+                }
+            }
+            SyncMessageType::Command => {
+                let command: SemanticCommand = serde_json::from_value(message.payload.clone())
+                    .context("Failed to parse command")?;
+                if self.is_dry_run() {
+                    self.plan(PlannedAction::new(
+                        "execute_command",
+                        "",
+                        "",
+                        serde_json::to_value(&command).unwrap_or_default(),
+                    ))
+                    .await;
+                    return Ok(());
+                }
+                if let Err(e) = command.execute(client).await {
+                    self.send_alert(
+                        String::new(),
+                        String::new(),
+                        format!("Failed to execute {:?}: {}", command, e),
+                        AlertSeverity::Warning,
+                    )?;
+                } else {
+                    println!("Executed command from master: {:?}", command);
+                }
+            }
             SyncMessageType::StateSync => {
                 println!("Applying complete initial state from master...");
+                *self.master_layout.write().await =
+                    Some(MasterLayout::from_state(&message.payload));
+
+                // A resync asked for because of drift, or forced by the master, reapplies
+                // everything: the hashes say what was applied, not what OBS shows now
+                let forced = self.full_resync_pending.swap(false, Ordering::SeqCst)
+                    || message.payload["force"].as_bool().unwrap_or(false);
+                if forced {
+                    println!("Full resync, reapplying every item");
+                    self.applied_cache.write().await.clear();
+                    self.applied_image_hashes.write().await.clear();
+                }
+
+                // Cached hashes are only valid for the scene collection they were recorded in
+                match client.scene_collections().current().await {
+                    Ok(collection) => self
+                        .applied_cache
+                        .write()
+                        .await
+                        .ensure_collection(&collection),
+                    Err(e) => {
+                        eprintln!(
+                            "Failed to get scene collection, ignoring slave cache: {}",
+                            e
+                        );
+                        self.applied_cache.write().await.clear();
+                    }
+                }
 
-                // Apply all scenes and items
+                // Apply all scenes and items, collecting what failed into one report
+                let strict = self.strict_state_sync.load(Ordering::SeqCst);
+                let mut report = StateSyncReport::default();
                 if let Some(scenes) = message.payload["scenes"].as_array() {
                     for scene in scenes {
                         let scene_name = scene["name"].as_str().unwrap_or("");
+                        if !Self::is_target_allowed(
+                            &*self.scene_target_overrides.read().await,
+                            &self.sync_targets.read().await,
+                            scene_name,
+                            &SyncTargetType::Source,
+                        ) {
+                            println!("Skipping scene {} (excluded by scene override)", scene_name);
+                            continue;
+                        }
                         println!("Processing scene: {}", scene_name);
 
-                        // Apply items in this scene
+                        // Apply items in this scene, up to `apply_concurrency` at a time
                         if let Some(items) = scene["items"].as_array() {
-                            for item in items {
-                                let source_name = item["source_name"].as_str().unwrap_or("");
-                                let scene_item_id = item["scene_item_id"].as_i64().unwrap_or(0);
-
-                                println!(
-                                    "  - Applying item: {} (id: {})",
-                                    source_name, scene_item_id
-                                );
-
-                                // Apply transform if available
-                                if let Some(transform) = item["transform"].as_object() {
-                                    if let Err(e) = self
-                                        .apply_transform(
-                                            client,
-                                            scene_name,
-                                            scene_item_id,
-                                            transform,
-                                        )
-                                        .await
-                                    {
-                                        eprintln!(
-                                            "Failed to apply transform for {}: {}",
-                                            source_name, e
-                                        );
-                                    }
-                                }
-
-                                // Apply image data if available
-                                if let Some(image_data) = item["image_data"].as_object() {
-                                    if let (Some(file), Some(data)) = (
-                                        image_data.get("file").and_then(|v| v.as_str()),
-                                        image_data.get("data").and_then(|v| v.as_str()),
-                                    ) {
-                                        if let Err(e) = self
-                                            .handle_image_update(
-                                                client,
-                                                source_name,
-                                                file,
-                                                Some(data),
-                                            )
-                                            .await
-                                        {
-                                            eprintln!(
-                                                "Failed to apply image for {}: {}",
-                                                source_name, e
-                                            );
+                            let concurrency = self.apply_concurrency.load(Ordering::SeqCst);
+                            let applies: Vec<_> = items
+                                .iter()
+                                .map(|item| async move {
+                                    let source_name = item["source_name"].as_str().unwrap_or("");
+                                    let outcome =
+                                        self.apply_state_item(client, scene_name, item).await;
+                                    (source_name, outcome)
+                                })
+                                .collect();
+                            let outcomes: Vec<(&str, StateItemOutcome)> =
+                                run_limited(applies, concurrency).await;
+                            for (source_name, outcome) in outcomes {
+                                match outcome {
+                                    StateItemOutcome::Applied => report.items_applied += 1,
+                                    StateItemOutcome::Unchanged => report.items_unchanged += 1,
+                                    StateItemOutcome::Planned | StateItemOutcome::Ignored => {}
+                                    StateItemOutcome::Failed(reasons) => {
+                                        report.items_failed += 1;
+                                        for reason in reasons {
+                                            report.fail(scene_name, source_name, reason);
                                         }
                                     }
                                 }
+                            }
 
-                                // Apply filters if available
-                                if let Some(filters) = item["filters"].as_array() {
-                                    for filter in filters {
-                                        let filter_name = filter["name"].as_str().unwrap_or("");
-                                        let filter_enabled =
-                                            filter["enabled"].as_bool().unwrap_or(true);
-                                        if let Some(filter_settings) =
-                                            filter["settings"].as_object()
-                                        {
-                                            // Apply filter settings
-                                            if let Err(e) = self
-                                                .apply_filter_settings(
-                                                    client,
-                                                    source_name,
-                                                    filter_name,
-                                                    filter_settings,
-                                                )
-                                                .await
-                                            {
-                                                eprintln!(
-                                                    "Failed to apply filter {} for {}: {}",
-                                                    filter_name, source_name, e
-                                                );
-                                            } else {
-                                                // Set filter enabled state
-                                                if let Err(e) = client
-                                                    .filters()
-                                                    .set_enabled(obws::requests::filters::SetEnabled {
-                                                        source: obws::requests::sources::SourceId::Name(source_name),
-                                                        filter: filter_name,
-                                                        enabled: filter_enabled,
-                                                    })
-                                                    .await
-                                                {
-                                                    eprintln!(
-                                                        "Failed to set filter {} enabled state for {}: {}",
-                                                        filter_name, source_name, e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
+                            // Stacking order, bottom first
+                            let mut order: Vec<(u64, ReorderedItem)> = items
+                                .iter()
+                                .filter_map(|item| {
+                                    Some((
+                                        item["scene_item_index"].as_u64()?,
+                                        ReorderedItem {
+                                            scene_item_id: item["scene_item_id"]
+                                                .as_i64()
+                                                .unwrap_or(0),
+                                            source_name: item["source_name"].as_str()?.to_string(),
+                                        },
+                                    ))
+                                })
+                                .collect();
+                            order.sort_by_key(|(index, _)| *index);
+                            let order: Vec<ReorderedItem> =
+                                order.into_iter().map(|(_, item)| item).collect();
+                            if self.is_dry_run() {
+                                self.plan(PlannedAction::new(
+                                    "reorder_items",
+                                    scene_name,
+                                    "",
+                                    serde_json::to_value(&order).unwrap_or_default(),
+                                ))
+                                .await;
+                            } else if !order.is_empty() {
+                                if let Err(e) =
+                                    self.apply_item_order(client, scene_name, &order).await
+                                {
+                                    report.fail(scene_name, "", format!("item order: {}", e));
                                 }
                             }
                         }
+                        if strict && report.items_failed > 0 {
+                            report.aborted = true;
+                            break;
+                        }
+                    }
+                }
+                self.save_applied_cache().await;
+                if report.aborted {
+                    return self.finish_state_sync(report).await;
+                }
+
+                // Volume and mute, so inputs muted on the master start muted here
+                let inputs: Vec<InputAudioData> =
+                    serde_json::from_value(message.payload["inputs"].clone()).unwrap_or_default();
+                let ignored = self.ignored_sources.read().await.clone();
+                for input in inputs
+                    .iter()
+                    .filter(|input| !ignored.contains(&input.input_name))
+                {
+                    if self.is_dry_run() {
+                        self.plan(PlannedAction::new(
+                            "set_input_audio",
+                            "",
+                            &input.input_name,
+                            serde_json::to_value(input).unwrap_or_default(),
+                        ))
+                        .await;
+                        continue;
+                    }
+                    if let Err(e) = Self::apply_input_audio(client, input).await {
+                        report.fail("", &input.input_name, format!("audio: {}", e));
+                    }
+                }
+
+                if self.is_dry_run() {
+                    for (action, key) in [
+                        ("set_program_scene", "current_program_scene"),
+                        ("set_preview_scene", "current_preview_scene"),
+                    ] {
+                        if let Some(scene_name) = message.payload[key].as_str() {
+                            self.plan(PlannedAction::new(
+                                action,
+                                scene_name,
+                                "",
+                                serde_json::Value::Null,
+                            ))
+                            .await;
+                        }
                     }
+                    self.state_sync_applied.notify_waiters();
+                    return Ok(());
                 }
 
-                // Apply current program scene
-                if let Some(scene_name) = message.payload["current_program_scene"].as_str() {
+                // Apply current program scene, unless a later switch has already been applied
+                if let Some(scene_name) = message.payload["current_program_scene"]
+                    .as_str()
+                    .filter(|_| !self.is_overtaken_by_scene_change(&message))
+                {
                     if let Err(e) = crate::obs::commands::OBSCommands::set_current_program_scene(
                         client, scene_name,
                     )
                     .await
                     {
-                        self.send_alert(
-                            scene_name.to_string(),
-                            String::new(),
-                            format!("Failed to sync initial scene: {}", e),
-                            AlertSeverity::Warning,
-                        )?;
+                        report.fail(scene_name, "", format!("program scene: {}", e));
+                        report.aborted = strict;
                     } else {
                         println!("✓ Applied current program scene: {}", scene_name);
                     }
                 }
 
-                // Apply preview scene if in studio mode
+                // The master only reports a preview scene while in studio mode
                 if let Some(preview_scene) = message.payload["current_preview_scene"].as_str() {
-                    // Setting preview scene requires studio mode to be enabled
-                    match client
-                        .scenes()
-                        .set_current_preview_scene(preview_scene)
-                        .await
-                    {
+                    match OBSCommands::set_current_preview_scene(client, preview_scene).await {
                         Ok(_) => {
                             println!("✓ Applied current preview scene: {}", preview_scene);
                         }
                         Err(e) => {
-                            // Studio mode might not be enabled, log warning but don't fail
-                            println!("⚠️  Failed to set preview scene (Studio Mode may not be enabled): {}", e);
-                            self.send_alert(
-                                preview_scene.to_string(),
-                                String::new(),
-                                format!("Failed to sync preview scene: {} (Studio Mode may not be enabled)", e),
-                                AlertSeverity::Warning,
-                            )?;
+                            // Reported but not critical
+                            report.fail(preview_scene, "", format!("preview scene: {}", e));
+                        }
+                    }
+                }
+
+                return self.finish_state_sync(report).await;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Publish the outcome of a StateSync: one alert if anything failed, the full
+    /// report for the UI, and in strict mode a request for a fresh StateSync
+    async fn finish_state_sync(&self, mut report: StateSyncReport) -> Result<()> {
+        report.timestamp = chrono::Utc::now().timestamp_millis();
+        if report.aborted {
+            // Ask once; if the fresh StateSync fails too, the problem isn't transient
+            if !self.strict_resync_requested.swap(true, Ordering::SeqCst) {
+                let held_images = self.held_image_hashes().await;
+                if let Some(sender) = self.state_report_tx.read().await.as_ref() {
+                    self.expect_full_resync();
+                    report.resync_requested = sender
+                        .send(SyncMessage::state_sync_request(held_images))
+                        .is_ok();
+                }
+            }
+        } else {
+            self.strict_resync_requested.store(false, Ordering::SeqCst);
+        }
+
+        let summary = report.summary();
+        if report.failures.is_empty() {
+            println!("✓ {}", summary);
+        } else {
+            eprintln!("⚠️  {}", summary);
+            for failure in &report.failures {
+                self.record_apply_error(format!(
+                    "StateSync {}/{}: {}",
+                    failure.scene_name, failure.source_name, failure.reason
+                ))
+                .await;
+            }
+            self.send_alert(
+                String::new(),
+                String::new(),
+                summary,
+                if report.aborted || report.items_failed > 0 {
+                    AlertSeverity::Error
+                } else {
+                    AlertSeverity::Warning
+                },
+            )?;
+        }
+        if let Some(tx) = self.state_sync_report_tx.read().await.as_ref() {
+            let _ = tx.send(report);
+        }
+        self.state_sync_applied.notify_waiters();
+        Ok(())
+    }
+
+    async fn apply_input_audio(client: &obws::Client, input: &InputAudioData) -> Result<()> {
+        let input_id = obws::requests::inputs::InputId::Name(&input.input_name);
+        client
+            .inputs()
+            .set_volume(
+                input_id,
+                obws::requests::inputs::Volume::Mul(input.volume_mul as f32),
+            )
+            .await
+            .context("Failed to set volume")?;
+        if let Some(muted) = input.muted {
+            client
+                .inputs()
+                .set_muted(input_id, muted)
+                .await
+                .context("Failed to set mute")?;
+        }
+        Ok(())
+    }
+
+    /// Apply one scene item from a StateSync (transform, image, filters), skipping items
+    /// whose hash matches the last clean apply
+    async fn apply_state_item(
+        &self,
+        client: &obws::Client,
+        scene_name: &str,
+        item: &serde_json::Value,
+    ) -> StateItemOutcome {
+        let source_name = item["source_name"].as_str().unwrap_or("");
+        let scene_item_id = item["scene_item_id"].as_i64().unwrap_or(0);
+        let item_hash = item["item_hash"].as_str();
+
+        if self.ignored_sources.read().await.contains(source_name) {
+            println!("  - Skipping ignored item: {}", source_name);
+            return StateItemOutcome::Ignored;
+        }
+
+        // Names and paths come from the network; refuse odd ones before they reach OBS or disk
+        if let Err(e) = sanitize::check_state_item(scene_name, item) {
+            eprintln!("⚠️  Rejected StateSync item {}: {}", source_name, e);
+            return StateItemOutcome::Failed(vec![e.to_string()]);
+        }
+
+        if let Some(hash) = item_hash {
+            if self
+                .applied_cache
+                .read()
+                .await
+                .is_unchanged(scene_name, source_name, hash)
+            {
+                println!("  - Skipping unchanged item: {}", source_name);
+                return StateItemOutcome::Unchanged;
+            }
+        }
+
+        let scene_item_id = self
+            .resolve_scene_item_id(client, scene_name, Some(source_name), scene_item_id)
+            .await;
+        if self.is_dry_run() {
+            // Not recorded in the applied cache, so a later real apply still writes it
+            let mut details = item.clone();
+            if let Some(image) = details["image_data"].as_object_mut() {
+                image.remove("data");
+            }
+            details["scene_item_id"] = serde_json::json!(scene_item_id);
+            self.plan(PlannedAction::new(
+                "apply_state_item",
+                scene_name,
+                source_name,
+                details,
+            ))
+            .await;
+            return StateItemOutcome::Planned;
+        }
+        println!("  - Applying item: {} (id: {})", source_name, scene_item_id);
+        let mut failures = Vec::new();
+
+        // Apply transform if available
+        if let Some(transform) = item["transform"].as_object() {
+            if let Err(e) = self
+                .apply_transform(client, scene_name, scene_item_id, transform, None)
+                .await
+            {
+                failures.push(format!("transform: {}", e));
+            }
+        }
+
+        // Apply image data if available
+        if let Some(image_data) = item["image_data"].as_object() {
+            // Images this slave reported having come without their bytes
+            if let Some(file) = image_data.get("file").and_then(|v| v.as_str()) {
+                let image = ReceivedImage {
+                    data: image_data.get("data").and_then(|v| v.as_str()),
+                    hash: image_data.get("hash").and_then(|v| v.as_str()),
+                    format: image_data.get("format").and_then(|v| v.as_str()),
+                    compression: image_data.get("compression").and_then(|v| v.as_str()),
+                };
+                if let Err(e) = self
+                    .handle_image_update(client, source_name, file, image)
+                    .await
+                {
+                    failures.push(format!("image: {}", e));
+                }
+            }
+        }
+
+        // Apply filters if available
+        if let Some(filters) = item["filters"].as_array() {
+            for filter in filters {
+                let filter_name = filter["name"].as_str().unwrap_or("");
+                let filter_enabled = filter["enabled"].as_bool().unwrap_or(true);
+                if let Some(filter_settings) = filter["settings"].as_object() {
+                    // Apply filter settings
+                    if let Err(e) = self
+                        .apply_filter_settings(client, source_name, filter_name, filter_settings)
+                        .await
+                    {
+                        failures.push(format!("filter {}: {}", filter_name, e));
+                    } else {
+                        // Set filter enabled state
+                        if let Err(e) = client
+                            .filters()
+                            .set_enabled(obws::requests::filters::SetEnabled {
+                                source: obws::requests::sources::SourceId::Name(source_name),
+                                filter: filter_name,
+                                enabled: filter_enabled,
+                            })
+                            .await
+                        {
+                            failures.push(format!("filter {} enabled state: {}", filter_name, e));
                         }
                     }
                 }
+            }
+        }
+
+        if !failures.is_empty() {
+            return StateItemOutcome::Failed(failures);
+        }
+        if let Some(hash) = item_hash {
+            self.applied_cache
+                .write()
+                .await
+                .record(scene_name, source_name, hash);
+        }
+        StateItemOutcome::Applied
+    }
+
+    /// Merge the master's `transform` fields into the item's current transform,
+    /// leaving the fields `mask` excludes at their local values. Anchors are resolved
+    /// separately since they need the canvas size.
+    fn merge_transform(
+        current_transform: SceneItemTransform,
+        transform: &serde_json::Map<String, serde_json::Value>,
+        mask: TransformFieldMask,
+    ) -> SceneItemTransform {
+        let field = |name: &str, current: f32| -> f32 {
+            transform
+                .get(name)
+                .and_then(|v| v.as_f64())
+                .map(|v| v as f32)
+                .unwrap_or(current)
+        };
+        let crop = |name: &str, current: u32| -> u32 {
+            transform
+                .get(name)
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+                .unwrap_or(current)
+        };
+        let alignment = |name: &str, current: Alignment| -> Alignment {
+            transform
+                .get(name)
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u8::try_from(v).ok())
+                .and_then(Alignment::from_bits)
+                .unwrap_or(current)
+        };
 
-                println!("✓ Initial state fully applied");
+        // Masked-out fields keep the local value
+        let mut new_transform = current_transform;
+        if mask.sync_position {
+            new_transform.position_x = field("position_x", new_transform.position_x);
+            new_transform.position_y = field("position_y", new_transform.position_y);
+            // The point the position refers to
+            new_transform.alignment = alignment("alignment", new_transform.alignment);
+        }
+        if mask.sync_scale {
+            new_transform.scale_x = field("scale_x", new_transform.scale_x);
+            new_transform.scale_y = field("scale_y", new_transform.scale_y);
+            // Bounds decide the size of bounded sources, overriding the scale
+            if let Some(bounds_type) = transform
+                .get("bounds_type")
+                .filter(|v| v.is_string())
+                .and_then(|v| serde_json::from_value::<BoundsType>(v.clone()).ok())
+            {
+                new_transform.bounds_type = bounds_type;
             }
-            _ => {}
+            new_transform.bounds_alignment =
+                alignment("bounds_alignment", new_transform.bounds_alignment);
+            new_transform.bounds_width = field("bounds_width", new_transform.bounds_width);
+            new_transform.bounds_height = field("bounds_height", new_transform.bounds_height);
         }
-
-        Ok(())
+        if mask.sync_rotation {
+            new_transform.rotation = field("rotation", new_transform.rotation);
+        }
+        if mask.sync_crop {
+            new_transform.crop_left = crop("crop_left", new_transform.crop_left);
+            new_transform.crop_right = crop("crop_right", new_transform.crop_right);
+            new_transform.crop_top = crop("crop_top", new_transform.crop_top);
+            new_transform.crop_bottom = crop("crop_bottom", new_transform.crop_bottom);
+        }
+        new_transform
     }
 
+    /// Merge `transform` into the item's current transform and apply it.
+    /// `update_timestamp` is set for live TransformUpdates, which may be interpolated.
     async fn apply_transform(
         &self,
         client: &obws::Client,
         scene_name: &str,
         scene_item_id: i64,
         transform: &serde_json::Map<String, serde_json::Value>,
+        update_timestamp: Option<i64>,
     ) -> Result<()> {
         // Convert scene_name to SceneId
         let scene_id: obws::requests::scenes::SceneId = scene_name.into();
@@ -628,40 +3053,58 @@ impl SlaveSync {
             }
         };
 
-        // Extract values from JSON payload (convert to f32 to match SceneItemTransform)
-        let position_x = transform
-            .get("position_x")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(current_transform.position_x);
-        let position_y = transform
-            .get("position_y")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(current_transform.position_y);
-        let scale_x = transform
-            .get("scale_x")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(current_transform.scale_x);
-        let scale_y = transform
-            .get("scale_y")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(current_transform.scale_y);
-        let rotation = transform
-            .get("rotation")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(current_transform.rotation);
-
-        // Build new transform by updating current transform
-        let mut new_transform = current_transform;
-        new_transform.position_x = position_x;
-        new_transform.position_y = position_y;
-        new_transform.scale_x = scale_x;
-        new_transform.scale_y = scale_y;
-        new_transform.rotation = rotation;
+        let mask = *self.transform_mask.read().await;
+        let start_transform = current_transform.clone();
+        let mut new_transform = Self::merge_transform(current_transform, transform, mask);
+        if mask.sync_position {
+            // Anchored sources are placed relative to this slave's canvas
+            let anchor = transform
+                .get("anchor")
+                .cloned()
+                .and_then(|v| serde_json::from_value::<Option<AnchoredPosition>>(v).ok())
+                .flatten();
+            if let Some(anchor) = anchor {
+                match canvas_size(client).await {
+                    Ok(canvas) => {
+                        let (x, y) = anchor.resolve(canvas);
+                        new_transform.position_x = x as f32;
+                        new_transform.position_y = y as f32;
+                    }
+                    Err(e) => throttled_eprintln!(
+                        "Using absolute position for item {}: {}",
+                        scene_item_id,
+                        e
+                    ),
+                }
+            }
+        }
+        let (position_x, position_y) = (new_transform.position_x, new_transform.position_y);
+        let (scale_x, scale_y) = (new_transform.scale_x, new_transform.scale_y);
+        let rotation = new_transform.rotation;
+
+        if let Some(timestamp) = update_timestamp {
+            if let Some((duration_ms, generation)) = self
+                .plan_interpolation(
+                    scene_name,
+                    scene_item_id,
+                    timestamp,
+                    &start_transform,
+                    &new_transform,
+                )
+                .await
+            {
+                self.spawn_interpolation(
+                    scene_name,
+                    scene_item_id,
+                    start_transform,
+                    new_transform,
+                    duration_ms,
+                    generation,
+                )
+                .await;
+                return Ok(());
+            }
+        }
 
         // Apply the transform using SetTransform
         use obws::requests::scene_items::SetTransform;
@@ -684,69 +3127,243 @@ impl SlaveSync {
         Ok(())
     }
 
+    /// Decide whether a TransformUpdate should be animated and for how long.
+    /// Every call bumps the item's generation, cancelling any animation in flight.
+    async fn plan_interpolation(
+        &self,
+        scene_name: &str,
+        scene_item_id: i64,
+        timestamp: i64,
+        start: &SceneItemTransform,
+        target: &SceneItemTransform,
+    ) -> Option<(u64, u64)> {
+        let settings = *self.interpolation.read().await;
+        let (previous_timestamp, generation) = {
+            let mut animations = self.transform_animations.write().await;
+            let entry = animations
+                .entry((scene_name.to_string(), scene_item_id))
+                .or_insert((0, 0));
+            entry.1 += 1;
+            (std::mem::replace(&mut entry.0, timestamp), entry.1)
+        };
+        if !settings.enabled || previous_timestamp == 0 {
+            return None;
+        }
+
+        // Animate over the gap since the previous update so motion keeps pace with the master
+        let duration_ms =
+            ((timestamp - previous_timestamp).max(0) as u64).min(settings.max_duration_ms);
+        let jump = ((target.position_x - start.position_x) as f64)
+            .hypot((target.position_y - start.position_y) as f64);
+        if duration_ms == 0 || settings.max_rate_hz == 0 || jump > settings.max_jump_px {
+            return None;
+        }
+        Some((duration_ms, generation))
+    }
+
+    /// Transforms to step through from `start` to `target`, at most `max_rate_hz` per
+    /// second over `duration_ms`. Intermediate steps only move, rotate and scale the
+    /// item; the last one is `target` in full.
+    fn interpolation_steps(
+        start: &SceneItemTransform,
+        target: &SceneItemTransform,
+        duration_ms: u64,
+        max_rate_hz: u64,
+    ) -> Vec<obws::requests::scene_items::SceneItemTransform> {
+        use obws::requests::scene_items::{Position, Scale};
+        let steps = (duration_ms * max_rate_hz / 1000).max(1);
+        let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+        (1..=steps)
+            .map(|step| {
+                if step == steps {
+                    return target.clone().into();
+                }
+                let t = step as f32 / steps as f32;
+                obws::requests::scene_items::SceneItemTransform {
+                    position: Some(Position {
+                        x: Some(lerp(start.position_x, target.position_x, t)),
+                        y: Some(lerp(start.position_y, target.position_y, t)),
+                    }),
+                    rotation: Some(lerp(start.rotation, target.rotation, t)),
+                    scale: Some(Scale {
+                        x: Some(lerp(start.scale_x, target.scale_x, t)),
+                        y: Some(lerp(start.scale_y, target.scale_y, t)),
+                    }),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Issue intermediate SetTransform calls from `start` to `target`, ending on `target`
+    async fn spawn_interpolation(
+        &self,
+        scene_name: &str,
+        scene_item_id: i64,
+        start: SceneItemTransform,
+        target: SceneItemTransform,
+        duration_ms: u64,
+        generation: u64,
+    ) {
+        let max_rate_hz = self.interpolation.read().await.max_rate_hz as u64;
+        let transforms = Self::interpolation_steps(&start, &target, duration_ms, max_rate_hz);
+        let steps = transforms.len();
+        let step_interval = tokio::time::Duration::from_millis(duration_ms / steps as u64);
+        let obs_client = self.obs_client.clone();
+        let transform_animations = self.transform_animations.clone();
+        let dry_run = self.dry_run.clone();
+        let scene_name = scene_name.to_string();
+
+        tokio::spawn(async move {
+            use obws::requests::scene_items::SetTransform;
+            let key = (scene_name.clone(), scene_item_id);
+
+            for (step, transform) in transforms.into_iter().enumerate() {
+                let superseded = transform_animations
+                    .read()
+                    .await
+                    .get(&key)
+                    .map(|(_, latest)| *latest != generation)
+                    .unwrap_or(true);
+                // Switching to observe-only stops animations already running
+                if superseded || dry_run.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                {
+                    let client_arc = obs_client.get_client_arc();
+                    let client_lock = client_arc.read().await;
+                    let Some(client) = client_lock.as_ref() else {
+                        return;
+                    };
+                    if let Err(e) = client
+                        .scene_items()
+                        .set_transform(SetTransform {
+                            scene: scene_name.as_str().into(),
+                            item_id: scene_item_id,
+                            transform,
+                        })
+                        .await
+                    {
+                        throttled_eprintln!(
+                            "Failed to apply interpolated transform for item {}: {}",
+                            scene_item_id,
+                            e
+                        );
+                        return;
+                    }
+                }
+
+                if step + 1 < steps {
+                    tokio::time::sleep(step_interval).await;
+                }
+            }
+            println!(
+                "Interpolated transform for item {} in scene {} over {}ms ({} steps)",
+                scene_item_id, scene_name, duration_ms, steps
+            );
+        });
+    }
+
+    /// Overlay the master's settings on the local input of the same name and kind
+    async fn apply_source_settings(
+        &self,
+        client: &obws::Client,
+        payload: &SourceSettingsPayload,
+    ) -> Result<()> {
+        let input = obws::requests::inputs::InputId::Name(&payload.source_name);
+        let local = client
+            .inputs()
+            .settings::<serde_json::Value>(input)
+            .await
+            .context("Failed to get local input settings")?;
+        if local.kind != payload.input_kind {
+            anyhow::bail!(
+                "Input kind differs (master: {}, local: {})",
+                payload.input_kind,
+                local.kind
+            );
+        }
+        client
+            .inputs()
+            .set_settings(obws::requests::inputs::SetSettings {
+                input,
+                settings: &payload.settings,
+                overlay: Some(true),
+            })
+            .await
+            .context("Failed to set input settings")?;
+        println!("Updated settings of {}", payload.source_name);
+        Ok(())
+    }
+
     async fn handle_image_update(
         &self,
         client: &obws::Client,
         source_name: &str,
         original_file_path: &str,
-        image_data: Option<&str>,
+        image: ReceivedImage<'_>,
     ) -> Result<()> {
-        if let Some(encoded_data) = image_data {
-            println!("Received image data for {}, decoding...", source_name);
-
-            // Decode base64 image data
-            let decoded_data =
-                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded_data)
-                    .context("Failed to decode image data")?;
-
-            println!("Decoded {} bytes of image data", decoded_data.len());
-
-            // Extract file extension from original file path
-            // Fall back to magic bytes detection if extension cannot be determined
-            let file_extension = if !original_file_path.is_empty() {
-                std::path::Path::new(original_file_path)
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                    .unwrap_or_else(|| Self::detect_image_format(&decoded_data))
-            } else {
-                Self::detect_image_format(&decoded_data)
-            };
+        let image_hash = image.hash;
 
-            // Create temp directory for synced images
-            let temp_dir = std::env::temp_dir().join("obs-sync");
-            fs::create_dir_all(&temp_dir)
+        // Skip rewriting images we already applied (e.g. StateSync after a reconnect)
+        if let Some(hash) = image_hash {
+            if self
+                .applied_image_hashes
+                .read()
                 .await
-                .context("Failed to create temp directory")?;
-
-            // Generate unique filename using original file name if available
-            let temp_file_path = if !original_file_path.is_empty() {
-                // Extract file name (without path) from original path
-                let original_file_name = std::path::Path::new(original_file_path)
-                    .file_stem()
-                    .and_then(|name| name.to_str())
-                    .unwrap_or(source_name);
-
-                temp_dir.join(format!(
-                    "{}_{}.{}",
-                    original_file_name.replace("/", "_").replace("\\", "_"),
-                    chrono::Utc::now().timestamp_millis(),
-                    file_extension
-                ))
-            } else {
-                temp_dir.join(format!(
-                    "{}_{}.{}",
-                    source_name.replace("/", "_").replace("\\", "_"),
-                    chrono::Utc::now().timestamp_millis(),
-                    file_extension
-                ))
-            };
-
-            println!("Saving image to: {:?}", temp_file_path);
+                .get(source_name)
+                .map(String::as_str)
+                == Some(hash)
+            {
+                println!(
+                    "Image for {} unchanged (hash {}), skipping",
+                    source_name, hash
+                );
+                return Ok(());
+            }
+        }
 
-            // Write decoded data to temp file
-            fs::write(&temp_file_path, &decoded_data)
-                .await
-                .context("Failed to write image file")?;
+        if image.data.is_some() || image_hash.is_some() {
+            // The same image may already be on disk from an earlier sync of any source
+            let cached_path = match image_hash {
+                Some(hash) => self.image_cache.write().await.get(hash),
+                None => None,
+            };
+            let temp_file_path = match (cached_path, image.data) {
+                (Some(path), _) if fs::try_exists(&path).await.unwrap_or(false) => {
+                    println!("Reusing cached image file for {}: {:?}", source_name, path);
+                    path
+                }
+                (stale, encoded_data) => {
+                    if let (Some(_), Some(hash)) = (stale, image_hash) {
+                        self.image_cache.write().await.remove(hash);
+                    }
+                    let Some(encoded_data) = encoded_data else {
+                        anyhow::bail!(
+                            "Image for {} was left out as already received, but is no longer cached",
+                            source_name
+                        );
+                    };
+                    let (path, size, content_hash) = Self::write_image_file(
+                        source_name,
+                        original_file_path,
+                        encoded_data,
+                        image.format,
+                        image.compression,
+                    )
+                    .await?;
+                    let evicted = {
+                        let mut cache = self.image_cache.write().await;
+                        if let Some(hash) = image_hash {
+                            cache.alias(hash, &content_hash);
+                        }
+                        cache.insert(content_hash, path.clone(), size)
+                    };
+                    Self::remove_image_files(evicted).await;
+                    path
+                }
+            };
 
             // Update OBS input settings with new file path
             let temp_file_str = temp_file_path.to_string_lossy().to_string();
@@ -768,6 +3385,16 @@ impl SlaveSync {
             {
                 Ok(_) => {
                     println!("Successfully applied image to {}", source_name);
+                    self.image_cache
+                        .write()
+                        .await
+                        .mark_in_use(source_name, &temp_file_path);
+                    if let Some(hash) = image_hash {
+                        self.applied_image_hashes
+                            .write()
+                            .await
+                            .insert(source_name.to_string(), hash.to_string());
+                    }
                     Ok(())
                 }
                 Err(e) => {
@@ -781,6 +3408,71 @@ impl SlaveSync {
         }
     }
 
+    /// Decode a received image and write it to a temp file named after its content hash,
+    /// unless that file already exists; returns the path, size and content hash
+    async fn write_image_file(
+        source_name: &str,
+        original_file_path: &str,
+        encoded_data: &str,
+        image_format: Option<&str>,
+        compression: Option<&str>,
+    ) -> Result<(PathBuf, u64, String)> {
+        sanitize::check_file_path("file", original_file_path)?;
+        println!("Received image data for {}, decoding...", source_name);
+
+        // Decode base64 image data, then undo the master's compression if any
+        let decoded_data =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded_data)
+                .context("Failed to decode image data")?;
+        let decoded_data = compression::decompress(decoded_data, compression)?;
+
+        println!("Decoded {} bytes of image data", decoded_data.len());
+
+        // Use the master's re-encoded format, else the original file's extension,
+        // falling back to magic bytes detection if neither is available
+        let file_extension = if let Some(format) = image_format {
+            format
+        } else if !original_file_path.is_empty() {
+            std::path::Path::new(original_file_path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_else(|| Self::detect_image_format(&decoded_data))
+        } else {
+            Self::detect_image_format(&decoded_data)
+        };
+        // The extension becomes part of the file name, so it must not smuggle in a path
+        sanitize::check_file_extension(file_extension)?;
+
+        // Create temp directory for synced images
+        let temp_dir = image_cache::image_dir();
+        fs::create_dir_all(&temp_dir)
+            .await
+            .context("Failed to create temp directory")?;
+
+        let content_hash = image_cache::content_hash(&decoded_data);
+        let temp_file_path = temp_dir.join(format!("{}.{}", content_hash, file_extension));
+
+        if fs::try_exists(&temp_file_path).await.unwrap_or(false) {
+            println!("Image already on disk: {:?}", temp_file_path);
+        } else {
+            println!("Saving image to: {:?}", temp_file_path);
+            fs::write(&temp_file_path, &decoded_data)
+                .await
+                .context("Failed to write image file")?;
+        }
+        Ok((temp_file_path, decoded_data.len() as u64, content_hash))
+    }
+
+    async fn remove_image_files(paths: Vec<PathBuf>) {
+        for path in paths {
+            if let Err(e) = fs::remove_file(&path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("Failed to remove cached image {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
     /// Detect image format from magic bytes
     fn detect_image_format(data: &[u8]) -> &'static str {
         if data.len() < 4 {
@@ -835,22 +3527,618 @@ impl SlaveSync {
         Ok(())
     }
 
-    fn send_alert(
+    async fn output_active(client: &obws::Client, output: OutputKind) -> Result<bool> {
+        Ok(match output {
+            OutputKind::Record => client.recording().status().await?.active,
+            OutputKind::Stream => client.streaming().status().await?.active,
+        })
+    }
+
+    /// Start or stop recording/streaming to match the master. OBS accepts a start
+    /// request before it knows whether the output can run (e.g. with an invalid
+    /// recording path), so a started output is checked again shortly after.
+    async fn apply_output_control(
+        &self,
+        client: &obws::Client,
+        payload: &OutputControlPayload,
+    ) -> Result<()> {
+        if Self::output_active(client, payload.output).await? == payload.active {
+            return Ok(());
+        }
+        match (payload.output, payload.active) {
+            (OutputKind::Record, true) => client.recording().start().await?,
+            (OutputKind::Record, false) => {
+                client.recording().stop().await?;
+            }
+            (OutputKind::Stream, true) => client.streaming().start().await?,
+            (OutputKind::Stream, false) => client.streaming().stop().await?,
+        }
+        println!(
+            "{} {:?} output",
+            if payload.active { "Started" } else { "Stopped" },
+            payload.output
+        );
+        if !payload.active {
+            return Ok(());
+        }
+
+        let output = payload.output;
+        let obs_client = self.obs_client.clone();
+        let alert_tx = self.alert_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_millis(OUTPUT_START_CHECK_MS)).await;
+            let client_arc = obs_client.get_client_arc();
+            let client_lock = client_arc.read().await;
+            let Some(client) = client_lock.as_ref() else {
+                return;
+            };
+            if let Ok(false) = Self::output_active(client, output).await {
+                let alert = DesyncAlert::new(
+                    String::new(),
+                    String::new(),
+                    format!(
+                        "{:?} output did not start; check the output settings (e.g. the recording path)",
+                        output
+                    ),
+                    AlertSeverity::Error,
+                );
+                if let Err(e) = alert_tx.send(alert) {
+                    throttled_eprintln!("Failed to send desync alert: {}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    pub fn send_alert(
         &self,
         scene_name: String,
         source_name: String,
         message: String,
         severity: AlertSeverity,
     ) -> Result<()> {
-        let alert = DesyncAlert {
-            id: uuid::Uuid::new_v4().to_string(),
-            timestamp: chrono::Utc::now().timestamp_millis(),
-            scene_name,
-            source_name,
-            message,
-            severity,
-        };
-        self.alert_tx.send(alert)?;
+        self.alert_tx
+            .send(DesyncAlert::new(scene_name, source_name, message, severity))?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obs::mock::MockObs;
+
+    /// Every combination of the four mask flags
+    fn all_masks() -> impl Iterator<Item = TransformFieldMask> {
+        (0..16u8).map(|bits| TransformFieldMask {
+            sync_position: bits & 1 != 0,
+            sync_scale: bits & 2 != 0,
+            sync_rotation: bits & 4 != 0,
+            sync_crop: bits & 8 != 0,
+        })
+    }
+
+    #[test]
+    fn merge_transform_takes_only_masked_in_fields() {
+        let local = SceneItemTransform {
+            position_x: 10.0,
+            position_y: 20.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            bounds_width: 100.0,
+            bounds_height: 50.0,
+            rotation: 0.0,
+            crop_left: 1,
+            crop_right: 2,
+            crop_top: 3,
+            crop_bottom: 4,
+            ..Default::default()
+        };
+        let master = serde_json::json!({
+            "position_x": 300.0,
+            "position_y": 400.0,
+            "scale_x": 2.0,
+            "scale_y": 0.5,
+            "bounds_width": 640.0,
+            "bounds_height": 360.0,
+            "rotation": 90.0,
+            "crop_left": 10,
+            "crop_right": 20,
+            "crop_top": 30,
+            "crop_bottom": 40,
+        });
+        let master = master.as_object().unwrap();
+
+        for mask in all_masks() {
+            let merged = SlaveSync::merge_transform(local.clone(), master, mask);
+
+            let position = (merged.position_x, merged.position_y);
+            if mask.sync_position {
+                assert_eq!(position, (300.0, 400.0), "{:?}", mask);
+            } else {
+                assert_eq!(position, (10.0, 20.0), "{:?}", mask);
+            }
+
+            let scale = (
+                merged.scale_x,
+                merged.scale_y,
+                merged.bounds_width,
+                merged.bounds_height,
+            );
+            if mask.sync_scale {
+                assert_eq!(scale, (2.0, 0.5, 640.0, 360.0), "{:?}", mask);
+            } else {
+                assert_eq!(scale, (1.0, 1.0, 100.0, 50.0), "{:?}", mask);
+            }
+
+            let expected_rotation = if mask.sync_rotation { 90.0 } else { 0.0 };
+            assert_eq!(merged.rotation, expected_rotation, "{:?}", mask);
+
+            let crop = (
+                merged.crop_left,
+                merged.crop_right,
+                merged.crop_top,
+                merged.crop_bottom,
+            );
+            if mask.sync_crop {
+                assert_eq!(crop, (10, 20, 30, 40), "{:?}", mask);
+            } else {
+                assert_eq!(crop, (1, 2, 3, 4), "{:?}", mask);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_transform_keeps_local_values_the_master_left_out() {
+        let local = SceneItemTransform {
+            position_x: 10.0,
+            rotation: 45.0,
+            crop_left: 5,
+            ..Default::default()
+        };
+        let master = serde_json::json!({ "position_y": 400.0 });
+        let merged = SlaveSync::merge_transform(
+            local,
+            master.as_object().unwrap(),
+            TransformFieldMask::default(),
+        );
+
+        assert_eq!((merged.position_x, merged.position_y), (10.0, 400.0));
+        assert_eq!(merged.rotation, 45.0);
+        assert_eq!(merged.crop_left, 5);
+    }
+
+    #[tokio::test]
+    async fn image_with_a_traversal_path_is_rejected_before_anything_is_written() {
+        let data = format!("traversal test {}", uuid::Uuid::new_v4());
+        let encoded =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data.as_bytes());
+        let written = image_cache::image_dir().join(format!(
+            "{}.png",
+            image_cache::content_hash(data.as_bytes())
+        ));
+
+        for file in ["../../etc/cron.d/evil.png", "/etc/cron.d/evil.png"] {
+            let result =
+                SlaveSync::write_image_file("Logo", file, &encoded, Some("png"), None).await;
+            assert!(result.is_err(), "{} accepted", file);
+        }
+        assert!(!written.exists());
+
+        // The same image under a plain file name is written
+        let (path, _, _) =
+            SlaveSync::write_image_file("Logo", "evil.png", &encoded, Some("png"), None)
+                .await
+                .unwrap();
+        assert_eq!(path, written);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Apply `moves` the way OBS applies SetSceneItemIndex
+    fn apply_moves(order: &[i64], moves: &[(i64, usize, usize)]) -> Vec<i64> {
+        let mut order = order.to_vec();
+        for &(id, from, to) in moves {
+            assert_eq!(order[from], id);
+            order.remove(from);
+            order.insert(to, id);
+        }
+        order
+    }
+
+    fn local(id: i64, source_name: &str) -> LocalItem {
+        LocalItem {
+            id,
+            source_name: source_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn normalize_reconciles_a_divergent_layout_and_keeps_what_the_master_skipped() {
+        let layout = MasterLayout::from_state(&serde_json::json!({
+            "scenes": [
+                { "name": "Main", "items": [
+                    { "source_name": "Logo", "scene_item_index": 0 },
+                    { "source_name": "Camera", "scene_item_index": 1 },
+                    { "source_name": "Overlay", "scene_item_index": 2 },
+                    { "source_name": "Mic", "scene_item_index": 3 },
+                ] },
+                { "name": "Break", "items": [] },
+                { "name": "New", "items": [] },
+            ],
+            "skipped_scenes": ["Filtered", "Unlistable"],
+            "skipped_sources": ["Private"],
+        }));
+        let local_scenes: Vec<String> = ["Main", "Break", "Old", "Filtered", "Unlistable"]
+            .map(String::from)
+            .to_vec();
+
+        let (add, remove) = layout.plan_scenes(&local_scenes, |_| true);
+        assert_eq!(add, vec!["New".to_string()]);
+        assert_eq!(remove, vec!["Old".to_string()]);
+        // Scenes excluded by an override are left alone
+        let (_, remove) = layout.plan_scenes(&local_scenes, |name| name != "Old");
+        assert!(remove.is_empty());
+
+        let local_items = vec![
+            local(1, "Camera"),
+            local(2, "Extra"),
+            local(3, "Overlay"),
+            local(4, "Private"),
+            local(5, "Logo"),
+        ];
+        let plan = layout.plan_items(&layout.scenes[0], &local_items);
+        assert_eq!(plan.remove, vec![local(2, "Extra")]);
+        let paired: Vec<(&str, Option<i64>)> = plan
+            .paired
+            .iter()
+            .map(|(item, id)| (item.source_name.as_str(), *id))
+            .collect();
+        assert_eq!(
+            paired,
+            vec![
+                ("Logo", Some(5)),
+                ("Camera", Some(1)),
+                ("Overlay", Some(3)),
+                ("Mic", None),
+            ]
+        );
+
+        // After the removal and with Mic created as id 6
+        let current = vec![1, 3, 4, 5, 6];
+        let desired = vec![5, 1, 3, 6];
+        let reordered = apply_moves(&current, &reorder_moves(&current, &desired));
+        let synced: Vec<i64> = reordered
+            .iter()
+            .copied()
+            .filter(|id| desired.contains(id))
+            .collect();
+        assert_eq!(synced, desired);
+        assert!(reordered.contains(&4), "the filtered source was dropped");
+    }
+
+    #[test]
+    fn reorder_is_a_no_op_when_already_in_order() {
+        assert!(reorder_moves(&[1, 2, 3], &[1, 2, 3]).is_empty());
+        assert!(reorder_moves(&[1, 9, 2], &[1, 2]).is_empty());
+    }
+
+    #[test]
+    fn scenes_without_an_override_follow_the_global_targets() {
+        let overrides =
+            SceneTargetOverrides::from([("Cam".to_string(), vec![SyncTargetType::Program])]);
+        let global = vec![SyncTargetType::Program];
+        let allowed = |scene: &str, target: SyncTargetType| {
+            SlaveSync::is_target_allowed(&overrides, &global, scene, &target)
+        };
+
+        assert!(allowed("Cam", SyncTargetType::Program));
+        assert!(!allowed("Cam", SyncTargetType::Source));
+        // Same as the master, which sends no source updates when Source isn't global
+        assert!(allowed("Main", SyncTargetType::Program));
+        assert!(!allowed("Main", SyncTargetType::Source));
+    }
+
+    #[test]
+    fn interpolation_steps_between_the_two_transforms() {
+        let start = SceneItemTransform {
+            position_x: 0.0,
+            position_y: 100.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            ..Default::default()
+        };
+        let target = SceneItemTransform {
+            position_x: 120.0,
+            position_y: 100.0,
+            scale_x: 2.0,
+            scale_y: 2.0,
+            crop_left: 8,
+            ..Default::default()
+        };
+
+        // 200ms at up to 30 updates a second
+        let steps = SlaveSync::interpolation_steps(&start, &target, 200, 30);
+
+        assert_eq!(steps.len(), 6);
+        let xs: Vec<f32> = steps
+            .iter()
+            .map(|step| step.position.as_ref().unwrap().x.unwrap())
+            .collect();
+        assert_eq!(xs, vec![20.0, 40.0, 60.0, 80.0, 100.0, 120.0]);
+        assert_eq!(steps[2].scale.as_ref().unwrap().x, Some(1.5));
+        // Only the last step carries the rest of the target, such as its crop
+        assert!(steps[..5].iter().all(|step| step.crop.is_none()));
+        assert_eq!(steps[5].crop.as_ref().unwrap().left, Some(8));
+    }
+
+    #[tokio::test]
+    async fn master_directed_messages_are_flagged_as_role_confusion() {
+        let (slave_sync, _alerts) = SlaveSync::new(Arc::new(OBSClient::new()));
+
+        for message in [
+            SyncMessage::state_sync_request(Vec::new()),
+            SyncMessage::new(
+                SyncMessageType::StateReport,
+                SyncTargetType::Program,
+                serde_json::json!({ "current_scene": "Main" }),
+            ),
+        ] {
+            let error = slave_sync.apply_sync_message(message).await.unwrap_err();
+            assert!(error.to_string().contains("check roles"));
+        }
+    }
+
+    #[tokio::test]
+    async fn item_applies_respect_the_concurrency_limit() {
+        for (limit, allowed) in [(3, 3), (0, 1)] {
+            let in_flight = AtomicUsize::new(0);
+            let peak = AtomicUsize::new(0);
+            let applies: Vec<_> = (0..12)
+                .map(|item| {
+                    let (in_flight, peak) = (&in_flight, &peak);
+                    async move {
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        item
+                    }
+                })
+                .collect();
+
+            let mut applied = run_limited(applies, limit).await;
+
+            applied.sort();
+            assert_eq!(applied, (0..12).collect::<Vec<_>>());
+            assert_eq!(peak.load(Ordering::SeqCst), allowed);
+        }
+    }
+
+    #[tokio::test]
+    async fn media_check_tells_existing_files_from_missing_ones() {
+        let dir = std::env::temp_dir().join(format!("obs-sync-media-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let present = dir.join("intro.mp4");
+        std::fs::write(&present, b"video").unwrap();
+        let present = present.to_string_lossy().to_string();
+        let missing = dir.join("outro.mp4").to_string_lossy().to_string();
+
+        let media = SlaveSync::media_availability(
+            "Intro",
+            "ffmpeg_source",
+            "ffmpeg_source",
+            &serde_json::json!({ "is_local_file": true, "local_file": present }),
+        )
+        .await;
+        let playlist = SlaveSync::media_availability(
+            "Playlist",
+            "vlc_source",
+            "vlc_source",
+            &serde_json::json!({ "playlist": [{ "value": present }, { "value": missing }] }),
+        )
+        .await;
+        // A media source playing a URL has no local file to check
+        let stream = SlaveSync::media_availability(
+            "Stream",
+            "ffmpeg_source",
+            "ffmpeg_source",
+            &serde_json::json!({ "is_local_file": false, "input": "rtmp://example/live" }),
+        )
+        .await;
+
+        let found = |results: &[MediaAvailability]| -> Vec<(String, bool)> {
+            results
+                .iter()
+                .map(|media| (media.path.clone(), media.exists))
+                .collect()
+        };
+        assert_eq!(found(&media), vec![(present.clone(), true)]);
+        assert_eq!(found(&playlist), vec![(present, true), (missing, false)]);
+        assert!(stream.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn alternating_checks_do_not_flap_the_sync_state() {
+        let mut state = SyncStateHysteresis::new();
+        let alternating = [false, true, false, true, false, true];
+        let reported: Vec<bool> = alternating
+            .iter()
+            .map(|&in_sync| state.observe(in_sync, 2))
+            .collect();
+        assert_eq!(reported, vec![true; 6]);
+
+        // Two desynced checks in a row flip it, and two in-sync ones flip it back
+        let settled: Vec<bool> = [false, false, true, false, true, true]
+            .iter()
+            .map(|&in_sync| state.observe(in_sync, 2))
+            .collect();
+        assert_eq!(settled, vec![true, false, false, false, false, true]);
+
+        // A threshold of 1 follows every check
+        assert!(!state.observe(false, 1));
+    }
+
+    #[test]
+    fn name_matching_only_trusts_unique_source_names() {
+        let item = |id: i64, source_name: &str| obws::responses::scene_items::SceneItem {
+            id,
+            index: 0,
+            source_name: source_name.to_string(),
+            source_type: obws::responses::scene_items::SourceType::Input,
+            input_kind: None,
+            is_group: None,
+        };
+        // This slave built the scene in a different order than the master
+        let items = [item(4, "Camera"), item(5, "Logo"), item(6, "Logo")];
+
+        assert_eq!(SlaveSync::item_id_by_name(&items, "Camera", 1), 4);
+        // Either Logo could be meant, so the master's id is kept
+        assert_eq!(SlaveSync::item_id_by_name(&items, "Logo", 6), 6);
+        assert_eq!(SlaveSync::item_id_by_name(&items, "Logo", 2), 2);
+        assert_eq!(SlaveSync::item_id_by_name(&items, "Browser", 3), 3);
+    }
+
+    #[tokio::test]
+    async fn repeated_image_reuses_the_cached_file() {
+        let obs = MockObs::start(|_, _| Ok(serde_json::Value::Null)).await;
+        let obs_client = obs.obs_client().await;
+        let (slave_sync, _alerts) = SlaveSync::new(obs_client.clone());
+        let client_arc = obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().unwrap();
+
+        let bytes = uuid::Uuid::new_v4().to_string();
+        let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes);
+        let master_hash = format!("master-{}", bytes);
+        let image = |data| ReceivedImage {
+            data: Some(data),
+            hash: Some(master_hash.as_str()),
+            format: None,
+            compression: None,
+        };
+        slave_sync
+            .handle_image_update(client, "Logo", "logo.png", image(&data))
+            .await
+            .unwrap();
+        // Undecodable bytes prove the repeat is neither decoded nor written
+        slave_sync
+            .handle_image_update(client, "Sponsor", "logo.png", image("not base64!"))
+            .await
+            .unwrap();
+
+        let files: Vec<serde_json::Value> = obs
+            .requests_of("SetInputSettings")
+            .iter()
+            .map(|request| request["inputSettings"]["file"].clone())
+            .collect();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0], files[1]);
+        let path = PathBuf::from(files[0].as_str().unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), bytes);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn normalize_brings_a_drifted_scene_in_line_with_the_master() {
+        // Local "Main", bottom first: Camera, Extra, Overlay, Logo
+        let local = Arc::new(std::sync::Mutex::new(vec![
+            (1_i64, "Camera".to_string()),
+            (2, "Extra".to_string()),
+            (3, "Overlay".to_string()),
+            (5, "Logo".to_string()),
+        ]));
+        let scene = local.clone();
+        let obs = MockObs::start(move |request_type, data| {
+            let mut items = scene.lock().unwrap();
+            let id = data["sceneItemId"].as_i64().unwrap_or_default();
+            match request_type {
+                "GetSceneItemList" => Ok(serde_json::json!({
+                    "sceneItems": items.iter().enumerate().map(|(index, (id, name))| {
+                        serde_json::json!({
+                            "sceneItemId": id,
+                            "sceneItemIndex": index,
+                            "sourceName": name,
+                            "sourceType": "OBS_SOURCE_TYPE_INPUT",
+                            "inputKind": null,
+                            "isGroup": null,
+                        })
+                    }).collect::<Vec<_>>(),
+                })),
+                "RemoveSceneItem" => {
+                    items.retain(|(local, _)| *local != id);
+                    Ok(serde_json::Value::Null)
+                }
+                "CreateSceneItem" => {
+                    let name = data["sourceName"].as_str().unwrap().to_string();
+                    items.push((6, name));
+                    Ok(serde_json::json!({ "sceneItemId": 6 }))
+                }
+                "SetSceneItemIndex" => {
+                    let from = items.iter().position(|(local, _)| *local == id).unwrap();
+                    let item = items.remove(from);
+                    items.insert(data["sceneItemIndex"].as_u64().unwrap() as usize, item);
+                    Ok(serde_json::Value::Null)
+                }
+                "GetSceneItemEnabled" => Ok(serde_json::json!({ "sceneItemEnabled": true })),
+                _ => Ok(serde_json::Value::Null),
+            }
+        })
+        .await;
+        let obs_client = obs.obs_client().await;
+        let (slave_sync, _alerts) = SlaveSync::new(obs_client.clone());
+        let client_arc = obs_client.get_client_arc();
+        let client_lock = client_arc.read().await;
+        let client = client_lock.as_ref().unwrap();
+
+        let layout = MasterLayout::from_state(&serde_json::json!({
+            "scenes": [{ "name": "Main", "items": [
+                { "source_name": "Logo", "scene_item_index": 0, "enabled": true },
+                { "source_name": "Camera", "scene_item_index": 1, "enabled": true },
+                { "source_name": "Overlay", "scene_item_index": 2 },
+                { "source_name": "Mic", "scene_item_index": 3 },
+            ] }],
+        }));
+        let mut changes = Vec::new();
+        slave_sync
+            .normalize_scene_items(client, &layout, &layout.scenes[0], &mut changes)
+            .await;
+
+        let names: Vec<String> = local
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect();
+        assert_eq!(names, ["Logo", "Camera", "Overlay", "Mic"]);
+        let actions: Vec<(NormalizeAction, Option<&str>)> = changes
+            .iter()
+            .map(|change| (change.action, change.source_name.as_deref()))
+            .collect();
+        assert_eq!(actions[0], (NormalizeAction::RemoveItem, Some("Extra")));
+        assert_eq!(actions[1], (NormalizeAction::AddItem, Some("Mic")));
+        assert!(actions[2..]
+            .iter()
+            .all(|(action, _)| *action == NormalizeAction::ReorderItem));
+        assert!(changes
+            .iter()
+            .all(|change| change.error.is_none() && !change.planned));
+    }
+
+    #[tokio::test]
+    async fn mapped_command_runs_its_obs_action() {
+        let obs = MockObs::start(|_, _| Ok(serde_json::json!({ "inputMuted": true }))).await;
+        let (slave_sync, _alerts) = SlaveSync::new(obs.obs_client().await);
+
+        let command = SemanticCommand::ToggleInputMute {
+            input_name: "Mic".to_string(),
+        };
+        slave_sync
+            .apply_sync_message(SyncMessage::command(&command))
+            .await
+            .unwrap();
+
+        assert_eq!(obs.request_types(), vec!["ToggleInputMute"]);
+        assert_eq!(obs.requests_of("ToggleInputMute")[0]["inputName"], "Mic");
+    }
+}
@@ -18,6 +18,8 @@ import { SplashScreen } from "./components/SplashScreen";
 import { VersionInfo } from "./components/VersionInfo";
 import { DonationModal } from "./components/DonationModal";
 import { AppMode } from "./types/sync";
+import { StatusEvent } from "./types/network";
+import { listen } from "@tauri-apps/api/event";
 
 function App() {
   const [showSplash, setShowSplash] = useState(true);
@@ -55,6 +57,21 @@ function App() {
     }
   }, [syncError]);
 
+  // Connection failures and apply errors reported by the backend
+  useEffect(() => {
+    const unlistenPromise = listen<StatusEvent>("connection-status", (event) => {
+      const { level, message, reconnection } = event.payload;
+      const attempts =
+        reconnection && reconnection.isReconnecting && reconnection.attemptCount > 0
+          ? ` (再接続 ${reconnection.attemptCount}/${reconnection.maxAttempts || "∞"})`
+          : "";
+      toast[level](`${message}${attempts}`);
+    });
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
   const handleConnectOBS = async () => {
     setIsConnectingOBS(true);
     try {
@@ -95,7 +112,9 @@ function App() {
     try {
       await setMode(mode);
       setAppMode(mode);
-      toast.success(`${mode === AppMode.Master ? "Master" : "Slave"}モードに設定しました`);
+      const modeLabel =
+        mode === AppMode.Master ? "Master" : mode === AppMode.Bidirectional ? "双方向Slave" : "Slave";
+      toast.success(`${modeLabel}モードに設定しました`);
     } catch (error) {
       console.error("Failed to set mode:", error);
     }
@@ -113,7 +132,7 @@ function App() {
       } catch (error) {
         console.error("Failed to stop master server:", error);
       }
-    } else if (appMode === AppMode.Slave) {
+    } else if (appMode === AppMode.Slave || appMode === AppMode.Bidirectional) {
       try {
         await networkStatus.disconnectFromMaster();
       } catch (error) {
@@ -187,7 +206,11 @@ function App() {
           {appMode && (
             <div className="mode-badge">
               <span className={`badge ${appMode === AppMode.Master ? 'badge-master' : 'badge-slave'}`}>
-                {appMode === AppMode.Master ? '🎛️ Master' : '📺 Slave'}
+                {appMode === AppMode.Master
+                  ? '🎛️ Master'
+                  : appMode === AppMode.Bidirectional
+                    ? '🔁 Bidirectional'
+                    : '📺 Slave'}
               </span>
             </div>
           )}
@@ -240,6 +263,25 @@ function App() {
                   <button className="btn-mode-select">選択</button>
                 </div>
               </div>
+
+              <div 
+                className="mode-card mode-card-slave"
+                onClick={() => handleSetMode(AppMode.Bidirectional)}
+              >
+                <div className="mode-card-icon">🔁</div>
+                <h3 className="mode-card-title">双方向モード</h3>
+                <p className="mode-card-description">
+                  Slaveとして受信しつつ、ローカルのシーン切替と位置変更をMasterに送信します
+                </p>
+                <ul className="mode-card-features">
+                  <li>✓ Slaveモードの全機能</li>
+                  <li>✓ ローカル変更をMaster経由で共有</li>
+                  <li>✓ Master側で受け入れ設定が必要</li>
+                </ul>
+                <div className="mode-card-action">
+                  <button className="btn-mode-select">選択</button>
+                </div>
+              </div>
             </div>
           </div>
         ) : (
@@ -378,7 +420,7 @@ function App() {
                   </section>
                 )}
                 
-                {appMode === AppMode.Slave && (
+                {(appMode === AppMode.Slave || appMode === AppMode.Bidirectional) && (
                   <section className="section">
                     <div className="section-header">
                       <h2>
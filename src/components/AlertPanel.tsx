@@ -144,7 +144,7 @@ export const AlertPanel = ({ alerts, onClearAlert, onClearAllAlerts }: AlertPane
                     )}
                     <div className="alert-message">
                       <span className="message-icon">
-                        {alert.severity === "error" ? "❌" : "⚠️"}
+                        {alert.resolved ? "✅" : alert.severity === "error" ? "❌" : "⚠️"}
                       </span>
                       {alert.message}
                     </div>
@@ -10,7 +10,7 @@ export const ConnectionStatus = ({ status }: ConnectionStatusProps) => {
       <div className="status-indicator">
         <span className={`status-dot ${status.connected ? "connected" : "disconnected"}`}></span>
         <span className="status-text">
-          {status.connected ? "接続中" : "未接続"}
+          {status.connected ? "接続中" : status.reconnecting ? "再接続中…" : "未接続"}
         </span>
       </div>
 
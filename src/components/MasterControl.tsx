@@ -1,7 +1,9 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, type MouseEvent } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useNetworkStatus } from "../hooks/useNetworkStatus";
 import { ConnectionState } from "../types/network";
+import { ResyncProgress } from "../types/sync";
 import { parseErrorMessage } from "../utils/errorMessages";
 
 export const MasterControl = () => {
@@ -9,7 +11,9 @@ export const MasterControl = () => {
   const [isStarting, setIsStarting] = useState(false);
   const [isStopping, setIsStopping] = useState(false);
   const [localIpAddress, setLocalIpAddress] = useState<string | null>(null);
-  const { status, clients, slaveStatuses, performanceMetrics, startMasterServer, stopMasterServer } = useNetworkStatus();
+  const [isResyncing, setIsResyncing] = useState(false);
+  const [resyncProgress, setResyncProgress] = useState<ResyncProgress | null>(null);
+  const { status, clients, slaveStatuses, performanceMetrics, metricsByType, startMasterServer, stopMasterServer } = useNetworkStatus();
 
   useEffect(() => {
     const fetchLocalIp = async () => {
@@ -24,6 +28,15 @@ export const MasterControl = () => {
     fetchLocalIp();
   }, []);
 
+  useEffect(() => {
+    const unlisten = listen<ResyncProgress>("resync-progress", (event) => {
+      setResyncProgress(event.payload.done ? null : event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleStart = async () => {
     setIsStarting(true);
     try {
@@ -47,12 +60,23 @@ export const MasterControl = () => {
   };
 
   const handleResyncAll = async () => {
+    setIsResyncing(true);
     try {
       await invoke("resync_all_slaves");
       alert("全Slaveに再同期を送信しました");
     } catch (error) {
       console.error("Failed to resync all slaves:", error);
       alert(`再同期に失敗しました: ${error}`);
+    } finally {
+      setIsResyncing(false);
+    }
+  };
+
+  const handleCancelResync = async () => {
+    try {
+      await invoke("cancel_resync");
+    } catch (error) {
+      console.error("Failed to cancel resync:", error);
     }
   };
 
@@ -66,6 +90,20 @@ export const MasterControl = () => {
     }
   };
 
+  const handlePushSource = async (event: MouseEvent, sourceName: string) => {
+    event.preventDefault();
+    if (!confirm(`${sourceName} の現在の状態を全Slaveに再送信しますか？`)) {
+      return;
+    }
+    try {
+      await invoke("push_source", { sourceName });
+      alert(`${sourceName} を全Slaveに再送信しました`);
+    } catch (error) {
+      console.error("Failed to push source:", error);
+      alert(`再送信に失敗しました: ${error}`);
+    }
+  };
+
   const isConnected = status.state === ConnectionState.Connected;
   const isConnecting = status.state === ConnectionState.Connecting;
 
@@ -164,6 +202,14 @@ export const MasterControl = () => {
                     {performanceMetrics.averageLatencyMs.toFixed(2)} ms
                   </span>
                 </div>
+                {metricsByType.map((typeMetrics) => (
+                  <div key={typeMetrics.messageType} className="metric-item">
+                    <span className="metric-label">{typeMetrics.messageType}:</span>
+                    <span className="metric-value">
+                      {typeMetrics.averageLatencyMs.toFixed(2)} ms ({typeMetrics.count}件)
+                    </span>
+                  </div>
+                ))}
               </div>
             </div>
           )}
@@ -172,12 +218,29 @@ export const MasterControl = () => {
             <button 
               onClick={handleResyncAll}
               className="btn-secondary"
-              disabled={clients.length === 0}
+              disabled={clients.length === 0 || isResyncing}
             >
               <span>🔄</span>
               全Slaveに再同期
             </button>
+            {isResyncing && (
+              <button onClick={handleCancelResync} className="btn-secondary">
+                <span>⏹</span>
+                再同期を中止
+              </button>
+            )}
           </div>
+          {resyncProgress && (
+            <div className="resync-progress">
+              <progress value={resyncProgress.sceneIndex} max={resyncProgress.sceneCount} />
+              <span className="resync-progress-text">
+                {resyncProgress.target ? `${resyncProgress.target}: ` : ""}
+                シーン {resyncProgress.sceneIndex} / {resyncProgress.sceneCount} ({resyncProgress.sceneName})
+                {resyncProgress.itemCount > 0 &&
+                  ` アイテム ${resyncProgress.itemIndex} / ${resyncProgress.itemCount}`}
+              </span>
+            </div>
+          )}
           
           {clients.length > 0 && (
             <div className="clients-list">
@@ -204,7 +267,12 @@ export const MasterControl = () => {
                     <div key={client.id} className={`client-card ${!isSynced ? "client-card-desynced" : ""}`}>
                       <div className="client-header">
                         <span className={`client-status-dot ${isSynced ? "synced" : "desynced"}`}></span>
-                        <span className="client-id">{client.id}</span>
+                        <span className="client-id" title={client.id}>{client.displayName || client.id}</span>
+                        {slaveStatus?.rttMs != null && (
+                          <span className="client-rtt" title="ハートビートの往復時間">
+                            {Math.round(slaveStatus.rttMs)}ms
+                          </span>
+                        )}
                         {!isSynced && (
                           <span className="client-desync-badge">⚠️ ズレあり</span>
                         )}
@@ -234,7 +302,14 @@ export const MasterControl = () => {
                           <p className="desync-details-title">ズレの詳細:</p>
                           <ul className="desync-details-list">
                             {desyncDetails.map((detail, index) => (
-                              <li key={index} className={`desync-detail-item desync-${detail.severity.toLowerCase()}`}>
+                              <li
+                                key={index}
+                                className={`desync-detail-item desync-${detail.severity.toLowerCase()}`}
+                                onContextMenu={(event) =>
+                                  detail.sourceName && handlePushSource(event, detail.sourceName)
+                                }
+                                title={detail.sourceName ? "右クリックでこのソースだけ再送信" : undefined}
+                              >
                                 <span className="desync-icon">
                                   {detail.severity === "Critical" ? "❌" : "⚠️"}
                                 </span>
@@ -1,7 +1,7 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useNetworkStatus } from "../hooks/useNetworkStatus";
-import { ConnectionState } from "../types/network";
+import { ConnectionState, MasterProbeResult } from "../types/network";
 import { parseErrorMessage } from "../utils/errorMessages";
 
 export const SlaveMonitor = () => {
@@ -9,7 +9,10 @@ export const SlaveMonitor = () => {
   const [port, setPort] = useState(8080);
   const [isConnecting, setIsConnecting] = useState(false);
   const [isDisconnecting, setIsDisconnecting] = useState(false);
-  const { status, reconnectionStatus, performanceMetrics, connectToMaster, disconnectFromMaster } = useNetworkStatus();
+  const [isProbing, setIsProbing] = useState(false);
+  const [probeResult, setProbeResult] = useState<MasterProbeResult | null>(null);
+  const [probeError, setProbeError] = useState<string | null>(null);
+  const { status, reconnectionStatus, performanceMetrics, metricsByType, connectToMaster, disconnectFromMaster } = useNetworkStatus();
 
   const handleConnect = async () => {
     setIsConnecting(true);
@@ -22,6 +25,19 @@ export const SlaveMonitor = () => {
     }
   };
 
+  const handleProbe = async () => {
+    setIsProbing(true);
+    setProbeResult(null);
+    setProbeError(null);
+    try {
+      setProbeResult(await invoke<MasterProbeResult>("probe_master", { host, port }));
+    } catch (error) {
+      setProbeError(String(error));
+    } finally {
+      setIsProbing(false);
+    }
+  };
+
   const handleDisconnect = async () => {
     setIsDisconnecting(true);
     try {
@@ -86,6 +102,30 @@ export const SlaveMonitor = () => {
           </span>
         </div>
 
+        {!isConnected && (
+          <div className="form-group">
+            <button
+              onClick={handleProbe}
+              className="btn-secondary"
+              disabled={isProbing || isConnecting}
+            >
+              {isProbing ? "確認中..." : "接続テスト"}
+            </button>
+            {probeResult && !probeResult.compatible && (
+              <span className="input-hint">
+                ❌ プロトコルのバージョンが一致しません (Master v{probeResult.protocolVersion})
+              </span>
+            )}
+            {probeResult && probeResult.compatible && (
+              <span className="input-hint">
+                ✅ 接続可能 (v{probeResult.appVersion}, {probeResult.latencyMs}ms
+                {probeResult.authRequired ? ", 認証が必要" : ""})
+              </span>
+            )}
+            {probeError && <span className="input-hint">❌ {probeError}</span>}
+          </div>
+        )}
+
         <div className="control-actions">
           {!isConnected ? (
             <button
@@ -156,6 +196,14 @@ export const SlaveMonitor = () => {
                     {performanceMetrics.averageLatencyMs.toFixed(2)} ms
                   </span>
                 </div>
+                {metricsByType.map((typeMetrics) => (
+                  <div key={typeMetrics.messageType} className="metric-item">
+                    <span className="metric-label">{typeMetrics.messageType}:</span>
+                    <span className="metric-value">
+                      {typeMetrics.averageLatencyMs.toFixed(2)} ms ({typeMetrics.count}件)
+                    </span>
+                  </div>
+                ))}
               </div>
             </div>
           )}
@@ -190,6 +238,10 @@ export const SlaveMonitor = () => {
                 {reconnectionStatus.attemptCount} / {reconnectionStatus.maxAttempts}
               </span>
             </div>
+            <div className="status-item">
+              <span className="status-label">接続先:</span>
+              <span className="status-value">{reconnectionStatus.activeMaster}</span>
+            </div>
             {reconnectionStatus.lastError && (
               <div className="status-item">
                 <span className="status-label">エラー:</span>
@@ -207,6 +259,18 @@ export const SlaveMonitor = () => {
         </div>
       )}
 
+      {reconnectionStatus && reconnectionStatus.masterStopped && (
+        <div className="status-panel status-panel-info">
+          <div className="status-panel-header">
+            <span className="status-icon">⏹️</span>
+            <h4>Master停止</h4>
+          </div>
+          <div className="status-panel-content">
+            <p>Masterサーバーが停止されました。再接続は行いません。</p>
+          </div>
+        </div>
+      )}
+
       {status.state === ConnectionState.Connecting && (
         <div className="status-panel status-panel-info">
           <div className="status-panel-header">
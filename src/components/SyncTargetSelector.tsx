@@ -44,6 +44,12 @@ export const SyncTargetSelector = () => {
       title: "プログラム",
       description: "ライブ出力中のシーン",
     },
+    {
+      type: SyncTargetType.Output,
+      icon: "⏺️",
+      title: "録画・配信",
+      description: "録画と配信の開始・停止",
+    },
   ];
 
   return (
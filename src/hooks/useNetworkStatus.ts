@@ -1,11 +1,12 @@
 import { useState, useCallback, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { NetworkStatus, ConnectionState, ClientInfo, SlaveStatus, ReconnectionStatus } from "../types/network";
+import { NetworkStatus, ConnectionState, ClientInfo, SlaveStatus, ReconnectionStatus, MasterEndpoint } from "../types/network";
 
 interface NetworkConfig {
   host: string;
   port: number;
+  fallbackMasters?: MasterEndpoint[];
 }
 
 export interface PerformanceMetrics {
@@ -18,7 +19,21 @@ export interface PerformanceMetrics {
     messageType: string;
     latencyMs: number;
     messageSizeBytes: number;
+    /** "acked" entries carry a round-trip latency and aren't separate messages */
+    kind: "received" | "sent" | "acked";
   }>;
+  clockOffsetMs: number | null;
+  heartbeatRttMs: number | null;
+}
+
+/** Retained metrics of one message type (`get_metrics_by_type`) */
+export interface MessageTypeMetrics {
+  messageType: string;
+  count: number;
+  latencySamples: number;
+  /** 0 when there are no latency samples */
+  averageLatencyMs: number;
+  totalBytes: number;
 }
 
 export const useNetworkStatus = () => {
@@ -30,6 +45,7 @@ export const useNetworkStatus = () => {
   const [slaveStatuses, setSlaveStatuses] = useState<SlaveStatus[]>([]);
   const [reconnectionStatus, setReconnectionStatus] = useState<ReconnectionStatus | null>(null);
   const [performanceMetrics, setPerformanceMetrics] = useState<PerformanceMetrics | null>(null);
+  const [metricsByType, setMetricsByType] = useState<MessageTypeMetrics[]>([]);
   const pollingIntervalRef = useRef<number | null>(null);
   const reconnectionPollingRef = useRef<number | null>(null);
   const metricsPollingRef = useRef<number | null>(null);
@@ -79,6 +95,7 @@ export const useNetworkStatus = () => {
     try {
       const metrics = await invoke<PerformanceMetrics>("get_performance_metrics");
       setPerformanceMetrics(metrics);
+      setMetricsByType(await invoke<MessageTypeMetrics[]>("get_metrics_by_type"));
     } catch (err) {
       console.error("Failed to get performance metrics:", err);
     }
@@ -91,14 +108,13 @@ export const useNetworkStatus = () => {
       setStatus({ state: ConnectionState.Connected, connectedClients: 0 });
       setError(null);
       
-      // Start polling for client count, info, slave statuses, and performance metrics
+      // Clients joining or leaving arrive as clients-changed events; slave statuses
+      // and performance metrics are polled
       updateClientCount();
       updateClientsInfo();
       updateSlaveStatuses();
       updatePerformanceMetrics();
       pollingIntervalRef.current = window.setInterval(() => {
-        updateClientCount();
-        updateClientsInfo();
         updateSlaveStatuses();
       }, 1000);
       metricsPollingRef.current = window.setInterval(() => {
@@ -219,6 +235,32 @@ export const useNetworkStatus = () => {
     };
   }, []);
 
+  // Listen for slaves joining or leaving the master
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+
+    const setupListener = async () => {
+      const unlisten = await listen<ClientInfo[]>("clients-changed", (event) => {
+        setClients(event.payload);
+        setStatus((prev) => {
+          if (prev.state === ConnectionState.Connected) {
+            return { ...prev, connectedClients: event.payload.length };
+          }
+          return prev;
+        });
+      });
+      unlistenFn = unlisten;
+    };
+
+    setupListener();
+
+    return () => {
+      if (unlistenFn) {
+        unlistenFn();
+      }
+    };
+  }, []);
+
   // Cleanup polling on unmount
   useEffect(() => {
     return () => {
@@ -241,6 +283,7 @@ export const useNetworkStatus = () => {
     slaveStatuses,
     reconnectionStatus,
     performanceMetrics,
+    metricsByType,
     startMasterServer,
     stopMasterServer,
     connectToMaster,
@@ -1,6 +1,12 @@
 import { useState, useCallback, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { OBSConnectionConfig, OBSConnectionStatus, OBSSource } from "../types/obs";
+import { listen } from "@tauri-apps/api/event";
+import {
+  OBSConnectionConfig,
+  OBSConnectionEvent,
+  OBSConnectionStatus,
+  OBSSource,
+} from "../types/obs";
 
 export const useOBSConnection = () => {
   const [status, setStatus] = useState<OBSConnectionStatus>({
@@ -99,6 +105,26 @@ export const useOBSConnection = () => {
     };
   }, [refreshStatus]);
 
+  // Refresh right away when the backend loses or regains OBS
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+
+    const setupListener = async () => {
+      const unlisten = await listen<OBSConnectionEvent>("obs-connection", () => {
+        refreshStatus();
+      });
+      unlistenFn = unlisten;
+    };
+
+    setupListener();
+
+    return () => {
+      if (unlistenFn) {
+        unlistenFn();
+      }
+    };
+  }, [refreshStatus]);
+
   return {
     status,
     sources,
@@ -1,5 +1,8 @@
 import { useState, useCallback, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { SyncTargetType } from "../types/obs";
+import { AppMode } from "../types/sync";
+import { MasterEndpoint } from "../types/network";
 
 export interface OBSSettings {
   host: string;
@@ -7,13 +10,157 @@ export interface OBSSettings {
   password: string;
 }
 
+export interface HandshakeGuard {
+  enabled: boolean;
+  allowedOrigins: string[];
+  allowedHosts: string[];
+  pathToken?: string;
+}
+
+export interface RawEventPassthrough {
+  enabled: boolean;
+  eventTypes: string[];
+}
+
+export interface ImageReencode {
+  enabled: boolean;
+  format: "png" | "jpeg" | "webp";
+  quality: number;
+  minSizeBytes: number;
+}
+
 export interface MasterSettings {
   defaultPort: number;
+  handshakeGuard?: HandshakeGuard;
+  rawEventPassthrough?: RawEventPassthrough;
+  imageReencode?: ImageReencode;
+  transformAnchors?: Record<string, TransformAnchor>;
+  hotkeyCommands?: Record<string, SemanticCommand>;
+  useTls?: boolean;
+  tlsCertPath?: string;
+  tlsKeyPath?: string;
+  authToken?: string;
+  /** Apply and re-broadcast changes from bidirectional slaves */
+  acceptSlaveUpdates?: boolean;
+  heartbeatIntervalSecs?: number;
+  /** Slaves silent for this long are dropped as disconnected */
+  heartbeatTimeoutSecs?: number;
+  /** Cap on state and image traffic to slaves in kbps; 0 for unlimited */
+  bandwidthLimitKbps?: number;
+}
+
+export type SemanticCommand =
+  | { command: "studio_transition" }
+  | { command: "set_input_mute"; input_name: string; muted: boolean }
+  | { command: "toggle_input_mute"; input_name: string }
+  | { command: "set_program_scene"; scene_name: string }
+  | { command: "trigger_hotkey"; hotkey_name: string };
+
+export type TransformAnchor =
+  | "topLeft"
+  | "top"
+  | "topRight"
+  | "left"
+  | "center"
+  | "right"
+  | "bottomLeft"
+  | "bottom"
+  | "bottomRight"
+  | "proportional";
+
+export interface TransformFieldMask {
+  syncPosition: boolean;
+  syncScale: boolean;
+  syncRotation: boolean;
+  syncCrop: boolean;
+}
+
+export interface TransformInterpolation {
+  enabled: boolean;
+  maxDurationMs: number;
+  maxRateHz: number;
+  maxJumpPx: number;
 }
 
 export interface SlaveSettings {
   defaultHost: string;
   defaultPort: number;
+  /** Masters to fail over to when the default one can't be reached */
+  fallbackMasters?: MasterEndpoint[];
+  transformMask?: TransformFieldMask;
+  interpolation?: TransformInterpolation;
+  applyConcurrency?: number;
+  reconnectStrategy?: ReconnectStrategy;
+  /** null retries forever */
+  maxReconnectAttempts?: number | null;
+  syncHysteresis?: number;
+  preferNameMatching?: boolean;
+  /** Report planned changes instead of applying them to OBS */
+  dryRun?: boolean;
+  imageCache?: ImageCacheLimits;
+  /** Name shown for this machine on the master, e.g. "Stage-Left-PC" */
+  displayName?: string | null;
+  /** Abort a StateSync at the first failed item and request a fresh one */
+  strictStateSync?: boolean;
+  /** Sources customized on this machine that sync leaves alone */
+  ignoredSources?: string[];
+  /** Connect with wss:// (used by auto-start) */
+  useTls?: boolean;
+  tlsCaCertPath?: string | null;
+  tlsFingerprint?: string | null;
+  /** Token for a master that requires authentication */
+  authToken?: string | null;
+}
+
+export interface ImageCacheLimits {
+  maxAgeSecs: number;
+  maxBytes: number;
+}
+
+export type ReconnectStrategy =
+  | { type: "fixed"; secs: number }
+  | { type: "linear"; step: number; cap: number }
+  | { type: "exponential"; base: number; cap: number };
+
+export interface SyncFilters {
+  scenes: string[];
+  /** Source names or glob patterns never synced */
+  sources: string[];
+  /** When non-empty, only sources matching one of these names or patterns sync */
+  includeSources?: string[];
+  inputKinds: string[];
+  messageTypes: string[];
+}
+
+/** Argument of `set_source_sync_rules`: `include` is includeSources, `exclude` is sources */
+export interface SourceSyncRules {
+  include: string[];
+  exclude: string[];
+}
+
+export interface KeepaliveConfig {
+  enabled: boolean;
+  idleSecs: number;
+  intervalSecs: number;
+}
+
+export type SyncProfile = "normal" | "aggressive" | "relaxed";
+
+/** Drift allowed before the desync check reports a transform mismatch */
+export interface DiffTolerance {
+  position: number;
+  scale: number;
+  /** Degrees */
+  rotation: number;
+  /** Whole pixels per edge */
+  crop?: number;
+}
+
+export interface AutoStartSettings {
+  connectObs: boolean;
+  role?: AppMode | null;
+  port?: number | null;
+  masterHost?: string | null;
 }
 
 export interface AppSettings {
@@ -21,6 +168,18 @@ export interface AppSettings {
   master: MasterSettings;
   slave: SlaveSettings;
   donationDialogShown?: boolean;
+  syncProfile?: SyncProfile;
+  /** Replaces the sync profile's tolerances when set */
+  diffTolerance?: DiffTolerance | null;
+  sceneTargetOverrides?: Record<string, SyncTargetType[]>;
+  syncFilters?: SyncFilters;
+  keepalive?: KeepaliveConfig;
+  autoStart?: AutoStartSettings;
+}
+
+export interface SettingsReload {
+  applied: string[];
+  restartRequired: string[];
 }
 
 export const useSettings = () => {
@@ -68,6 +227,17 @@ export const useSettings = () => {
     }
   }, []);
 
+  const exportSettings = useCallback(async (path: string) => {
+    await invoke("export_settings", { path });
+  }, []);
+
+  /** Replace the saved settings with a file exported on another machine */
+  const importSettings = useCallback(async (path: string) => {
+    const reload = await invoke<SettingsReload>("import_settings", { path });
+    await loadSettings();
+    return reload;
+  }, [loadSettings]);
+
   // Load settings on mount
   useEffect(() => {
     loadSettings();
@@ -78,5 +248,7 @@ export const useSettings = () => {
     isLoading,
     loadSettings,
     saveSettings,
+    exportSettings,
+    importSettings,
   };
 };
@@ -3,6 +3,21 @@
 export interface NetworkConfig {
   host: string;
   port: number;
+  pathToken?: string;
+  useTls?: boolean;
+  /** PEM certificate the master's certificate must chain to */
+  tlsCaCertPath?: string;
+  /** SHA-256 fingerprint of the master certificate (get_master_tls_fingerprint) */
+  tlsFingerprint?: string;
+  /** Shared token the master requires before sending any state */
+  authToken?: string;
+  /** Masters tried in order when the one at host:port can't be reached */
+  fallbackMasters?: MasterEndpoint[];
+}
+
+export interface MasterEndpoint {
+  host: string;
+  port: number;
 }
 
 export interface MasterServerConfig extends NetworkConfig {
@@ -34,7 +49,9 @@ export interface SlaveInfo {
 }
 
 export interface ClientInfo {
+  /** Slave's machine id, or its socket address for older slaves */
   id: string;
+  displayName?: string | null;
   ipAddress: string;
   connectedAt: number;
   lastActivity: number;
@@ -53,11 +70,117 @@ export interface SlaveStatus {
   isSynced: boolean;
   desyncDetails: DesyncDetail[];
   lastReportTime: number;
+  reportLatencyMs?: number | null;
+  applyErrors?: string[];
+  /** Rolling round trip of the master's heartbeat pings */
+  rttMs?: number | null;
+}
+
+export interface SlaveLatency {
+  clientId: string;
+  roundTripMs: number | null;
+}
+
+export interface SlaveDetail {
+  info: ClientInfo;
+  status: SlaveStatus | null;
+  statusAgeMs: number | null;
+  isStale: boolean;
+  capabilities: Record<string, unknown> | null;
+}
+
+export type SlaveSyncState = "synced" | "desynced" | "pending";
+
+export interface SlaveDesyncSummary {
+  clientId: string;
+  ipAddress: string;
+  /** "pending" until the slave sends its first state report */
+  state: SlaveSyncState;
+  criticalCount: number;
+  warningCount: number;
+  lastReportTime: number | null;
 }
 
 export interface ReconnectionStatus {
   isReconnecting: boolean;
   attemptCount: number;
+  /** 0 means the slave retries forever */
   maxAttempts: number;
   lastError?: string;
+  parseErrorCount: number;
+  /** The master announced a deliberate shutdown; no reconnects are attempted */
+  masterStopped: boolean;
+  /** host:port of the master currently being connected to */
+  activeMaster: string;
+}
+
+/** Result of `probe_master` */
+export interface MasterProbeResult {
+  appVersion: string;
+  protocolVersion: number;
+  /** Whether this build can sync with the master */
+  compatible: boolean;
+  authRequired: boolean;
+  latencyMs: number;
+}
+
+export type StatusLevel = "info" | "warning" | "error";
+
+/** Payload of the `connection-status` event */
+export interface StatusEvent {
+  level: StatusLevel;
+  message: string;
+  timestamp: number;
+  reconnection?: ReconnectionStatus;
+}
+
+export interface MediaAvailability {
+  sourceName: string;
+  inputKind: string;
+  path: string;
+  exists: boolean;
+}
+
+export interface RawSceneItem {
+  id: number;
+  sourceName: string;
+  inputKind?: string;
+  enabled: boolean;
+}
+
+export interface FleetReportEntry {
+  clientId: string;
+  displayName?: string | null;
+  ipAddress: string;
+  connectedAt: number;
+  lastReportTime?: number;
+  isSynced?: boolean;
+  isStale: boolean;
+  reportLatencyMs?: number;
+  desyncDetails: unknown[];
+  applyErrors: string[];
+}
+
+export interface FleetReport {
+  generatedAt: number;
+  masterObsVersion?: string;
+  clients: FleetReportEntry[];
+}
+
+export type NormalizeAction =
+  | "addScene"
+  | "removeScene"
+  | "addItem"
+  | "removeItem"
+  | "reorderItem"
+  | "setEnabled";
+
+export interface NormalizeChange {
+  action: NormalizeAction;
+  sceneName: string;
+  sourceName?: string;
+  detail: string;
+  error?: string;
+  /** Dry run: only planned, OBS was not changed */
+  planned: boolean;
 }
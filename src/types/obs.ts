@@ -10,8 +10,15 @@ export interface OBSConnectionStatus {
   connected: boolean;
   obsVersion?: string;
   obsWebSocketVersion?: string;
+  /** OBSとの接続が切れ、自動で再接続を試みている */
+  reconnecting?: boolean;
 }
 
+/** Payload of the `obs-connection` event */
+export type OBSConnectionEvent =
+  | { type: "lost" }
+  | { type: "reconnected"; attempts: number };
+
 export interface OBSSource {
   sourceName: string;
   sourceType: string;
@@ -56,6 +63,7 @@ export enum SyncTargetType {
   Source = "source",
   Preview = "preview",
   Program = "program",
+  Output = "output",
 }
 
 export interface SyncTarget {
@@ -5,6 +5,8 @@ import { OBSSceneItem, OBSTransform, SyncTargetType } from "./obs";
 export enum AppMode {
   Master = "master",
   Slave = "slave",
+  /** Slave that also sends its own scene switches and transforms to the master */
+  Bidirectional = "bidirectional",
 }
 
 export enum SyncMessageType {
@@ -31,11 +33,18 @@ export interface SourceUpdatePayload {
 export interface TransformUpdatePayload {
   sceneName: string;
   sceneItemId: number;
+  sourceName?: string;
   transform: OBSTransform;
 }
 
+export interface SceneTransition {
+  name: string;
+  durationMs?: number;
+}
+
 export interface SceneChangePayload {
   sceneName: string;
+  transition?: SceneTransition;
 }
 
 export interface ImageUpdatePayload {
@@ -66,4 +75,54 @@ export interface DesyncAlert {
   sourceName: string;
   message: string;
   severity: "warning" | "error";
+  /** Notice that a previously alerted difference has gone away */
+  resolved?: boolean;
+}
+
+/** How far a state sync has got (`resync-progress` event) */
+export interface ResyncProgress {
+  /** Slave being caught up; null when the state goes to all slaves */
+  target: string | null;
+  sceneName: string;
+  sceneIndex: number;
+  sceneCount: number;
+  /** 0 when the scene starts */
+  itemIndex: number;
+  itemCount: number;
+  /** Last event of a run; carries no scene and zero counts */
+  done: boolean;
+}
+
+/** Change a dry-run slave would have made to OBS (`planned-action` event) */
+export interface PlannedAction {
+  id: string;
+  timestamp: number;
+  action: string;
+  sceneName: string;
+  sourceName: string;
+  details: unknown;
+}
+
+export interface StateSyncFailure {
+  sceneName: string;
+  sourceName: string;
+  reason: string;
+}
+
+/** Outcome of applying one StateSync on a slave (`state-sync-report` event) */
+export interface StateSyncReport {
+  timestamp: number;
+  itemsApplied: number;
+  itemsUnchanged: number;
+  itemsFailed: number;
+  failures: StateSyncFailure[];
+  aborted: boolean;
+  resyncRequested: boolean;
+}
+
+/** Source added more than once to a master scene (`ambiguous-sources` event) */
+export interface AmbiguousSource {
+  sceneName: string;
+  sourceName: string;
+  sceneItemIds: number[];
 }